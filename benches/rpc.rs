@@ -0,0 +1,143 @@
+//! RPC Benchmarks
+//! Tracked numbers for the hot paths, so performance regressions show up
+//! as a change between `cargo bench` runs:
+//! 1. Unary echo round trip, one call at a time
+//! 2. Calculate throughput with 64 calls in flight
+//! 3. Echo of a 1 MB message
+//! 4. Chunked echo of 1 MB and 10 MB payloads, with shared Bytes chunks
+//!    and with Vec<u8> copies, to track the copies made per chunk
+//!
+//! Every benchmark runs against an in-process server (BenchHarness) on a
+//! shared multi-threaded runtime.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use embedded_recruitment_task::bench_util::BenchHarness;
+use embedded_recruitment_task::client::{Bytes, EchoService, Operation};
+use tokio::runtime::Runtime;
+use tokio_stream::StreamExt;
+
+// Calls in flight at once in the throughput benchmark
+const CONCURRENT_CALLS: usize = 64;
+// Size of the large echo payload
+const LARGE_PAYLOAD: usize = 1024 * 1024;
+// Payloads of the chunked echo, and the size of each chunk
+const CHUNKED_PAYLOADS: [usize; 2] = [1024 * 1024, 10 * 1024 * 1024];
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Runtime shared by the server, the clients and criterion's async driver
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread().enable_all().build().expect("Failed to build runtime")
+}
+
+fn harness(runtime: &Runtime) -> BenchHarness {
+    runtime.block_on(BenchHarness::start()).expect("Failed to start benchmark server")
+}
+
+// One echo at a time: latency of a single round trip
+fn echo_round_trip(c: &mut Criterion) {
+    let runtime = runtime();
+    let harness = harness(&runtime);
+    let echo = harness.client().echo();
+
+    c.bench_function("echo_round_trip", |b| {
+        b.to_async(&runtime).iter(|| {
+            let mut echo = echo.clone();
+            async move { echo.echo("ping").await.expect("Echo failed") }
+        })
+    });
+}
+
+// 64 calculations per iteration, all in flight on one connection
+fn calculate_throughput(c: &mut Criterion) {
+    let runtime = runtime();
+    let harness = harness(&runtime);
+    let calculator = harness.client().calculator();
+
+    let mut group = c.benchmark_group("calculate");
+    group.throughput(Throughput::Elements(CONCURRENT_CALLS as u64));
+    group.bench_function(BenchmarkId::new("concurrent", CONCURRENT_CALLS), |b| {
+        b.to_async(&runtime).iter(|| {
+            let calculator = calculator.clone();
+            async move {
+                let tasks: Vec<_> = (0..CONCURRENT_CALLS).map(|i| {
+                    let mut calculator = calculator.clone();
+                    tokio::spawn(async move { calculator.calculate(i as f64, 2.0, Operation::Multiply).await })
+                }).collect();
+                for task in tasks {
+                    task.await.expect("Task panicked").expect("Calculate failed");
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+// Echo of a 1 MB message: encoding, framing and copying costs
+fn large_echo(c: &mut Criterion) {
+    let runtime = runtime();
+    let harness = harness(&runtime);
+    let echo = harness.client().echo();
+    let payload = "x".repeat(LARGE_PAYLOAD);
+
+    let mut group = c.benchmark_group("echo_large");
+    group.throughput(Throughput::Bytes(LARGE_PAYLOAD as u64));
+    group.sample_size(20);
+    group.bench_function(BenchmarkId::new("bytes", LARGE_PAYLOAD), |b| {
+        b.to_async(&runtime).iter(|| {
+            let (mut echo, payload) = (echo.clone(), payload.clone());
+            async move { echo.echo(payload).await.expect("Echo failed") }
+        })
+    });
+    group.finish();
+}
+
+// Chunked echo of each payload, with chunks that are slices of one shared
+// buffer ("bytes") and with chunks copied into a Vec<u8> each way ("vec"),
+// so the numbers show what the copies cost next to encoding and forwarding
+fn chunked_echo(c: &mut Criterion) {
+    let runtime = runtime();
+    let harness = harness(&runtime);
+    let echo = harness.client().echo();
+
+    let mut group = c.benchmark_group("echo_chunked");
+    group.sample_size(10);
+    for size in CHUNKED_PAYLOADS {
+        let payload = Bytes::from(vec![0x5a; size]);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("bytes", size), &payload, |b, payload| {
+            b.to_async(&runtime).iter(|| {
+                let chunks: Vec<Bytes> = (0..size).step_by(CHUNK_SIZE)
+                    .map(|start| payload.slice(start..size.min(start + CHUNK_SIZE)))
+                    .collect();
+                echo_chunks(echo.clone(), chunks, size, |chunk| chunk.len())
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("vec", size), &payload, |b, payload| {
+            b.to_async(&runtime).iter(|| {
+                let chunks: Vec<Vec<u8>> = (0..size).step_by(CHUNK_SIZE)
+                    .map(|start| payload[start..size.min(start + CHUNK_SIZE)].to_vec())
+                    .collect();
+                echo_chunks(echo.clone(), chunks, size, |chunk| chunk.to_vec().len())
+            })
+        });
+    }
+    group.finish();
+}
+
+// Echo `chunks`, passing every echoed chunk to `receive`, which returns its length
+async fn echo_chunks<B: Into<Bytes> + Send + 'static>(
+    mut echo: EchoService,
+    chunks: Vec<B>,
+    size: usize,
+    receive: impl Fn(Bytes) -> usize,
+) {
+    let mut stream = echo.echo_chunked(tokio_stream::iter(chunks)).await.expect("Chunked echo failed");
+    let mut received = 0;
+    while let Some(chunk) = stream.next().await {
+        received += receive(chunk.expect("Chunk failed"));
+    }
+    assert_eq!(received, size);
+}
+
+criterion_group!(benches, echo_round_trip, calculate_throughput, large_echo, chunked_echo);
+criterion_main!(benches);
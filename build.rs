@@ -10,12 +10,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // - Request/response structs
     // - Client stubs
     // - Server traits
-    tonic_build::compile_protos("src/proto/echo.proto")?;
+    // Chunk payloads decode into `bytes::Bytes` sharing the received buffer,
+    // so the chunked echo forwards them without copying
+    tonic_build::configure()
+        .bytes([".echo.EchoChunk.data"])
+        .compile(&["src/proto/echo.proto"], &["src/proto"])?;
 
     // Compile calculator service proto file
     // Generated code will be placed in target directory
     // and included in the final build
     tonic_build::compile_protos("src/proto/calculator.proto")?;
+
+    // Compile admin service proto file (operational endpoints)
+    tonic_build::compile_protos("src/proto/admin.proto")?;
+
+    // Compile broadcast service proto file (publish/subscribe echo)
+    tonic_build::compile_protos("src/proto/broadcast.proto")?;
     
     // Return success or propagate any compilation errors
     Ok(())
@@ -0,0 +1,19 @@
+#!/bin/sh
+# Feature matrix check, in the spirit of `cargo hack --each-feature`
+# Builds the library with no features, then with each optional feature on
+# its own, so a feature that only compiles thanks to another (or to the
+# features the dev-dependencies turn on for the tests) is caught.
+# Kept out of `cargo test`: it is a build of its own, and slow.
+#
+# Usage: scripts/check-features.sh [extra cargo args]
+set -eu
+
+cd "$(dirname "$0")/.."
+
+# A target directory of its own, so the test build is not invalidated
+target_dir=target/feature-check
+
+for features in "" test-util bench-util nfc; do
+    echo "== cargo check --no-default-features --features '${features}'"
+    cargo check --lib --no-default-features --features "${features}" --target-dir "${target_dir}" "$@"
+done
@@ -0,0 +1,132 @@
+//! Benchmark Utilities
+//! An in-process server for benchmarks, so numbers are comparable between
+//! runs and machines without starting a separate process.
+//! Only compiled with the `bench-util` feature.
+//!
+//! `BenchHarness` does the setup every benchmark repeats:
+//! 1. Starts a server on a free loopback port with the given builder settings
+//! 2. Connects a client and warms the connection up before measuring
+//! 3. Turns server logging down to warnings, so file I/O per request does
+//!    not dominate the numbers
+//! 4. Shuts the server down when dropped
+//!
+//! Loopback TCP is used rather than an in-memory transport: connection
+//! tracking and limits work on accepted sockets, and the kernel's loopback
+//! path costs far less than the gRPC stack being measured.
+
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::time::Duration;
+use tonic::Status;
+use tracing::{error, warn};
+use tracing_subscriber::filter::LevelFilter;
+use crate::server::{GrpcServerBuilder, Shutdown};
+use crate::{logging, GrpcClient, GrpcServer};
+
+// Readiness budget for the spawned server: 100 x 10ms = 1s
+const CONNECT_RETRIES: u32 = 100;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// Echoes sent by `start` before returning, so the first measured call
+/// finds the connection and the server's lazily created state ready
+pub const DEFAULT_WARMUP_CALLS: usize = 100;
+
+/// In-process server with a connected client, for benchmarks
+pub struct BenchHarness {
+    addr: SocketAddr,
+    client: GrpcClient,
+    shutdown: Shutdown,
+}
+
+impl BenchHarness {
+    /// Start a server with default settings and a warmed-up client
+    ///
+    /// # Returns
+    /// * `Result<BenchHarness, Status>` - The running harness, or the status of the failed setup.
+    pub async fn start() -> Result<Self, Status> {
+        Self::start_with(|builder| builder).await
+    }
+
+    /// Same as `start`, but lets the benchmark customize the server builder
+    ///
+    /// # Arguments
+    /// * `configure` - Applied to a builder whose address is already set.
+    ///
+    /// # Returns
+    /// * `Result<BenchHarness, Status>` - The running harness, or the status of the failed setup.
+    pub async fn start_with(
+        configure: impl FnOnce(GrpcServerBuilder) -> GrpcServerBuilder,
+    ) -> Result<Self, Status> {
+        let addr = free_loopback_addr()?;
+        let builder = GrpcServer::builder().socket_addr(addr).startup_banner(false);
+        let (server, shutdown) = configure(builder).build()?;
+        tokio::spawn(async move {
+            if let Err(e) = server.serve().await {
+                error!("Benchmark server error: {}", e);
+            }
+        });
+
+        let client = Self::connect(addr).await?;
+        // The server has initialized logging once it accepts calls
+        if let Err(e) = logging::set_level(None, LevelFilter::WARN) {
+            warn!("Benchmark server keeps its log level: {}", e);
+        }
+        let harness = BenchHarness { addr, client, shutdown };
+        harness.warmup(DEFAULT_WARMUP_CALLS).await?;
+        Ok(harness)
+    }
+
+    // Connect eagerly, retrying while the server is still binding
+    async fn connect(addr: SocketAddr) -> Result<GrpcClient, Status> {
+        GrpcClient::builder(format!("http://{}", addr))?
+            .connect_retries(CONNECT_RETRIES, CONNECT_RETRY_DELAY)
+            .connect_eager()
+            .await
+    }
+
+    /// Address the server listens on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The warmed-up client; clones share its connection
+    pub fn client(&self) -> &GrpcClient {
+        &self.client
+    }
+
+    /// A client on a connection of its own, not warmed up
+    ///
+    /// # Returns
+    /// * `Result<GrpcClient, Status>` - The connected client.
+    pub async fn new_client(&self) -> Result<GrpcClient, Status> {
+        Self::connect(self.addr).await
+    }
+
+    /// Send `calls` echoes through the shared client and discard the results
+    ///
+    /// # Arguments
+    /// * `calls` - Number of echoes; criterion does its own warmup on top.
+    ///
+    /// # Returns
+    /// * `Result<(), Status>` - The status of the first failed echo, if any.
+    pub async fn warmup(&self, calls: usize) -> Result<(), Status> {
+        let mut echo = self.client.echo();
+        for _ in 0..calls {
+            echo.echo("warmup").await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BenchHarness {
+    fn drop(&mut self) {
+        self.shutdown.trigger();
+    }
+}
+
+// Let the OS pick a free port, then release it for the server
+// Another process could take it in between; harmless for benchmarks
+fn free_loopback_addr() -> Result<SocketAddr, Status> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .map_err(|e| Status::internal(format!("no free loopback port: {}", e)))
+}
@@ -4,28 +4,207 @@
 //! 2. Using multiple services (echo and calculator)
 //! 3. Making async RPC calls
 //! 4. Error handling with Result
+//!
+//! Usage (every command accepts a leading `--addr HOST:PORT`, default 127.0.0.1:12345):
+//! - `grpc_client` runs the echo and calculator demo
+//! - `grpc_client calc-expr "2 + 3 * 4" [--verbose] [--local]` evaluates an
+//!   expression; `--verbose` prints each step, `--local` skips the server's
+//!   Evaluate RPC and always runs the expression as Calculate calls
+//! - `grpc_client --bench [--requests N] [--concurrency C] [--in-process]` sends
+//!   N echoes (default 1000) from C tasks (default 10) and prints the throughput;
+//!   `--in-process` benchmarks a server started inside the client instead
+//!   (needs the `bench-util` feature)
+//! - `grpc_client ping [--count N] [--interval 200ms] [--max-loss PCT]` sends
+//!   sequential echo probes, one line each, then prints loss and latency
+//!   statistics; `--count 0` (the default) runs until Ctrl-C. Exits with an
+//!   error when the loss exceeds `--max-loss` percent (default 0)
 
 // Import our client type from the main library
 use embedded_recruitment_task::GrpcClient;
+use embedded_recruitment_task::client::{run_bench, PingStats};
+use std::time::Duration;
+
+const USAGE: &str = "usage: grpc_client [--addr HOST:PORT] [calc-expr EXPRESSION [--verbose] [--local] \
+| --bench [--requests N] [--concurrency C] [--in-process] | ping [--count N] [--interval DURATION] [--max-loss PCT]]";
+
+// Server contacted when --addr is not given
+const DEFAULT_ADDR: &str = "http://127.0.0.1:12345";
+
+// Ping defaults when --interval is not given
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(1);
+
+// Benchmark defaults when --requests / --concurrency are not given
+const DEFAULT_BENCH_REQUESTS: usize = 1000;
+const DEFAULT_BENCH_CONCURRENCY: usize = 10;
 
 // Configure async runtime and provide error handling
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // An optional server address comes before the command
+    let mut addr = DEFAULT_ADDR.to_string();
+    if args.first().map(String::as_str) == Some("--addr") {
+        let value = args.get(1).ok_or("--addr needs a value")?;
+        addr = if value.contains("://") { value.clone() } else { format!("http://{}", value) };
+        args.drain(..2);
+    }
+
     // Initialize and connect the client to our server
-    let client = GrpcClient::builder("http://127.0.0.1:12345")?
-        .connect()?;
+    let builder = GrpcClient::builder(&addr)?;
+    let snapshot = serde_json::to_string(&builder.config_snapshot())?;
+    let client = builder.connect()?;
+    // Logging is set up by connect(); the snapshot goes to the log file
+    tracing::info!("Client configuration: {}", snapshot);
+
+    match args.first().map(String::as_str) {
+        None => demo(&client).await,
+        Some("calc-expr") => calc_expr(&client, &args[1..]).await,
+        Some("--bench") => bench(&client, &args[1..]).await,
+        Some("ping") => ping(&client, &addr, &args[1..]).await,
+        Some(other) => Err(format!("unknown command {:?}\n{}", other, USAGE).into()),
+    }
+}
 
+// Demonstrate both services with one call each
+async fn demo(client: &GrpcClient) -> Result<(), Box<dyn std::error::Error>> {
     // Get service handles for both available services
     let mut echo = client.echo();
     let mut calc = client.calculator();
-    
+
     // Demonstrate echo service functionality
     let response = echo.echo("Hello OpenTier :)").await?;
     println!("Echo response: {}", response);
-    
+
     // Demonstrate calculator service functionality with addition
     let result = calc.calculate(2.0, 3.0, embedded_recruitment_task::proto::calculator::Operation::Add).await?;
     println!("Calculator response: 2 + 3 = {}", result);
-    
+
+    Ok(())
+}
+
+// Run the echo benchmark with the counts given on the command line
+async fn bench(client: &GrpcClient, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut requests, mut concurrency) = (DEFAULT_BENCH_REQUESTS, DEFAULT_BENCH_CONCURRENCY);
+    let mut in_process = false;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let target = match flag.as_str() {
+            "--requests" => &mut requests,
+            "--concurrency" => &mut concurrency,
+            "--in-process" => {
+                in_process = true;
+                continue;
+            }
+            _ => return Err(format!("unexpected argument {:?}\n{}", flag, USAGE).into()),
+        };
+        let value = args.next().ok_or(format!("{} needs a value", flag))?;
+        *target = value.parse().map_err(|_| format!("{}: expected a count, got {:?}", flag, value))?;
+    }
+
+    if in_process {
+        return bench_in_process(requests, concurrency).await;
+    }
+    println!("Sending {} echo requests from {} tasks", requests, concurrency);
+    let report = run_bench(client, requests, concurrency).await;
+    println!("{}", report);
+    Ok(())
+}
+
+// Run the echo benchmark against a server started in this process
+#[cfg(feature = "bench-util")]
+async fn bench_in_process(requests: usize, concurrency: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let harness = embedded_recruitment_task::bench_util::BenchHarness::start().await?;
+    println!("Sending {} echo requests from {} tasks to an in-process server on {}", requests, concurrency, harness.addr());
+    let report = run_bench(harness.client(), requests, concurrency).await;
+    println!("{}", report);
+    Ok(())
+}
+
+#[cfg(not(feature = "bench-util"))]
+async fn bench_in_process(_requests: usize, _concurrency: usize) -> Result<(), Box<dyn std::error::Error>> {
+    Err("--in-process needs the bench-util feature (cargo run --features bench-util --bin grpc_client)".into())
+}
+
+// Probe the server with sequential echoes, like the classic ping
+async fn ping(client: &GrpcClient, addr: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut count, mut interval, mut max_loss) = (0usize, DEFAULT_PING_INTERVAL, 0.0f64);
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("{} needs a value\n{}", flag, USAGE))?;
+        match flag.as_str() {
+            "--count" => count = value.parse().map_err(|_| format!("--count: expected a count, got {:?}", value))?,
+            "--interval" => interval = parse_duration(value).ok_or(format!("--interval: expected e.g. 200ms or 1.5s, got {:?}", value))?,
+            "--max-loss" => max_loss = value.parse().map_err(|_| format!("--max-loss: expected a percentage, got {:?}", value))?,
+            _ => return Err(format!("unexpected argument {:?}\n{}", flag, USAGE).into()),
+        }
+    }
+
+    println!("PING {} every {:?}", addr, interval);
+    let mut stats = PingStats::default();
+    let mut echo = client.echo();
+    let probes = echo.ping_n(count, interval, &mut stats, |probe, result| match result {
+        Ok(latency) => println!("probe {}: time={:?}", probe, latency),
+        Err(status) => println!("probe {}: failed: {:?}: {}", probe, status.code(), status.message()),
+    });
+    // Ctrl-C ends the run; the probes completed so far are still summarized
+    tokio::select! {
+        _ = probes => {}
+        _ = tokio::signal::ctrl_c() => println!(),
+    }
+
+    println!("--- {} ping statistics ---", addr);
+    println!("{}", stats);
+    if stats.loss_percent() > max_loss {
+        return Err(format!("{:.1}% loss exceeds the allowed {}%", stats.loss_percent(), max_loss).into());
+    }
+    Ok(())
+}
+
+// Parse "200ms", "1.5s" or a bare number of milliseconds
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if let Some(ms) = text.strip_suffix("ms") {
+        return ms.trim().parse().ok().map(Duration::from_millis);
+    }
+    if let Some(secs) = text.strip_suffix('s') {
+        return secs.trim().parse::<f64>().ok().and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+    }
+    text.parse().ok().map(Duration::from_millis)
+}
+
+// Evaluate one expression given on the command line
+async fn calc_expr(client: &GrpcClient, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut expression = None;
+    let (mut verbose, mut local) = (false, false);
+    for arg in args {
+        match arg.as_str() {
+            "--verbose" | "-v" => verbose = true,
+            "--local" => local = true,
+            _ if expression.is_none() => expression = Some(arg.as_str()),
+            _ => return Err(format!("unexpected argument {:?}\n{}", arg, USAGE).into()),
+        }
+    }
+    let expression = expression.ok_or(USAGE)?;
+
+    let mut calc = client.calculator();
+    let evaluation = if local {
+        calc.evaluate_locally(expression).await?
+    } else {
+        calc.evaluate(expression).await?
+    };
+
+    if verbose {
+        if evaluation.on_server {
+            println!("evaluated by the server's Evaluate RPC");
+        }
+        for (index, step) in evaluation.steps.iter().enumerate() {
+            println!(
+                "step {}: {} {:?} {} = {}    ({})",
+                index + 1, step.first, step.operation, step.second, step.result, step.expression
+            );
+        }
+    }
+    println!("{} = {}", expression.trim(), evaluation.result);
     Ok(())
-}
\ No newline at end of file
+}
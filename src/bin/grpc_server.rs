@@ -3,24 +3,108 @@
 //! It demonstrates:
 //! 1. Simple server setup using builder pattern
 //! 2. Error handling with Result
-//! 3. Async runtime configuration with tokio
+//! 3. Explicit tokio runtime configuration (thread counts and names)
+//! 4. Optional configuration from environment variables (--env-config)
+//! 5. Reopening the log file on SIGHUP (Unix), for logrotate
+//! 6. Printing the effective configuration, secrets redacted (config_snapshot)
+//! 7. Exit status from why the server stopped: 0 after a shutdown request,
+//!    non-zero after a failure or when every listener closed on its own
+//!
+//! Flags:
+//!   --env-config                 read settings from GRPC_SERVER_* variables
+//!   --worker-threads N           async worker threads (default: one per core)
+//!   --max-blocking-threads N     blocking pool limit (default: 512)
+//!   --thread-name-prefix NAME    name runtime threads NAME-0, NAME-1, ...
+//! Runtime flags override the corresponding environment variables.
 
 // Import our server type from the main library
-use embedded_recruitment_task::GrpcServer;
+use embedded_recruitment_task::{GrpcServer, RuntimeConfig, ServerConfig};
+use embedded_recruitment_task::server::{GrpcServerBuilder, ShutdownReason};
+#[cfg(unix)]
+use embedded_recruitment_task::logging;
+
+// Prefix of the variables read with --env-config (GRPC_SERVER_ADDR, ...)
+const ENV_PREFIX: &str = "GRPC_SERVER";
+
+// Settings taken from the command line
+#[derive(Default)]
+struct Args {
+    env_config: bool,
+    runtime: RuntimeConfig,
+}
+
+// Parse the command line; accepts both "--flag value" and "--flag=value"
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        if flag == "--env-config" {
+            parsed.env_config = true;
+            continue;
+        }
+        let mut value = || inline.clone().or_else(|| args.next()).ok_or(format!("{} needs a value", flag));
+        match flag.as_str() {
+            "--worker-threads" => parsed.runtime.worker_threads = Some(count(&flag, &value()?)?),
+            "--max-blocking-threads" => parsed.runtime.max_blocking_threads = Some(count(&flag, &value()?)?),
+            "--thread-name-prefix" => parsed.runtime.thread_name_prefix = Some(value()?),
+            _ => return Err(format!("unknown argument {:?}", flag)),
+        }
+    }
+    Ok(parsed)
+}
+
+// Thread count flag value; zero is rejected when the runtime is built
+fn count(flag: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("{}: expected a thread count, got {:?}", flag, value))
+}
+
+// No #[tokio::main]: the runtime is sized from the flags before it starts
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = parse_args(std::env::args().skip(1))?;
+
+    // --env-config reads every setting from GRPC_SERVER_* variables;
+    // otherwise the built-in defaults are used
+    let (builder, mut runtime) = if args.env_config {
+        let config = ServerConfig::from_env(ENV_PREFIX)?;
+        let runtime = config.runtime.clone();
+        (GrpcServerBuilder::from_config(config)?, runtime)
+    } else {
+        (GrpcServer::builder().address("127.0.0.1:12345"), RuntimeConfig::default())
+    };
+
+    // Flags take precedence over the environment
+    runtime.worker_threads = args.runtime.worker_threads.or(runtime.worker_threads);
+    runtime.max_blocking_threads = args.runtime.max_blocking_threads.or(runtime.max_blocking_threads);
+    runtime.thread_name_prefix = args.runtime.thread_name_prefix.or(runtime.thread_name_prefix);
+
+    // Build the runtime first so invalid thread settings fail before anything starts
+    let tokio_runtime = runtime.build_runtime()?;
 
-// Configure async runtime and provide error handling
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize server using builder pattern
-    // _shutdown is a channel sender we could use to gracefully shutdown the server
-    let (server, _shutdown) = GrpcServer::builder()
-        .address("127.0.0.1:12345")
-        .build()?;
-        
+    // _shutdown is a handle we could use to gracefully shutdown the server
+    let (server, _shutdown) = builder.runtime_config(runtime).build()?;
+    // Every option in effect, secrets redacted
+    println!("Server configuration: {}", serde_json::to_string(&server.config_snapshot())?);
+
+    // Follow log rotation: logrotate renames the file, then sends SIGHUP
+    #[cfg(unix)]
+    {
+        let _runtime = tokio_runtime.enter();
+        logging::reopen_on_sighup()?;
+    }
+
     // Log server startup information
-    println!("Server listening on 127.0.0.1:12345");  // Updated log message
-    
+    println!("Server listening on {}", server.addr());
+
     // Start the server and await completion or error
-    server.serve().await?;
-    Ok(())
-}
\ No newline at end of file
+    let reason = tokio_runtime.block_on(server.serve())?;
+    println!("Server stopped: {}", reason);
+    match reason {
+        ShutdownReason::SignalReceived | ShutdownReason::HandlesDropped => Ok(()),
+        ShutdownReason::AllListenersClosed => Err(format!("server stopped unexpectedly: {}", reason).into()),
+    }
+}
@@ -0,0 +1,119 @@
+//! Echo Throughput Benchmark
+//! Measures how many echo calls a server answers per second through one
+//! client, as used by `grpc_client --bench`:
+//! 1. run_bench: fires a fixed number of echoes from concurrent tasks
+//! 2. BenchReport: counts, total time, throughput and latency percentiles
+//!
+//! The tasks share the client's channel and take request numbers from one
+//! counter, so exactly the requested number of calls is made whatever the
+//! concurrency. Percentiles use the nearest-rank method over successful calls.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+use super::GrpcClient;
+
+/// Outcome of one benchmark run
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchReport {
+    /// Calls made
+    pub requests: usize,
+    /// Calls that failed; they are excluded from the latencies
+    pub failures: usize,
+    /// Wall-clock time of the whole run
+    pub elapsed: Duration,
+    /// Median latency of successful calls
+    pub p50: Duration,
+    /// 99th percentile latency of successful calls
+    pub p99: Duration,
+}
+
+impl BenchReport {
+    /// Calls completed per second, failed ones included
+    pub fn requests_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.requests as f64 / secs } else { 0.0 }
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "requests:     {} ({} failed)", self.requests, self.failures)?;
+        writeln!(f, "total time:   {:?}", self.elapsed)?;
+        writeln!(f, "requests/sec: {:.1}", self.requests_per_sec())?;
+        write!(f, "latency:      p50 {:?}, p99 {:?}", self.p50, self.p99)
+    }
+}
+
+/// Send `requests` echo calls from `concurrency` tasks and report the results
+///
+/// # Arguments
+/// * `client` - The client whose channel the calls share.
+/// * `requests` - Total number of echo calls.
+/// * `concurrency` - Number of tasks sending calls at once; at least one is used.
+///
+/// # Returns
+/// * `BenchReport` - Counts, timing and latency percentiles of the run.
+pub async fn run_bench(client: &GrpcClient, requests: usize, concurrency: usize) -> BenchReport {
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+
+    let tasks: Vec<_> = (0..concurrency.max(1)).map(|_| {
+        let (client, next) = (client.clone(), next.clone());
+        tokio::spawn(async move {
+            let mut echo = client.echo();
+            let mut latencies = Vec::new();
+            while next.fetch_add(1, Ordering::Relaxed) < requests {
+                let sent = Instant::now();
+                match echo.echo("bench").await {
+                    Ok(_) => latencies.push(sent.elapsed()),
+                    Err(e) => warn!("Benchmark echo failed: {}", e),
+                }
+            }
+            latencies
+        })
+    }).collect();
+
+    // Every call without a latency failed, including those of a panicked task
+    let mut latencies = Vec::with_capacity(requests);
+    for task in tasks {
+        latencies.extend(task.await.unwrap_or_default());
+    }
+    let elapsed = started.elapsed();
+    latencies.sort();
+
+    BenchReport {
+        requests,
+        failures: requests - latencies.len(),
+        elapsed,
+        p50: percentile(&latencies, 50),
+        p99: percentile(&latencies, 99),
+    }
+}
+
+// Nearest-rank percentile of sorted samples; zero without samples
+// Also used by the ping statistics
+pub(super) fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+// Unit tests for the percentile helper
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 50), Duration::from_millis(50));
+        assert_eq!(percentile(&samples, 99), Duration::from_millis(99));
+        assert_eq!(percentile(&samples[..1], 99), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+}
@@ -0,0 +1,74 @@
+//! Local Bind Connector
+//! This file implements the connector behind GrpcClientBuilder::bind_local,
+//! for multi-homed hosts whose connections must leave through one interface:
+//! 1. Resolve the server to the addresses of the local address's family
+//! 2. Bind a fresh socket to the local address, then connect it
+//! 3. Try the next address if one fails, like tonic's own connector
+//!
+//! Every failure is reported as Unavailable naming the local address.
+
+use std::{future::Future, io, net::SocketAddr, pin::Pin, task::{Context, Poll}};
+use tokio::net::{lookup_host, TcpSocket, TcpStream};
+use tonic::{transport::Uri, Status};
+use tower::Service;
+use tracing::debug;
+use super::proxy::target_authority;
+
+// tower Service<Uri> that returns a stream bound to the local address
+#[derive(Debug, Clone)]
+pub(crate) struct LocalBindConnector {
+    local: SocketAddr,  // Address the socket is bound to; port 0 lets the OS pick
+}
+
+impl LocalBindConnector {
+    pub(crate) fn new(local: SocketAddr) -> Self {
+        Self { local }
+    }
+
+    // Bind to the local address and connect to the first reachable server address
+    async fn connect(self, target: Uri) -> Result<TcpStream, Status> {
+        let target = target_authority(&target)?;
+        let unavailable = |reason: String| Status::unavailable(
+            format!("cannot connect to {} from {}: {}", target, self.local, reason)
+        );
+
+        let addrs = lookup_host(target.as_str()).await.map_err(|e| unavailable(e.to_string()))?;
+        let mut last_error = None;
+        for addr in addrs.filter(|addr| addr.is_ipv4() == self.local.is_ipv4()) {
+            match self.connect_to(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => {
+                    debug!("Connecting to {} from {} failed: {}", addr, self.local, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(unavailable(match last_error {
+            Some(e) => e.to_string(),
+            None => format!("no {} address", if self.local.is_ipv4() { "IPv4" } else { "IPv6" }),
+        }))
+    }
+
+    async fn connect_to(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+        socket.bind(self.local)?;
+        let stream = socket.connect(addr).await?;
+        // Same as tonic's own connector
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+}
+
+impl Service<Uri> for LocalBindConnector {
+    type Response = TcpStream;
+    type Error = Status;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, Status>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, target: Uri) -> Self::Future {
+        Box::pin(self.clone().connect(target))
+    }
+}
@@ -0,0 +1,112 @@
+//! Total Call Budget
+//! Bounds every call made through the service wrappers as a whole, as set
+//! with GrpcClientBuilder::total_call_budget:
+//! 1. The clock starts when the call is handed to the channel, so time
+//!    spent connecting counts, unlike the per-request deadline
+//! 2. The response headers must arrive within the budget
+//! 3. The rest of the budget carries over to the response body, so a
+//!    response (or a stream) that trickles in is cut off as well
+//!
+//! A call over budget fails with DeadlineExceeded naming the phase it was
+//! in: waiting for the response (connecting or sending) or receiving it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::time::{Duration, Instant, Sleep};
+use tonic::body::BoxBody;
+use tonic::codegen::http::HeaderMap;
+use tonic::codegen::Body;
+use tonic::transport::{Body as TransportBody, Channel};
+use tonic::Status;
+use tower::{BoxError, Service};
+
+// Channel enforcing the budget, if any, on every call
+#[derive(Debug, Clone)]
+pub(crate) struct CallBudget {
+    inner: Channel,
+    budget: Option<Duration>,
+}
+
+impl CallBudget {
+    pub(crate) fn new(inner: Channel, budget: Option<Duration>) -> Self {
+        Self { inner, budget }
+    }
+}
+
+// Error returned once the budget is spent
+fn exceeded(budget: Duration, phase: &str) -> Status {
+    Status::deadline_exceeded(format!("call budget of {:?} exceeded while {}", budget, phase))
+}
+
+impl Service<http::Request<BoxBody>> for CallBudget {
+    type Response = http::Response<BoxBody>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: http::Request<BoxBody>) -> Self::Future {
+        let budget = self.budget;
+        let deadline = budget.map(|budget| Instant::now() + budget);
+        let response = self.inner.call(req);
+        Box::pin(async move {
+            let response = match (budget, deadline) {
+                (Some(budget), Some(deadline)) => tokio::time::timeout_at(deadline, response)
+                    .await
+                    .map_err(|_| exceeded(budget, "waiting for the response (connecting or sending the request)"))??,
+                _ => response.await?,
+            };
+            Ok(response.map(|body| BudgetBody {
+                inner: body,
+                budget: budget.zip(deadline).map(|(budget, deadline)| (budget, Box::pin(tokio::time::sleep_until(deadline)))),
+            }.boxed_unsync()))
+        })
+    }
+}
+
+// Response body failing once the deadline passes
+struct BudgetBody {
+    inner: TransportBody,
+    budget: Option<(Duration, Pin<Box<Sleep>>)>,
+}
+
+impl BudgetBody {
+    // Error out if the deadline has passed
+    fn check(&mut self, cx: &mut Context<'_>) -> Result<(), Status> {
+        let Some((budget, sleep)) = &mut self.budget else { return Ok(()) };
+        match sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Err(exceeded(*budget, "receiving the response")),
+            Poll::Pending => Ok(()),
+        }
+    }
+}
+
+impl Body for BudgetBody {
+    type Data = <TransportBody as Body>::Data;
+    type Error = Status;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        if let Poll::Ready(item) = Pin::new(&mut self.inner).poll_data(cx) {
+            return Poll::Ready(item.map(|data| data.map_err(|e| Status::from_error(Box::new(e)))));
+        }
+        match self.check(cx) {
+            Ok(()) => Poll::Pending,
+            Err(status) => Poll::Ready(Some(Err(status))),
+        }
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        if let Poll::Ready(trailers) = Pin::new(&mut self.inner).poll_trailers(cx) {
+            return Poll::Ready(trailers.map_err(|e| Status::from_error(Box::new(e))));
+        }
+        self.check(cx)?;
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
@@ -0,0 +1,148 @@
+//! Client Calculator Cache
+//! Results kept on the client so repeated calculations cost no round trip
+//! (GrpcClientBuilder::calculator_cache):
+//! 1. `calculate` results, keyed by the bit patterns of both operands plus
+//!    the operation, like the server's cache
+//! 2. `evaluate` results, keyed by the expression without its whitespace,
+//!    so `2+3` and `2 + 3` share an entry
+//! 3. Calculations with a non-finite operand are never cached, nor are
+//!    errors; expressions cannot contain one (crate::expr rejects them)
+//! 4. Entries expire after the configured TTL; when full, the least
+//!    recently used entry is evicted (see crate::lru)
+//!
+//! Clones share the entries, and so do the clones of the GrpcClient and
+//! its service handles. Hits and misses are counted in the client metrics.
+
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::lru::Lru;
+use crate::proto::calculator::Operation;
+use super::metrics::ClientMetrics;
+use super::services::Evaluation;
+
+// (first operand bits, second operand bits, operation)
+type CalculationKey = (u64, u64, i32);
+
+// A cached value and when it stops being served
+type Expiring<V> = (V, Instant);
+
+/// Bounded LRU cache of calculator results with a time to live, shared by clones
+#[derive(Debug, Clone)]
+pub(crate) struct CalculatorCache {
+    calculations: Arc<Mutex<Lru<CalculationKey, Expiring<f64>>>>,
+    evaluations: Arc<Mutex<Lru<String, Expiring<Evaluation>>>>,
+    ttl: Duration,
+    metrics: ClientMetrics,
+}
+
+impl CalculatorCache {
+    /// Create a cache holding at most `capacity` calculations and as many
+    /// expressions (at least one of each), each for `ttl`
+    pub(crate) fn new(capacity: usize, ttl: Duration, metrics: ClientMetrics) -> Self {
+        Self {
+            calculations: Arc::new(Mutex::new(Lru::new(capacity))),
+            evaluations: Arc::new(Mutex::new(Lru::new(capacity))),
+            ttl,
+            metrics,
+        }
+    }
+
+    /// The cached result of a calculation, counting the hit or miss
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The result; None on a miss or for a non-finite operand, which is not counted.
+    pub(crate) fn calculation(&self, first: f64, second: f64, operation: Operation) -> Option<f64> {
+        let key = calculation_key(first, second, operation)?;
+        self.lookup(&self.calculations, &key)
+    }
+
+    /// Remember the result of a successful calculation
+    pub(crate) fn store_calculation(&self, first: f64, second: f64, operation: Operation, result: f64) {
+        if let Some(key) = calculation_key(first, second, operation) {
+            self.calculations.lock().unwrap().insert(key, (result, Instant::now() + self.ttl));
+        }
+    }
+
+    /// The cached evaluation of a valid expression, counting the hit or miss
+    pub(crate) fn evaluation(&self, expression: &str) -> Option<Evaluation> {
+        self.lookup(&self.evaluations, &expression_key(expression))
+    }
+
+    /// Remember the evaluation of an expression
+    pub(crate) fn store_evaluation(&self, expression: &str, evaluation: Evaluation) {
+        self.evaluations.lock().unwrap().insert(expression_key(expression), (evaluation, Instant::now() + self.ttl));
+    }
+
+    /// Drop every cached result
+    pub(crate) fn clear(&self) {
+        self.calculations.lock().unwrap().clear();
+        self.evaluations.lock().unwrap().clear();
+    }
+
+    // The unexpired value of `key`; expired entries are dropped and count as misses
+    fn lookup<K: Hash + Eq + Clone, V: Clone>(&self, entries: &Mutex<Lru<K, Expiring<V>>>, key: &K) -> Option<V> {
+        let mut entries = entries.lock().unwrap();
+        match entries.get(key) {
+            Some((value, expires)) if Instant::now() < expires => {
+                self.metrics.calculator_cache_hit();
+                Some(value)
+            }
+            Some(_) => {
+                entries.remove(key);
+                self.metrics.calculator_cache_miss();
+                None
+            }
+            None => {
+                self.metrics.calculator_cache_miss();
+                None
+            }
+        }
+    }
+}
+
+// Key of a calculation; None if an operand is not finite
+fn calculation_key(first: f64, second: f64, operation: Operation) -> Option<CalculationKey> {
+    (first.is_finite() && second.is_finite()).then(|| (first.to_bits(), second.to_bits(), operation as i32))
+}
+
+// Key of an expression
+// Whitespace never separates two tokens that would otherwise merge in a
+// valid expression, so dropping it keeps different expressions apart
+fn expression_key(expression: &str) -> String {
+    expression.split_whitespace().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_evicts_and_counts_as_miss() {
+        let metrics = ClientMetrics::default();
+        let cache = CalculatorCache::new(4, Duration::ZERO, metrics.clone());
+
+        cache.store_calculation(1.0, 2.0, Operation::Add, 3.0);
+        assert_eq!(cache.calculation(1.0, 2.0, Operation::Add), None);
+        assert_eq!(cache.calculations.lock().unwrap().len(), 0);
+        assert_eq!(metrics.snapshot().calculator_cache_misses, 1);
+    }
+
+    #[test]
+    fn test_non_finite_skipped_and_whitespace_ignored() {
+        let metrics = ClientMetrics::default();
+        let cache = CalculatorCache::new(4, Duration::from_secs(60), metrics.clone());
+
+        cache.store_calculation(f64::INFINITY, 1.0, Operation::Add, f64::INFINITY);
+        assert_eq!(cache.calculation(f64::INFINITY, 1.0, Operation::Add), None);
+        assert_eq!(cache.calculations.lock().unwrap().len(), 0);
+
+        let evaluation = Evaluation { result: 5.0, steps: Vec::new(), on_server: true };
+        cache.store_evaluation("2 + 3", evaluation.clone());
+        assert_eq!(cache.evaluation(" 2+3"), Some(evaluation));
+
+        // Non-finite lookups are not counted
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.calculator_cache_hits, snapshot.calculator_cache_misses), (1, 0));
+    }
+}
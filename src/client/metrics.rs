@@ -0,0 +1,54 @@
+//! Client Metrics
+//! Counters a client maintains about itself, readable at any time
+//! through a cheap, cloneable handle:
+//! 1. ClientMetrics: shared handle updated by the client and its clones
+//! 2. ClientMetricsSnapshot: plain copy of the values at one point in time
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Handle to the live metrics of one client
+///
+/// Obtained from `GrpcClient::metrics()`; every clone of the client, and
+/// every clone of this handle, shares the same counters.
+#[derive(Debug, Clone, Default)]
+pub struct ClientMetrics {
+    counters: Arc<Counters>,
+}
+
+// Backing storage shared by all handles
+#[derive(Debug, Default)]
+struct Counters {
+    calculator_cache_hits: AtomicU64,
+    calculator_cache_misses: AtomicU64,
+}
+
+/// Point-in-time copy of the client metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientMetricsSnapshot {
+    /// Calculations and evaluations answered from the calculator cache
+    /// (see `GrpcClientBuilder::calculator_cache`)
+    pub calculator_cache_hits: u64,
+    /// Cacheable calls that were not in the cache, or had expired, and went to the server
+    pub calculator_cache_misses: u64,
+}
+
+impl ClientMetrics {
+    /// Read the current values
+    pub fn snapshot(&self) -> ClientMetricsSnapshot {
+        ClientMetricsSnapshot {
+            calculator_cache_hits: self.counters.calculator_cache_hits.load(Ordering::Relaxed),
+            calculator_cache_misses: self.counters.calculator_cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    // A call was answered from the calculator cache
+    pub(crate) fn calculator_cache_hit(&self) {
+        self.counters.calculator_cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // A cacheable call had to go to the server
+    pub(crate) fn calculator_cache_miss(&self) {
+        self.counters.calculator_cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -1,16 +1,51 @@
 //! Client Module Organization
 //! This module provides a clean API for the gRPC client implementation:
 //! - client: Contains the core GrpcClient implementation
-//! - services: Contains specific service clients (Calculator, Echo)
+//! - services: Contains specific service clients (Calculator, Echo, Admin, Broadcast)
+//! - proxy: HTTP CONNECT connector used by GrpcClientBuilder::http_proxy
+//! - bind: Connector binding a local address, used by GrpcClientBuilder::bind_local
+//! - pipe: Named pipe connector used by GrpcClientBuilder::named_pipe (Windows)
+//! - observer: ClientObserver callbacks run around every call
+//! - state: Connection state inferred from call outcomes (ConnectionState)
+//! - bench: Echo throughput benchmark behind `grpc_client --bench`
+//! - ping: Sequential echo probes with statistics behind `grpc_client ping`
+//! - snapshot: Serializable summary of the builder's options (ClientConfigSnapshot)
+//! - retry: Establishment retries and resubscription of streaming calls
+//! - budget: Total time bound on each call, connecting included
+//! - per_runtime: Channels reopened after their runtime shuts down, one client per runtime
+//! - cache: Calculator results answered on the client (GrpcClientBuilder::calculator_cache)
+//! - metrics: Counters of the client, e.g. calculator cache hits (ClientMetrics)
 //!
 //! The pub use statements make the main types directly available to users
 //! of our library, following the facade pattern for a cleaner API.
 
 // Declare our submodules
+#[allow(clippy::module_inception)]
 mod client;
 mod services;
+mod proxy;
+mod bind;
+#[cfg(windows)]
+mod pipe;
+mod observer;
+mod state;
+mod bench;
+mod ping;
+mod snapshot;
+mod retry;
+mod budget;
+mod per_runtime;
+mod cache;
+mod metrics;
 
 // Re-export main types for easier access
 // Users can now use them directly from the crate root
-pub use client::GrpcClient;
+pub use client::{GrpcClient, GrpcClientBuilder};
+pub use per_runtime::GrpcClientFactory;
+pub use observer::ClientObserver;
+pub use state::ConnectionState;
+pub use bench::{run_bench, BenchReport};
+pub use ping::PingStats;
+pub use snapshot::ClientConfigSnapshot;
+pub use metrics::{ClientMetrics, ClientMetricsSnapshot};
 pub use services::*;  // All public items from services module
\ No newline at end of file
@@ -0,0 +1,167 @@
+//! Client Call Observers
+//! Lets applications watch every call made through the service wrappers
+//! (for their own metrics or audit logs) without wrapping each method:
+//! 1. ClientObserver: user trait with no-op default methods
+//! 2. CallObserver: the optional observer as stored on GrpcClient
+//! 3. ObservedCall: one call in flight; reports its outcome exactly once
+//! 4. ObservedStream: response stream that reports when it ends
+//!
+//! The same outcomes drive the inferred connection state (see state.rs).
+//! Observer methods run inline on the calling task and must be cheap.
+//! A panicking observer is logged and otherwise ignored.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_stream::Stream;
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Status};
+use tracing::error;
+use super::state::StateWatch;
+
+/// Callbacks invoked around every call made through `GrpcClient`
+///
+/// Both methods default to doing nothing, so implementors only override
+/// what they need. `path` is the full gRPC method path, e.g.
+/// `/echo.EchoService/Echo`. Streaming calls report their response once
+/// the response stream ends; a stream dropped early reports `Cancelled`.
+/// Calls rejected by client-side validation never reach the network and
+/// are not reported.
+pub trait ClientObserver: Send + Sync + 'static {
+    /// A request is about to be sent
+    fn on_request(&self, _path: &str, _metadata: &MetadataMap) {}
+
+    /// A call finished with `code` after `latency`
+    fn on_response(&self, _path: &str, _code: Code, _latency: Duration) {}
+}
+
+// Observer and connection state watch configured on the client, if any
+#[derive(Clone, Default)]
+pub(crate) struct CallObserver {
+    observer: Option<Arc<dyn ClientObserver>>,
+    state: Option<StateWatch>,
+}
+
+impl CallObserver {
+    // Same settings with `observer`, replacing any earlier one
+    pub(crate) fn with_observer(self, observer: impl ClientObserver) -> Self {
+        Self { observer: Some(Arc::new(observer)), ..self }
+    }
+
+    // Same settings with `state` told about every call
+    pub(crate) fn with_state(self, state: StateWatch) -> Self {
+        Self { state: Some(state), ..self }
+    }
+
+    // Whether an observer was configured
+    pub(crate) fn is_set(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    // Whether a connection state callback was configured
+    pub(crate) fn watches_state(&self) -> bool {
+        self.state.is_some()
+    }
+
+    // Report the request and start timing the call
+    pub(crate) fn start(&self, path: &'static str, metadata: &MetadataMap) -> ObservedCall {
+        if let Some(state) = &self.state {
+            state.call_started();
+        }
+        if let Some(observer) = &self.observer {
+            guarded(path, || observer.on_request(path, metadata));
+        }
+        ObservedCall { observer: self.observer.clone(), state: self.state.clone(), path, started: Instant::now() }
+    }
+}
+
+// One call in flight
+// Reports Cancelled if dropped before finish(), e.g. when the caller
+// drops the call future or a response stream early
+pub(crate) struct ObservedCall {
+    observer: Option<Arc<dyn ClientObserver>>,
+    state: Option<StateWatch>,  // Taken when the outcome is reported
+    path: &'static str,
+    started: Instant,
+}
+
+impl ObservedCall {
+    // Report the outcome of a unary call or of opening a stream
+    pub(crate) fn finish<T>(self, result: &Result<T, Status>) {
+        self.report(match result {
+            Ok(_) => Code::Ok,
+            Err(status) => status.code(),
+        });
+    }
+
+    // Report the outcome of a streaming call once its response stream ends
+    pub(crate) fn finish_stream<S>(self, result: Result<S, Status>) -> Result<ObservedStream<S>, Status> {
+        match result {
+            Ok(stream) => Ok(ObservedStream { inner: stream, call: Some(self) }),
+            Err(status) => {
+                self.report(status.code());
+                Err(status)
+            }
+        }
+    }
+
+    fn report(mut self, code: Code) {
+        if let Some(state) = self.state.take() {
+            state.call_finished(code);
+        }
+        if let Some(observer) = self.observer.take() {
+            let latency = self.started.elapsed();
+            guarded(self.path, || observer.on_response(self.path, code, latency));
+        }
+    }
+}
+
+impl Drop for ObservedCall {
+    fn drop(&mut self) {
+        if let Some(observer) = self.observer.take() {
+            let latency = self.started.elapsed();
+            guarded(self.path, || observer.on_response(self.path, Code::Cancelled, latency));
+        }
+    }
+}
+
+// Response stream reporting the call outcome when it ends:
+// Ok after the last message, or the code of the first error
+pub(crate) struct ObservedStream<S> {
+    inner: S,
+    call: Option<ObservedCall>,
+}
+
+impl<S, T> Stream for ObservedStream<S>
+where
+    S: Stream<Item = Result<T, Status>> + Unpin,
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(item) => item,
+        };
+        let outcome = match &item {
+            None => Some(Code::Ok),
+            Some(Err(status)) => Some(status.code()),
+            Some(Ok(_)) => None,
+        };
+        if let Some(code) = outcome {
+            if let Some(call) = self.call.take() {
+                call.report(code);
+            }
+        }
+        Poll::Ready(item)
+    }
+}
+
+// Run an observer callback, containing any panic
+fn guarded(path: &str, callback: impl FnOnce()) {
+    if catch_unwind(AssertUnwindSafe(callback)).is_err() {
+        error!("Client observer panicked while observing {}", path);
+    }
+}
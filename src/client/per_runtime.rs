@@ -0,0 +1,150 @@
+//! Clients Across Tokio Runtimes
+//! A tonic channel runs its connections as tasks on the runtime it was
+//! opened on, and they die with that runtime. This file lets clients
+//! outlive it:
+//! 1. RuntimeWatch: tells whether the runtime a channel was opened on is gone
+//! 2. SharedChannel: a client's channel, reopened on the current runtime
+//!    once its own is gone (see GrpcClient::reconnect)
+//! 3. GrpcClientFactory: one client per runtime, for applications that use
+//!    several runtimes at once (see GrpcClientBuilder::build_per_runtime)
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::{self, Handle};
+use tokio::sync::oneshot;
+use tonic::transport::Channel;
+use tonic::Status;
+use tracing::{info, warn};
+use super::client::{GrpcClient, GrpcClientBuilder};
+
+// Watches the runtime that was current when it was created
+// A task parked on that runtime holds the receiver; the runtime drops the
+// task when it shuts down, which closes the channel. Dropping the watch
+// ends the task.
+#[derive(Debug)]
+pub(crate) struct RuntimeWatch {
+    alive: Option<oneshot::Sender<()>>,
+}
+
+impl RuntimeWatch {
+    // Watch the current runtime; outside any runtime there is nothing to watch
+    pub(crate) fn current() -> Self {
+        let Ok(handle) = Handle::try_current() else {
+            return Self { alive: None };
+        };
+        let (alive, parked) = oneshot::channel::<()>();
+        handle.spawn(async move {
+            let _ = parked.await;
+        });
+        Self { alive: Some(alive) }
+    }
+
+    // Whether the watched runtime has shut down
+    pub(crate) fn is_gone(&self) -> bool {
+        self.alive.as_ref().is_some_and(|alive| alive.is_closed())
+    }
+}
+
+// A channel and the runtime its connections run on
+struct OpenChannel {
+    channel: Channel,
+    runtime: RuntimeWatch,
+}
+
+/// Channel of a client, shared by its clones
+///
+/// Reopened with the client's builder when the runtime it was opened on is
+/// gone, so the next service handle gets a working channel.
+#[derive(Clone)]
+pub(crate) struct SharedChannel {
+    open: Arc<Mutex<OpenChannel>>,
+    builder: Arc<GrpcClientBuilder>,  // Reopens the channel; already validated by connect
+}
+
+impl SharedChannel {
+    pub(crate) fn new(channel: Channel, builder: GrpcClientBuilder) -> Self {
+        let open = OpenChannel { channel, runtime: RuntimeWatch::current() };
+        Self { open: Arc::new(Mutex::new(open)), builder: Arc::new(builder) }
+    }
+
+    // The channel, reopened first if its runtime is gone and a runtime is current
+    pub(crate) fn get(&self) -> Channel {
+        let mut open = self.open.lock().unwrap();
+        if open.runtime.is_gone() && Handle::try_current().is_ok() {
+            info!("Runtime of the channel to {} has shut down; reopening it", self.builder.uri());
+            if let Err(status) = self.reopen(&mut open) {
+                warn!("Failed to reopen the channel to {}: {}", self.builder.uri(), status.message());
+            }
+        }
+        open.channel.clone()
+    }
+
+    // Open a new channel on the current runtime for every clone
+    pub(crate) fn reconnect(&self) -> Result<(), Status> {
+        if Handle::try_current().is_err() {
+            return Err(Status::failed_precondition("reconnect must be called within a tokio runtime"));
+        }
+        self.reopen(&mut self.open.lock().unwrap())
+    }
+
+    fn reopen(&self, open: &mut OpenChannel) -> Result<(), Status> {
+        let channel = self.builder.open_lazy()?;
+        *open = OpenChannel { channel, runtime: RuntimeWatch::current() };
+        Ok(())
+    }
+}
+
+/// Hands out one client per tokio runtime
+///
+/// Created with `GrpcClientBuilder::build_per_runtime`. Each runtime gets
+/// its own client and connection the first time it asks, so runtimes
+/// never share connection tasks; clients of runtimes that have shut down
+/// are dropped when the next client is requested. Cloning the factory
+/// shares the clients.
+#[derive(Clone)]
+pub struct GrpcClientFactory {
+    builder: GrpcClientBuilder,
+    clients: Arc<Mutex<HashMap<runtime::Id, (GrpcClient, RuntimeWatch)>>>,
+}
+
+impl fmt::Debug for GrpcClientFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrpcClientFactory")
+            .field("builder", &self.builder)
+            .field("runtimes", &self.clients.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl GrpcClientFactory {
+    pub(crate) fn new(builder: GrpcClientBuilder) -> Self {
+        Self { builder, clients: Arc::default() }
+    }
+
+    /// The client of the current runtime, connecting it on first use
+    ///
+    /// # Returns
+    /// * `Result<GrpcClient, Status>` - The client, `FailedPrecondition` outside a tokio
+    ///   runtime, or the error `GrpcClientBuilder::connect` reports.
+    pub fn client(&self) -> Result<GrpcClient, Status> {
+        let handle = Handle::try_current()
+            .map_err(|_| Status::failed_precondition("a per-runtime client must be requested within a tokio runtime"))?;
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|_, (_, runtime)| !runtime.is_gone());
+        if let Some((client, _)) = clients.get(&handle.id()) {
+            return Ok(client.clone());
+        }
+        let client = self.builder.clone().connect()?;
+        clients.insert(handle.id(), (client.clone(), RuntimeWatch::current()));
+        Ok(client)
+    }
+
+    /// Number of runtimes currently holding a client
+    ///
+    /// # Returns
+    /// * `usize` - Clients kept, including those of runtimes gone since the last `client` call.
+    pub fn runtimes(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+}
@@ -0,0 +1,166 @@
+//! Echo Ping
+//! Sequential minimal echoes with ping-style statistics, as used by
+//! `grpc_client ping`:
+//! 1. EchoService::ping_n: sends probes one after another at an interval
+//! 2. PingStats: sent/received/lost counts and min/avg/max/p95 latency
+//!
+//! Probes are recorded as they complete, so the statistics stay valid when
+//! a run is interrupted (e.g. by Ctrl-C) and its future dropped.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+use tonic::Status;
+use super::bench::percentile;
+use super::services::EchoService;
+
+// Payload of every probe; short so timing is dominated by the network
+const PROBE_MESSAGE: &str = "ping";
+
+/// Statistics of the probes made so far
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PingStats {
+    /// Latencies of the answered probes, in the order they were sent
+    latencies: Vec<Duration>,
+    /// Probes sent, answered or not
+    sent: usize,
+}
+
+impl PingStats {
+    /// Record the outcome of one probe
+    ///
+    /// # Arguments
+    /// * `result` - The probe's latency, or the status it failed with.
+    pub fn record(&mut self, result: &Result<Duration, Status>) {
+        self.sent += 1;
+        if let Ok(latency) = result {
+            self.latencies.push(*latency);
+        }
+    }
+
+    /// Probes sent
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    /// Probes answered
+    pub fn received(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Probes that failed
+    pub fn lost(&self) -> usize {
+        self.sent - self.received()
+    }
+
+    /// Share of failed probes in percent; zero before the first probe
+    pub fn loss_percent(&self) -> f64 {
+        if self.sent == 0 { 0.0 } else { self.lost() as f64 * 100.0 / self.sent as f64 }
+    }
+
+    /// Fastest answer; zero without answers
+    pub fn min(&self) -> Duration {
+        self.latencies.iter().min().copied().unwrap_or_default()
+    }
+
+    /// Mean latency of the answers; zero without answers
+    pub fn avg(&self) -> Duration {
+        match u32::try_from(self.latencies.len()) {
+            Ok(count) if count > 0 => self.latencies.iter().sum::<Duration>() / count,
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Slowest answer; zero without answers
+    pub fn max(&self) -> Duration {
+        self.latencies.iter().max().copied().unwrap_or_default()
+    }
+
+    /// 95th percentile latency (nearest rank); zero without answers
+    pub fn p95(&self) -> Duration {
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        percentile(&sorted, 95)
+    }
+}
+
+impl fmt::Display for PingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} probes sent, {} received, {} lost ({:.1}% loss)",
+            self.sent, self.received(), self.lost(), self.loss_percent()
+        )?;
+        write!(
+            f,
+            "latency min/avg/max/p95 = {:?}/{:?}/{:?}/{:?}",
+            self.min(), self.avg(), self.max(), self.p95()
+        )
+    }
+}
+
+impl EchoService {
+    /// Send one minimal echo and measure its round trip
+    ///
+    /// # Returns
+    /// * `Result<Duration, Status>` - The latency, or the status of the failed echo.
+    pub async fn ping(&mut self) -> Result<Duration, Status> {
+        let started = Instant::now();
+        self.echo(PROBE_MESSAGE).await?;
+        Ok(started.elapsed())
+    }
+
+    /// Send `count` probes one after another, `interval` apart
+    ///
+    /// Each probe starts `interval` after the previous one started, or right
+    /// after it finished if it took longer. Outcomes go into `stats` as they
+    /// complete, so dropping the future keeps everything recorded so far.
+    ///
+    /// # Arguments
+    /// * `count` - Probes to send; 0 sends until the future is dropped.
+    /// * `interval` - Time between the starts of consecutive probes.
+    /// * `stats` - Receives every probe's outcome.
+    /// * `on_probe` - Called after each probe with its number (from 1) and outcome.
+    pub async fn ping_n(
+        &mut self,
+        count: usize,
+        interval: Duration,
+        stats: &mut PingStats,
+        mut on_probe: impl FnMut(usize, &Result<Duration, Status>),
+    ) {
+        let mut ticks = tokio::time::interval(interval.max(Duration::from_millis(1)));
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut probe = 0;
+        while count == 0 || probe < count {
+            ticks.tick().await;
+            probe += 1;
+            let result = self.ping().await;
+            stats.record(&result);
+            on_probe(probe, &result);
+        }
+    }
+}
+
+// Unit tests for the statistics
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_stats_summary() {
+        let mut stats = PingStats::default();
+        assert_eq!((stats.loss_percent(), stats.avg(), stats.p95()), (0.0, Duration::ZERO, Duration::ZERO));
+
+        for ms in [40, 10, 30, 20] {
+            stats.record(&Ok(Duration::from_millis(ms)));
+        }
+        stats.record(&Err(Status::unavailable("down")));
+
+        assert_eq!((stats.sent(), stats.received(), stats.lost()), (5, 4, 1));
+        assert_eq!(stats.loss_percent(), 20.0);
+        assert_eq!(stats.min(), Duration::from_millis(10));
+        assert_eq!(stats.avg(), Duration::from_millis(25));
+        assert_eq!(stats.max(), Duration::from_millis(40));
+        assert_eq!(stats.p95(), Duration::from_millis(40));
+        assert!(stats.to_string().starts_with("5 probes sent, 4 received, 1 lost (20.0% loss)"), "{}", stats);
+    }
+}
@@ -0,0 +1,57 @@
+//! Named Pipe Connector (Windows)
+//! This file implements the connector behind GrpcClientBuilder::named_pipe:
+//! 1. Open the server's named pipe instead of a TCP connection
+//! 2. Wait for a free pipe instance while the server's are all busy
+//! 3. Hand the pipe to tonic as if it were a TCP stream
+//!
+//! Failures to open the pipe are reported as Unavailable naming the pipe.
+
+use std::{future::Future, pin::Pin, task::{Context, Poll}};
+use std::time::{Duration, Instant};
+use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient};
+use tonic::{transport::Uri, Status};
+use tower::Service;
+
+// Windows error while every instance of the pipe is connected to a client
+const ERROR_PIPE_BUSY: i32 = 231;
+// Pause between attempts on a busy pipe, and the total time to keep trying
+const BUSY_RETRY_DELAY: Duration = Duration::from_millis(20);
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// tower Service<Uri> that opens the named pipe; the URI is ignored
+#[derive(Debug, Clone)]
+pub(crate) struct NamedPipeConnector {
+    path: String,  // Full pipe path, already validated by crate::pipe::pipe_path
+}
+
+impl NamedPipeConnector {
+    pub(crate) fn new(path: String) -> Self {
+        Self { path }
+    }
+}
+
+impl Service<Uri> for NamedPipeConnector {
+    type Response = NamedPipeClient;
+    type Error = Status;
+    type Future = Pin<Box<dyn Future<Output = Result<NamedPipeClient, Status>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let path = self.path.clone();
+        Box::pin(async move {
+            let started = Instant::now();
+            loop {
+                match ClientOptions::new().open(&path) {
+                    Ok(client) => return Ok(client),
+                    // The server creates the next instance as soon as one is taken
+                    Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) && started.elapsed() < BUSY_TIMEOUT => {}
+                    Err(e) => return Err(Status::unavailable(format!("cannot open named pipe {}: {}", path, e))),
+                }
+                tokio::time::sleep(BUSY_RETRY_DELAY).await;
+            }
+        })
+    }
+}
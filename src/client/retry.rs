@@ -0,0 +1,173 @@
+//! Streaming Call Retries
+//! Retry policy for server-streaming calls, set with
+//! `GrpcClientBuilder::stream_retries` and `resubscribe_on_error`:
+//! 1. Establishing a stream (the call before any message flows) is retried
+//!    when it fails with Unavailable, e.g. a restarting server or an
+//!    injected fault; nothing has been delivered yet, so this is always safe
+//! 2. An error after messages have flowed is returned to the caller as is
+//! 3. With resubscribe_on_error the stream is instead re-established after
+//!    a mid-stream Unavailable, and messages the caller already received
+//!    are skipped by their resume key (e.g. an echo's sequence number)
+//!
+//! Other codes are never retried: they describe the request, not the connection.
+
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Code, Status};
+use tracing::warn;
+
+// Whether a failure is worth retrying: only the connection-level one
+fn retryable(status: &Status) -> bool {
+    status.code() == Code::Unavailable
+}
+
+// Retry settings shared by the streaming calls of one client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct StreamRetry {
+    pub(crate) retries: u32,  // Attempts made after a failed establishment
+    pub(crate) delay: Duration,  // Pause before each of them
+    pub(crate) resubscribe: bool,  // Re-establish after a mid-stream Unavailable
+}
+
+impl StreamRetry {
+    // Establish a stream with `open`, retrying while it fails with Unavailable
+    pub(crate) async fn establish<S, F, Fut>(&self, path: &str, open: &mut F) -> Result<S, Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<S, Status>>,
+    {
+        let attempts = self.retries.saturating_add(1);
+        let mut attempt = 1;
+        loop {
+            match open().await {
+                Err(status) if retryable(&status) && attempt < attempts => {
+                    warn!(
+                        "Establishing {} failed (attempt {} of {}): {}; retrying in {:?}",
+                        path, attempt, attempts, status.message(), self.delay
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(self.delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Forward `stream`, re-establishing it with `open` after a mid-stream
+    // Unavailable; items whose `key` is not past the last forwarded one are
+    // skipped, so the caller sees each message once
+    pub(crate) fn resubscribing<S, T, K, F, Fut>(
+        self,
+        path: &'static str,
+        stream: S,
+        mut open: F,
+        key: fn(&T) -> K,
+    ) -> ReceiverStream<Result<T, Status>>
+    where
+        S: Stream<Item = Result<T, Status>> + Unpin + Send + 'static,
+        T: Send + 'static,
+        K: PartialOrd + Send + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<S, Status>> + Send,
+    {
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            let mut stream = stream;
+            let mut last: Option<K> = None;
+            loop {
+                let item = tokio::select! {
+                    item = stream.next() => item,
+                    // The caller dropped the stream
+                    _ = tx.closed() => return,
+                };
+                let forwarded = match item {
+                    None => return,
+                    Some(Ok(item)) => {
+                        let next = key(&item);
+                        if last.as_ref().is_some_and(|last| next <= *last) {
+                            continue;
+                        }
+                        last = Some(next);
+                        Ok(item)
+                    }
+                    Some(Err(status)) if retryable(&status) => {
+                        warn!("{} failed mid-stream: {}; resubscribing", path, status.message());
+                        match self.establish(path, &mut open).await {
+                            Ok(reopened) => {
+                                stream = reopened;
+                                continue;
+                            }
+                            Err(status) => Err(status),
+                        }
+                    }
+                    Some(Err(status)) => Err(status),
+                };
+                let failed = forwarded.is_err();
+                if tx.send(forwarded).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use tokio_stream::Iter;
+
+    type Items = Iter<std::vec::IntoIter<Result<u32, Status>>>;
+
+    fn items(items: Vec<Result<u32, Status>>) -> Items {
+        tokio_stream::iter(items)
+    }
+
+    #[tokio::test]
+    async fn test_establish_retries_only_unavailable() {
+        let retry = StreamRetry { retries: 2, ..Default::default() };
+        let calls = AtomicU32::new(0);
+        let mut open = || {
+            let call = calls.fetch_add(1, Ordering::Relaxed);
+            async move { if call < 2 { Err(Status::unavailable("down")) } else { Ok(call) } }
+        };
+        assert_eq!(retry.establish("test", &mut open).await.unwrap(), 2);
+
+        calls.store(0, Ordering::Relaxed);
+        let once = StreamRetry { retries: 1, ..Default::default() };
+        assert_eq!(once.establish("test", &mut open).await.unwrap_err().code(), Code::Unavailable);
+        assert_eq!(calls.load(Ordering::Relaxed), 2, "retried past the limit");
+
+        let invalid = AtomicU32::new(0);
+        let mut reject = || {
+            invalid.fetch_add(1, Ordering::Relaxed);
+            async { Err::<(), _>(Status::invalid_argument("bad")) }
+        };
+        assert_eq!(retry.establish("test", &mut reject).await.unwrap_err().code(), Code::InvalidArgument);
+        assert_eq!(invalid.load(Ordering::Relaxed), 1, "InvalidArgument was retried");
+    }
+
+    #[tokio::test]
+    async fn test_resubscribing_skips_delivered_items() {
+        let retry = StreamRetry { resubscribe: true, ..Default::default() };
+        let reopened = Arc::new(AtomicU32::new(0));
+        let counter = reopened.clone();
+        let first = items(vec![Ok(1), Ok(2), Err(Status::unavailable("connection lost"))]);
+        let open = move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            async { Ok(items(vec![Ok(1), Ok(2), Ok(3), Err(Status::resource_exhausted("full"))])) }
+        };
+        let received: Vec<_> = retry.resubscribing("test", first, open, |item| *item).collect().await;
+
+        assert_eq!(reopened.load(Ordering::Relaxed), 1);
+        assert_eq!(received.len(), 4, "{:?}", received);
+        let values: Vec<u32> = received[..3].iter().map(|item| *item.as_ref().unwrap()).collect();
+        assert_eq!(values, [1, 2, 3]);
+        assert_eq!(received[3].as_ref().unwrap_err().code(), Code::ResourceExhausted, "other errors are not retried");
+    }
+}
@@ -0,0 +1,251 @@
+//! Admin Service Client Implementation
+//! Thin wrapper over the generated admin client for operational queries
+//! such as latency percentiles and server information, and for changing
+//! the server's log level, runtime settings and service states (which
+//! needs the admin token, see `with_token`).
+
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::MetadataValue;
+use tonic::{Request, Status};
+use tracing::debug;
+use crate::proto::admin::{
+    admin_service_client::AdminServiceClient,
+    LatencyStatsRequest, LatencyStatsResponse, ServerInfoRequest, ServerInfoResponse,
+    SetLogLevelRequest, GetLogLevelRequest,
+    GetConfigRequest, SetConfigRequest, ConfigResponse,
+    SetServiceStateRequest, ServingState, WatchOperationsRequest,
+    CacheStatsRequest, CacheStatsResponse, ListConnectionsRequest, ConnectionInfo,
+};
+use crate::proto::calculator::Operation;
+use super::super::client::{GrpcClient, TracedChannel};
+use super::super::observer::CallObserver;
+use super::super::retry::StreamRetry;
+
+// Method paths reported to the observer
+const LATENCY_STATS_PATH: &str = "/admin.AdminService/LatencyStats";
+const SERVER_INFO_PATH: &str = "/admin.AdminService/ServerInfo";
+const SET_LOG_LEVEL_PATH: &str = "/admin.AdminService/SetLogLevel";
+const GET_LOG_LEVEL_PATH: &str = "/admin.AdminService/GetLogLevel";
+const GET_CONFIG_PATH: &str = "/admin.AdminService/GetConfig";
+const SET_CONFIG_PATH: &str = "/admin.AdminService/SetConfig";
+const SET_SERVICE_STATE_PATH: &str = "/admin.AdminService/SetServiceState";
+const WATCH_OPERATIONS_PATH: &str = "/admin.AdminService/WatchOperations";
+const CACHE_STATS_PATH: &str = "/admin.AdminService/CacheStats";
+const LIST_CONNECTIONS_PATH: &str = "/admin.AdminService/ListConnections";
+
+// Client wrapper with generated gRPC client
+#[derive(Clone)]
+pub struct AdminService {
+    // Internal generated client instance
+    client: AdminServiceClient<TracedChannel>,
+    // Notified around every call (see GrpcClientBuilder::observer)
+    observer: CallObserver,
+    // Sent as "authorization: Bearer <token>" with every call when set
+    token: Option<String>,
+    // Establishment retries of watch_operations (see GrpcClientBuilder::stream_retries)
+    stream_retry: StreamRetry,
+}
+
+// Extension method for main client
+impl GrpcClient {
+    /// Create new admin service instance
+    ///
+    /// # Returns
+    /// * `AdminService` - A new instance of the admin service client.
+    pub fn admin(&self) -> AdminService {
+        AdminService {
+            client: AdminServiceClient::new(self.traced_channel()),
+            observer: self.observer(),
+            token: None,
+            stream_retry: self.stream_retry(),
+        }
+    }
+}
+
+impl AdminService {
+    /// Authenticate subsequent calls with the server's admin token
+    /// 
+    /// # Arguments
+    /// * `token` - The token configured with `GrpcServerBuilder::admin_token`.
+    /// 
+    /// # Returns
+    /// * `Self` - This service, sending the token with every call.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    // Wrap a message, attaching the admin token if one was given
+    fn request<T>(&self, message: T) -> Result<Request<T>, Status> {
+        let mut request = Request::new(message);
+        if let Some(token) = &self.token {
+            let value = MetadataValue::try_from(format!("Bearer {}", token))
+                .map_err(|_| Status::invalid_argument("admin token contains characters not allowed in metadata"))?;
+            request.metadata_mut().insert("authorization", value);
+        }
+        Ok(request)
+    }
+
+    /// Latency percentiles of the server's recent application RPCs
+    ///
+    /// # Returns
+    /// * `Result<LatencyStatsResponse, Status>` - p50/p90/p99 in milliseconds and the sample count.
+    pub async fn latency_stats(&mut self) -> Result<LatencyStatsResponse, Status> {
+        let request = self.request(LatencyStatsRequest {})?;
+        let call = self.observer.start(LATENCY_STATS_PATH, request.metadata());
+        let response = self.client.latency_stats(request).await;
+        call.finish(&response);
+        let stats = response?.into_inner();
+        debug!("Received latency stats: {:?}", stats);
+        Ok(stats)
+    }
+
+    /// Hit and miss counts of the server's calculator and echo caches
+    ///
+    /// # Returns
+    /// * `Result<CacheStatsResponse, Status>` - The counts; zero for a cache the server runs without.
+    pub async fn cache_stats(&mut self) -> Result<CacheStatsResponse, Status> {
+        let request = self.request(CacheStatsRequest {})?;
+        let call = self.observer.start(CACHE_STATS_PATH, request.metadata());
+        let response = self.client.cache_stats(request).await;
+        call.finish(&response);
+        let stats = response?.into_inner();
+        debug!("Received cache stats: {:?}", stats);
+        Ok(stats)
+    }
+
+    /// Client connections the server has open, oldest first
+    ///
+    /// # Returns
+    /// * `Result<Vec<ConnectionInfo>, Status>` - Peer address, accept time and request count
+    ///   of each connection, including the one this call is made on.
+    pub async fn list_connections(&mut self) -> Result<Vec<ConnectionInfo>, Status> {
+        let request = self.request(ListConnectionsRequest {})?;
+        let call = self.observer.start(LIST_CONNECTIONS_PATH, request.metadata());
+        let response = self.client.list_connections(request).await;
+        call.finish(&response);
+        let connections = response?.into_inner().connections;
+        debug!("Received {} open connections", connections.len());
+        Ok(connections)
+    }
+
+    /// Version and tokio runtime settings of the server
+    ///
+    /// # Returns
+    /// * `Result<ServerInfoResponse, Status>` - The server information.
+    pub async fn server_info(&mut self) -> Result<ServerInfoResponse, Status> {
+        let request = self.request(ServerInfoRequest {})?;
+        let call = self.observer.start(SERVER_INFO_PATH, request.metadata());
+        let response = self.client.server_info(request).await;
+        call.finish(&response);
+        let info = response?.into_inner();
+        debug!("Received server info: {:?}", info);
+        Ok(info)
+    }
+
+    /// Change the server's log level at runtime; requires `with_token`
+    /// 
+    /// # Arguments
+    /// * `target` - A module path such as `embedded_recruitment_task::server`, or `None` for the default level.
+    /// * `level` - One of `off`, `error`, `warn`, `info`, `debug`, `trace`.
+    /// 
+    /// # Returns
+    /// * `Result<String, Status>` - The server's log filter after the change.
+    pub async fn set_log_level(&mut self, target: Option<&str>, level: &str) -> Result<String, Status> {
+        let request = self.request(SetLogLevelRequest {
+            target: target.unwrap_or_default().to_string(),
+            level: level.to_string(),
+        })?;
+        let call = self.observer.start(SET_LOG_LEVEL_PATH, request.metadata());
+        let response = self.client.set_log_level(request).await;
+        call.finish(&response);
+        let filter = response?.into_inner().filter;
+        debug!("Server log filter is now {}", filter);
+        Ok(filter)
+    }
+
+    /// The server's current log filter, in RUST_LOG syntax
+    /// 
+    /// # Returns
+    /// * `Result<String, Status>` - E.g. `"info,h2=warn"`.
+    pub async fn get_log_level(&mut self) -> Result<String, Status> {
+        let request = self.request(GetLogLevelRequest {})?;
+        let call = self.observer.start(GET_LOG_LEVEL_PATH, request.metadata());
+        let response = self.client.get_log_level(request).await;
+        call.finish(&response);
+        let filter = response?.into_inner().filter;
+        debug!("Server log filter is {}", filter);
+        Ok(filter)
+    }
+
+    /// The server's runtime-adjustable settings
+    /// 
+    /// # Returns
+    /// * `Result<ConfigResponse, Status>` - The settings currently in effect.
+    pub async fn get_config(&mut self) -> Result<ConfigResponse, Status> {
+        let request = self.request(GetConfigRequest {})?;
+        let call = self.observer.start(GET_CONFIG_PATH, request.metadata());
+        let response = self.client.get_config(request).await;
+        call.finish(&response);
+        let config = response?.into_inner();
+        debug!("Server settings are {:?}", config);
+        Ok(config)
+    }
+
+    /// Change the server's runtime settings without a restart; requires `with_token`
+    /// 
+    /// # Arguments
+    /// * `changes` - The settings to change; fields left unset keep their current value.
+    /// 
+    /// # Returns
+    /// * `Result<ConfigResponse, Status>` - The server's settings after the change.
+    pub async fn set_config(&mut self, changes: SetConfigRequest) -> Result<ConfigResponse, Status> {
+        let request = self.request(changes)?;
+        let call = self.observer.start(SET_CONFIG_PATH, request.metadata());
+        let response = self.client.set_config(request).await;
+        call.finish(&response);
+        let config = response?.into_inner();
+        debug!("Server settings are now {:?}", config);
+        Ok(config)
+    }
+
+    /// Take one of the server's services offline or back online; requires `with_token`
+    /// 
+    /// While a service is `NotServing`, its calls fail with `Unavailable`;
+    /// the other services are unaffected.
+    /// 
+    /// # Arguments
+    /// * `service` - Fully qualified service name, e.g. `calculator.CalculatorService`.
+    /// * `state` - The state to switch to.
+    /// 
+    /// # Returns
+    /// * `Result<ServingState, Status>` - The state now in effect, or `NotFound` for an unknown service.
+    pub async fn set_service_state(&mut self, service: &str, state: ServingState) -> Result<ServingState, Status> {
+        let request = self.request(SetServiceStateRequest { service: service.to_string(), state: state.into() })?;
+        let call = self.observer.start(SET_SERVICE_STATE_PATH, request.metadata());
+        let response = self.client.set_service_state(request).await;
+        call.finish(&response);
+        let state = response?.into_inner().state();
+        debug!("Service {} is now {:?}", service, state);
+        Ok(state)
+    }
+
+    /// Watch the calculator operations the server accepts
+    /// 
+    /// The first item is the current set; each later item follows a change,
+    /// e.g. the calculator being taken offline with `set_service_state`.
+    /// Dropping the stream ends the watch. Establishing it is retried as set
+    /// with `GrpcClientBuilder::stream_retries`.
+    /// 
+    /// # Returns
+    /// * `Result<impl Stream<Item = Result<Vec<Operation>, Status>>, Status>` - The enabled operations after each change.
+    pub async fn watch_operations(&mut self) -> Result<impl Stream<Item = Result<Vec<Operation>, Status>>, Status> {
+        let mut open = || async {
+            let request = self.request(WatchOperationsRequest {})?;
+            let call = self.observer.start(WATCH_OPERATIONS_PATH, request.metadata());
+            call.finish_stream(self.client.clone().watch_operations(request).await.map(|r| r.into_inner()))
+        };
+        let response = self.stream_retry.establish(WATCH_OPERATIONS_PATH, &mut open).await?;
+        Ok(response.map(|item| item.map(|response| response.operations().collect())))
+    }
+}
@@ -0,0 +1,98 @@
+//! Broadcast Service Client Implementation
+//! Thin wrapper over the generated broadcast client: subscribe to a topic
+//! and receive every message published to it, or publish one yourself.
+
+use tokio_stream::Stream;
+use tonic::{Request, Status};
+use tracing::{debug, error};
+use crate::proto::broadcast::{
+    broadcast_service_client::BroadcastServiceClient,
+    BroadcastMessage, PublishRequest, SubscribeRequest,
+};
+use super::super::client::{GrpcClient, TracedChannel};
+use super::super::observer::CallObserver;
+use super::super::retry::StreamRetry;
+
+// Method paths reported to the observer
+const SUBSCRIBE_PATH: &str = "/broadcast.BroadcastService/Subscribe";
+const PUBLISH_PATH: &str = "/broadcast.BroadcastService/Publish";
+
+// Client wrapper with generated gRPC client
+#[derive(Clone)]
+pub struct BroadcastService {
+    // Internal generated client instance
+    client: BroadcastServiceClient<TracedChannel>,
+    // Notified around every call (see GrpcClientBuilder::observer)
+    observer: CallObserver,
+    // Establishment retries of subscribe (see GrpcClientBuilder::stream_retries)
+    stream_retry: StreamRetry,
+}
+
+// Extension method for main client
+impl GrpcClient {
+    /// Create new broadcast service instance
+    ///
+    /// # Returns
+    /// * `BroadcastService` - A new instance of the broadcast service client.
+    pub fn broadcast(&self) -> BroadcastService {
+        BroadcastService {
+            client: BroadcastServiceClient::new(self.traced_channel()),
+            observer: self.observer(),
+            stream_retry: self.stream_retry(),
+        }
+    }
+}
+
+impl BroadcastService {
+    /// Follow a topic
+    /// 
+    /// Every message published to `topic` once this returns is delivered,
+    /// in publish order; a subscriber that reads too slowly skips the
+    /// messages the server could not hold for it. The stream ends when it
+    /// is dropped or the server shuts down. Establishing it is retried as
+    /// set with `GrpcClientBuilder::stream_retries`.
+    /// 
+    /// # Arguments
+    /// * `topic` - Any non-empty topic name.
+    /// 
+    /// # Returns
+    /// * `Result<impl Stream<Item = Result<BroadcastMessage, Status>>, Status>` - The published messages,
+    ///   or `InvalidArgument` for an empty topic.
+    pub async fn subscribe(
+        &mut self,
+        topic: impl Into<String>,
+    ) -> Result<impl Stream<Item = Result<BroadcastMessage, Status>>, Status> {
+        let topic = topic.into();
+        debug!("Subscribing to topic {}", topic);
+        let mut open = || async {
+            let request = Request::new(SubscribeRequest { topic: topic.clone() });
+            let call = self.observer.start(SUBSCRIBE_PATH, request.metadata());
+            call.finish_stream(self.client.clone().subscribe(request).await.map(|r| r.into_inner()))
+        };
+        self.stream_retry
+            .establish(SUBSCRIBE_PATH, &mut open)
+            .await
+            .inspect_err(|e| error!("Subscribe to topic {} failed: {}", topic, e))
+    }
+
+    /// Send a message to every current subscriber of a topic
+    /// 
+    /// # Arguments
+    /// * `topic` - Any non-empty topic name.
+    /// * `message` - The text to broadcast.
+    /// 
+    /// # Returns
+    /// * `Result<u64, Status>` - How many subscribers it was sent to; zero when nobody follows the topic.
+    pub async fn publish(&mut self, topic: impl Into<String>, message: impl Into<String>) -> Result<u64, Status> {
+        let request = Request::new(PublishRequest { topic: topic.into(), message: message.into() });
+        let call = self.observer.start(PUBLISH_PATH, request.metadata());
+        let response = self.client.publish(request).await;
+        call.finish(&response);
+        let subscribers = response
+            .inspect_err(|e| error!("Publish failed: {}", e))?
+            .into_inner()
+            .subscribers;
+        debug!("Published to {} subscribers", subscribers);
+        Ok(subscribers)
+    }
+}
@@ -3,22 +3,176 @@
 //! 1. Ergonomic API design for client usage
 //! 2. Early validation before making RPC calls
 //! 3. Error handling and status code mapping
+//! 4. Parsing operators from text ("+", "add", "DIVIDE", ...)
+//! 5. Reporting each call to the client's observer
+//! 6. Exact decimal arithmetic with operands passed as strings
+//! 7. Evaluating expressions on the server, or step by step when it lacks Evaluate
+//! 8. Folding a list with one operation (reduce)
+//! 9. Typed errors decoded from the server's structured details (CalculatorError)
+//! 10. A mock backend for unit tests without a server (CalculatorService::with_mock)
+//! 11. Running statistics over a streamed dataset (aggregate_stream)
+//! 12. The server's history of successful calculations (history)
+//! 13. Percentages of a value (percent)
+//! 14. Answering repeated calculate and evaluate calls from the client's cache
 
+use std::fmt;
+use std::str::FromStr;
+use tokio_stream::{Stream, StreamExt};
 use tonic::{Request, Status, Code};
-use tracing::{info, error};
+use tracing::{debug, error, info};
 // Import the generated client and message types
+use crate::proto::calculator::calculator_error_detail::Kind;
 use crate::proto::calculator::{
     calculator_service_client::CalculatorServiceClient,
-    CalculateRequest, Operation,
+    CalculatorErrorDetail, CalculateRequest, Operation, AggregateRequest, AggregateResponse,
+    AggregateValue, AggregateProgress,
+    CalculateDecimalRequest, EvaluateRequest, ReduceRequest,
+    RunningTotalStep, RunningTotalResponse,
+    CalculateResponse, CalculateDecimalResponse, EvaluateResponse, ReduceResponse,
+    GetHistoryRequest, GetHistoryResponse,
 };
-use super::super::client::GrpcClient;
+use crate::expr;
+use crate::proto::validation::Validate;
+use super::super::client::{GrpcClient, TracedChannel};
+use super::super::observer::CallObserver;
+use super::super::cache::CalculatorCache;
+use super::call_result::{self, CallResult};
+#[cfg(any(test, feature = "test-util"))]
+use super::mock::MockCalculator;
+
+// Method paths reported to the observer
+const CALCULATE_PATH: &str = "/calculator.CalculatorService/Calculate";
+const AGGREGATE_PATH: &str = "/calculator.CalculatorService/Aggregate";
+const AGGREGATE_STREAM_PATH: &str = "/calculator.CalculatorService/AggregateStream";
+const RUNNING_TOTAL_PATH: &str = "/calculator.CalculatorService/RunningTotal";
+const CALCULATE_DECIMAL_PATH: &str = "/calculator.CalculatorService/CalculateDecimal";
+const EVALUATE_PATH: &str = "/calculator.CalculatorService/Evaluate";
+const REDUCE_PATH: &str = "/calculator.CalculatorService/Reduce";
+const GET_HISTORY_PATH: &str = "/calculator.CalculatorService/GetHistory";
 
 // Client-side service wrapper
 // Clone allows creating multiple instances from one
 #[derive(Clone)]
 pub struct CalculatorService {
-    // Hold the generated client with transport channel
-    client: CalculatorServiceClient<tonic::transport::Channel>,
+    // Executes the RPCs: the generated client, or a mock in tests
+    backend: CalculatorBackend,
+    // Notified around every call (see GrpcClientBuilder::observer)
+    observer: CallObserver,
+    // Whether the server has the Evaluate RPC; None until first tried
+    server_evaluate: Option<bool>,
+    // Results of earlier calls (see GrpcClientBuilder::calculator_cache)
+    cache: Option<CalculatorCache>,
+}
+
+// What the wrapper sends its calls to
+#[derive(Clone)]
+enum CalculatorBackend {
+    Grpc(CalculatorServiceClient<TracedChannel>),
+    #[cfg(any(test, feature = "test-util"))]
+    Mock(MockCalculator),
+}
+
+// Dispatch one unary RPC to the generated client or the matching mock
+macro_rules! unary {
+    ($name:ident, $req:ty, $resp:ty) => {
+        async fn $name(&mut self, request: Request<$req>) -> Result<tonic::Response<$resp>, Status> {
+            match self {
+                CalculatorBackend::Grpc(client) => client.$name(request).await,
+                #[cfg(any(test, feature = "test-util"))]
+                CalculatorBackend::Mock(mock) => mock.$name.call(request),
+            }
+        }
+    };
+}
+
+impl CalculatorBackend {
+    unary!(calculate, CalculateRequest, CalculateResponse);
+    unary!(calculate_decimal, CalculateDecimalRequest, CalculateDecimalResponse);
+    unary!(reduce, ReduceRequest, ReduceResponse);
+    unary!(evaluate, EvaluateRequest, EvaluateResponse);
+    unary!(aggregate, AggregateRequest, AggregateResponse);
+    unary!(get_history, GetHistoryRequest, GetHistoryResponse);
+
+    // The generated client, for the streaming calls the mock does not cover
+    fn grpc(&mut self) -> Result<&mut CalculatorServiceClient<TracedChannel>, Status> {
+        match self {
+            CalculatorBackend::Grpc(client) => Ok(client),
+            #[cfg(any(test, feature = "test-util"))]
+            CalculatorBackend::Mock(_) => Err(Status::unimplemented("streaming calls are not mocked")),
+        }
+    }
+}
+
+/// One operation performed while evaluating an expression step by step
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationStep {
+    pub first: f64,
+    pub second: f64,
+    pub operation: Operation,
+    pub result: f64,
+    /// The sub-expression this step computed, e.g. `3 * 4`
+    pub expression: String,
+}
+
+/// Outcome of [`CalculatorService::evaluate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// Value of the whole expression
+    pub result: f64,
+    /// The Calculate calls made, in order; empty when the server evaluated it
+    pub steps: Vec<EvaluationStep>,
+    /// True if the server's Evaluate RPC computed the result
+    pub on_server: bool,
+}
+
+/// Calculator failure with the server's structured detail decoded
+/// 
+/// Lets callers branch on the kind of failure instead of matching the
+/// message, e.g. `calculate(..).await.map_err(CalculatorError::from)`.
+/// Local validation failures carry the same details as the server's.
+#[derive(Debug)]
+pub struct CalculatorError {
+    /// Why the call failed; None when the status has no detail (e.g. Unavailable)
+    pub detail: Option<CalculatorErrorDetail>,
+    /// The status as returned by the call
+    pub status: Status,
+}
+
+impl CalculatorError {
+    /// Decode the structured detail attached to a calculator status
+    /// 
+    /// # Arguments
+    /// * `status` - A status returned by any `CalculatorService` method.
+    /// 
+    /// # Returns
+    /// * `Option<CalculatorErrorDetail>` - The detail, or None if the status carries none.
+    pub fn from_status(status: &Status) -> Option<CalculatorErrorDetail> {
+        CalculatorErrorDetail::from_status(status)
+    }
+
+    /// Kind of failure; `Kind::Unspecified` when the status has no detail
+    pub fn kind(&self) -> Kind {
+        self.detail.as_ref().map_or(Kind::Unspecified, CalculatorErrorDetail::kind)
+    }
+}
+
+impl From<Status> for CalculatorError {
+    fn from(status: Status) -> Self {
+        CalculatorError { detail: CalculatorErrorDetail::from_status(&status), status }
+    }
+}
+
+impl fmt::Display for CalculatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status)
+    }
+}
+
+impl std::error::Error for CalculatorError {}
+
+// InvalidArgument carrying a CalculatorErrorDetail, for local validation
+fn invalid(message: impl Into<String>, kind: Kind, operand: Option<u32>) -> Status {
+    CalculatorErrorDetail::new(kind, operand).to_status(Code::InvalidArgument, message)
 }
 
 // Extension trait implementation for GrpcClient
@@ -29,47 +183,163 @@ impl GrpcClient {
     /// * `CalculatorService` - A new instance of the calculator service client.
     pub fn calculator(&self) -> CalculatorService {
         // Create new client using the shared channel
+        let mut client = CalculatorServiceClient::new(self.traced_channel());
+        if let Some(encoding) = self.compression() {
+            client = client.send_compressed(encoding).accept_compressed(encoding);
+        }
         CalculatorService {
-            client: CalculatorServiceClient::new(self.get_channel())
+            backend: CalculatorBackend::Grpc(client),
+            observer: self.observer(),
+            server_evaluate: None,
+            cache: self.calculator_cache(),
+        }
+    }
+}
+
+// Parse an operation from its symbol or name, ignoring case
+// Lets callers write "+".parse() or "divide".parse() instead of
+// importing the generated enum
+impl FromStr for Operation {
+    type Err = Status;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "+" | "add" => Ok(Operation::Add),
+            "-" | "subtract" => Ok(Operation::Subtract),
+            "*" | "multiply" => Ok(Operation::Multiply),
+            "/" | "divide" => Ok(Operation::Divide),
+            "%" | "percent" => Ok(Operation::Percent),
+            _ => Err(invalid(
+                format!("unknown operation {:?}; expected one of + - * / % or add, subtract, multiply, divide, percent", s),
+                Kind::UnsupportedOperation,
+                None,
+            )),
         }
     }
 }
 
 // Main service implementation
 impl CalculatorService {
+    /// Create a calculator service answered by `mock` instead of a server
+    /// 
+    /// Streaming calls (`running_total`, `aggregate_stream`) fail with Unimplemented.
+    /// 
+    /// # Arguments
+    /// * `mock` - Canned responses per RPC; keep a clone to inspect the requests.
+    /// 
+    /// # Returns
+    /// * `CalculatorService` - A service that never touches the network.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn with_mock(mock: MockCalculator) -> Self {
+        CalculatorService {
+            backend: CalculatorBackend::Mock(mock),
+            observer: CallObserver::default(),
+            server_evaluate: None,
+            cache: None,
+        }
+    }
+
     /// High-level calculate method that handles all operations
     /// 
+    /// Answered without a call when the client's calculator cache holds the result.
+    /// 
     /// # Arguments
     /// * `first` - The first operand as a floating-point number.
     /// * `second` - The second operand as a floating-point number.
     /// * `operation` - The operation to perform as an `Operation` enum.
     /// 
     /// # Returns
-    /// * `Result<f64, Status>` - A result containing the calculation result or an error status.
+    /// * `Result<f64, Status>` - A result containing the calculation result or an error status;
+    ///   [`CalculatorError::from_status`] decodes why a calculation was rejected.
     pub async fn calculate(&mut self, first: f64, second: f64, operation: Operation) -> Result<f64, Status> {
-        // Early validation for division by zero
-        // Better to fail fast before making network call
-        if matches!(operation, Operation::Divide) && second == 0.0 {
-            return Err(Status::new(
-                Code::InvalidArgument,
-                "division by zero is not allowed"
-            ));
+        if let Some(result) = self.cache.as_ref().and_then(|cache| cache.calculation(first, second, operation)) {
+            debug!("Answered calculate from the cache: {} {:?} {} = {}", first, operation, second, result);
+            return Ok(result);
+        }
+        let result = self.calculate_detailed(first, second, operation).await?.value;
+        if let Some(cache) = &self.cache {
+            cache.store_calculation(first, second, operation, result);
         }
+        Ok(result)
+    }
+
+    /// Take `pct` percent of `value`, e.g. `percent(200.0, 50.0)` gives 100
+    /// 
+    /// Shorthand for `calculate(value, pct, Operation::Percent)`.
+    /// 
+    /// # Arguments
+    /// * `value` - The amount the percentage is taken of.
+    /// * `pct` - The percentage; negative percentages give a result of the opposite sign.
+    /// 
+    /// # Returns
+    /// * `Result<f64, Status>` - `value * (pct / 100)`, or the same errors as `calculate`
+    ///   (`OutOfRange` when the result overflows).
+    pub async fn percent(&mut self, value: f64, pct: f64) -> Result<f64, Status> {
+        self.calculate(value, pct, Operation::Percent).await
+    }
+
+    /// Calculate method also returning the response metadata and latency
+    /// 
+    /// # Arguments
+    /// * `first` - The first operand as a floating-point number.
+    /// * `second` - The second operand as a floating-point number.
+    /// * `operation` - The operation to perform as an `Operation` enum.
+    /// 
+    /// # Returns
+    /// * `Result<CallResult<f64>, Status>` - The result with its metadata, or the same errors as `calculate`.
+    pub async fn calculate_detailed(&mut self, first: f64, second: f64, operation: Operation) -> Result<CallResult<f64>, Status> {
+        let response = self.send_calculate(first, second, operation, false).await?;
+        Ok(response.map(|response| response.result))
+    }
 
-        info!("Sending calculate request: {} {:?} {}", first, operation, second);
-        // Create and send the gRPC request
-        let request = Request::new(CalculateRequest {
+    /// Calculate method also returning the inverse operation applied to the result
+    /// 
+    /// The inverse recovers `first` up to rounding, e.g. `2 + 3 = 5` comes
+    /// back with `5 - 3 = 2`, which lets a UI offer "undo". It is None when
+    /// multiplying by zero, which cannot be undone, when it is not finite,
+    /// and from servers that predate it.
+    /// 
+    /// # Arguments
+    /// * `first` - The first operand as a floating-point number.
+    /// * `second` - The second operand as a floating-point number.
+    /// * `operation` - The operation to perform as an `Operation` enum.
+    /// 
+    /// # Returns
+    /// * `Result<(f64, Option<f64>), Status>` - The result and its inverse, or the same errors as `calculate`.
+    pub async fn calculate_with_inverse(&mut self, first: f64, second: f64, operation: Operation) -> Result<(f64, Option<f64>), Status> {
+        let response = self.send_calculate(first, second, operation, true).await?.value;
+        debug!("Received inverse: {:?}", response.inverse);
+        Ok((response.result, response.inverse))
+    }
+
+    // Send one Calculate request, mapping errors as calculate documents
+    async fn send_calculate(
+        &mut self,
+        first: f64,
+        second: f64,
+        operation: Operation,
+        include_inverse: bool,
+    ) -> Result<CallResult<CalculateResponse>, Status> {
+        let request = CalculateRequest {
             first_number: first,
             second_number: second,
             operation: operation.into(),
-        });
+            include_inverse,
+        };
+        // Early validation (division by zero, non-finite operands)
+        // Better to fail fast before making network call
+        request.validate()?;
+
+        debug!("Sending calculate request: {} {:?} {}", first, operation, second);
+        // Send the gRPC request
+        let request = Request::new(request);
 
         // Handle different types of responses and errors
-        match self.client.calculate(request).await {
+        let response = call_result::send(&self.observer, CALCULATE_PATH, request, |r| self.backend.calculate(r)).await;
+        match response {
             Ok(response) => {
-                let result = response.into_inner().result;
-                info!("Received calculate response: {}", result);
-                Ok(result)
+                debug!("Received calculate response: {}", response.value.result);
+                Ok(response)
             },
             Err(status) if status.code() == Code::Unavailable => {
                 error!("Service temporarily unavailable");
@@ -84,24 +354,361 @@ impl CalculatorService {
             },
         }
     }
+
+    /// Calculate with the operation given as text, e.g. `calculate_str(6.0, "*", 7.0)`
+    /// 
+    /// # Arguments
+    /// * `first` - The first operand as a floating-point number.
+    /// * `operation` - A symbol (`+ - * / %`) or name (`add`, `DIVIDE`, ...), case-insensitive.
+    /// * `second` - The second operand as a floating-point number.
+    /// 
+    /// # Returns
+    /// * `Result<f64, Status>` - The result, or `InvalidArgument` for an unknown operation.
+    pub async fn calculate_str(&mut self, first: f64, operation: &str, second: f64) -> Result<f64, Status> {
+        let operation = operation.parse()?;
+        self.calculate(first, second, operation).await
+    }
+
+    /// Calculate on exact decimals, e.g. `calculate_decimal("0.1", "0.2", Operation::Add)` gives `"0.3"`
+    /// 
+    /// Operands are parsed by the server, which keeps up to 28 significant
+    /// digits instead of rounding to binary floating point.
+    /// 
+    /// # Arguments
+    /// * `first` - The first operand as a decimal string.
+    /// * `second` - The second operand as a decimal string.
+    /// * `operation` - The operation to perform as an `Operation` enum.
+    /// 
+    /// # Returns
+    /// * `Result<String, Status>` - The normalized decimal result, `InvalidArgument` for an
+    ///   unparseable operand or division by zero, or `OutOfRange` on overflow.
+    pub async fn calculate_decimal(&mut self, first: &str, second: &str, operation: Operation) -> Result<String, Status> {
+        Ok(self.calculate_decimal_detailed(first, second, operation).await?.value)
+    }
+
+    /// Decimal calculate method also returning the response metadata and latency
+    /// 
+    /// # Arguments
+    /// * `first` - The first operand as a decimal string.
+    /// * `second` - The second operand as a decimal string.
+    /// * `operation` - The operation to perform as an `Operation` enum.
+    /// 
+    /// # Returns
+    /// * `Result<CallResult<String>, Status>` - The result with its metadata, or the same errors as `calculate_decimal`.
+    pub async fn calculate_decimal_detailed(&mut self, first: &str, second: &str, operation: Operation) -> Result<CallResult<String>, Status> {
+        debug!("Sending decimal calculate request: {} {:?} {}", first, operation, second);
+        let request = Request::new(CalculateDecimalRequest {
+            first_number: first.to_string(),
+            second_number: second.to_string(),
+            operation: operation.into(),
+        });
+        let response = call_result::send(&self.observer, CALCULATE_DECIMAL_PATH, request, |r| self.backend.calculate_decimal(r))
+            .await
+            .inspect_err(|e| error!("Decimal calculate request failed: {}", e))?;
+        debug!("Received decimal calculate response: {}", response.value.result);
+        Ok(response.map(|response| response.result))
+    }
+
+    /// Fold `values` left to right with one operation on the server
+    /// 
+    /// E.g. `reduce(&[1.0, 2.0, 3.0, 4.0], Operation::Multiply)` gives 24.
+    /// 
+    /// # Arguments
+    /// * `values` - The list to fold; must be non-empty.
+    /// * `operation` - The operation applied between consecutive values.
+    /// 
+    /// # Returns
+    /// * `Result<f64, Status>` - The result, `InvalidArgument` for an empty list or division by
+    ///   zero, or `OutOfRange` on overflow; errors name the index of the failing value.
+    pub async fn reduce(&mut self, values: &[f64], operation: Operation) -> Result<f64, Status> {
+        Ok(self.reduce_detailed(values, operation).await?.value)
+    }
+
+    /// Reduce method also returning the response metadata and latency
+    /// 
+    /// # Arguments
+    /// * `values` - The list to fold; must be non-empty.
+    /// * `operation` - The operation applied between consecutive values.
+    /// 
+    /// # Returns
+    /// * `Result<CallResult<f64>, Status>` - The result with its metadata, or the same errors as `reduce`.
+    pub async fn reduce_detailed(&mut self, values: &[f64], operation: Operation) -> Result<CallResult<f64>, Status> {
+        // Early validation, mirroring the server's rules
+        if values.is_empty() {
+            return Err(invalid("cannot reduce an empty list", Kind::EmptyInput, None));
+        }
+
+        debug!("Sending reduce request: {:?} over {} values", operation, values.len());
+        let request = Request::new(ReduceRequest { values: values.to_vec(), operation: operation.into() });
+        let response = call_result::send(&self.observer, REDUCE_PATH, request, |r| self.backend.reduce(r))
+            .await
+            .inspect_err(|e| error!("Reduce request failed: {}", e))?;
+        debug!("Received reduce response: {}", response.value.result);
+        Ok(response.map(|response| response.result))
+    }
+
+    /// Evaluate an expression such as `"2 + 3 * (4 - 1)"`
+    /// 
+    /// Uses the server's Evaluate RPC when it has one. A server answering
+    /// Unimplemented is remembered, and the expression is instead run as a
+    /// sequence of Calculate calls (see [`evaluate_locally`](Self::evaluate_locally)).
+    /// Both paths perform the same operations in the same order, so they
+    /// return the same result. With the client's calculator cache enabled,
+    /// an expression evaluated recently is answered without any call.
+    /// 
+    /// # Arguments
+    /// * `expression` - Numbers, `+ - * /`, unary minus and parentheses.
+    /// 
+    /// # Returns
+    /// * `Result<Evaluation, Status>` - The result, `InvalidArgument` for a syntax error, or the
+    ///   failing step's status naming its sub-expression (e.g. division by zero).
+    pub async fn evaluate(&mut self, expression: &str) -> Result<Evaluation, Status> {
+        // Parse first so syntax errors never cost a round trip
+        parse(expression)?;
+        if let Some(evaluation) = self.cache.as_ref().and_then(|cache| cache.evaluation(expression)) {
+            debug!("Answered evaluate from the cache: {} = {}", expression, evaluation.result);
+            return Ok(evaluation);
+        }
+        let evaluation = self.evaluate_uncached(expression).await?;
+        if let Some(cache) = &self.cache {
+            cache.store_evaluation(expression, evaluation.clone());
+        }
+        Ok(evaluation)
+    }
+
+    // Evaluate on the server, or step by step when it lacks Evaluate
+    async fn evaluate_uncached(&mut self, expression: &str) -> Result<Evaluation, Status> {
+        if self.server_evaluate == Some(false) {
+            return self.evaluate_locally(expression).await;
+        }
+
+        debug!("Sending evaluate request: {}", expression);
+        let request = Request::new(EvaluateRequest { expression: expression.to_string() });
+        let call = self.observer.start(EVALUATE_PATH, request.metadata());
+        let response = self.backend.evaluate(request).await;
+        call.finish(&response);
+        match response {
+            Ok(response) => {
+                self.server_evaluate = Some(true);
+                let result = response.into_inner().result;
+                debug!("Received evaluate response: {}", result);
+                Ok(Evaluation { result, steps: Vec::new(), on_server: true })
+            }
+            Err(status) if status.code() == Code::Unimplemented => {
+                info!("Server has no Evaluate RPC; evaluating step by step");
+                self.server_evaluate = Some(false);
+                self.evaluate_locally(expression).await
+            }
+            Err(e) => {
+                error!("Evaluate request failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Evaluate an expression as a sequence of Calculate calls
+    /// 
+    /// The expression is planned with `crate::expr::plan`, so precedence and
+    /// parentheses decide the order of the calls.
+    /// 
+    /// # Arguments
+    /// * `expression` - Numbers, `+ - * /`, unary minus and parentheses.
+    /// 
+    /// # Returns
+    /// * `Result<Evaluation, Status>` - The result with every step made, `InvalidArgument` for a
+    ///   syntax error, or the failing step's status naming its sub-expression.
+    pub async fn evaluate_locally(&mut self, expression: &str) -> Result<Evaluation, Status> {
+        let plan = parse(expression)?;
+        let mut results = Vec::with_capacity(plan.steps.len());
+        let mut steps = Vec::with_capacity(plan.steps.len());
+        for step in plan.steps {
+            let first = step.first.resolve(&results);
+            let second = step.second.resolve(&results);
+            let result = self
+                .calculate(first, second, step.operation)
+                .await
+                .map_err(|status| {
+                    let message = format!("{} in `{}`", status.message(), step.expression);
+                    // Operand indices are relative to the step, so they are dropped
+                    match CalculatorErrorDetail::from_status(&status) {
+                        Some(detail) => CalculatorErrorDetail { operand: None, ..detail }.to_status(status.code(), message),
+                        None => Status::new(status.code(), message),
+                    }
+                })?;
+            results.push(result);
+            steps.push(EvaluationStep { first, second, operation: step.operation, result, expression: step.expression });
+        }
+        Ok(Evaluation { result: plan.result.resolve(&results), steps, on_server: false })
+    }
+
+    /// Compute sum, mean, min, max and count of a dataset on the server
+    /// 
+    /// # Arguments
+    /// * `values` - The dataset; must be non-empty and free of NaN.
+    /// 
+    /// # Returns
+    /// * `Result<AggregateResponse, Status>` - A result containing the statistics or an error status.
+    pub async fn aggregate(&mut self, values: &[f64]) -> Result<AggregateResponse, Status> {
+        // Early validation, mirroring the server's rules
+        if values.is_empty() {
+            return Err(invalid("cannot aggregate an empty dataset", Kind::EmptyInput, None));
+        }
+
+        debug!("Sending aggregate request with {} values", values.len());
+        let request = Request::new(AggregateRequest { values: values.to_vec() });
+        let call = self.observer.start(AGGREGATE_PATH, request.metadata());
+        let response = self.backend.aggregate(request).await;
+        call.finish(&response);
+        let stats = response
+            .inspect_err(|e| error!("Aggregate request failed: {}", e))?
+            .into_inner();
+        debug!("Received aggregate response: {:?}", stats);
+        Ok(stats)
+    }
+
+    /// The server's most recent successful calculations, newest first
+    /// 
+    /// Servers keep the last 1000 in memory unless configured with another
+    /// store, such as a file that survives restarts.
+    /// 
+    /// # Arguments
+    /// * `limit` - Most entries to return; zero asks for the server's default of 100.
+    /// 
+    /// # Returns
+    /// * `Result<GetHistoryResponse, Status>` - The entries and how many the server keeps,
+    ///   or `Internal` if the server cannot read its history.
+    pub async fn history(&mut self, limit: u32) -> Result<GetHistoryResponse, Status> {
+        debug!("Sending history request for up to {} entries", limit);
+        let request = Request::new(GetHistoryRequest { limit });
+        let call = self.observer.start(GET_HISTORY_PATH, request.metadata());
+        let response = self.backend.get_history(request).await;
+        call.finish(&response);
+        let history = response
+            .inspect_err(|e| error!("History request failed: {}", e))?
+            .into_inner();
+        debug!("Received {} of {} history entries", history.entries.len(), history.total);
+        Ok(history)
+    }
+
+    /// Stream a dataset to the server and receive its statistics after every value
+    /// 
+    /// Suited to datasets too large to send in one `aggregate` request. The
+    /// server answers each value with the count, sum, mean and variance so
+    /// far, and ends the stream with `InvalidArgument` at the first NaN or
+    /// infinite value.
+    /// 
+    /// # Arguments
+    /// * `values` - A stream of the values to summarize, in order.
+    /// 
+    /// # Returns
+    /// * `Result<impl Stream<Item = Result<AggregateProgress, Status>>, Status>` - A result containing the stream of statistics or an error status.
+    pub async fn aggregate_stream(
+        &mut self,
+        values: impl Stream<Item = f64> + Send + 'static,
+    ) -> Result<impl Stream<Item = Result<AggregateProgress, Status>>, Status> {
+        info!("Starting streaming aggregate");
+        let outbound = values.map(|value| AggregateValue { value });
+        let request = Request::new(outbound);
+        let call = self.observer.start(AGGREGATE_STREAM_PATH, request.metadata());
+        call.finish_stream(self.backend.grpc()?.aggregate_stream(request).await.map(|r| r.into_inner()))
+    }
+
+    /// Apply a stream of steps to a running total kept by the server
+    /// 
+    /// The server answers every step with the new total. A step it cannot
+    /// apply (e.g. division by zero) is answered with the unchanged total
+    /// and a non-empty `error`, and later steps are still processed.
+    /// 
+    /// # Arguments
+    /// * `steps` - A stream of `(value, operation)` pairs applied in order.
+    /// 
+    /// # Returns
+    /// * `Result<impl Stream<Item = Result<RunningTotalResponse, Status>>, Status>` - A result containing the stream of totals or an error status.
+    pub async fn running_total(
+        &mut self,
+        steps: impl Stream<Item = (f64, Operation)> + Send + 'static,
+    ) -> Result<impl Stream<Item = Result<RunningTotalResponse, Status>>, Status> {
+        info!("Starting running total stream");
+        let outbound = steps.map(|(value, operation)| RunningTotalStep {
+            value,
+            operation: operation.into(),
+        });
+        let request = Request::new(outbound);
+        let call = self.observer.start(RUNNING_TOTAL_PATH, request.metadata());
+        call.finish_stream(self.backend.grpc()?.running_total(request).await.map(|r| r.into_inner()))
+    }
 }
 
-// Tests that checks if the second operand is zero that is not allowed
+// Plan an expression, reporting syntax errors as InvalidArgument
+fn parse(expression: &str) -> Result<expr::Plan, Status> {
+    expr::plan(expression).map_err(|e| invalid(e.to_string(), Kind::InvalidInput, None))
+}
+
+// Tests of the wrapper's own logic, run against a mock backend
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
     async fn test_calculator_validation() {
-        let client = GrpcClient::builder("http://[::1]:50051")
-            .unwrap()
-            .connect()
-            .unwrap();
-        
-        let mut calc = client.calculator();
+        let mock = MockCalculator::default();
+        let mut calc = CalculatorService::with_mock(mock.clone());
         
         let err = calc.calculate(10.0, 0.0, Operation::Divide).await.unwrap_err();
         assert_eq!(err.code(), Code::InvalidArgument);
         assert!(err.message().contains("division by zero"));
+
+        // Rejected before reaching the backend
+        assert!(mock.calculate.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_calculate_maps_unavailable() {
+        let mock = MockCalculator::default();
+        mock.calculate.push(Err(Status::unavailable("tcp connect error: connection refused")));
+        mock.calculate.push(Err(Status::internal("boom")));
+        let mut calc = CalculatorService::with_mock(mock);
+
+        // Transport details are replaced by a stable message
+        let err = calc.calculate(1.0, 2.0, Operation::Add).await.unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+        assert_eq!(err.message(), "service temporarily unavailable");
+
+        // Other failures pass through unchanged
+        let err = calc.calculate(1.0, 2.0, Operation::Add).await.unwrap_err();
+        assert_eq!((err.code(), err.message()), (Code::Internal, "boom"));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_falls_back_to_calculate() {
+        let mock = MockCalculator::default();
+        mock.evaluate.push(Err(Status::unimplemented("unknown method")));
+        mock.calculate.push(Ok(CalculateResponse { result: 12.0, inverse: None }));
+        mock.calculate.push(Ok(CalculateResponse { result: 14.0, inverse: None }));
+        let mut calc = CalculatorService::with_mock(mock.clone());
+
+        let evaluation = calc.evaluate("2 + 3 * 4").await.unwrap();
+        assert_eq!(evaluation.result, 14.0);
+        assert!(!evaluation.on_server);
+        let sent: Vec<_> = mock.calculate.requests().iter().map(|r| (r.first_number, r.second_number)).collect();
+        assert_eq!(sent, vec![(3.0, 4.0), (2.0, 12.0)]);
+
+        // The missing RPC is remembered, so Evaluate is not tried again
+        mock.calculate.push(Ok(CalculateResponse { result: 3.0, inverse: None }));
+        calc.evaluate("1 + 2").await.unwrap();
+        assert_eq!(mock.evaluate.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_operation_from_str() {
+        assert_eq!("+".parse::<Operation>().unwrap(), Operation::Add);
+        assert_eq!("add".parse::<Operation>().unwrap(), Operation::Add);
+        assert_eq!("DIVIDE".parse::<Operation>().unwrap(), Operation::Divide);
+        assert_eq!(" Multiply ".parse::<Operation>().unwrap(), Operation::Multiply);
+        assert_eq!("%".parse::<Operation>().unwrap(), Operation::Percent);
+
+        let err = "%%".parse::<Operation>().unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("%%"));
     }
 }
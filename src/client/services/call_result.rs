@@ -0,0 +1,73 @@
+//! Call Results
+//! The `_detailed` methods return the call's value together with what the
+//! plain methods throw away:
+//! 1. CallResult: the value, the response metadata, the latency and the request ID
+//! 2. send: performs one unary call with observer reporting and timing,
+//!    shared by every wrapper method built on it
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status};
+use crate::proto::CONNECTION_REQUEST_COUNT_METADATA;
+use super::super::observer::CallObserver;
+
+// Conventional request ID header, e.g. added by proxies and gateways
+const REQUEST_ID_METADATA: &str = "x-request-id";
+
+/// A call's value with the response metadata and timing
+#[derive(Debug, Clone)]
+pub struct CallResult<T> {
+    /// What the plain method returns
+    pub value: T,
+    /// Headers of the response
+    pub metadata: MetadataMap,
+    /// Time from sending the request to receiving the response
+    pub latency: Duration,
+    /// The response's `x-request-id`; None if nothing on the path set it
+    pub request_id: Option<String>,
+}
+
+impl<T> CallResult<T> {
+    // Pair `value` with the response's metadata
+    fn new(value: T, metadata: MetadataMap, latency: Duration) -> Self {
+        let request_id = metadata
+            .get(REQUEST_ID_METADATA)
+            .and_then(|id| id.to_str().ok())
+            .map(str::to_string);
+        Self { value, metadata, latency, request_id }
+    }
+
+    /// Transform the value, keeping the metadata and timing
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> CallResult<U> {
+        CallResult { value: f(self.value), metadata: self.metadata, latency: self.latency, request_id: self.request_id }
+    }
+
+    /// RPCs served on this connection so far, this one included
+    ///
+    /// # Returns
+    /// * `Option<u64>` - The count, or None if the server does not report it.
+    pub fn connection_request_count(&self) -> Option<u64> {
+        self.metadata.get(CONNECTION_REQUEST_COUNT_METADATA)?.to_str().ok()?.parse().ok()
+    }
+}
+
+// Send `request` through `rpc`, reporting it to `observer` as `path`
+pub(super) async fn send<Req, Resp, F, Fut>(
+    observer: &CallObserver,
+    path: &'static str,
+    request: Request<Req>,
+    rpc: F,
+) -> Result<CallResult<Resp>, Status>
+where
+    F: FnOnce(Request<Req>) -> Fut,
+    Fut: Future<Output = Result<Response<Resp>, Status>>,
+{
+    let call = observer.start(path, request.metadata());
+    let started = Instant::now();
+    let response = rpc(request).await;
+    let latency = started.elapsed();
+    call.finish(&response);
+    let (metadata, value, _) = response?.into_parts();
+    Ok(CallResult::new(value, metadata, latency))
+}
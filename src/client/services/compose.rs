@@ -0,0 +1,40 @@
+//! Composed Client Helpers
+//! Calls that combine several services, for demo tooling:
+//! 1. calculate_and_echo: computes a result and echoes it back as text
+//!
+//! A failure names the leg it came from but keeps the original code and
+//! metadata, so e.g. `CalculatorError::from` still decodes its detail.
+
+use tonic::Status;
+use tracing::debug;
+use crate::proto::calculator::Operation;
+use super::super::client::GrpcClient;
+
+// Prefix a failed leg's message with its name; code and metadata are kept
+fn leg_failed(leg: &str, status: Status) -> Status {
+    Status::with_metadata(status.code(), format!("{} failed: {}", leg, status.message()), status.metadata().clone())
+}
+
+impl GrpcClient {
+    /// Calculate `first <operation> second` and echo the formatted result
+    ///
+    /// # Arguments
+    /// * `first` - The first operand.
+    /// * `second` - The second operand.
+    /// * `operation` - The operation to perform.
+    ///
+    /// # Returns
+    /// * `Result<String, Status>` - The echoed result, e.g. `"5"` for `2 + 3`; a failure's
+    ///   message starts with `calculate failed:` or `echo failed:` depending on the leg.
+    pub async fn calculate_and_echo(&self, first: f64, second: f64, operation: Operation) -> Result<String, Status> {
+        let result = self.calculator()
+            .calculate(first, second, operation)
+            .await
+            .map_err(|status| leg_failed("calculate", status))?;
+        debug!("Echoing calculate result {}", result);
+        self.echo()
+            .echo(result.to_string())
+            .await
+            .map_err(|status| leg_failed("echo", status))
+    }
+}
@@ -3,22 +3,126 @@
 //! 1. Simple gRPC client wrapper implementation
 //! 2. Generic input handling with Into<String>
 //! 3. Client-side validation
+//! 4. Reporting each call to the client's observer
+//! 5. Typed errors decoded from the server's status metadata (EchoError)
+//! 6. A mock backend for unit tests without a server (EchoService::with_mock)
+//! 7. Retried stream establishment and opt-in resubscription (echo_stream)
 
+use std::fmt;
+use bytes::Bytes;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tokio_stream::{Stream, StreamExt};
 use tonic::{Request, Status, Code};
-use tracing::info;
+use tracing::{debug, info};
+use crate::proto::validation::Validate;
 use crate::proto::echo::{
     echo_service_client::EchoServiceClient,
-    EchoRequest,
+    EchoRequest, EchoResponse, EchoChunk, EchoStreamRequest, EchoValidation,
+    LIMIT_METADATA, ACTUAL_METADATA,
 };
-use super::super::client::GrpcClient;
+use super::super::client::{GrpcClient, TracedChannel};
+use super::super::observer::CallObserver;
+use super::super::retry::StreamRetry;
+use super::payload::Payload;
+use super::call_result::{self, CallResult};
+#[cfg(any(test, feature = "test-util"))]
+use super::mock::MockRpc;
+
+// Payload of the round-trip probe; short so timing is dominated by the network
+const PING_MESSAGE: &str = "ping";
+
+// Method paths reported to the observer
+const ECHO_PATH: &str = "/echo.EchoService/Echo";
+const ECHO_STREAM_PATH: &str = "/echo.EchoService/EchoStream";
+const ECHO_CHUNKED_PATH: &str = "/echo.EchoService/EchoChunked";
 
 // Client wrapper with generated gRPC client
 #[derive(Clone)]
 pub struct EchoService {
-    // Internal generated client instance
-    client: EchoServiceClient<tonic::transport::Channel>,
+    // Executes the RPCs: the generated client, or a mock in tests
+    backend: EchoBackend,
+    // Whether full payloads may be logged (see GrpcClientBuilder::log_payloads)
+    log_payloads: bool,
+    // Whether empty messages are sent (see GrpcClientBuilder::allow_empty_echo)
+    allow_empty: bool,
+    // Notified around every call (see GrpcClientBuilder::observer)
+    observer: CallObserver,
+    // Retry policy of echo_stream (see GrpcClientBuilder::stream_retries)
+    stream_retry: StreamRetry,
+}
+
+// What the wrapper sends its calls to
+// The generated client is boxed; it is far larger than the mock
+#[derive(Clone)]
+enum EchoBackend {
+    Grpc(Box<EchoServiceClient<TracedChannel>>),
+    #[cfg(any(test, feature = "test-util"))]
+    Mock(MockRpc<EchoRequest, EchoResponse>),
+}
+
+impl EchoBackend {
+    async fn echo(&mut self, request: Request<EchoRequest>) -> Result<tonic::Response<EchoResponse>, Status> {
+        match self {
+            EchoBackend::Grpc(client) => client.echo(request).await,
+            #[cfg(any(test, feature = "test-util"))]
+            EchoBackend::Mock(mock) => mock.call(request),
+        }
+    }
+
+    // The generated client, for the streaming calls the mock does not cover
+    fn grpc(&mut self) -> Result<&mut EchoServiceClient<TracedChannel>, Status> {
+        match self {
+            EchoBackend::Grpc(client) => Ok(&mut **client),
+            #[cfg(any(test, feature = "test-util"))]
+            EchoBackend::Mock(_) => Err(Status::unimplemented("streaming calls are not mocked")),
+        }
+    }
+}
+
+/// Echo failure with the server's structured details decoded
+#[derive(Debug)]
+pub enum EchoError {
+    /// The message is longer than the server's limit (see `GrpcServerBuilder::echo_max_chars`)
+    TooLong {
+        /// Longest message the server accepts, in characters
+        limit: usize,
+        /// Length of the rejected message, in characters
+        actual: usize,
+    },
+    /// Any other failure, as returned by the call
+    Status(Status),
+}
+
+// Read a numeric status metadata value
+fn metadata_number(status: &Status, key: &str) -> Option<usize> {
+    status.metadata().get(key)?.to_str().ok()?.parse().ok()
 }
 
+impl From<Status> for EchoError {
+    fn from(status: Status) -> Self {
+        if status.code() == Code::InvalidArgument {
+            let limit = metadata_number(&status, LIMIT_METADATA);
+            let actual = metadata_number(&status, ACTUAL_METADATA);
+            if let (Some(limit), Some(actual)) = (limit, actual) {
+                return EchoError::TooLong { limit, actual };
+            }
+        }
+        EchoError::Status(status)
+    }
+}
+
+impl fmt::Display for EchoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EchoError::TooLong { limit, actual } => write!(f, "message too long: {}/{}", actual, limit),
+            EchoError::Status(status) => write!(f, "{}", status),
+        }
+    }
+}
+
+impl std::error::Error for EchoError {}
+
 // Extension method for main client
 impl GrpcClient {
     /// Create new echo service instance
@@ -26,14 +130,67 @@ impl GrpcClient {
     /// # Returns
     /// * `EchoService` - A new instance of the echo service client.
     pub fn echo(&self) -> EchoService {
+        let mut client = EchoServiceClient::new(self.traced_channel());
+        if let Some(encoding) = self.compression() {
+            client = client.send_compressed(encoding).accept_compressed(encoding);
+        }
         EchoService {
-            client: EchoServiceClient::new(self.get_channel())
+            backend: EchoBackend::Grpc(Box::new(client)),
+            log_payloads: self.log_payloads(),
+            allow_empty: self.allow_empty_echo(),
+            observer: self.observer(),
+            stream_retry: self.stream_retry(),
         }
     }
+
+    /// Measure the round-trip time of a minimal echo call
+    /// 
+    /// Includes connection setup if the channel is not connected yet;
+    /// call it twice when only the steady-state latency is of interest.
+    /// 
+    /// # Returns
+    /// * `Result<Duration, Status>` - The measured latency, or the status of the failed echo.
+    pub async fn round_trip(&self) -> Result<Duration, Status> {
+        let mut echo = self.echo();
+        let started = Instant::now();
+        echo.echo(PING_MESSAGE).await?;
+        let elapsed = started.elapsed();
+        debug!("Round trip took {:?}", elapsed);
+        Ok(elapsed)
+    }
 }
 
 // Main service implementation
 impl EchoService {
+    /// Create an echo service answered by `mock` instead of a server
+    /// 
+    /// Uses the builder defaults: empty messages are rejected locally and
+    /// payloads are not logged. Streaming calls fail with Unimplemented.
+    /// 
+    /// # Arguments
+    /// * `mock` - Canned Echo responses; keep a clone to inspect the requests.
+    /// 
+    /// # Returns
+    /// * `EchoService` - A service that never touches the network.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn with_mock(mock: MockRpc<EchoRequest, EchoResponse>) -> Self {
+        EchoService {
+            backend: EchoBackend::Mock(mock),
+            log_payloads: false,
+            allow_empty: false,
+            observer: CallObserver::default(),
+            stream_retry: StreamRetry::default(),
+        }
+    }
+
+    // Reject empty or whitespace-only messages unless they are allowed
+    fn check(&self, request: &EchoRequest) -> Result<(), Status> {
+        if self.allow_empty {
+            return Ok(());
+        }
+        request.validate()
+    }
+
     /// Echo method that accepts any string-like input
     /// 
     /// # Arguments
@@ -42,39 +199,223 @@ impl EchoService {
     /// # Returns
     /// * `Result<String, Status>` - A result containing the echoed message or an error status.
     pub async fn echo(&mut self, message: impl Into<String>) -> Result<String, Status> {
-        let message = message.into();
+        Ok(self.echo_detailed(message).await?.value.message)
+    }
+
+    /// Echo method with failures decoded into [`EchoError`]
+    /// 
+    /// Same call as `echo`, but a message over the server's character limit
+    /// comes back as `EchoError::TooLong` with both numbers.
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// 
+    /// # Returns
+    /// * `Result<String, EchoError>` - A result containing the echoed message or the decoded error.
+    pub async fn echo_checked(&mut self, message: impl Into<String>) -> Result<String, EchoError> {
+        Ok(self.echo(message).await?)
+    }
+
+    /// Echo method also returning the time the server spent in its handler
+    /// 
+    /// The duration comes from the response's `server_process_ns` and covers
+    /// only the handler body, so `round trip - duration` approximates the
+    /// network and (de)serialization cost. Servers that predate the field
+    /// report `Duration::ZERO`.
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// 
+    /// # Returns
+    /// * `Result<(String, Duration), Status>` - The echoed message and the handler time, or an error status.
+    pub async fn echo_with_timing(&mut self, message: impl Into<String>) -> Result<(String, Duration), Status> {
+        let response = self.echo_detailed(message).await?.value;
+        let processing = Duration::from_nanos(response.server_process_ns.unwrap_or_default());
+        debug!("Server spent {:?} in the echo handler", processing);
+        Ok((response.message, processing))
+    }
+
+    /// Echo method returning the full response, including the server's ordering stamps
+    /// 
+    /// `sequence` counts the echoes answered on this client's connection,
+    /// starting at 1; it restarts whenever the channel reconnects. The
+    /// connection request count also includes the connection's other RPCs.
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// 
+    /// # Returns
+    /// * `Result<CallResult<EchoResponse>, Status>` - A result containing the full echo response
+    ///   with its metadata and latency, or an error status.
+    pub async fn echo_detailed(&mut self, message: impl Into<String>) -> Result<CallResult<EchoResponse>, Status> {
+        let request = EchoRequest { message: message.into(), delay_ms: 0, validation: EchoValidation::None.into() };
         
         // Client-side validation before making RPC call
-        if message.trim().is_empty() {
-            return Err(Status::new(
-                Code::InvalidArgument,
-                "empty message is not allowed"
-            ));
-        }
+        self.check(&request)?;
+
+        debug!("Sending echo request ({})", Payload::new(&request.message, self.log_payloads));
+        // Send request
+        let request = Request::new(request);
+        let detailed = call_result::send(&self.observer, ECHO_PATH, request, |r| self.backend.echo(r)).await?;
+        debug!(
+            "Received echo response #{} ({})",
+            detailed.value.sequence,
+            Payload::new(&detailed.value.message, self.log_payloads)
+        );
+        Ok(detailed)
+    }
+
+    /// Echo method that has the server check or normalize the message first
+    /// 
+    /// With `RejectControl` a message containing a control character other
+    /// than `\n`, `\r` and `\t`, or an unassigned code point, fails with
+    /// `InvalidArgument`; the byte offset of the first one is in the message
+    /// and in the `offset` metadata. `NormalizeNfc` returns the message in
+    /// Normalization Form C, or `Unimplemented` from servers built without
+    /// the `nfc` feature. Servers that predate validation ignore it.
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// * `validation` - The checks and normalization to apply.
+    /// 
+    /// # Returns
+    /// * `Result<String, Status>` - A result containing the echoed (possibly normalized) message or an error status.
+    pub async fn echo_validated(&mut self, message: impl Into<String>, validation: EchoValidation) -> Result<String, Status> {
+        let request = EchoRequest { message: message.into(), delay_ms: 0, validation: validation.into() };
+
+        // Same client-side validation as a plain echo
+        self.check(&request)?;
+
+        debug!("Sending echo request with {:?} validation ({})", validation, Payload::new(&request.message, self.log_payloads));
+        let request = Request::new(request);
+        let response = call_result::send(&self.observer, ECHO_PATH, request, |r| self.backend.echo(r)).await?;
+        debug!("Received validated echo response ({})", Payload::new(&response.value.message, self.log_payloads));
+        Ok(response.value.message)
+    }
+
+    /// Echo method that asks the server to wait before responding
+    /// 
+    /// Keeps the request in flight on the server for roughly `delay`,
+    /// which is useful for exercising concurrency limits and timeouts.
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// * `delay` - How long the server should wait before responding (max 10s).
+    /// 
+    /// # Returns
+    /// * `Result<String, Status>` - A result containing the echoed message or an error status.
+    pub async fn echo_delayed(&mut self, message: impl Into<String>, delay: Duration) -> Result<String, Status> {
+        Ok(self.echo_delayed_detailed(message, delay).await?.value)
+    }
+
+    /// Delayed echo method also returning the response metadata and latency
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// * `delay` - How long the server should wait before responding (max 10s).
+    /// 
+    /// # Returns
+    /// * `Result<CallResult<String>, Status>` - The echoed message with its metadata, or the same errors as `echo_delayed`.
+    pub async fn echo_delayed_detailed(&mut self, message: impl Into<String>, delay: Duration) -> Result<CallResult<String>, Status> {
+        // Saturate rather than wrap for absurdly large durations;
+        // the server rejects anything above its limit anyway
+        let delay_ms = u32::try_from(delay.as_millis()).unwrap_or(u32::MAX);
+        let request = EchoRequest { message: message.into(), delay_ms, validation: EchoValidation::None.into() };
+
+        // Same client-side validation as a plain echo
+        self.check(&request)?;
+
+        debug!("Sending delayed echo request ({}ms, {})", delay_ms, Payload::new(&request.message, self.log_payloads));
+        let request = Request::new(request);
+        let response = call_result::send(&self.observer, ECHO_PATH, request, |r| self.backend.echo(r)).await?;
+        debug!("Received delayed echo response ({})", Payload::new(&response.value.message, self.log_payloads));
+        Ok(response.map(|response| response.message))
+    }
+
+    /// Streaming echo method that receives the message back `repeat` times
+    /// 
+    /// Dropping the returned stream cancels the call; the server stops
+    /// producing responses shortly after. A `repeat` of zero yields a stream
+    /// that ends immediately without items or error. Establishing the stream
+    /// is retried as set with `GrpcClientBuilder::stream_retries`; a
+    /// mid-stream error ends the stream unless `resubscribe_on_error` is set.
+    /// 
+    /// # Arguments
+    /// * `message` - A string-like type representing the message to echo.
+    /// * `repeat` - How many times the server should send the message back.
+    /// 
+    /// # Returns
+    /// * `Result<impl Stream<Item = Result<String, Status>>, Status>` - A result containing the stream of echoed messages or an error status.
+    pub async fn echo_stream(
+        &mut self,
+        message: impl Into<String>,
+        repeat: u32,
+    ) -> Result<impl Stream<Item = Result<String, Status>>, Status> {
+        let template = EchoStreamRequest { message: message.into(), repeat };
 
-        info!("Sending echo request with message: {}", message);
-        // Create and send request
-        let request = Request::new(EchoRequest { message });
-        let response = self.client.echo(request).await?;
-        let response_message = response.into_inner().message;
-        info!("Received echo response with message: {}", response_message);
-        Ok(response_message)
+        // Client-side validation before making RPC call
+        template.validate()?;
+
+        debug!("Sending streaming echo request x{} ({})", repeat, Payload::new(&template.message, self.log_payloads));
+        let client = self.backend.grpc()?.clone();
+        let observer = self.observer.clone();
+        // Each attempt is a new call, reported to the observer on its own
+        let mut open = move || {
+            let mut client = client.clone();
+            let observer = observer.clone();
+            let request = Request::new(template.clone());
+            async move {
+                let call = observer.start(ECHO_STREAM_PATH, request.metadata());
+                call.finish_stream(client.echo_stream(request).await.map(|r| r.into_inner()))
+            }
+        };
+        let retry = self.stream_retry;
+        let stream = retry.establish(ECHO_STREAM_PATH, &mut open).await?;
+        let responses: Pin<Box<dyn Stream<Item = Result<EchoResponse, Status>> + Send>> = if retry.resubscribe {
+            // A new stream starts again at sequence 1; echoes already received are skipped
+            Box::pin(retry.resubscribing(ECHO_STREAM_PATH, stream, open, |response| response.sequence))
+        } else {
+            Box::pin(stream)
+        };
+        Ok(responses.map(|item| item.map(|response| response.message)))
+    }
+
+    /// Chunked echo method that streams a payload to the server and back
+    /// 
+    /// Neither side buffers the whole payload: chunks are sent as the input
+    /// stream yields them and returned as the server forwards them. Chunks
+    /// are not copied on the way: `Vec<u8>` and `Bytes` chunks are sent as
+    /// they are, and echoed chunks share the buffer they were received in.
+    /// 
+    /// # Arguments
+    /// * `chunks` - A stream of byte chunks making up the payload, e.g. `Vec<u8>` or `Bytes`.
+    /// 
+    /// # Returns
+    /// * `Result<impl Stream<Item = Result<Bytes, Status>>, Status>` - A result containing the stream of echoed chunks or an error status.
+    pub async fn echo_chunked<B: Into<Bytes>>(
+        &mut self,
+        chunks: impl Stream<Item = B> + Send + 'static,
+    ) -> Result<impl Stream<Item = Result<Bytes, Status>>, Status> {
+        info!("Starting chunked echo stream");
+        // Wrap each raw chunk into the protocol message
+        let outbound = chunks.map(|data| EchoChunk { data: data.into() });
+        let request = Request::new(outbound);
+        let call = self.observer.start(ECHO_CHUNKED_PATH, request.metadata());
+        let response = call.finish_stream(self.backend.grpc()?.echo_chunked(request).await.map(|r| r.into_inner()))?;
+        // Unwrap each echoed message back into raw bytes
+        Ok(response.map(|chunk| chunk.map(|chunk| chunk.data)))
     }
 }
 
-// Test for not allowing empty messages to be sent
+// Tests of the wrapper's own logic, run against a mock backend
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
     async fn test_echo_empty_message() {
-        let client = GrpcClient::builder("http://[::1]:50051")
-            .unwrap()
-            .connect()
-            .unwrap();
-            
-        let mut echo = client.echo();
+        let mock = MockRpc::new("Echo");
+        let mut echo = EchoService::with_mock(mock.clone());
         
         let err = echo.echo("").await.unwrap_err();
         assert_eq!(err.code(), Code::InvalidArgument);
@@ -83,5 +424,35 @@ mod tests {
         let err = echo.echo("    ").await.unwrap_err();
         assert_eq!(err.code(), Code::InvalidArgument);
         assert!(err.message().contains("empty message"));
+
+        // Rejected before reaching the backend
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_echo_uses_backend_response() {
+        let mock = MockRpc::new("Echo");
+        mock.push(Ok(EchoResponse { message: "pong".into(), sequence: 7, ..Default::default() }));
+        let mut echo = EchoService::with_mock(mock.clone());
+
+        let response = echo.echo_detailed("ping").await.unwrap().value;
+        assert_eq!((response.message.as_str(), response.sequence), ("pong", 7));
+        assert_eq!(mock.requests(), vec![EchoRequest { message: "ping".into(), delay_ms: 0, validation: 0 }]);
+
+        // Nothing queued for a second call
+        assert_eq!(echo.echo("ping").await.unwrap_err().code(), Code::Unimplemented);
+    }
+
+    #[tokio::test]
+    async fn test_echo_checked_decodes_too_long() {
+        let mut status = Status::new(Code::InvalidArgument, "message too long");
+        status.metadata_mut().insert(LIMIT_METADATA, 5.into());
+        status.metadata_mut().insert(ACTUAL_METADATA, 9.into());
+        let mock = MockRpc::new("Echo");
+        mock.push(Err(status));
+        let mut echo = EchoService::with_mock(mock);
+
+        let err = echo.echo_checked("too long!").await.unwrap_err();
+        assert!(matches!(err, EchoError::TooLong { limit: 5, actual: 9 }), "got {:?}", err);
     }
 }
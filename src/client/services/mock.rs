@@ -0,0 +1,102 @@
+//! Mock Service Backends
+//! Lets the client wrappers be unit-tested without a server or network:
+//! 1. MockRpc: canned responses for one unary RPC, plus the requests it received
+//! 2. MockCalculator: one MockRpc per unary calculator RPC
+//!
+//! Pass them to `EchoService::with_mock` or `CalculatorService::with_mock`.
+//! Responses are returned in the order they were queued; a call with none
+//! left fails with Unimplemented. Streaming RPCs are not mocked and also
+//! fail with Unimplemented.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tonic::{Request, Response, Status};
+use crate::proto::calculator::{
+    AggregateRequest, AggregateResponse, CalculateDecimalRequest, CalculateDecimalResponse,
+    CalculateRequest, CalculateResponse, EvaluateRequest, EvaluateResponse,
+    ReduceRequest, ReduceResponse, GetHistoryRequest, GetHistoryResponse,
+};
+
+/// Canned responses for one unary RPC
+///
+/// Clones share their queue and recorded requests, so a test keeps one
+/// handle and gives another to the service.
+pub struct MockRpc<Req, Resp> {
+    name: &'static str,
+    state: Arc<Mutex<MockState<Req, Resp>>>,
+}
+
+struct MockState<Req, Resp> {
+    responses: VecDeque<Result<Resp, Status>>,
+    requests: Vec<Req>,
+}
+
+impl<Req, Resp> MockRpc<Req, Resp> {
+    /// Create a mock with no queued responses
+    ///
+    /// # Arguments
+    /// * `name` - RPC name used in the error for an unexpected call.
+    pub fn new(name: &'static str) -> Self {
+        let state = MockState { responses: VecDeque::new(), requests: Vec::new() };
+        MockRpc { name, state: Arc::new(Mutex::new(state)) }
+    }
+
+    /// Queue the outcome of the next call
+    pub fn push(&self, response: Result<Resp, Status>) -> &Self {
+        self.lock().responses.push_back(response);
+        self
+    }
+
+    /// Messages of the calls made so far, oldest first
+    pub fn requests(&self) -> Vec<Req>
+    where
+        Req: Clone,
+    {
+        self.lock().requests.clone()
+    }
+
+    // Record the request and pop the next queued response
+    pub(crate) fn call(&self, request: Request<Req>) -> Result<Response<Resp>, Status> {
+        let mut state = self.lock();
+        state.requests.push(request.into_inner());
+        match state.responses.pop_front() {
+            Some(response) => response.map(Response::new),
+            None => Err(Status::unimplemented(format!("mock {} has no response queued", self.name))),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState<Req, Resp>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Manual impl: the message types need not be Clone for the handle to be
+impl<Req, Resp> Clone for MockRpc<Req, Resp> {
+    fn clone(&self) -> Self {
+        MockRpc { name: self.name, state: self.state.clone() }
+    }
+}
+
+/// Canned responses for the unary calculator RPCs
+#[derive(Clone)]
+pub struct MockCalculator {
+    pub calculate: MockRpc<CalculateRequest, CalculateResponse>,
+    pub calculate_decimal: MockRpc<CalculateDecimalRequest, CalculateDecimalResponse>,
+    pub reduce: MockRpc<ReduceRequest, ReduceResponse>,
+    pub evaluate: MockRpc<EvaluateRequest, EvaluateResponse>,
+    pub aggregate: MockRpc<AggregateRequest, AggregateResponse>,
+    pub get_history: MockRpc<GetHistoryRequest, GetHistoryResponse>,
+}
+
+impl Default for MockCalculator {
+    fn default() -> Self {
+        MockCalculator {
+            calculate: MockRpc::new("Calculate"),
+            calculate_decimal: MockRpc::new("CalculateDecimal"),
+            reduce: MockRpc::new("Reduce"),
+            evaluate: MockRpc::new("Evaluate"),
+            aggregate: MockRpc::new("Aggregate"),
+            get_history: MockRpc::new("GetHistory"),
+        }
+    }
+}
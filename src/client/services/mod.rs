@@ -2,14 +2,45 @@
 //! This module organizes the client-side service implementations:
 //! - calculator: Calculator service client
 //! - echo: Echo service client
+//! - admin: Admin service client (operational queries)
+//! - broadcast: Broadcast service client (publish/subscribe echo)
+//! - call_result: Values returned with response metadata and latency (CallResult)
+//! - compose: Helpers combining several services (calculate_and_echo)
+//! - mock: Canned backends for unit tests (tests and feature "test-util")
 //!
 //! We re-export the main types and the Operation enum for easier access
 
 mod calculator;
 mod echo;
+mod admin;
+mod broadcast;
+mod payload;
+mod compose;
+mod call_result;
+#[cfg(any(test, feature = "test-util"))]
+mod mock;
 
 // Re-export service clients and common types
-pub use calculator::CalculatorService;
-pub use echo::EchoService;
-// Re-export Operation enum for calculator service
-pub use crate::proto::calculator::Operation;
+pub use calculator::{CalculatorService, CalculatorError, Evaluation, EvaluationStep};
+pub use echo::{EchoService, EchoError};
+pub use admin::AdminService;
+pub use broadcast::BroadcastService;
+pub use call_result::CallResult;
+// Re-export the mock backends accepted by the with_mock constructors
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::{MockRpc, MockCalculator};
+// Re-export the chunk type returned by EchoService::echo_chunked
+pub use bytes::Bytes;
+// Re-export the full echo response returned in EchoService::echo_detailed
+// and the validation accepted by EchoService::echo_validated
+pub use crate::proto::echo::{EchoResponse, EchoValidation};
+// Re-export Operation enum and result types for calculator service
+pub use crate::proto::calculator::{Operation, AggregateResponse, AggregateProgress, RunningTotalResponse};
+// Re-export the history returned by CalculatorService::history
+pub use crate::proto::calculator::{GetHistoryResponse, HistoryEntry};
+// Re-export the structured detail decoded by CalculatorError
+pub use crate::proto::calculator::{CalculatorErrorDetail, calculator_error_detail::Kind as CalculatorErrorKind};
+// Re-export result types for admin service
+pub use crate::proto::admin::{LatencyStatsResponse, ServerInfoResponse, CacheStatsResponse, SetConfigRequest, ConfigResponse, ServingState};
+// Re-export the message received by BroadcastService::subscribe
+pub use crate::proto::broadcast::BroadcastMessage;
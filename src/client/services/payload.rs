@@ -0,0 +1,80 @@
+//! Payload Logging Helper
+//! Client wrappers must not write whole request/response bodies into the log:
+//! large payloads bloat log files and sensitive data ends up on disk.
+//! This helper formats a payload as its byte length plus a short preview,
+//! unless full payload logging was explicitly enabled on the client builder.
+
+use std::fmt;
+
+// Number of characters shown in a truncated preview
+const PREVIEW_CHARS: usize = 64;
+
+/// Display adapter that renders a payload according to the logging policy
+/// 
+/// Formatting only happens when the log event is actually enabled,
+/// so wrapping a payload costs nothing when debug logging is off.
+pub(crate) struct Payload<'a> {
+    text: &'a str,
+    full: bool,  // Log the whole payload instead of a preview
+}
+
+impl<'a> Payload<'a> {
+    /// Wrap a payload for logging
+    /// 
+    /// # Arguments
+    /// * `text` - The payload to log.
+    /// * `full` - Whether the full payload may be logged (`log_payloads(true)`).
+    /// 
+    /// # Returns
+    /// * `Payload` - A value whose Display impl applies the policy.
+    pub(crate) fn new(text: &'a str, full: bool) -> Self {
+        Self { text, full }
+    }
+}
+
+impl fmt::Display for Payload<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes: ", self.text.len())?;
+        if self.full {
+            return write!(f, "{:?}", self.text);
+        }
+
+        // Cut on a character boundary, never inside a multi-byte sequence
+        match self.text.char_indices().nth(PREVIEW_CHARS) {
+            Some((cut, _)) => write!(f, "{:?}…", &self.text[..cut]),
+            None => write!(f, "{:?}", self.text),
+        }
+    }
+}
+
+// Unit tests for the preview formatting
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_preview_truncates() {
+        let long = "a".repeat(1000);
+        let rendered = Payload::new(&long, false).to_string();
+        assert!(rendered.starts_with("1000 bytes: "));
+        assert!(rendered.ends_with('…'));
+        assert!(rendered.len() < 100);
+
+        // Short payloads are shown whole without the ellipsis
+        assert_eq!(Payload::new("hi", false).to_string(), "2 bytes: \"hi\"");
+
+        // Multi-byte characters are counted as characters, not bytes
+        let emoji = "🚀".repeat(100);
+        let rendered = Payload::new(&emoji, false).to_string();
+        assert!(rendered.starts_with("400 bytes: "));
+        assert_eq!(rendered.matches('🚀').count(), PREVIEW_CHARS);
+    }
+
+    #[test]
+    fn test_payload_full_logging() {
+        let long = "b".repeat(1000);
+        let rendered = Payload::new(&long, true).to_string();
+        assert!(rendered.contains(&long));
+        assert!(!rendered.ends_with('…'));
+    }
+}
@@ -0,0 +1,48 @@
+//! Client Configuration Snapshot
+//! A serializable summary of every option set on a `GrpcClientBuilder`,
+//! for logging how a client was configured. Proxy credentials only appear
+//! as `Redacted`; the builder Debug output is printed from this snapshot,
+//! so it redacts the same way.
+
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::redact::Redacted;
+
+/// Options of a client builder, secrets redacted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientConfigSnapshot {
+    /// Server endpoint URI
+    pub address: String,
+    /// Whether the endpoint is reached over TLS
+    pub tls: bool,
+    /// Whether a client certificate is presented (mutual TLS); the key is never shown
+    pub tls_identity: bool,
+    /// `"custom"`, `"http proxy <host:port>"` for `http_proxy` or
+    /// `"named pipe <path>"` for `named_pipe`; None for plain TCP
+    pub connector: Option<String>,
+    /// Set when the proxy URL carries credentials
+    pub proxy_credentials: Option<Redacted>,
+    pub resolve_all: bool,
+    pub request_timeout: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    pub total_call_budget: Option<Duration>,
+    /// Compression encoding, e.g. `"gzip"`
+    pub compression: Option<String>,
+    pub log_payloads: bool,
+    pub allow_empty_echo: bool,
+    pub connect_retries: u32,
+    pub retry_delay: Duration,
+    pub warmup_rpc: bool,
+    pub warmup_timeout: Duration,
+    /// Whether a ClientObserver was installed
+    pub observer: bool,
+    /// Whether a connection state callback was installed
+    pub on_state_change: bool,
+    pub skip_logging_init: bool,
+    pub stream_retries: u32,
+    pub stream_retry_delay: Duration,
+    pub resubscribe_on_error: bool,
+    /// Capacity of the calculator result cache; None when it is off
+    pub calculator_cache: Option<usize>,
+    pub calculator_cache_ttl: Option<Duration>,
+}
@@ -0,0 +1,197 @@
+//! Client Connection State
+//! A coarse connection state for UI indicators
+//! (GrpcClientBuilder::on_state_change). tonic does not expose the state
+//! of its channel, so it is inferred from the outcome of calls:
+//! 1. Connecting: no call has completed yet, or a call is under way after
+//!    a failure (the channel reconnects on the next call)
+//! 2. Ready: the last call reached the server, whatever status it returned
+//! 3. TransientFailure: the last call failed with Unavailable
+//!
+//! Cancelled and DeadlineExceeded say nothing about the connection and
+//! leave the state as it is. Calls rejected by client-side validation
+//! never reach the network and are not counted either.
+//!
+//! Transitions are reported in the order they happened, one at a time, so
+//! the last state reported is always the current one once calls settle.
+
+use std::collections::VecDeque;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use tonic::Code;
+use tracing::error;
+
+/// Connection state of a client, as inferred from its calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// No call has completed yet, or one is retrying after a failure
+    Connecting,
+    /// The server answered the last call
+    Ready,
+    /// The last call could not reach the server (Unavailable)
+    TransientFailure,
+}
+
+// Callback run on every transition
+type StateCallback = Arc<dyn Fn(ConnectionState) + Send + Sync + 'static>;
+
+// Current state and the transitions not reported yet
+#[derive(Debug)]
+struct Transitions {
+    state: ConnectionState,
+    pending: VecDeque<ConnectionState>,  // Oldest first
+    reporting: bool,  // Whether some call is running the callback
+}
+
+// Current state plus the callback told about changes, shared by clones
+#[derive(Clone)]
+pub(crate) struct StateWatch {
+    transitions: Arc<Mutex<Transitions>>,
+    callback: StateCallback,
+}
+
+impl StateWatch {
+    pub(crate) fn new(callback: impl Fn(ConnectionState) + Send + Sync + 'static) -> Self {
+        Self {
+            transitions: Arc::new(Mutex::new(Transitions {
+                state: ConnectionState::Connecting,
+                pending: VecDeque::new(),
+                reporting: false,
+            })),
+            callback: Arc::new(callback),
+        }
+    }
+
+    // A call is about to be sent; after a failure it has to reconnect first
+    pub(crate) fn call_started(&self) {
+        self.transition(|state| (state == ConnectionState::TransientFailure).then_some(ConnectionState::Connecting));
+    }
+
+    // A call ended with `code`
+    pub(crate) fn call_finished(&self, code: Code) {
+        let next = match code {
+            Code::Unavailable => ConnectionState::TransientFailure,
+            Code::Cancelled | Code::DeadlineExceeded => return,
+            _ => ConnectionState::Ready,
+        };
+        self.transition(|_| Some(next));
+    }
+
+    // Move to the state `next` picks, if any, and report a change
+    // Changes are queued under the lock in the order they happen; the first
+    // call to find nobody reporting delivers the queue, oldest first, while
+    // later ones only queue theirs. The callback runs without the lock, so
+    // it may make calls itself; their transitions are delivered after it.
+    fn transition(&self, next: impl FnOnce(ConnectionState) -> Option<ConnectionState>) {
+        let mut transitions = self.transitions.lock().unwrap();
+        match next(transitions.state) {
+            Some(next) if next != transitions.state => {
+                transitions.state = next;
+                transitions.pending.push_back(next);
+            }
+            _ => return,
+        }
+        if transitions.reporting {
+            return;
+        }
+        transitions.reporting = true;
+        loop {
+            let Some(state) = transitions.pending.pop_front() else {
+                transitions.reporting = false;
+                return;
+            };
+            drop(transitions);
+            if catch_unwind(AssertUnwindSafe(|| (self.callback)(state))).is_err() {
+                error!("Connection state callback panicked on {:?}", state);
+            }
+            transitions = self.transitions.lock().unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions_follow_call_outcomes() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let watch = StateWatch::new({
+            let seen = seen.clone();
+            move |state| seen.lock().unwrap().push(state)
+        });
+
+        watch.call_started();
+        watch.call_finished(Code::Ok);
+        // A server-side error still means the server was reached
+        watch.call_finished(Code::InvalidArgument);
+        watch.call_finished(Code::Unavailable);
+        watch.call_finished(Code::Cancelled);
+        watch.call_started();
+        watch.call_finished(Code::DeadlineExceeded);
+        watch.call_finished(Code::Ok);
+
+        use ConnectionState::*;
+        assert_eq!(*seen.lock().unwrap(), [Ready, TransientFailure, Connecting, Ready]);
+    }
+
+    // Calls finishing on many threads at once, with a slow callback widening
+    // the window between a change and its report
+    #[test]
+    fn test_concurrent_transitions_reported_in_order() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let watch = StateWatch::new({
+            let seen = seen.clone();
+            move |state| {
+                // Slower to report one state than the others
+                if state == ConnectionState::Ready {
+                    std::thread::sleep(std::time::Duration::from_micros(50));
+                }
+                seen.lock().unwrap().push(state);
+            }
+        });
+
+        let threads: Vec<_> = (0..8)
+            .map(|thread| {
+                let watch = watch.clone();
+                std::thread::spawn(move || {
+                    for i in 0..200 {
+                        watch.call_started();
+                        let code = if (i + thread) % 3 == 0 { Code::Unavailable } else { Code::Ok };
+                        watch.call_finished(code);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.last(), Some(&watch.transitions.lock().unwrap().state));
+        // Every report is a change from the one before
+        assert!(seen.windows(2).all(|pair| pair[0] != pair[1]), "out of order reports");
+    }
+
+    // A callback making calls of its own neither deadlocks nor reorders
+    #[test]
+    fn test_callback_may_change_state() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let watch = Arc::new(Mutex::new(None::<StateWatch>));
+        let inner = StateWatch::new({
+            let (seen, watch) = (seen.clone(), watch.clone());
+            move |state| {
+                seen.lock().unwrap().push(state);
+                if state == ConnectionState::TransientFailure {
+                    // e.g. a UI retrying at once
+                    let watch = watch.lock().unwrap().clone().unwrap();
+                    watch.call_started();
+                }
+            }
+        });
+        *watch.lock().unwrap() = Some(inner.clone());
+
+        inner.call_finished(Code::Unavailable);
+        use ConnectionState::*;
+        assert_eq!(*seen.lock().unwrap(), [TransientFailure, Connecting]);
+    }
+}
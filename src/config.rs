@@ -0,0 +1,445 @@
+//! Environment Configuration
+//! Container deployments configure the server and client through
+//! environment variables rather than files or flags. This module:
+//! 1. Reads `<PREFIX>_<NAME>` variables into plain config structs
+//! 2. Parses every value into its real type (sizes, durations, paths, ...)
+//! 3. Reports all missing or invalid variables in one error
+//!
+//! The structs are consumed by `GrpcServerBuilder::from_config` and
+//! `GrpcClientBuilder::from_config`. Empty variables count as unset.
+//! `RuntimeConfig` sizes the tokio runtime of the server binary and can
+//! also be filled from command-line flags.
+
+use std::env::{self, VarError};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tonic::codec::CompressionEncoding;
+use serde::{Deserialize, Serialize};
+
+/// Error listing every problem found in a configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// One entry per missing or invalid setting, in reading order
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration: {}", self.problems.join("; "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Server settings read by `ServerConfig::from_env`
+///
+/// | Variable                          | Type                 | Required |
+/// |-----------------------------------|----------------------|----------|
+/// | `<PREFIX>_ADDR`                   | socket address       | yes      |
+/// | `<PREFIX>_MAX_MESSAGE_SIZE`       | bytes                | no       |
+/// | `<PREFIX>_REQUEST_TIMEOUT_MS`     | milliseconds         | no       |
+/// | `<PREFIX>_TLS_CERT` / `_TLS_KEY`  | PEM file paths       | together |
+/// | `<PREFIX>_COMPRESSION`            | `gzip` or `none`     | no       |
+/// | `<PREFIX>_MAX_CONCURRENT_PER_PEER`| count                | no       |
+/// | `<PREFIX>_STARTUP_BANNER`         | boolean              | no       |
+/// | `<PREFIX>_WORKER_THREADS`         | count                | no       |
+/// | `<PREFIX>_MAX_BLOCKING_THREADS`   | count                | no       |
+/// | `<PREFIX>_THREAD_NAME_PREFIX`     | text                 | no       |
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub addr: SocketAddr,
+    pub max_message_size: Option<usize>,
+    pub request_timeout: Option<Duration>,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    pub compression: Option<CompressionEncoding>,
+    pub max_concurrent_per_peer: Option<usize>,
+    pub startup_banner: Option<bool>,
+    pub runtime: RuntimeConfig,
+}
+
+impl ServerConfig {
+    /// Read the server configuration from `<prefix>_*` variables
+    ///
+    /// # Arguments
+    /// * `prefix` - Variable name prefix, e.g. `"GRPC_SERVER"` for `GRPC_SERVER_ADDR`.
+    ///
+    /// # Returns
+    /// * `Result<Self, ConfigError>` - The configuration, or every problem found.
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        let mut env = EnvReader::new(prefix);
+
+        let addr = env.required("ADDR", "a socket address like 0.0.0.0:50051");
+        let max_message_size = env.parse("MAX_MESSAGE_SIZE", "a size in bytes");
+        let request_timeout = env.parse("REQUEST_TIMEOUT_MS", "milliseconds").map(Duration::from_millis);
+        let tls_cert = env.parse("TLS_CERT", "a file path");
+        let tls_key = env.parse("TLS_KEY", "a file path");
+        let compression = env.compression("COMPRESSION");
+        let max_concurrent_per_peer = env.parse("MAX_CONCURRENT_PER_PEER", "a positive count");
+        let startup_banner = env.flag("STARTUP_BANNER");
+        let runtime = RuntimeConfig {
+            worker_threads: env.parse("WORKER_THREADS", "a positive count"),
+            max_blocking_threads: env.parse("MAX_BLOCKING_THREADS", "a positive count"),
+            thread_name_prefix: env.parse("THREAD_NAME_PREFIX", "a thread name prefix"),
+        };
+
+        // A certificate without its key (or vice versa) cannot be used
+        if tls_cert.is_some() != tls_key.is_some() {
+            env.problem(format!(
+                "{} and {} must be set together",
+                env.key("TLS_CERT"),
+                env.key("TLS_KEY")
+            ));
+        }
+        for (name, value) in [
+            ("MAX_CONCURRENT_PER_PEER", max_concurrent_per_peer),
+            ("WORKER_THREADS", runtime.worker_threads),
+            ("MAX_BLOCKING_THREADS", runtime.max_blocking_threads),
+        ] {
+            if value == Some(0) {
+                env.problem(format!("{}: must be at least 1", env.key(name)));
+            }
+        }
+
+        env.finish()?;
+        Ok(Self {
+            // finish() fails whenever a required value is missing
+            addr: addr.expect("required variable checked by finish()"),
+            max_message_size,
+            request_timeout,
+            tls_cert,
+            tls_key,
+            compression,
+            max_concurrent_per_peer,
+            startup_banner,
+            runtime,
+        })
+    }
+}
+
+/// Tokio runtime settings of the server binary
+///
+/// Unset values keep tokio's defaults: one worker thread per CPU core,
+/// at most 512 blocking threads, threads named `tokio-rt-worker`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    pub worker_threads: Option<usize>,
+    pub max_blocking_threads: Option<usize>,
+    pub thread_name_prefix: Option<String>,
+}
+
+impl RuntimeConfig {
+    /// Tokio's blocking pool limit, used when `max_blocking_threads` is unset
+    pub const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+    /// Tokio's thread name, used when `thread_name_prefix` is unset
+    pub const DEFAULT_THREAD_NAME: &'static str = "tokio-rt-worker";
+
+    /// Build a multi-threaded runtime with these settings
+    ///
+    /// Threads are named `<prefix>-<n>` when a prefix is set.
+    ///
+    /// # Returns
+    /// * `Result<Runtime, ConfigError>` - The runtime, or every invalid setting.
+    pub fn build_runtime(&self) -> Result<Runtime, ConfigError> {
+        let mut problems = Vec::new();
+        if self.worker_threads == Some(0) {
+            problems.push("worker threads: must be at least 1".to_string());
+        }
+        if self.max_blocking_threads == Some(0) {
+            problems.push("max blocking threads: must be at least 1".to_string());
+        }
+        if self.thread_name_prefix.as_deref().is_some_and(|prefix| prefix.trim().is_empty()) {
+            problems.push("thread name prefix: must not be empty".to_string());
+        }
+        if !problems.is_empty() {
+            return Err(ConfigError { problems });
+        }
+
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(threads) = self.worker_threads {
+            builder.worker_threads(threads);
+        }
+        if let Some(threads) = self.max_blocking_threads {
+            builder.max_blocking_threads(threads);
+        }
+        if let Some(prefix) = self.thread_name_prefix.clone() {
+            let next = AtomicUsize::new(0);
+            builder.thread_name_fn(move || format!("{}-{}", prefix, next.fetch_add(1, Ordering::Relaxed)));
+        }
+        builder.build().map_err(|e| ConfigError {
+            problems: vec![format!("cannot start runtime: {}", e)],
+        })
+    }
+
+    /// Blocking thread limit in effect with these settings
+    pub fn effective_max_blocking_threads(&self) -> usize {
+        self.max_blocking_threads.unwrap_or(Self::DEFAULT_MAX_BLOCKING_THREADS)
+    }
+
+    /// Thread name prefix in effect with these settings
+    pub fn effective_thread_name_prefix(&self) -> &str {
+        self.thread_name_prefix.as_deref().unwrap_or(Self::DEFAULT_THREAD_NAME)
+    }
+}
+
+/// Client settings read by `ClientConfig::from_env`
+///
+/// | Variable                      | Type               | Required |
+/// |-------------------------------|--------------------|----------|
+/// | `<PREFIX>_ENDPOINT`           | URI                | yes      |
+/// | `<PREFIX>_TLS_CA`             | PEM file path      | no       |
+/// | `<PREFIX>_TLS_DOMAIN`         | DNS name           | no       |
+/// | `<PREFIX>_REQUEST_TIMEOUT_MS` | milliseconds       | no       |
+/// | `<PREFIX>_COMPRESSION`        | `gzip` or `none`   | no       |
+/// | `<PREFIX>_LOG_PAYLOADS`       | boolean            | no       |
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    pub endpoint: String,
+    pub tls_ca: Option<PathBuf>,
+    pub tls_domain: Option<String>,
+    pub request_timeout: Option<Duration>,
+    pub compression: Option<CompressionEncoding>,
+    pub log_payloads: Option<bool>,
+}
+
+impl ClientConfig {
+    /// Read the client configuration from `<prefix>_*` variables
+    ///
+    /// # Arguments
+    /// * `prefix` - Variable name prefix, e.g. `"GRPC_CLIENT"` for `GRPC_CLIENT_ENDPOINT`.
+    ///
+    /// # Returns
+    /// * `Result<Self, ConfigError>` - The configuration, or every problem found.
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        let mut env = EnvReader::new(prefix);
+
+        // Validated as a URI but kept verbatim for GrpcClientBuilder::new
+        let endpoint = env.required_with("ENDPOINT", "a URI like http://127.0.0.1:50051", |value| {
+            value.parse::<http::Uri>().ok().map(|_| value.to_string())
+        });
+        let tls_ca = env.parse("TLS_CA", "a file path");
+        let tls_domain = env.parse("TLS_DOMAIN", "a DNS name");
+        let request_timeout = env.parse("REQUEST_TIMEOUT_MS", "milliseconds").map(Duration::from_millis);
+        let compression = env.compression("COMPRESSION");
+        let log_payloads = env.flag("LOG_PAYLOADS");
+
+        // The domain override only matters when TLS is configured
+        if tls_domain.is_some() && tls_ca.is_none() {
+            env.problem(format!("{} requires {}", env.key("TLS_DOMAIN"), env.key("TLS_CA")));
+        }
+
+        env.finish()?;
+        Ok(Self {
+            endpoint: endpoint.expect("required variable checked by finish()"),
+            tls_ca,
+            tls_domain,
+            request_timeout,
+            compression,
+            log_payloads,
+        })
+    }
+}
+
+// Reads prefixed variables and collects problems instead of stopping
+struct EnvReader<'a> {
+    prefix: &'a str,
+    problems: Vec<String>,
+}
+
+impl<'a> EnvReader<'a> {
+    fn new(prefix: &'a str) -> Self {
+        Self { prefix, problems: Vec::new() }
+    }
+
+    // Full variable name for a setting
+    fn key(&self, name: &str) -> String {
+        format!("{}_{}", self.prefix, name)
+    }
+
+    fn problem(&mut self, problem: String) {
+        self.problems.push(problem);
+    }
+
+    // Raw value; None when unset or empty
+    fn raw(&mut self, name: &str) -> Option<String> {
+        let key = self.key(name);
+        match env::var(&key) {
+            Ok(value) if value.trim().is_empty() => None,
+            Ok(value) => Some(value.trim().to_string()),
+            Err(VarError::NotPresent) => None,
+            Err(VarError::NotUnicode(_)) => {
+                self.problem(format!("{}: value is not valid UTF-8", key));
+                None
+            }
+        }
+    }
+
+    // Parse a present value, recording a problem when it does not parse
+    fn check<T>(&mut self, name: &str, value: String, expected: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let parsed = parse(&value);
+        if parsed.is_none() {
+            let key = self.key(name);
+            self.problem(format!("{}: expected {}, got {:?}", key, expected, value));
+        }
+        parsed
+    }
+
+    // Optional value parsed with a custom function
+    fn parse_with<T>(&mut self, name: &str, expected: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let value = self.raw(name)?;
+        self.check(name, value, expected, parse)
+    }
+
+    // Optional value parsed with FromStr
+    fn parse<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        self.parse_with(name, expected, |value| value.parse().ok())
+    }
+
+    // Value that must be present, parsed with a custom function
+    fn required_with<T>(&mut self, name: &str, expected: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        match self.raw(name) {
+            Some(value) => self.check(name, value, expected, parse),
+            None => {
+                let key = self.key(name);
+                self.problem(format!("{} is required", key));
+                None
+            }
+        }
+    }
+
+    // Value that must be present, parsed with FromStr
+    fn required<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        self.required_with(name, expected, |value| value.parse().ok())
+    }
+
+    // Boolean accepting the usual spellings
+    fn flag(&mut self, name: &str) -> Option<bool> {
+        self.parse_with(name, "true or false", |value| match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(true),
+            "0" | "false" | "no" | "off" => Some(false),
+            _ => None,
+        })
+    }
+
+    // Compression algorithm; "none" means explicitly disabled
+    fn compression(&mut self, name: &str) -> Option<CompressionEncoding> {
+        self.parse_with(name, "gzip or none", |value| match value.to_ascii_lowercase().as_str() {
+            "gzip" => Some(Some(CompressionEncoding::Gzip)),
+            "none" => Some(None),
+            _ => None,
+        }).flatten()
+    }
+
+    fn finish(self) -> Result<(), ConfigError> {
+        if self.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError { problems: self.problems })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // The environment is process-wide; tests touching it take this lock
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    // Run `test` with exactly the given variables set under `prefix`
+    fn with_env<T>(prefix: &str, vars: &[(&str, &str)], test: impl FnOnce() -> T) -> T {
+        let _lock = ENV_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (name, value) in vars {
+            env::set_var(format!("{}_{}", prefix, name), value);
+        }
+        let result = test();
+        for (name, _) in vars {
+            env::remove_var(format!("{}_{}", prefix, name));
+        }
+        result
+    }
+
+    #[test]
+    fn test_server_config_from_env() {
+        let config = with_env("CFG_SERVER_OK", &[
+            ("ADDR", "127.0.0.1:50051"),
+            ("MAX_MESSAGE_SIZE", "1048576"),
+            ("REQUEST_TIMEOUT_MS", "2500"),
+            ("TLS_CERT", "/certs/server.pem"),
+            ("TLS_KEY", "/certs/server.key"),
+            ("COMPRESSION", "GZIP"),
+            ("STARTUP_BANNER", "off"),
+            ("WORKER_THREADS", "4"),
+            ("THREAD_NAME_PREFIX", "grpc"),
+        ], || ServerConfig::from_env("CFG_SERVER_OK")).unwrap();
+
+        assert_eq!(config.addr, "127.0.0.1:50051".parse().unwrap());
+        assert_eq!(config.max_message_size, Some(1_048_576));
+        assert_eq!(config.request_timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(config.tls_cert, Some(PathBuf::from("/certs/server.pem")));
+        assert_eq!(config.tls_key, Some(PathBuf::from("/certs/server.key")));
+        assert_eq!(config.compression, Some(CompressionEncoding::Gzip));
+        assert_eq!(config.max_concurrent_per_peer, None);
+        assert_eq!(config.startup_banner, Some(false));
+        assert_eq!(config.runtime, RuntimeConfig {
+            worker_threads: Some(4),
+            max_blocking_threads: None,
+            thread_name_prefix: Some("grpc".to_string()),
+        });
+    }
+
+    #[test]
+    fn test_server_config_reports_all_problems() {
+        let err = with_env("CFG_SERVER_BAD", &[
+            ("MAX_MESSAGE_SIZE", "4MB"),
+            ("REQUEST_TIMEOUT_MS", "-1"),
+            ("TLS_CERT", "/certs/server.pem"),
+            ("COMPRESSION", "brotli"),
+            ("WORKER_THREADS", "0"),
+        ], || ServerConfig::from_env("CFG_SERVER_BAD")).unwrap_err();
+
+        assert_eq!(err.problems.len(), 6, "{}", err);
+        let message = err.to_string();
+        for expected in [
+            "CFG_SERVER_BAD_ADDR is required",
+            "CFG_SERVER_BAD_MAX_MESSAGE_SIZE: expected a size in bytes, got \"4MB\"",
+            "CFG_SERVER_BAD_REQUEST_TIMEOUT_MS",
+            "CFG_SERVER_BAD_COMPRESSION: expected gzip or none",
+            "CFG_SERVER_BAD_TLS_CERT and CFG_SERVER_BAD_TLS_KEY must be set together",
+            "CFG_SERVER_BAD_WORKER_THREADS: must be at least 1",
+        ] {
+            assert!(message.contains(expected), "missing {:?} in {}", expected, message);
+        }
+    }
+
+    #[test]
+    fn test_client_config_from_env() {
+        let config = with_env("CFG_CLIENT_OK", &[
+            ("ENDPOINT", "https://grpc.example:50051"),
+            ("TLS_CA", "/certs/ca.pem"),
+            ("TLS_DOMAIN", "grpc.example"),
+            ("COMPRESSION", "none"),
+            ("LOG_PAYLOADS", "true"),
+        ], || ClientConfig::from_env("CFG_CLIENT_OK")).unwrap();
+
+        assert_eq!(config.endpoint, "https://grpc.example:50051");
+        assert_eq!(config.tls_ca, Some(PathBuf::from("/certs/ca.pem")));
+        assert_eq!(config.tls_domain.as_deref(), Some("grpc.example"));
+        assert_eq!(config.request_timeout, None);
+        assert_eq!(config.compression, None);
+        assert_eq!(config.log_payloads, Some(true));
+
+        let err = with_env("CFG_CLIENT_BAD", &[
+            ("TLS_DOMAIN", "grpc.example"),
+            ("LOG_PAYLOADS", "maybe"),
+        ], || ClientConfig::from_env("CFG_CLIENT_BAD")).unwrap_err();
+        assert_eq!(err.problems.len(), 3, "{}", err);
+    }
+}
@@ -0,0 +1,299 @@
+//! Arithmetic Expressions
+//! This file parses expressions such as `2 + 3 * (4 - 1)` and plans them as
+//! a sequence of single calculator operations. It is shared by:
+//! 1. The server's Evaluate RPC, which runs the plan locally
+//! 2. The client, which can run the plan as individual Calculate RPCs when
+//!    the server has no Evaluate RPC
+//!
+//! Supported syntax: decimal numbers (optionally with an exponent),
+//! `+ - * /` with the usual precedence and left associativity, unary minus
+//! and parentheses. Both sides follow the same plan, so they perform the
+//! same floating-point operations in the same order and agree exactly.
+
+use std::fmt;
+use crate::proto::calculator::Operation;
+
+// Deepest parenthesis / unary minus nesting accepted
+// Keeps the recursive parser's stack use bounded for untrusted input
+const MAX_DEPTH: usize = 64;
+
+/// Why an expression could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset in the expression where the problem was found
+    pub position: usize,
+    /// What was wrong
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid expression at position {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Input of a planned step: a literal or the result of an earlier step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    /// A number written in the expression
+    Value(f64),
+    /// The result of the step at this index in [`Plan::steps`]
+    Step(usize),
+}
+
+impl Operand {
+    /// The operand's value, given the results of the steps run so far
+    ///
+    /// # Panics
+    /// If it refers to a step whose result is not in `results` yet; steps
+    /// only refer to earlier ones, so running them in order never panics.
+    pub fn resolve(&self, results: &[f64]) -> f64 {
+        match *self {
+            Operand::Value(value) => value,
+            Operand::Step(index) => results[index],
+        }
+    }
+}
+
+/// One operation of a plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStep {
+    pub first: Operand,
+    pub second: Operand,
+    pub operation: Operation,
+    /// The part of the expression this step computes, e.g. `6 / (4 - 4)`
+    pub expression: String,
+}
+
+/// An expression decomposed into single operations, in evaluation order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    /// Every step only refers to steps before it
+    pub steps: Vec<PlannedStep>,
+    /// The value of the whole expression; a plain number needs no steps
+    pub result: Operand,
+}
+
+/// Parse `expression` and plan its evaluation
+///
+/// # Arguments
+/// * `expression` - The text to parse, e.g. `"2 + 3 * 4"`.
+///
+/// # Returns
+/// * `Result<Plan, ParseError>` - The steps to run, or where parsing failed.
+pub fn plan(expression: &str) -> Result<Plan, ParseError> {
+    let mut parser = Parser { source: expression, position: 0, depth: 0, steps: Vec::new() };
+    let result = parser.expr()?;
+    parser.skip_whitespace();
+    if let Some(c) = parser.peek() {
+        return Err(parser.error(format!("unexpected {:?}", c)));
+    }
+    Ok(Plan { steps: parser.steps, result: result.operand })
+}
+
+// A parsed sub-expression: its value and where it starts in the source
+struct Parsed {
+    operand: Operand,
+    start: usize,
+}
+
+// Recursive descent parser that emits steps as it reduces operators
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+    depth: usize,
+    steps: Vec<PlannedStep>,
+}
+
+impl Parser<'_> {
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Parsed, ParseError> {
+        let mut left = self.term()?;
+        loop {
+            let operation = match self.peek_operator() {
+                Some('+') => Operation::Add,
+                Some('-') => Operation::Subtract,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.term()?;
+            left = self.step(left, right.operand, operation);
+        }
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<Parsed, ParseError> {
+        let mut left = self.factor()?;
+        loop {
+            let operation = match self.peek_operator() {
+                Some('*') => Operation::Multiply,
+                Some('/') => Operation::Divide,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.factor()?;
+            left = self.step(left, right.operand, operation);
+        }
+    }
+
+    // factor := '-' factor | number | '(' expr ')'
+    fn factor(&mut self) -> Result<Parsed, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                let inner = self.nested(Self::factor)?;
+                Ok(match inner.operand {
+                    // Negative literals need no step
+                    Operand::Value(value) => Parsed { operand: Operand::Value(-value), start },
+                    operand => self.step(Parsed { operand: Operand::Value(0.0), start }, operand, Operation::Subtract),
+                })
+            }
+            Some('(') => {
+                self.position += 1;
+                let inner = self.nested(Self::expr)?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return Err(self.error("expected ')'".to_string()));
+                }
+                self.position += 1;
+                Ok(Parsed { operand: inner.operand, start })
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) => Err(self.error(format!("expected a number or '(', found {:?}", c))),
+            None => Err(self.error("expected a number or '(', found the end".to_string())),
+        }
+    }
+
+    // Parse with one more level of nesting, enforcing MAX_DEPTH
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Parsed, ParseError>) -> Result<Parsed, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("nested more than {} levels deep", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    // number := digits ['.' digits] [('e' | 'E') ['+' | '-'] digits]
+    fn number(&mut self) -> Result<Parsed, ParseError> {
+        let start = self.position;
+        let rest = &self.source[start..];
+        let mut end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        // Optional exponent, only if digits follow
+        if let Some(exponent) = rest[end..].strip_prefix(['e', 'E']) {
+            let signed = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            let digits = signed.find(|c: char| !c.is_ascii_digit()).unwrap_or(signed.len());
+            if digits > 0 {
+                end += rest[end..].len() - signed.len() + digits;
+            }
+        }
+        let text = &rest[..end];
+        let value: f64 = text.parse().map_err(|_| self.error(format!("invalid number {:?}", text)))?;
+        if !value.is_finite() {
+            return Err(self.error(format!("number {:?} is out of range", text)));
+        }
+        self.position += end;
+        Ok(Parsed { operand: Operand::Value(value), start })
+    }
+
+    // Record `left <operation> right` as a step spanning from `left` to here
+    fn step(&mut self, left: Parsed, right: Operand, operation: Operation) -> Parsed {
+        self.steps.push(PlannedStep {
+            first: left.operand,
+            second: right,
+            operation,
+            expression: self.source[left.start..self.position].trim().to_string(),
+        });
+        Parsed { operand: Operand::Step(self.steps.len() - 1), start: left.start }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    // Next non-whitespace character if it is a binary operator
+    fn peek_operator(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.peek().filter(|c| "+-*/".contains(*c))
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        ParseError { position: self.position, message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Run a plan with plain f64 arithmetic
+    fn run(plan: &Plan) -> f64 {
+        let mut results = Vec::new();
+        for step in &plan.steps {
+            let (first, second) = (step.first.resolve(&results), step.second.resolve(&results));
+            results.push(match step.operation {
+                Operation::Add => first + second,
+                Operation::Subtract => first - second,
+                Operation::Multiply => first * second,
+                Operation::Divide => first / second,
+                Operation::Percent => first * (second / 100.0),
+            });
+        }
+        plan.result.resolve(&results)
+    }
+
+    fn eval(expression: &str) -> f64 {
+        run(&plan(expression).unwrap())
+    }
+
+    #[test]
+    fn test_precedence_and_associativity() {
+        assert_eq!(eval("2 + 3 * 4"), 14.0);
+        assert_eq!(eval("2 * 3 + 4"), 10.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("64 / 4 / 2"), 8.0);
+        assert_eq!(eval("1.5e2 - .5"), 149.5);
+    }
+
+    #[test]
+    fn test_parentheses_and_unary_minus() {
+        assert_eq!(eval("(2 + 3) * 4"), 20.0);
+        assert_eq!(eval("((7))"), 7.0);
+        assert_eq!(eval("-3 * -(1 + 1)"), 6.0);
+        assert_eq!(eval("2 - -3"), 5.0);
+    }
+
+    #[test]
+    fn test_plan_steps() {
+        let plan = plan("6 / (4 - 4) + 1").unwrap();
+        let expressions: Vec<_> = plan.steps.iter().map(|step| step.expression.as_str()).collect();
+        assert_eq!(expressions, ["4 - 4", "6 / (4 - 4)", "6 / (4 - 4) + 1"]);
+        assert_eq!(plan.steps[1].second, Operand::Step(0));
+        assert_eq!(plan.result, Operand::Step(2));
+
+        // A lone number has no steps
+        let plan = super::plan(" 42 ").unwrap();
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.result, Operand::Value(42.0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (expression, position) in [("", 0), ("2 +", 3), ("2 * (3", 6), ("2 3", 2), ("4 % 2", 2), ("1..2", 0), ("1e999", 0)] {
+            let err = plan(expression).unwrap_err();
+            assert_eq!(err.position, position, "{:?}: {}", expression, err);
+        }
+        let deep = format!("{}1{}", "(".repeat(MAX_DEPTH + 1), ")".repeat(MAX_DEPTH + 1));
+        assert!(plan(&deep).unwrap_err().message.contains("nested"));
+    }
+}
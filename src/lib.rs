@@ -10,9 +10,22 @@ pub mod proto;     // Generated Protocol Buffer code
 pub mod client;    // Client-side implementation
 pub mod server;    // Server-side implementation
 pub mod logging;  // logging implementation
+pub mod config;   // Environment-based configuration
+pub mod trace;    // Trace context propagation (W3C traceparent) and correlation IDs
+pub mod expr;     // Arithmetic expression parsing shared by client and server
+pub mod redact;   // Placeholder shown instead of secrets in Debug output and snapshots
+pub mod pipe;     // Windows named pipe names shared by client and server
+mod lru;          // Bounded least recently used map behind the server and client caches
+#[cfg(feature = "test-util")]
+pub mod test_util;  // Test harness and latency assertions (feature "test-util")
+#[cfg(feature = "test-util")]
+pub use test_util as testing;  // The same harness as embedded_recruitment_task::testing
+#[cfg(feature = "bench-util")]
+pub mod bench_util;  // In-process benchmark server (feature "bench-util")
 
 // Re-export main types for easier access
 // This allows users to access these types directly from the crate root
 // Example: use crate_name::GrpcServer instead of crate_name::server::GrpcServer
 pub use server::GrpcServer;    // Main server type with builder pattern
-pub use client::GrpcClient;    // Main client type with builder pattern
\ No newline at end of file
+pub use client::GrpcClient;    // Main client type with builder pattern
+pub use config::{ServerConfig, ClientConfig, RuntimeConfig};  // Environment-loaded settings
\ No newline at end of file
@@ -0,0 +1,145 @@
+//! Runtime Log Level
+//! This file lets the log filter of a running process be changed without
+//! a restart, e.g. to get debug logs while investigating a live incident.
+//!
+//! setup.rs installs the filter behind a reload handle and registers it
+//! here. The filter is rebuilt from three parts each time it changes:
+//! 1. The directives from RUST_LOG, as read at startup
+//! 2. The default level (initially the component's level)
+//! 3. Per-target levels set through `set_level`
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use tracing_subscriber::filter::{Directive, EnvFilter, LevelFilter};
+use tracing_subscriber::{reload, Registry};
+
+// Handle to the installed filter plus the parts it was built from
+static FILTER: OnceLock<Mutex<FilterState>> = OnceLock::new();
+
+struct FilterState {
+    handle: reload::Handle<EnvFilter, Registry>,
+    env: String,  // RUST_LOG at startup; empty when unset
+    default: LevelFilter,
+    targets: BTreeMap<String, LevelFilter>,
+}
+
+impl FilterState {
+    // Build the filter these settings describe
+    // Later directives win, so explicit settings override RUST_LOG
+    fn build(&self) -> Result<EnvFilter, LogLevelError> {
+        // Lossy like EnvFilter::from_default_env: bad RUST_LOG parts are skipped
+        let mut filter = EnvFilter::builder()
+            .parse_lossy(&self.env)
+            .add_directive(self.default.into());
+        for (target, level) in &self.targets {
+            filter = filter.add_directive(directive(target, *level)?);
+        }
+        Ok(filter)
+    }
+}
+
+// Parse "target=level", rejecting targets the filter syntax cannot express
+fn directive(target: &str, level: LevelFilter) -> Result<Directive, LogLevelError> {
+    let text = format!("{}={}", target, level);
+    let invalid = || LogLevelError::InvalidDirective(format!("invalid log target {:?}", target));
+    if target.is_empty() || target.contains(|c: char| c.is_whitespace() || ",=[]{}\"".contains(c)) {
+        return Err(invalid());
+    }
+    text.parse().map_err(|_| invalid())
+}
+
+/// Why the log level could not be read or changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogLevelError {
+    /// Logging was not initialized by this crate, or another global
+    /// subscriber was already installed, so there is no filter to change
+    NotInitialized,
+    /// The requested target or level cannot be expressed as a filter directive
+    InvalidDirective(String),
+}
+
+impl fmt::Display for LogLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogLevelError::NotInitialized => write!(f, "logging has not been initialized by this process"),
+            LogLevelError::InvalidDirective(reason) => write!(f, "invalid log directive: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LogLevelError {}
+
+// Called by setup.rs once its subscriber is installed
+pub(super) fn register(handle: reload::Handle<EnvFilter, Registry>, env: String, default: LevelFilter) {
+    let state = FilterState { handle, env, default, targets: BTreeMap::new() };
+    if FILTER.set(Mutex::new(state)).is_err() {
+        tracing::debug!("Log filter already registered");
+    }
+}
+
+/// Change the log level of the running process
+///
+/// The new filter is validated before it is applied, so an invalid
+/// directive leaves the current filter untouched.
+///
+/// # Arguments
+/// * `target` - A module path such as `embedded_recruitment_task::server`, or `None` for the default level.
+/// * `level` - The new level; `LevelFilter::OFF` silences the target.
+///
+/// # Returns
+/// * `Result<(), LogLevelError>` - Ok once the filter is in effect.
+pub fn set_level(target: Option<&str>, level: LevelFilter) -> Result<(), LogLevelError> {
+    let mut state = FILTER.get().ok_or(LogLevelError::NotInitialized)?.lock().unwrap();
+
+    // Build from a copy so a failure changes nothing
+    let mut default = state.default;
+    let mut targets = state.targets.clone();
+    match target {
+        Some(target) => {
+            directive(target, level)?;
+            targets.insert(target.to_string(), level);
+        }
+        None => default = level,
+    }
+    let candidate = FilterState { handle: state.handle.clone(), env: state.env.clone(), default, targets };
+    let filter = candidate.build()?;
+
+    state
+        .handle
+        .reload(filter)
+        .map_err(|_| LogLevelError::NotInitialized)?;
+    *state = candidate;
+    tracing::info!("Log filter changed to {}", state.handle.with_current(|f| f.to_string()).unwrap_or_default());
+    Ok(())
+}
+
+/// The log filter currently in effect, in RUST_LOG syntax
+///
+/// # Returns
+/// * `Result<String, LogLevelError>` - E.g. `"info,embedded_recruitment_task::server=debug"`.
+pub fn current_filter() -> Result<String, LogLevelError> {
+    let state = FILTER.get().ok_or(LogLevelError::NotInitialized)?.lock().unwrap();
+    state
+        .handle
+        .with_current(|filter| filter.to_string())
+        .map_err(|_| LogLevelError::NotInitialized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directive_validation() {
+        assert!(directive("embedded_recruitment_task::server", LevelFilter::DEBUG).is_ok());
+        assert!(directive("h2", LevelFilter::OFF).is_ok());
+        for target in ["", "a b", "a=b", "a,b", "a[span]"] {
+            assert!(
+                matches!(directive(target, LevelFilter::INFO), Err(LogLevelError::InvalidDirective(_))),
+                "{:?} was accepted",
+                target
+            );
+        }
+    }
+}
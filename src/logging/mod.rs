@@ -3,8 +3,16 @@
 
 mod setup;
 mod types;
+mod panic;
+mod level;
+mod reopen;
 
-pub use types::Component;
+pub use types::{Component, LoggingOptions, TimestampFormat};
+pub use panic::install_panic_hook;
+pub use level::{set_level, current_filter, LogLevelError};
+pub use reopen::reopen_logs;
+#[cfg(unix)]
+pub use reopen::reopen_on_sighup;
 use setup::init_logging;
 
 /// Initialize logging for the specified component
@@ -16,7 +24,24 @@ use setup::init_logging;
 /// * `Result<(), Box<dyn std::error::Error>>` - A result indicating success or failure.
 #[inline]
 pub fn init(component: Component) -> Result<(), Box<dyn std::error::Error>> {
-    init_logging(component)
+    init_with(component, LoggingOptions::default())
+}
+
+/// Initialize logging for the specified component with explicit options
+/// 
+/// # Arguments
+/// * `component` - The component for which to initialize logging.
+/// * `options` - E.g. `LoggingOptions { panic_hook: false, ..Default::default() }` to keep the panic hook untouched.
+///   The timestamp format only takes effect for the first initialization in the process.
+/// 
+/// # Returns
+/// * `Result<(), Box<dyn std::error::Error>>` - A result indicating success or failure.
+pub fn init_with(component: Component, options: LoggingOptions) -> Result<(), Box<dyn std::error::Error>> {
+    init_logging(component, options.timestamp)?;
+    if options.panic_hook {
+        install_panic_hook();
+    }
+    Ok(())
 }
 
 pub mod prelude {
@@ -0,0 +1,65 @@
+//! Panic Logging
+//! This file routes panics into the logging system, so deployments that
+//! only keep the log files still record why a thread died.
+//!
+//! The hook logs the payload, the source location and, when enabled with
+//! RUST_BACKTRACE (or RUST_LIB_BACKTRACE), a backtrace through
+//! `tracing::error!`, then hands over to the previously installed hook
+//! (by default the one printing to stderr).
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::Cell;
+use std::panic::PanicHookInfo;
+use std::sync::Once;
+
+// Guards against replacing the hook more than once
+static INSTALL: Once = Once::new();
+
+thread_local! {
+    // Set while this thread is inside the hook
+    // A panic raised by logging itself (panic-in-panic) skips straight to
+    // the previous hook instead of recursing and allocating again
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Log every panic through `tracing` before running the previous panic hook
+///
+/// Called by `init_server`/`init_client` unless disabled with
+/// [`LoggingOptions::panic_hook`](super::LoggingOptions). Safe to call any
+/// number of times; only the first call installs the hook.
+pub fn install_panic_hook() {
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if !IN_HOOK.replace(true) {
+                log_panic(info);
+                IN_HOOK.set(false);
+            }
+            previous(info);
+        }));
+    });
+}
+
+// Emit one error event describing the panic
+fn log_panic(info: &PanicHookInfo<'_>) {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload");
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "unknown location".to_string());
+    let thread = std::thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+
+    // capture() is a cheap no-op unless backtraces were enabled
+    let backtrace = Backtrace::capture();
+    if backtrace.status() == BacktraceStatus::Captured {
+        tracing::error!(target: "panic", "thread '{}' panicked at {}: {}\nbacktrace:\n{}", thread, location, message, backtrace);
+    } else {
+        tracing::error!(target: "panic", "thread '{}' panicked at {}: {}", thread, location, message);
+    }
+}
@@ -0,0 +1,97 @@
+//! Reopenable Log File
+//! Rotation tools such as logrotate rename the current log file and expect
+//! the process to continue in a new one. The file layer writes through
+//! LogFile, whose handle can be swapped for a fresh one at any time:
+//! 1. LogFile: the open file behind a lock, reopened by path on request
+//! 2. reopen_logs: reopens the file of the installed subscriber
+//! 3. reopen_on_sighup: does so whenever the process receives SIGHUP (Unix)
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing_subscriber::fmt::writer::{MakeWriter, MutexGuardWriter};
+
+// File written by the subscriber installed in setup.rs
+static LOG_FILE: OnceLock<LogFile> = OnceLock::new();
+
+// Log file that can be reopened under its original path
+// Clones share the open file, so the subscriber's copy sees every reopen
+#[derive(Debug, Clone)]
+pub(crate) struct LogFile {
+    path: Arc<PathBuf>,
+    file: Arc<Mutex<File>>,
+}
+
+impl LogFile {
+    // Open `path` for appending, creating it if needed
+    pub(crate) fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = append(&path)?;
+        Ok(Self { path: Arc::new(path), file: Arc::new(Mutex::new(file)) })
+    }
+
+    // Switch to whatever file is now at the original path
+    // Lines written meanwhile go to the old file until the swap
+    fn reopen(&self) -> io::Result<()> {
+        let file = append(&self.path)?;
+        *self.file.lock().unwrap_or_else(|e| e.into_inner()) = file;
+        Ok(())
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = MutexGuardWriter<'a, File>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.file.make_writer()
+    }
+}
+
+// Called by setup.rs once the subscriber writing to `file` is installed
+pub(crate) fn register(file: LogFile) {
+    let _ = LOG_FILE.set(file);
+}
+
+/// Reopen the log file, e.g. after logrotate renamed it
+///
+/// Later log lines go to a new file under the original name; lines
+/// already written stay in the renamed file.
+///
+/// # Returns
+/// * `io::Result<()>` - An error if logging was not initialized by this crate
+///   or the file cannot be opened.
+pub fn reopen_logs() -> io::Result<()> {
+    let file = LOG_FILE.get()
+        .ok_or_else(|| io::Error::other("logging was not initialized by this crate"))?;
+    file.reopen()?;
+    tracing::info!("Reopened log file {}", file.path.display());
+    Ok(())
+}
+
+/// Reopen the log file whenever the process receives SIGHUP
+///
+/// Spawns a task on the current tokio runtime that runs for the life of
+/// the process; a failed reopen is logged to the old file, which is kept.
+///
+/// # Returns
+/// * `io::Result<()>` - An error if the signal handler cannot be installed.
+#[cfg(unix)]
+pub fn reopen_on_sighup() -> io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            if let Err(e) = reopen_logs() {
+                // Still written to the old file, which stays open
+                tracing::error!("Failed to reopen log file on SIGHUP: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
@@ -1,42 +1,167 @@
 //! Logging Setup
 //! This file provides the setup functions for initializing logging.
+//!
+//! Server and client share one process in the in-process tests, so
+//! initialization may be requested several times, possibly concurrently.
+//! Only the first request installs a subscriber; every later one is a
+//! no-op that reports the outcome of that first attempt.
 
-use tracing_subscriber::{fmt, EnvFilter};
-use tracing_appender::rolling::{RollingFileAppender, Rotation};
-use super::types::Component;
-use std::sync::{Once, Mutex};
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::TryInitError;
+use super::types::{Component, TimestampFormat};
+use super::level;
+use super::reopen::{self, LogFile};
+use std::sync::OnceLock;
 
-// Ensure logging is initialized only once
-static INIT_LOGGER: Once = Once::new();
-static LOGGER_MUTEX: Mutex<()> = Mutex::new(());
+// Directory the log files are written to
+const LOG_DIR: &str = "logs";
+
+// Outcome of the one real initialization attempt
+// OnceLock blocks concurrent callers until the first one has finished,
+// and unlike Once it is not poisoned if that attempt fails
+static INIT_RESULT: OnceLock<Result<Component, String>> = OnceLock::new();
 
 /// Initialize logging for the specified component
-/// 
+///
 /// # Arguments
 /// * `component` - The component for which to initialize logging.
-/// 
+/// * `timestamp` - Timestamp format of the log lines; ignored after the first call.
+///
 /// # Returns
 /// * `Result<(), Box<dyn std::error::Error>>` - A result indicating success or failure.
-pub(crate) fn init_logging(component: Component) -> Result<(), Box<dyn std::error::Error>> {
-    INIT_LOGGER.call_once(|| {
-        let _lock = LOGGER_MUTEX.lock().unwrap();
-        let (name, level) = component.config();
-        
-        let file_appender = RollingFileAppender::builder()
-            .rotation(Rotation::NEVER)
-            .filename_prefix(name)
-            .build("logs").expect("Failed to create file appender");
-
-        fmt::Subscriber::builder()
-            .with_ansi(false)
-            .with_target(false)
-            .with_writer(file_appender)
-            .with_env_filter(EnvFilter::from_default_env().add_directive(level.into()))
-            .try_init()
-            .expect("Failed to initialize logger");
-
-        tracing::info!("Initialized logging for {:?}", component);
-    });
-
-    Ok(())
-}
\ No newline at end of file
+pub(crate) fn init_logging(component: Component, timestamp: TimestampFormat) -> Result<(), Box<dyn std::error::Error>> {
+    match INIT_RESULT.get_or_init(|| install(component, timestamp)) {
+        Ok(first) => {
+            if *first != component {
+                tracing::debug!("Logging already initialized for {:?}; {:?} shares it", first, component);
+            }
+            Ok(())
+        }
+        Err(e) => Err(e.clone().into()),
+    }
+}
+
+// Timer behind TimestampFormat::UnixEpoch; the other variants reuse the
+// stock SystemTime timer or drop the timestamp altogether
+impl FormatTime for TimestampFormat {
+    fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        match self {
+            TimestampFormat::Rfc3339 => SystemTime.format_time(w),
+            TimestampFormat::UnixEpoch => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default();
+                write!(w, "{}.{:06}", now.as_secs(), now.subsec_micros())
+            }
+            TimestampFormat::None => Ok(()),
+        }
+    }
+}
+
+// Formatting layer shared by install and the tests, writing to `writer`
+// None goes through without_time so no stray separator is left behind
+fn fmt_layer<S, W>(writer: W, timestamp: TimestampFormat) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = fmt::layer()
+        .with_ansi(false)
+        .with_target(false)
+        .with_writer(writer);
+    match timestamp {
+        TimestampFormat::None => layer.without_time().boxed(),
+        timer => layer.with_timer(timer).boxed(),
+    }
+}
+
+// Create the log directory and install the global subscriber
+fn install(component: Component, timestamp: TimestampFormat) -> Result<Component, String> {
+    let (name, level) = component.config();
+
+    // create_dir_all succeeds if the directory exists or appears concurrently
+    std::fs::create_dir_all(LOG_DIR)
+        .map_err(|e| format!("Failed to create log directory {}: {}", LOG_DIR, e))?;
+    // Written through LogFile so reopen_logs can follow log rotation
+    let log_file = LogFile::open(std::path::Path::new(LOG_DIR).join(name))
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+
+    // The filter sits behind a reload handle so logging::set_level can
+    // change it while the process runs
+    let env = std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_default();
+    let (filter, handle) = reload::Layer::new(EnvFilter::builder().parse_lossy(&env).add_directive(level.into()));
+    let installed: Result<(), TryInitError> = Registry::default()
+        .with(filter)
+        .with(fmt_layer(log_file.clone(), timestamp))
+        .try_init();
+
+    // Another global subscriber (e.g. from a test harness) stays in charge
+    if installed.is_err() {
+        tracing::debug!("Global subscriber already set; skipping logging setup for {:?}", component);
+        return Ok(component);
+    }
+    level::register(handle, env, level);
+    reopen::register(log_file);
+
+    tracing::info!("Initialized logging for {:?}", component);
+    Ok(component)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    // In-memory writer collecting everything the layer formats
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Format one INFO line with the given timestamp format
+    fn line(timestamp: TimestampFormat) -> String {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = Registry::default().with(fmt_layer(move || writer.clone(), timestamp));
+        tracing::subscriber::with_default(subscriber, || tracing::info!("hello"));
+        let bytes = captured.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_timestamp_formats() {
+        // RFC 3339: 2024-05-01T12:00:00.123456Z
+        let rfc = line(TimestampFormat::Rfc3339);
+        let (stamp, rest) = rfc.split_once(' ').unwrap();
+        let shape: String = stamp
+            .chars()
+            .map(|c| if c.is_ascii_digit() { 'd' } else { c })
+            .collect();
+        assert_eq!(shape, "dddd-dd-ddTdd:dd:dd.ddddddZ", "{:?}", rfc);
+        assert!(rest.trim_start().starts_with("INFO hello"), "{:?}", rfc);
+
+        // Unix epoch: seconds.micros
+        let epoch = line(TimestampFormat::UnixEpoch);
+        let (secs, micros) = epoch.split_once(' ').unwrap().0.split_once('.').unwrap();
+        assert!(secs.parse::<u64>().unwrap() > 1_600_000_000, "{:?}", epoch);
+        assert_eq!(micros.len(), 6, "{:?}", epoch);
+
+        // None: the level comes first
+        assert!(line(TimestampFormat::None).trim_start().starts_with("INFO hello"));
+    }
+}
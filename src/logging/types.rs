@@ -4,7 +4,7 @@
 use tracing_subscriber::filter::LevelFilter;
 
 /// Enum representing different components of the application
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Component {
     Server,
     Client,
@@ -23,4 +23,30 @@ impl Component {
             Component::Test  => ("test",   LevelFilter::TRACE),
         }
     }
-}
\ No newline at end of file
+}
+/// Timestamp written at the start of every log line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+    /// UTC RFC 3339 with microseconds, e.g. `2024-05-01T12:00:00.123456Z`
+    #[default]
+    Rfc3339,
+    /// Seconds since the Unix epoch with microseconds, e.g. `1714564800.123456`
+    UnixEpoch,
+    /// No timestamp; useful when journald or a collector adds its own
+    None,
+}
+
+/// Options applied when initializing logging
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoggingOptions {
+    /// Route panics into the log (see `install_panic_hook`); on by default
+    pub panic_hook: bool,
+    /// Timestamp format of the log lines; RFC 3339 by default
+    pub timestamp: TimestampFormat,
+}
+
+impl Default for LoggingOptions {
+    fn default() -> Self {
+        Self { panic_hook: true, timestamp: TimestampFormat::default() }
+    }
+}
@@ -0,0 +1,85 @@
+//! Least Recently Used Map
+//! The bounded map behind the server's response caches and the client's
+//! calculator cache:
+//! 1. Holds at most `capacity` entries (at least one)
+//! 2. When full, inserting a new key evicts the least recently used one
+//! 3. Reading a key counts as using it
+//!
+//! Not synchronized; the caches keep it behind a Mutex.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+// Entries plus their recency order
+// `order` maps a monotonically increasing use stamp to the key last used
+// at that stamp, so its first entry is the least recently used one
+#[derive(Debug)]
+pub(crate) struct Lru<K, V> {
+    capacity: usize,
+    values: HashMap<K, (V, u64)>,  // value and last use stamp
+    order: BTreeMap<u64, K>,
+    next_stamp: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Lru<K, V> {
+    // Empty map holding at most `capacity` values (at least one)
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            values: HashMap::with_capacity(capacity),
+            order: BTreeMap::new(),
+            next_stamp: 0,
+        }
+    }
+
+    // Mark `key` as used now, replacing its previous stamp
+    fn touch(&mut self, key: K, previous: Option<u64>) -> u64 {
+        if let Some(previous) = previous {
+            self.order.remove(&previous);
+        }
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.order.insert(stamp, key);
+        stamp
+    }
+
+    // The value of `key`, now the most recently used, if present
+    pub(crate) fn get(&mut self, key: &K) -> Option<V> {
+        let (value, stamp) = self.values.get(key).map(|(value, stamp)| (value.clone(), *stamp))?;
+        let stamp = self.touch(key.clone(), Some(stamp));
+        self.values.insert(key.clone(), (value.clone(), stamp));
+        Some(value)
+    }
+
+    // Store `value` under `key`, evicting the least recently used entry when full
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        let previous = self.values.get(&key).map(|&(_, stamp)| stamp);
+        if previous.is_none() && self.values.len() == self.capacity {
+            if let Some((_, evicted)) = self.order.pop_first() {
+                self.values.remove(&evicted);
+            }
+        }
+        let stamp = self.touch(key.clone(), previous);
+        self.values.insert(key, (value, stamp));
+    }
+
+    // Drop `key`, if present
+    pub(crate) fn remove(&mut self, key: &K) {
+        if let Some((_, stamp)) = self.values.remove(key) {
+            self.order.remove(&stamp);
+        }
+    }
+
+    // Drop every entry
+    pub(crate) fn clear(&mut self) {
+        self.values.clear();
+        self.order.clear();
+    }
+
+    // Number of entries held
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+}
@@ -0,0 +1,79 @@
+//! Windows Named Pipe Names
+//! Shared by GrpcServerBuilder::named_pipe and GrpcClientBuilder::named_pipe:
+//! 1. Short names such as `calculator` become `\\.\pipe\calculator`
+//! 2. Names Windows would reject are reported before anything is opened
+//!
+//! Named pipes only exist on Windows; on other platforms both builders
+//! reject them with Unimplemented (see `unsupported`).
+
+use tonic::{Code, Status};
+
+/// Prefix of every local named pipe path
+pub const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+// Longest pipe path Windows accepts, prefix included
+const MAX_PIPE_PATH: usize = 256;
+
+// Whether this platform has named pipes
+pub(crate) const NAMED_PIPES_SUPPORTED: bool = cfg!(windows);
+
+/// Full path of the local named pipe `name`
+///
+/// The `\\.\pipe\` prefix is added unless `name` already has it (in any
+/// case, as Windows compares pipe names case-insensitively).
+///
+/// # Arguments
+/// * `name` - A pipe name, e.g. `calculator` or `\\.\pipe\calculator`.
+///
+/// # Returns
+/// * `Result<String, Status>` - The full pipe path, or `InvalidArgument` for an
+///   empty name, a name containing a backslash or one longer than 256 characters.
+pub fn pipe_path(name: &str) -> Result<String, Status> {
+    let invalid = |reason: &str| Status::new(
+        Code::InvalidArgument,
+        format!("invalid named pipe {:?}: {}", name, reason)
+    );
+
+    let has_prefix = name.get(..PIPE_PREFIX.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(PIPE_PREFIX));
+    let short = if has_prefix { &name[PIPE_PREFIX.len()..] } else { name };
+    if short.is_empty() {
+        return Err(invalid("the name is empty"));
+    }
+    if short.contains('\\') {
+        return Err(invalid("the name must not contain a backslash"));
+    }
+    let path = format!("{}{}", PIPE_PREFIX, short);
+    if path.chars().count() > MAX_PIPE_PATH {
+        return Err(invalid(&format!("the path is longer than {} characters", MAX_PIPE_PATH)));
+    }
+    Ok(path)
+}
+
+// Error returned for named pipes on platforms without them
+pub(crate) fn unsupported() -> Status {
+    Status::new(Code::Unimplemented, "named pipes are only supported on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Short names get the prefix; full paths are kept as given
+    #[test]
+    fn test_pipe_path_adds_prefix() {
+        assert_eq!(pipe_path("calculator").unwrap(), r"\\.\pipe\calculator");
+        assert_eq!(pipe_path(r"\\.\pipe\calculator").unwrap(), r"\\.\pipe\calculator");
+        assert_eq!(pipe_path(r"\\.\PIPE\calculator").unwrap(), r"\\.\pipe\calculator");
+        assert_eq!(pipe_path("grpc/echo v1").unwrap(), r"\\.\pipe\grpc/echo v1");
+    }
+
+    // Names Windows would reject fail with InvalidArgument
+    #[test]
+    fn test_pipe_path_rejects_invalid_names() {
+        let too_long = "p".repeat(MAX_PIPE_PATH);
+        for name in ["", PIPE_PREFIX, r"\\server\pipe\remote", r"a\b", too_long.as_str()] {
+            let err = pipe_path(name).unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument, "{:?}", name);
+        }
+    }
+}
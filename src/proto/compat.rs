@@ -0,0 +1,438 @@
+//! Wire Compatibility Samples
+//! This module exposes canonical sample messages for every protocol type.
+//! The compatibility tests encode these samples and compare them against
+//! checked-in golden files, so a changed field number or type breaks the
+//! build loudly instead of silently breaking older clients.
+//!
+//! To cover a new service, add its messages to `samples()`; the harness in
+//! `tests/compat_test.rs` picks up every entry automatically.
+
+use prost::bytes::Bytes;
+use prost::Message;
+use super::calculator::{
+    calculator_error_detail, CalculatorErrorDetail,
+    AggregateRequest, AggregateResponse, AggregateValue, AggregateProgress, CalculateDecimalRequest, CalculateDecimalResponse,
+    EvaluateRequest, EvaluateResponse, ReduceRequest, ReduceResponse,
+    GetHistoryRequest, GetHistoryResponse, HistoryEntry,
+    CalculateRequest, CalculateResponse, Operation, RunningTotalResponse, RunningTotalStep,
+};
+use super::echo::{EchoChunk, EchoRequest, EchoResponse, EchoStreamRequest, EchoValidation};
+use super::admin::{
+    ConfigResponse, LatencyStatsResponse, LogLevelResponse, ServerInfoResponse, SetConfigRequest, SetLogLevelRequest,
+    ServiceStateResponse, ServingState, SetServiceStateRequest, OperationsResponse, CacheStatsResponse,
+    ConnectionInfo, ListConnectionsResponse,
+};
+use super::broadcast::{BroadcastMessage, PublishRequest, PublishResponse, SubscribeRequest};
+
+/// A named, encoded sample message
+/// 
+/// `name` doubles as the golden file name (`<name>.bin`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub name: &'static str,  // Stable identifier, also the golden file stem
+    pub encoded: Vec<u8>,    // Current protobuf encoding of the sample
+}
+
+impl Sample {
+    // Encode a message into a named sample
+    fn new(name: &'static str, message: &impl Message) -> Self {
+        Self { name, encoded: message.encode_to_vec() }
+    }
+}
+
+/// Canonical echo request, limited to the fields of the first release
+/// Both fields are non-default so each one is on the wire; the validation
+/// is left at NONE, which is not encoded
+pub fn echo_request() -> EchoRequest {
+    EchoRequest {
+        message: "golden echo ✓".into(),
+        delay_ms: 250,
+        validation: EchoValidation::None.into(),
+    }
+}
+
+/// Canonical echo request asking for validation
+pub fn echo_request_validated() -> EchoRequest {
+    EchoRequest {
+        validation: EchoValidation::RejectControlAndNormalizeNfc.into(),
+        ..echo_request()
+    }
+}
+
+/// Canonical echo response, limited to the fields of the first release
+/// Unset fields are not encoded, so the original golden file still applies
+pub fn echo_response() -> EchoResponse {
+    EchoResponse {
+        message: "golden echo ✓".into(),
+        sequence: 0,
+        server_total_echoes: 0,
+        server_process_ns: None,
+    }
+}
+
+/// Canonical echo response carrying the ordering diagnostics
+pub fn echo_response_sequenced() -> EchoResponse {
+    EchoResponse {
+        message: "golden echo ✓".into(),
+        sequence: 42,
+        server_total_echoes: 1_000_001,
+        server_process_ns: None,
+    }
+}
+
+/// Canonical echo response with handler timing
+pub fn echo_response_timed() -> EchoResponse {
+    EchoResponse {
+        server_process_ns: Some(125_000),
+        ..echo_response_sequenced()
+    }
+}
+
+/// Canonical chunk of the streaming echo
+pub fn echo_chunk() -> EchoChunk {
+    EchoChunk {
+        data: Bytes::from_static(&[0x00, 0x01, 0x7f, 0x80, 0xff]),
+    }
+}
+
+/// Canonical streaming echo request
+pub fn echo_stream_request() -> EchoStreamRequest {
+    EchoStreamRequest {
+        message: "golden stream".into(),
+        repeat: 3,
+    }
+}
+
+/// Canonical calculate request for the given operation
+/// Operands are non-zero so both double fields are encoded; the inverse
+/// is not asked for, so the original golden files still apply
+pub fn calculate_request(operation: Operation) -> CalculateRequest {
+    CalculateRequest {
+        first_number: 12.5,
+        second_number: -4.0,
+        operation: operation.into(),
+        include_inverse: false,
+    }
+}
+
+/// Canonical calculate request asking for the inverse
+pub fn calculate_request_with_inverse() -> CalculateRequest {
+    CalculateRequest {
+        include_inverse: true,
+        ..calculate_request(Operation::Add)
+    }
+}
+
+/// Canonical calculate response
+pub fn calculate_response() -> CalculateResponse {
+    CalculateResponse { result: 8.5, inverse: None }
+}
+
+/// Canonical calculate response carrying the inverse
+pub fn calculate_response_with_inverse() -> CalculateResponse {
+    CalculateResponse { result: 8.5, inverse: Some(12.5) }
+}
+
+/// Canonical aggregate request
+pub fn aggregate_request() -> AggregateRequest {
+    AggregateRequest {
+        values: vec![1.5, -2.0, 8.0],
+    }
+}
+
+/// Canonical aggregate response
+pub fn aggregate_response() -> AggregateResponse {
+    AggregateResponse {
+        sum: 7.5,
+        mean: 2.5,
+        min: -2.0,
+        max: 8.0,
+        count: 3,
+    }
+}
+
+/// Canonical value of a streamed dataset
+pub fn aggregate_value() -> AggregateValue {
+    AggregateValue { value: -2.0 }
+}
+
+/// Canonical statistics of a streamed dataset
+pub fn aggregate_progress() -> AggregateProgress {
+    AggregateProgress {
+        count: 2,
+        sum: -0.5,
+        mean: -0.25,
+        variance: 3.0625,
+    }
+}
+
+/// Canonical running total step
+pub fn running_total_step() -> RunningTotalStep {
+    RunningTotalStep {
+        value: 2.5,
+        operation: Operation::Multiply.into(),
+    }
+}
+
+/// Canonical running total response for a rejected step
+pub fn running_total_response() -> RunningTotalResponse {
+    RunningTotalResponse {
+        total: 10.0,
+        error: "division by zero is not allowed".to_string(),
+    }
+}
+
+/// Canonical decimal calculate request
+pub fn calculate_decimal_request() -> CalculateDecimalRequest {
+    CalculateDecimalRequest {
+        first_number: "0.1".into(),
+        second_number: "-12.345".into(),
+        operation: Operation::Subtract.into(),
+    }
+}
+
+/// Canonical decimal calculate response
+pub fn calculate_decimal_response() -> CalculateDecimalResponse {
+    CalculateDecimalResponse { result: "12.445".into() }
+}
+
+/// Canonical reduction request
+pub fn reduce_request() -> ReduceRequest {
+    ReduceRequest {
+        values: vec![10.0, -2.5, 0.25],
+        operation: Operation::Divide.into(),
+    }
+}
+
+/// Canonical reduction response
+pub fn reduce_response() -> ReduceResponse {
+    ReduceResponse { result: -16.0 }
+}
+
+/// Canonical expression evaluation request
+pub fn evaluate_request() -> EvaluateRequest {
+    EvaluateRequest { expression: "2 + 3 * (4 - 1.5)".into() }
+}
+
+/// Canonical expression evaluation response
+pub fn evaluate_response() -> EvaluateResponse {
+    EvaluateResponse { result: 9.5 }
+}
+
+/// Canonical history request
+pub fn get_history_request() -> GetHistoryRequest {
+    GetHistoryRequest { limit: 25 }
+}
+
+/// Canonical history entry
+pub fn history_entry() -> HistoryEntry {
+    HistoryEntry {
+        first_number: 10.5,
+        second_number: 2.0,
+        operation: Operation::Subtract.into(),
+        result: 8.5,
+        timestamp_ms: 1_700_000_000_000,
+    }
+}
+
+/// Canonical history response
+pub fn get_history_response() -> GetHistoryResponse {
+    GetHistoryResponse { entries: vec![history_entry()], total: 42 }
+}
+
+/// Canonical calculator error detail
+pub fn calculator_error_detail() -> CalculatorErrorDetail {
+    CalculatorErrorDetail::new(calculator_error_detail::Kind::DivisionByZero, Some(2))
+}
+
+/// Canonical latency stats response
+/// The request message is empty and has nothing to guard
+pub fn latency_stats_response() -> LatencyStatsResponse {
+    LatencyStatsResponse {
+        p50_ms: 1.25,
+        p90_ms: 4.5,
+        p99_ms: 120.0,
+        count: 4096,
+    }
+}
+
+/// Canonical server info response
+pub fn server_info_response() -> ServerInfoResponse {
+    ServerInfoResponse {
+        version: "1.2.3".into(),
+        worker_threads: 8,
+        max_blocking_threads: 64,
+        thread_name_prefix: "grpc-worker".into(),
+    }
+}
+
+/// Canonical log level change for one target
+pub fn set_log_level_request() -> SetLogLevelRequest {
+    SetLogLevelRequest {
+        target: "embedded_recruitment_task::server".into(),
+        level: "debug".into(),
+    }
+}
+
+/// Canonical log filter report
+pub fn log_level_response() -> LogLevelResponse {
+    LogLevelResponse {
+        filter: "info,embedded_recruitment_task::server=debug".into(),
+    }
+}
+
+/// Canonical runtime settings change
+pub fn set_config_request() -> SetConfigRequest {
+    SetConfigRequest {
+        allow_empty_echo: Some(true),
+        echo_max_chars: Some(280),
+    }
+}
+
+/// Canonical runtime settings report
+pub fn config_response() -> ConfigResponse {
+    ConfigResponse {
+        allow_empty_echo: true,
+        echo_max_chars: 280,
+    }
+}
+
+/// Canonical request taking a service offline
+pub fn set_service_state_request() -> SetServiceStateRequest {
+    SetServiceStateRequest {
+        service: "calculator.CalculatorService".into(),
+        state: ServingState::NotServing.into(),
+    }
+}
+
+/// Canonical service state report
+pub fn service_state_response() -> ServiceStateResponse {
+    ServiceStateResponse {
+        service: "calculator.CalculatorService".into(),
+        state: ServingState::NotServing.into(),
+    }
+}
+
+/// Canonical enabled-operations report
+pub fn operations_response() -> OperationsResponse {
+    OperationsResponse {
+        operations: vec![Operation::Add.into(), Operation::Divide.into()],
+    }
+}
+
+/// Canonical cache statistics report
+/// The request message is empty and has nothing to guard
+pub fn cache_stats_response() -> CacheStatsResponse {
+    CacheStatsResponse {
+        calculator_hits: 999,
+        calculator_misses: 1,
+        echo_hits: 42,
+        echo_misses: 7,
+    }
+}
+
+/// Canonical open connection
+pub fn connection_info() -> ConnectionInfo {
+    ConnectionInfo {
+        peer: "192.0.2.7:51234".into(),
+        connected_at_ms: 1_700_000_000_000,
+        request_count: 12,
+    }
+}
+
+/// Canonical connection listing
+/// The request message is empty and has nothing to guard
+pub fn list_connections_response() -> ListConnectionsResponse {
+    ListConnectionsResponse {
+        connections: vec![
+            connection_info(),
+            ConnectionInfo { peer: "[2001:db8::1]:443".into(), connected_at_ms: 1_700_000_000_500, request_count: 0 },
+        ],
+    }
+}
+
+/// Canonical broadcast subscription
+pub fn subscribe_request() -> SubscribeRequest {
+    SubscribeRequest { topic: "lobby".into() }
+}
+
+/// Canonical broadcast publish
+pub fn publish_request() -> PublishRequest {
+    PublishRequest { topic: "lobby".into(), message: "hello, room".into() }
+}
+
+/// Canonical broadcast publish outcome
+pub fn publish_response() -> PublishResponse {
+    PublishResponse { subscribers: 3 }
+}
+
+/// Canonical message received by a subscriber
+pub fn broadcast_message() -> BroadcastMessage {
+    BroadcastMessage { topic: "lobby".into(), message: "hello, room".into() }
+}
+
+/// Every operation with the name used for its golden file
+pub fn operations() -> [(Operation, &'static str); 5] {
+    [
+        (Operation::Add, "calculate_request_add"),
+        (Operation::Subtract, "calculate_request_subtract"),
+        (Operation::Multiply, "calculate_request_multiply"),
+        (Operation::Divide, "calculate_request_divide"),
+        (Operation::Percent, "calculate_request_percent"),
+    ]
+}
+
+/// All canonical samples, encoded with the current message definitions
+/// 
+/// # Returns
+/// * `Vec<Sample>` - One entry per message (and per enum value where relevant).
+pub fn samples() -> Vec<Sample> {
+    let mut samples = vec![
+        Sample::new("echo_request", &echo_request()),
+        Sample::new("echo_request_validated", &echo_request_validated()),
+        Sample::new("echo_response", &echo_response()),
+        Sample::new("echo_response_sequenced", &echo_response_sequenced()),
+        Sample::new("echo_response_timed", &echo_response_timed()),
+        Sample::new("echo_chunk", &echo_chunk()),
+        Sample::new("echo_stream_request", &echo_stream_request()),
+        Sample::new("calculate_request_with_inverse", &calculate_request_with_inverse()),
+        Sample::new("calculate_response", &calculate_response()),
+        Sample::new("calculate_response_with_inverse", &calculate_response_with_inverse()),
+        Sample::new("aggregate_request", &aggregate_request()),
+        Sample::new("aggregate_response", &aggregate_response()),
+        Sample::new("aggregate_value", &aggregate_value()),
+        Sample::new("aggregate_progress", &aggregate_progress()),
+        Sample::new("running_total_step", &running_total_step()),
+        Sample::new("running_total_response", &running_total_response()),
+        Sample::new("calculate_decimal_request", &calculate_decimal_request()),
+        Sample::new("calculate_decimal_response", &calculate_decimal_response()),
+        Sample::new("evaluate_request", &evaluate_request()),
+        Sample::new("evaluate_response", &evaluate_response()),
+        Sample::new("reduce_request", &reduce_request()),
+        Sample::new("reduce_response", &reduce_response()),
+        Sample::new("get_history_request", &get_history_request()),
+        Sample::new("history_entry", &history_entry()),
+        Sample::new("get_history_response", &get_history_response()),
+        Sample::new("calculator_error_detail", &calculator_error_detail()),
+        Sample::new("latency_stats_response", &latency_stats_response()),
+        Sample::new("server_info_response", &server_info_response()),
+        Sample::new("set_log_level_request", &set_log_level_request()),
+        Sample::new("log_level_response", &log_level_response()),
+        Sample::new("set_config_request", &set_config_request()),
+        Sample::new("config_response", &config_response()),
+        Sample::new("set_service_state_request", &set_service_state_request()),
+        Sample::new("service_state_response", &service_state_response()),
+        Sample::new("operations_response", &operations_response()),
+        Sample::new("cache_stats_response", &cache_stats_response()),
+        Sample::new("connection_info", &connection_info()),
+        Sample::new("list_connections_response", &list_connections_response()),
+        Sample::new("subscribe_request", &subscribe_request()),
+        Sample::new("publish_request", &publish_request()),
+        Sample::new("publish_response", &publish_response()),
+        Sample::new("broadcast_message", &broadcast_message()),
+    ];
+    for (operation, name) in operations() {
+        samples.push(Sample::new(name, &calculate_request(operation)));
+    }
+    samples
+}
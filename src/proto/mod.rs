@@ -5,12 +5,27 @@
 //! 1. Generated code using tonic::include_proto! macro
 //! 2. Separate modules for each service to maintain clean organization
 //! 3. Automatic code generation from .proto definitions
+//! 4. Canonical samples guarding wire compatibility (compat)
+//! 5. Field rules of the request messages shared by client and server (validation)
+
+/// Response metadata key holding how many RPCs the connection has served,
+/// counting the current one; set by Echo and Calculate
+pub const CONNECTION_REQUEST_COUNT_METADATA: &str = "x-connection-request-count";
 
 // Include generated code for echo service
 // tonic::include_proto! macro processes the proto file at compile time
 // and generates all necessary Rust types, traits, and implementations
 pub mod echo {
     tonic::include_proto!("echo");  // Generates from echo.proto
+
+    /// Status metadata key holding the server's echo character limit
+    /// Set on the InvalidArgument returned for a message that is too long
+    pub const LIMIT_METADATA: &str = "limit";
+    /// Status metadata key holding the rejected message's length in characters
+    pub const ACTUAL_METADATA: &str = "actual";
+    /// Status metadata key holding the byte offset of the character an
+    /// echo validation rejected
+    pub const OFFSET_METADATA: &str = "offset";
 }
 
 // Include generated code for calculator service
@@ -20,4 +35,49 @@ pub mod echo {
 // - Helper types and conversions
 pub mod calculator {
     tonic::include_proto!("calculator");  // Generates from calculator.proto
+
+    use prost::Message;
+    use tonic::metadata::{MetadataMap, MetadataValue};
+    use tonic::{Code, Status};
+
+    /// Binary status metadata key holding an encoded `CalculatorErrorDetail`
+    pub const ERROR_DETAIL_METADATA: &str = "calculator-error-bin";
+
+    impl CalculatorErrorDetail {
+        /// Detail of the given kind, optionally blaming one operand
+        pub fn new(kind: calculator_error_detail::Kind, operand: Option<u32>) -> Self {
+            Self { kind: kind.into(), operand }
+        }
+
+        /// Status with `code` and `message` carrying this detail
+        pub fn to_status(&self, code: Code, message: impl Into<String>) -> Status {
+            let mut metadata = MetadataMap::new();
+            metadata.insert_bin(ERROR_DETAIL_METADATA, MetadataValue::from_bytes(&self.encode_to_vec()));
+            Status::with_metadata(code, message, metadata)
+        }
+
+        /// Decode the detail carried by `status`, if it has one
+        pub fn from_status(status: &Status) -> Option<Self> {
+            let value = status.metadata().get_bin(ERROR_DETAIL_METADATA)?;
+            Self::decode(value.to_bytes().ok()?.as_ref()).ok()
+        }
+    }
 }
+
+// Include generated code for the admin service
+// Operational endpoints such as latency statistics
+pub mod admin {
+    tonic::include_proto!("admin");  // Generates from admin.proto
+}
+
+// Include generated code for the broadcast service
+// Publish/subscribe echo keyed by topic
+pub mod broadcast {
+    tonic::include_proto!("broadcast");  // Generates from broadcast.proto
+}
+
+// Canonical sample messages used by the wire compatibility tests
+pub mod compat;
+
+// Request field rules checked by both client and server
+pub mod validation;
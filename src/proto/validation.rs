@@ -0,0 +1,132 @@
+//! Request Validation
+//! Field rules of the request messages, checked by the client wrappers
+//! before sending and by the server handlers on receipt, so both sides
+//! reject the same requests with the same status:
+//! 1. Validate: implemented by every request message with such rules
+//! 2. EchoRequest and EchoStreamRequest: the message must not be empty or
+//!    only whitespace (`InvalidArgument`)
+//! 3. CalculateRequest: a known operation, finite operands and no division
+//!    by zero (`InvalidArgument` with a `CalculatorErrorDetail`)
+//!
+//! Rules that depend on how a server is configured (allow_empty_echo,
+//! echo_max_chars, SubnormalPolicy, the echo delay limit) stay with it.
+
+use tonic::{Code, Status};
+use super::calculator::calculator_error_detail::Kind;
+use super::calculator::{CalculateRequest, CalculatorErrorDetail, Operation};
+use super::echo::{EchoRequest, EchoStreamRequest};
+
+/// Constraints on the fields of a request message
+pub trait Validate {
+    /// Check the message against its rules
+    ///
+    /// # Returns
+    /// * `Result<(), Status>` - `InvalidArgument` describing the first rule broken.
+    fn validate(&self) -> Result<(), Status>;
+}
+
+impl Validate for EchoRequest {
+    fn validate(&self) -> Result<(), Status> {
+        check_message(&self.message)
+    }
+}
+
+impl Validate for EchoStreamRequest {
+    fn validate(&self) -> Result<(), Status> {
+        check_message(&self.message)
+    }
+}
+
+impl Validate for CalculateRequest {
+    fn validate(&self) -> Result<(), Status> {
+        let operation = operation(self.operation)?;
+        check_finite("first_number", 0, self.first_number)?;
+        check_finite("second_number", 1, self.second_number)?;
+        // IEEE 754 equality also matches -0.0
+        if operation == Operation::Divide && self.second_number == 0.0 {
+            return Err(invalid("division by zero is not allowed".into(), Kind::DivisionByZero, Some(1)));
+        }
+        Ok(())
+    }
+}
+
+// An echo message must have something other than whitespace in it
+fn check_message(message: &str) -> Result<(), Status> {
+    if message.trim().is_empty() {
+        return Err(Status::new(Code::InvalidArgument, "empty message is not allowed"));
+    }
+    Ok(())
+}
+
+/// Decode an operation from the wire
+///
+/// The generated accessor maps unknown values to Add, which would silently
+/// answer a request the client did not make.
+///
+/// # Returns
+/// * `Result<Operation, Status>` - The operation, or `InvalidArgument` for an unknown value.
+pub(crate) fn operation(value: i32) -> Result<Operation, Status> {
+    Operation::try_from(value)
+        .map_err(|_| invalid(format!("unknown operation {}", value), Kind::UnsupportedOperation, None))
+}
+
+// Reject a NaN or infinite operand, naming it in the error
+fn check_finite(name: &str, operand: u32, value: f64) -> Result<(), Status> {
+    if value.is_finite() {
+        return Ok(());
+    }
+    Err(invalid(format!("{} must be finite, got {}", name, value), Kind::NonFiniteOperand, Some(operand)))
+}
+
+// InvalidArgument carrying a CalculatorErrorDetail
+fn invalid(message: String, kind: Kind, operand: Option<u32>) -> Status {
+    CalculatorErrorDetail::new(kind, operand).to_status(Code::InvalidArgument, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculate(first: f64, second: f64, operation: i32) -> CalculateRequest {
+        CalculateRequest { first_number: first, second_number: second, operation, include_inverse: false }
+    }
+
+    // Kind and operand of a rejected calculation
+    fn rejection(request: CalculateRequest) -> (Kind, Option<u32>) {
+        let status = request.validate().expect_err("request was accepted");
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let detail = CalculatorErrorDetail::from_status(&status).expect("detail missing");
+        (detail.kind(), detail.operand)
+    }
+
+    #[test]
+    fn test_echo_messages() {
+        for message in ["hello", " padded ", "\u{1F600}"] {
+            let request = EchoRequest { message: message.into(), ..Default::default() };
+            assert!(request.validate().is_ok(), "{:?} rejected", message);
+        }
+        for message in ["", "   ", "\t\n", "\u{3000}"] {
+            let request = EchoRequest { message: message.into(), ..Default::default() };
+            let status = request.validate().expect_err("whitespace message accepted");
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert_eq!(status.message(), "empty message is not allowed");
+
+            let stream = EchoStreamRequest { message: message.into(), repeat: 3 };
+            assert_eq!(stream.validate().unwrap_err().code(), Code::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_calculate_requests() {
+        assert!(calculate(1.0, 0.0, Operation::Multiply as i32).validate().is_ok());
+        assert!(calculate(f64::MAX, 1e-300, Operation::Divide as i32).validate().is_ok());
+
+        assert_eq!(rejection(calculate(1.0, 0.0, Operation::Divide as i32)), (Kind::DivisionByZero, Some(1)));
+        assert_eq!(rejection(calculate(1.0, -0.0, Operation::Divide as i32)), (Kind::DivisionByZero, Some(1)));
+        assert_eq!(rejection(calculate(f64::NAN, 1.0, Operation::Add as i32)), (Kind::NonFiniteOperand, Some(0)));
+        assert_eq!(rejection(calculate(1.0, f64::NEG_INFINITY, Operation::Add as i32)), (Kind::NonFiniteOperand, Some(1)));
+        assert_eq!(rejection(calculate(1.0, 2.0, 42)), (Kind::UnsupportedOperation, None));
+        // The first rule broken is reported
+        assert_eq!(rejection(calculate(f64::NAN, 0.0, Operation::Divide as i32)), (Kind::NonFiniteOperand, Some(0)));
+    }
+}
@@ -0,0 +1,50 @@
+//! Secret Redaction
+//! Every Debug implementation and configuration snapshot that would show a
+//! secret (bearer tokens, TLS private keys, proxy credentials) shows a
+//! `Redacted` marker instead. Keeping the placeholder in this one type
+//! means a new secret-bearing option only needs to be recorded as
+//! `Option<Redacted>` to be safe everywhere it is printed or serialized.
+
+use std::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Text shown in place of a secret
+pub const REDACTED: &str = "<redacted>";
+
+/// Stands in for a configured secret; never holds the value itself
+///
+/// Debug and Display print `<redacted>`, and it serializes as that string.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Redacted;
+
+impl Redacted {
+    /// `Some(Redacted)` if a secret is configured, without looking at it
+    pub fn if_set<T>(secret: &Option<T>) -> Option<Redacted> {
+        secret.as_ref().map(|_| Redacted)
+    }
+}
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Redacted {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+// Accepts any string: a snapshot only records that the secret was set
+impl<'de> Deserialize<'de> for Redacted {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|_| Redacted)
+    }
+}
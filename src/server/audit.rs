@@ -0,0 +1,124 @@
+//! Audit Log
+//! Keeps an append-only record of the admin actions that change the
+//! server, separate from the regular log:
+//! 1. One JSON line per mutating admin RPC: time, peer, method, parameters, outcome
+//! 2. Written straight to its own file, not through tracing, so no log
+//!    level change can silence it
+//! 3. Lines are written on the blocking pool; the RPC waits for its line
+//! 4. A line that cannot be written fails the RPC with Internal
+//!    (fail-closed) unless the server was built with audit_fail_open(true);
+//!    the change itself has been made by then, the error says it is not on record
+//!
+//! Rejected attempts (missing or wrong admin token) are recorded too.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use serde_json::Value;
+use tonic::{Code, Status};
+use tracing::error;
+
+// File the audit entries go to when audit_log_path is not set
+pub(crate) const DEFAULT_AUDIT_LOG: &str = "logs/audit.log";
+
+// One audited admin RPC, serialized as a single JSON line
+#[derive(Debug, Serialize)]
+pub(crate) struct AuditEntry {
+    timestamp_ms: u64,  // Milliseconds since the Unix epoch
+    peer: Option<SocketAddr>,  // None when the transport has no address
+    method: &'static str,  // Admin RPC name, e.g. "SetLogLevel"
+    parameters: Value,  // The request fields, never the token
+    outcome: String,  // Status code name, "Ok" on success
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,  // Error message of a failed call
+}
+
+impl AuditEntry {
+    // Entry for a call to `method` from `peer`; the outcome is filled in by record
+    pub(crate) fn new(method: &'static str, peer: Option<SocketAddr>, parameters: Value) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| u64::try_from(since.as_millis()).unwrap_or(u64::MAX));
+        Self { timestamp_ms, peer, method, parameters, outcome: String::new(), message: None }
+    }
+}
+
+// Appends audit entries to one file, opened on first use
+// Clones share the file, so concurrent entries never interleave
+#[derive(Debug, Clone)]
+pub(crate) struct AuditLogger {
+    path: Arc<PathBuf>,
+    file: Arc<Mutex<Option<File>>>,
+    // Let the RPC succeed when its entry cannot be written
+    fail_open: bool,
+}
+
+impl Default for AuditLogger {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUDIT_LOG, false)
+    }
+}
+
+impl AuditLogger {
+    // Log to `path`; its directory is created with the first entry
+    pub(crate) fn new(path: impl Into<PathBuf>, fail_open: bool) -> Self {
+        Self { path: Arc::new(path.into()), file: Arc::new(Mutex::new(None)), fail_open }
+    }
+
+    // Record `entry` with the outcome of `result`
+    // Err(Internal) if the entry could not be written and the logger is fail-closed
+    pub(crate) async fn record<T>(&self, mut entry: AuditEntry, result: &Result<T, Status>) -> Result<(), Status> {
+        match result {
+            Ok(_) => entry.outcome = format!("{:?}", Code::Ok),
+            Err(status) => {
+                entry.outcome = format!("{:?}", status.code());
+                entry.message = Some(status.message().to_string());
+            }
+        }
+        let method = entry.method;
+        let logger = self.clone();
+        let written = tokio::task::spawn_blocking(move || logger.append(&entry))
+            .await
+            .unwrap_or_else(|e| Err(io::Error::other(e)));
+
+        match written {
+            Ok(()) => Ok(()),
+            Err(e) if self.fail_open => {
+                error!("Failed to audit {} in {} (continuing, fail-open): {}", method, self.path.display(), e);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to audit {} in {}; refusing the call: {}", method, self.path.display(), e);
+                Err(Status::internal("the audit log cannot be written"))
+            }
+        }
+    }
+
+    // Write one line, opening the file if needed
+    // A failed write drops the handle so the next entry reopens the file
+    fn append(&self, entry: &AuditEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let open = match &mut *file {
+            Some(open) => open,
+            None => file.insert(open_append(&self.path)?),
+        };
+        let written = open.write_all(&line).and_then(|()| open.flush());
+        if written.is_err() {
+            *file = None;
+        }
+        written
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
@@ -0,0 +1,159 @@
+//! Request Authentication
+//! Decides who is calling before any service sees the request:
+//! 1. Authenticator: the extension point, given each request's metadata
+//! 2. Identity: who the caller turned out to be, stored in the request
+//!    extensions so handlers can read it with `req.extensions().get::<Identity>()`
+//! 3. NoAuth: the default, accepting everyone as anonymous
+//! 4. StaticTokenAuth: a fixed set of bearer tokens, each naming a caller
+//!
+//! The authenticator runs in the server interceptor for every service,
+//! including admin; the admin token is still checked on top of it.
+
+use std::collections::HashMap;
+use std::fmt;
+use tonic::metadata::MetadataMap;
+use tonic::Status;
+use tracing::warn;
+
+// Metadata key carrying the bearer token ("Bearer <token>")
+const AUTHORIZATION: &str = "authorization";
+
+// Name of the identity given to unauthenticated callers
+const ANONYMOUS: &str = "anonymous";
+
+/// The authenticated caller of a request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Identity {
+    name: String,
+}
+
+impl Identity {
+    /// Create an identity with the given name, e.g. a user or service account
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// The identity given by [`NoAuth`] to every caller
+    pub fn anonymous() -> Self {
+        Self::new(ANONYMOUS)
+    }
+
+    /// Name of the caller
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// True for the identity of [`Identity::anonymous`]
+    pub fn is_anonymous(&self) -> bool {
+        self.name == ANONYMOUS
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Authentication backend consulted for every request
+///
+/// Register an implementation with `GrpcServerBuilder::authenticator`.
+/// It runs on the request path, so it should not block; an error status
+/// is returned to the client as is and the request never reaches a service.
+pub trait Authenticator: Send + Sync + 'static {
+    /// Resolve the caller of a request from its metadata
+    ///
+    /// # Arguments
+    /// * `metadata` - The request's metadata (headers), e.g. `authorization`.
+    ///
+    /// # Returns
+    /// * `Result<Identity, Status>` - The caller, or the status rejecting the request
+    ///   (usually Unauthenticated).
+    fn authenticate(&self, metadata: &MetadataMap) -> Result<Identity, Status>;
+}
+
+/// Authenticator accepting every request as [`Identity::anonymous`]
+///
+/// Used when no authenticator is registered.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoAuth;
+
+impl Authenticator for NoAuth {
+    fn authenticate(&self, _metadata: &MetadataMap) -> Result<Identity, Status> {
+        Ok(Identity::anonymous())
+    }
+}
+
+/// Authenticator accepting a fixed set of bearer tokens
+///
+/// Clients send `authorization: Bearer <token>` metadata; each token maps
+/// to the identity it was registered with. Debug output never shows tokens.
+#[derive(Clone, Default)]
+pub struct StaticTokenAuth {
+    tokens: HashMap<String, Identity>,
+}
+
+impl StaticTokenAuth {
+    /// Create an authenticator accepting `token` as the caller `name`
+    pub fn new(token: impl Into<String>, name: impl Into<String>) -> Self {
+        Self::default().with_token(token, name)
+    }
+
+    /// Also accept `token`, as the caller `name`
+    pub fn with_token(mut self, token: impl Into<String>, name: impl Into<String>) -> Self {
+        self.tokens.insert(token.into(), Identity::new(name));
+        self
+    }
+}
+
+impl Authenticator for StaticTokenAuth {
+    fn authenticate(&self, metadata: &MetadataMap) -> Result<Identity, Status> {
+        let presented = metadata
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("bearer token required"))?;
+        self.tokens.get(presented).cloned().ok_or_else(|| {
+            warn!("Rejected request with an unknown bearer token");
+            Status::unauthenticated("invalid bearer token")
+        })
+    }
+}
+
+impl fmt::Debug for StaticTokenAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<_> = self.tokens.values().map(Identity::name).collect();
+        names.sort_unstable();
+        f.debug_struct("StaticTokenAuth").field("identities", &names).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bearer(token: &str) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        metadata.insert(AUTHORIZATION, format!("Bearer {}", token).parse().unwrap());
+        metadata
+    }
+
+    #[test]
+    fn test_static_token_auth() {
+        let auth = StaticTokenAuth::new("s3cret", "alice").with_token("other", "bob");
+        assert_eq!(auth.authenticate(&bearer("s3cret")).unwrap(), Identity::new("alice"));
+        assert_eq!(auth.authenticate(&bearer("other")).unwrap().name(), "bob");
+
+        let err = auth.authenticate(&bearer("wrong")).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+        let err = auth.authenticate(&MetadataMap::new()).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+        assert!(!format!("{:?}", auth).contains("s3cret"));
+    }
+
+    #[test]
+    fn test_no_auth() {
+        assert!(NoAuth.authenticate(&MetadataMap::new()).unwrap().is_anonymous());
+    }
+}
@@ -0,0 +1,64 @@
+//! Startup Banner
+//! This file defines the one-line configuration summary logged when the
+//! server starts, so operators can confirm the effective settings from logs.
+
+use std::fmt;
+use std::net::SocketAddr;
+
+// tonic's default limit for a single decoded message (4 MiB)
+// The server does not override it, so this is the effective value
+pub(crate) const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Effective server configuration, collected for display at startup
+#[derive(Debug, Clone)]
+pub(crate) struct StartupBanner {
+    pub(crate) addr: SocketAddr,              // Address the server listens on
+    pub(crate) tls: bool,                     // Whether TLS is enabled
+    pub(crate) services: Vec<&'static str>,   // Fully qualified gRPC service names
+    pub(crate) max_concurrent_per_peer: Option<usize>,  // Per-IP in-flight cap
+    pub(crate) max_queue_depth: Option<usize>,  // Server-wide queued + in-flight cap
+    pub(crate) max_message_size: usize,       // Largest accepted message in bytes
+}
+
+impl fmt::Display for StartupBanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gRPC server configuration | address: {}", self.addr)?;
+        write!(f, " | TLS: {}", if self.tls { "on" } else { "off" })?;
+        write!(f, " | services: {}", self.services.join(", "))?;
+        match self.max_concurrent_per_peer {
+            Some(limit) => write!(f, " | per-peer concurrency limit: {}", limit)?,
+            None => write!(f, " | per-peer concurrency limit: unlimited")?,
+        }
+        match self.max_queue_depth {
+            Some(depth) => write!(f, " | queue depth: {}", depth)?,
+            None => write!(f, " | queue depth: unlimited")?,
+        }
+        write!(f, " | max message size: {} bytes", self.max_message_size)
+    }
+}
+
+// Unit tests for the banner formatting
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner_display() {
+        let banner = StartupBanner {
+            addr: "127.0.0.1:8080".parse().unwrap(),
+            tls: false,
+            services: vec!["echo.EchoService", "calculator.CalculatorService"],
+            max_concurrent_per_peer: Some(3),
+            max_queue_depth: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        };
+
+        let line = banner.to_string();
+        assert!(line.contains("address: 127.0.0.1:8080"));
+        assert!(line.contains("TLS: off"));
+        assert!(line.contains("echo.EchoService, calculator.CalculatorService"));
+        assert!(line.contains("per-peer concurrency limit: 3"));
+        assert!(line.contains("queue depth: unlimited"));
+        assert!(line.contains("max message size: 4194304 bytes"));
+    }
+}
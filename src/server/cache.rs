@@ -0,0 +1,175 @@
+//! Response Caches
+//! Remember recent responses so a workload that repeats the same few
+//! requests skips recomputing them:
+//! 1. CalculationCache: Calculate results, keyed by the bit patterns of
+//!    both operands plus the operation, so 0.0 and -0.0 are distinct and
+//!    no float comparison is involved
+//! 2. Requests with a NaN operand are never cached: NaN has many bit
+//!    patterns and such results are not worth keeping
+//! 3. Only successful results are stored; errors such as division by zero
+//!    are recomputed (and reported) every time
+//! 4. EchoCache: only counts repeated echoes. An echo just moves the
+//!    request's message into the response, so a stored copy would cost
+//!    more than it saves; it keeps a hash of each recent message instead
+//! 5. When full, the least recently used entry is evicted (see crate::lru)
+//!
+//! Hits and misses of each cache are counted in the server metrics.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex};
+use crate::lru::Lru;
+use crate::proto::calculator::Operation;
+use super::metrics::ServerMetrics;
+
+// (first operand bits, second operand bits, operation)
+type Key = (u64, u64, i32);
+
+/// A result of CalculationCache and where it came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Cached {
+    pub(crate) result: f64,
+    pub(crate) hit: bool,  // Stored earlier rather than computed now
+}
+
+/// Bounded LRU cache of calculation results, shared by clones
+#[derive(Debug, Clone)]
+pub(crate) struct CalculationCache {
+    entries: Arc<Mutex<Lru<Key, f64>>>,
+    metrics: ServerMetrics,
+}
+
+impl CalculationCache {
+    /// Create a cache holding at most `capacity` results (at least one)
+    pub(crate) fn new(capacity: usize, metrics: ServerMetrics) -> Self {
+        Self { entries: Arc::new(Mutex::new(Lru::new(capacity))), metrics }
+    }
+
+    /// Return the cached result or compute, store and return it
+    ///
+    /// # Arguments
+    /// * `first`, `second`, `operation` - The calculation.
+    /// * `compute` - Produces the result on a miss; its errors are passed through uncached.
+    ///
+    /// # Returns
+    /// * `Result<Cached, E>` - The cached or computed result.
+    pub(crate) fn get_or_compute<E>(
+        &self,
+        first: f64,
+        second: f64,
+        operation: Operation,
+        compute: impl FnOnce() -> Result<f64, E>,
+    ) -> Result<Cached, E> {
+        if first.is_nan() || second.is_nan() {
+            return compute().map(|result| Cached { result, hit: false });
+        }
+        let key = (first.to_bits(), second.to_bits(), operation as i32);
+
+        if let Some(result) = self.entries.lock().unwrap().get(&key) {
+            self.metrics.cache_hit();
+            return Ok(Cached { result, hit: true });
+        }
+
+        // Compute without the lock; racing misses on one key just store it twice
+        self.metrics.cache_miss();
+        let result = compute()?;
+        self.entries.lock().unwrap().insert(key, result);
+        Ok(Cached { result, hit: false })
+    }
+}
+
+/// Bounded LRU of recently echoed messages, shared by clones
+///
+/// Holds 64-bit hashes, never the messages: two messages sharing a hash
+/// count as a repeat, which only skews the counters.
+#[derive(Debug, Clone)]
+pub(crate) struct EchoCache {
+    entries: Arc<Mutex<Lru<u64, ()>>>,
+    hasher: RandomState,
+    metrics: ServerMetrics,
+}
+
+impl EchoCache {
+    /// Create a cache remembering at most `capacity` messages (at least one)
+    pub(crate) fn new(capacity: usize, metrics: ServerMetrics) -> Self {
+        Self { entries: Arc::new(Mutex::new(Lru::new(capacity))), hasher: RandomState::new(), metrics }
+    }
+
+    /// Count `message` as a hit when it was echoed recently, else a miss
+    ///
+    /// # Arguments
+    /// * `message` - A validated echo message.
+    pub(crate) fn record(&self, message: &str) {
+        // Hash without the lock
+        let key = self.hasher.hash_one(message);
+        let mut entries = self.entries.lock().unwrap();
+        if entries.get(&key).is_some() {
+            self.metrics.echo_cache_hit();
+        } else {
+            self.metrics.echo_cache_miss();
+            entries.insert(key, ());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(cache: &CalculationCache, first: f64, second: f64) -> Result<f64, ()> {
+        cache.get_or_compute(first, second, Operation::Add, || Ok(first + second)).map(|cached| cached.result)
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let metrics = ServerMetrics::default();
+        let cache = CalculationCache::new(2, metrics.clone());
+
+        cached(&cache, 1.0, 1.0).unwrap();
+        cached(&cache, 2.0, 2.0).unwrap();
+        let hit = cache.get_or_compute(1.0, 1.0, Operation::Add, || Err(())).unwrap();  // 2+2 is now the oldest
+        assert_eq!(hit, Cached { result: 2.0, hit: true });
+        cached(&cache, 3.0, 3.0).unwrap();  // evicts 2+2
+        cached(&cache, 1.0, 1.0).unwrap();  // hit
+        cached(&cache, 2.0, 2.0).unwrap();  // miss again
+
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (2, 4));
+        assert_eq!(cache.entries.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_nan_and_errors_are_not_cached() {
+        let metrics = ServerMetrics::default();
+        let cache = CalculationCache::new(8, metrics.clone());
+
+        for _ in 0..3 {
+            assert!(cached(&cache, f64::NAN, 1.0).unwrap().is_nan());
+            let err: Result<Cached, &str> = cache.get_or_compute(1.0, 0.0, Operation::Divide, || Err("division by zero"));
+            assert!(err.is_err());
+        }
+        // Signed zeros are different keys
+        cached(&cache, 0.0, 1.0).unwrap();
+        cached(&cache, -0.0, 1.0).unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (0, 5));
+        assert_eq!(cache.entries.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_echo_cache_counts_and_bounds() {
+        let metrics = ServerMetrics::default();
+        let cache = EchoCache::new(2, metrics.clone());
+
+        cache.record("a");
+        cache.record("a");  // hit
+        cache.record("b");
+        cache.record("c");  // evicts a
+        cache.record("a");  // miss again
+
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.echo_cache_hits, snapshot.echo_cache_misses), (1, 4));
+        assert_eq!(cache.entries.lock().unwrap().len(), 2);
+    }
+}
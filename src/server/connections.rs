@@ -0,0 +1,212 @@
+//! Connection Tracking
+//! Observes client connections as they are accepted and closed:
+//! 1. ConnectionHooks: user callbacks, the built-in connection gauge and
+//!    the optional event tap
+//! 2. TrackedIo: wraps each accepted socket; creating it reports the
+//!    connection, dropping it (when hyper is done with the connection)
+//!    reports the disconnect
+//! 3. ConnectionLimit: optional cap on open connections; sockets beyond it
+//!    are closed as soon as they are accepted
+//! 4. OpenConnections: accept time of every open connection, listed by the
+//!    admin ListConnections RPC
+//!
+//! Wrapping the socket rather than the service means connections are seen
+//! even if they never send a request. TLS is negotiated on top of the
+//! wrapped socket (serving.rs), so it works the same with and without TLS.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tracing::{debug, warn, Span};
+use super::metrics::ServerMetrics;
+use super::events::{EventTap, ServerEvent};
+
+// Callback receiving the remote address of a connection
+pub(crate) type ConnectionHook = Arc<dyn Fn(SocketAddr) + Send + Sync + 'static>;
+
+// When each open connection was accepted, keyed by remote address
+pub(crate) type OpenConnections = Arc<Mutex<HashMap<SocketAddr, SystemTime>>>;
+
+// Cap on connections open at once, shared by every listener
+#[derive(Clone)]
+pub(crate) struct ConnectionLimit {
+    max: usize,
+    permits: Arc<Semaphore>,
+}
+
+impl ConnectionLimit {
+    pub(crate) fn new(max: usize) -> Self {
+        Self { max, permits: Arc::new(Semaphore::new(max)) }
+    }
+}
+
+// Everything that runs when a connection opens or closes
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionHooks {
+    pub(crate) on_connect: Vec<ConnectionHook>,
+    pub(crate) on_disconnect: Vec<ConnectionHook>,
+    // Release per-connection state kept by the services; runs inline on close
+    pub(crate) cleanup: Vec<ConnectionHook>,
+    pub(crate) metrics: ServerMetrics,
+    pub(crate) events: EventTap,
+    pub(crate) limit: Option<ConnectionLimit>,
+    pub(crate) open: OpenConnections,
+}
+
+impl ConnectionHooks {
+    // Wrap a freshly accepted socket, or drop it (closing the connection)
+    // when the connection limit is reached
+    // Refused connections are counted but never reach the hooks
+    pub(crate) fn admit<IO>(self: &Arc<Self>, io: IO) -> Option<TrackedIo<IO>>
+    where
+        IO: Connected<ConnectInfo = TcpConnectInfo>,
+    {
+        let permit = match &self.limit {
+            Some(limit) => match limit.permits.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    warn!(
+                        "Refusing connection from {:?}: {} connections already open",
+                        io.connect_info().remote_addr(), limit.max
+                    );
+                    self.metrics.connection_refused();
+                    return None;
+                }
+            },
+            None => None,
+        };
+        let mut tracked = self.track(io);
+        tracked.permit = permit;
+        Some(tracked)
+    }
+
+    // Wrap a freshly accepted socket
+    fn track<IO>(self: &Arc<Self>, io: IO) -> TrackedIo<IO>
+    where
+        IO: Connected<ConnectInfo = TcpConnectInfo>,
+    {
+        let peer = io.connect_info().remote_addr();
+        self.metrics.connection_opened();
+        debug!("Connection opened: {:?}", peer);
+        if let Some(addr) = peer {
+            self.open.lock().unwrap().insert(addr, SystemTime::now());
+            self.events.emit(ServerEvent::ConnectionOpened(addr));
+        }
+
+        // User hooks run on the blocking pool so a slow hook cannot stall accept
+        let connected = match peer {
+            Some(addr) if !self.on_connect.is_empty() => {
+                let hooks = self.on_connect.clone();
+                Some(tokio::task::spawn_blocking(move || run_hooks(&hooks, addr)))
+            }
+            _ => None,
+        };
+
+        TrackedIo { inner: io, peer, hooks: Arc::clone(self), connected, permit: None, span: Span::current() }
+    }
+
+    // Report the end of a connection opened by track()
+    fn closed(&self, peer: Option<SocketAddr>, connected: Option<JoinHandle<()>>) {
+        self.metrics.connection_closed();
+        debug!("Connection closed: {:?}", peer);
+
+        let Some(addr) = peer else { return };
+        self.open.lock().unwrap().remove(&addr);
+        run_hooks(&self.cleanup, addr);
+        self.events.emit(ServerEvent::ConnectionClosed(addr));
+        if self.on_disconnect.is_empty() {
+            return;
+        }
+        let hooks = self.on_disconnect.clone();
+
+        // Disconnect hooks wait for the connect hooks of the same connection,
+        // so a peer is never reported gone before it was reported present
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move {
+                    if let Some(connected) = connected {
+                        let _ = connected.await;
+                    }
+                    let _ = tokio::task::spawn_blocking(move || run_hooks(&hooks, addr)).await;
+                });
+            }
+            // Dropped outside the runtime (e.g. during runtime shutdown)
+            Err(_) => run_hooks(&hooks, addr),
+        }
+    }
+}
+
+// Call each hook in registration order
+fn run_hooks(hooks: &[ConnectionHook], addr: SocketAddr) {
+    for hook in hooks {
+        hook(addr);
+    }
+}
+
+// Accepted socket that reports its own end when dropped
+pub(crate) struct TrackedIo<IO> {
+    inner: IO,
+    peer: Option<SocketAddr>,
+    hooks: Arc<ConnectionHooks>,
+    connected: Option<JoinHandle<()>>,  // Pending on_connect hooks
+    permit: Option<OwnedSemaphorePermit>,  // Slot under the connection limit, freed on drop
+    span: Span,  // The server's span; the socket is dropped in the connection's own task
+}
+
+impl<IO> Drop for TrackedIo<IO> {
+    fn drop(&mut self) {
+        let _span = self.span.enter();
+        self.hooks.closed(self.peer, self.connected.take());
+    }
+}
+
+// Expose the same connect info as the wrapped socket so request
+// extensions (and with them the per-peer limit) are unaffected
+impl<IO: Connected> Connected for TrackedIo<IO> {
+    type ConnectInfo = IO::ConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.inner.connect_info()
+    }
+}
+
+// Plain delegation to the wrapped socket
+impl<IO: AsyncRead + Unpin> AsyncRead for TrackedIo<IO> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl<IO: AsyncWrite + Unpin> AsyncWrite for TrackedIo<IO> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
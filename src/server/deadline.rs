@@ -0,0 +1,119 @@
+//! Request Deadlines
+//! Reads the deadline a client attached to a call, so handlers can stop
+//! working on an answer nobody will wait for:
+//! 1. Clients send their deadline as `grpc-timeout` metadata, e.g. `50m`
+//! 2. `remaining` turns it into the time left when the handler asks
+//! 3. Long-running handlers check a `Deadline` between chunks of work and
+//!    answer `DeadlineExceeded` once it has passed
+//!
+//! Public so services added next to the built-in ones can do the same.
+
+use std::time::{Duration, Instant};
+use tonic::{Request, Status};
+
+// Metadata key of the client's deadline (gRPC over HTTP/2 spec)
+const GRPC_TIMEOUT: &str = "grpc-timeout";
+
+// The spec allows at most 8 digits before the unit
+const MAX_TIMEOUT_DIGITS: usize = 8;
+
+/// Time left before the client's deadline for `request`
+///
+/// The deadline is read from the `grpc-timeout` metadata, whose value is
+/// up to 8 digits followed by a unit (`H`, `M`, `S`, `m`, `u` or `n`).
+///
+/// # Arguments
+/// * `request` - The incoming request.
+///
+/// # Returns
+/// * `Option<Duration>` - The timeout the client sent, or `None` if it sent
+///   none or the value is malformed (the call then has no deadline).
+pub fn remaining<T>(request: &Request<T>) -> Option<Duration> {
+    let value = request.metadata().get(GRPC_TIMEOUT)?.to_str().ok()?;
+    parse_timeout(value)
+}
+
+// The same timeout read from the headers of an HTTP request, for the
+// server's own request timeout (serving.rs)
+pub(crate) fn header_timeout(headers: &http::HeaderMap) -> Option<Duration> {
+    parse_timeout(headers.get(GRPC_TIMEOUT)?.to_str().ok()?)
+}
+
+// Parse a grpc-timeout value such as "50m" or "2S"
+fn parse_timeout(value: &str) -> Option<Duration> {
+    let unit_at = value.len().checked_sub(1)?;
+    let (digits, unit) = value.split_at(unit_at);
+    if digits.is_empty() || digits.len() > MAX_TIMEOUT_DIGITS || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "S" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "n" => Some(Duration::from_nanos(amount)),
+        _ => None,
+    }
+}
+
+// Point in time after which a handler should give up
+// Without a client deadline it never passes
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline(Option<Instant>);
+
+impl Deadline {
+    // The deadline of `request`, measured from now
+    pub(crate) fn of<T>(request: &Request<T>) -> Self {
+        Self(remaining(request).and_then(|left| Instant::now().checked_add(left)))
+    }
+
+    // Time left, or None when the client set no deadline
+    pub(crate) fn remaining(&self) -> Option<Duration> {
+        self.0.map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    // DeadlineExceeded once the deadline has passed
+    pub(crate) fn check(&self) -> Result<(), Status> {
+        match self.0 {
+            Some(at) if Instant::now() >= at => Err(Status::deadline_exceeded("deadline exceeded before the call completed")),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    #[test]
+    fn test_parse_timeout_units() {
+        assert_eq!(parse_timeout("2H"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_timeout("3M"), Some(Duration::from_secs(180)));
+        assert_eq!(parse_timeout("1S"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_timeout("50m"), Some(Duration::from_millis(50)));
+        assert_eq!(parse_timeout("250u"), Some(Duration::from_micros(250)));
+        assert_eq!(parse_timeout("99999999n"), Some(Duration::from_nanos(99_999_999)));
+
+        for malformed in ["", "m", "50", "50x", "-5m", "+5m", "123456789m", "5 m"] {
+            assert_eq!(parse_timeout(malformed), None, "{:?} accepted", malformed);
+        }
+    }
+
+    #[test]
+    fn test_deadline_from_request() {
+        let mut request = Request::new(());
+        assert_eq!(remaining(&request), None);
+        assert!(Deadline::of(&request).check().is_ok(), "no deadline never passes");
+
+        request.set_timeout(Duration::from_secs(30));
+        let left = remaining(&request).expect("timeout not read back");
+        assert!(left <= Duration::from_secs(30) && left > Duration::from_secs(29), "{:?}", left);
+
+        request.metadata_mut().insert(GRPC_TIMEOUT, "0n".parse().unwrap());
+        let err = Deadline::of(&request).check().unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+    }
+}
@@ -0,0 +1,42 @@
+//! Server Events
+//! Optional stream of lifecycle events, mainly for tests that need to
+//! observe ordering (startup, connections, shutdown) without polling logs.
+//!
+//! Events are sent on an unbounded channel supplied by the caller, so
+//! emitting never blocks the server; a dropped receiver is ignored.
+
+use std::net::SocketAddr;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Something that happened in a running server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerEvent {
+    /// The listener is bound and the server accepts connections
+    Started(SocketAddr),
+    /// A client connection was accepted (peer address)
+    ConnectionOpened(SocketAddr),
+    /// A client connection was closed (peer address)
+    ConnectionClosed(SocketAddr),
+    /// The shutdown handle was triggered, or every handle was dropped;
+    /// in-flight RPCs are draining
+    ShutdownRequested,
+    /// serve() is about to return
+    Stopped,
+}
+
+// Sending side of the tap; a no-op when no channel was configured
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventTap(Option<UnboundedSender<ServerEvent>>);
+
+impl EventTap {
+    pub(crate) fn new(tx: UnboundedSender<ServerEvent>) -> Self {
+        Self(Some(tx))
+    }
+
+    // Send an event if anyone is listening
+    pub(crate) fn emit(&self, event: ServerEvent) {
+        if let Some(tx) = &self.0 {
+            let _ = tx.send(event);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Calculator History
+//! Keeps the successful Calculate calls for the GetHistory RPC:
+//! 1. HistoryStore: the extension point, appended to by every calculation
+//! 2. MemoryHistory: the default, a ring of the most recent entries that
+//!    is lost when the server stops
+//! 3. FileHistory: JSON lines appended to a file that survives restarts,
+//!    rotated to `<path>.1` once it reaches its size cap
+//!
+//! Writes never fail a calculation: an entry the store cannot take is
+//! logged and dropped. Only GetHistory reports store errors, as Internal.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+use crate::proto::calculator::Operation;
+
+// Entries MemoryHistory keeps unless told otherwise
+pub(crate) const DEFAULT_MEMORY_HISTORY: usize = 1000;
+
+/// One successful Calculate call
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub first_number: f64,
+    pub second_number: f64,
+    #[serde(with = "operation_name")]
+    pub operation: Operation,
+    pub result: f64,
+    /// When it was calculated, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+impl HistoryEntry {
+    /// Entry for a calculation made now
+    pub fn new(first_number: f64, second_number: f64, operation: Operation, result: f64) -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| u64::try_from(since.as_millis()).unwrap_or(u64::MAX));
+        Self { first_number, second_number, operation, result, timestamp_ms }
+    }
+}
+
+// Operations are stored by name ("ADD"), so the files stay readable
+mod operation_name {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use crate::proto::calculator::Operation;
+
+    pub(super) fn serialize<S: Serializer>(operation: &Operation, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(operation.as_str_name())
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Operation, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Operation::from_str_name(&name).ok_or_else(|| D::Error::custom(format!("unknown operation {:?}", name)))
+    }
+}
+
+/// Storage of the calculator history
+///
+/// Register an implementation with `GrpcServerBuilder::calculator_history_store`.
+/// `append` runs after every successful calculation and is awaited before
+/// the response is sent, so it should be quick; an error is logged and the
+/// entry dropped. Errors of `recent` and `len` fail GetHistory with Internal.
+#[tonic::async_trait]
+pub trait HistoryStore: Send + Sync + 'static {
+    /// Add an entry after the most recent one
+    async fn append(&self, entry: HistoryEntry) -> io::Result<()>;
+
+    /// Up to `limit` of the most recent entries, newest first
+    async fn recent(&self, limit: usize) -> io::Result<Vec<HistoryEntry>>;
+
+    /// Number of entries kept
+    async fn len(&self) -> io::Result<usize>;
+
+    /// Whether no entry is kept
+    async fn is_empty(&self) -> io::Result<bool> {
+        Ok(self.len().await? == 0)
+    }
+}
+
+/// History kept in memory, dropping the oldest entry once full
+#[derive(Debug)]
+pub struct MemoryHistory {
+    entries: Mutex<VecDeque<HistoryEntry>>,
+    capacity: usize,
+}
+
+impl MemoryHistory {
+    /// Keep the `capacity` most recent entries
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: Mutex::new(VecDeque::with_capacity(capacity.min(DEFAULT_MEMORY_HISTORY))), capacity }
+    }
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEMORY_HISTORY)
+    }
+}
+
+#[tonic::async_trait]
+impl HistoryStore for MemoryHistory {
+    async fn append(&self, entry: HistoryEntry) -> io::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+        Ok(())
+    }
+
+    async fn recent(&self, limit: usize) -> io::Result<Vec<HistoryEntry>> {
+        Ok(self.entries.lock().unwrap().iter().rev().take(limit).copied().collect())
+    }
+
+    async fn len(&self) -> io::Result<usize> {
+        Ok(self.entries.lock().unwrap().len())
+    }
+}
+
+/// History appended to a file as JSON lines
+///
+/// Once the next entry would take the file past `max_bytes` it is renamed
+/// to `<path>.1`, replacing the previous one, and a new file is started;
+/// the history therefore keeps between one and two files' worth of
+/// entries. Lines that do not parse, e.g. one cut short by a crash, are
+/// skipped with a warning. Files are written on the blocking pool.
+#[derive(Clone)]
+pub struct FileHistory {
+    path: Arc<PathBuf>,
+    rotated: Arc<PathBuf>,
+    max_bytes: u64,
+    state: Arc<Mutex<FileState>>,
+}
+
+// The open file and what both files hold
+struct FileState {
+    file: Option<File>,  // Dropped after a failed write, reopened by the next one
+    bytes: u64,  // Size of the current file
+    current: usize,  // Entries in the current file
+    previous: usize,  // Entries in the rotated file
+}
+
+impl fmt::Debug for FileHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileHistory")
+            .field("path", &self.path)
+            .field("max_bytes", &self.max_bytes)
+            .finish()
+    }
+}
+
+impl FileHistory {
+    /// Open the history at `path`, keeping the entries already in it
+    ///
+    /// # Arguments
+    /// * `path` - The file to append to; it and its directory are created if missing.
+    /// * `max_bytes` - Size at which the file is rotated to `<path>.1`.
+    ///
+    /// # Returns
+    /// * `io::Result<FileHistory>` - The store, or the error reading the existing files.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.into();
+        let mut rotated = path.clone().into_os_string();
+        rotated.push(".1");
+        let rotated = PathBuf::from(rotated);
+
+        let previous = read_entries(&rotated)?.len();
+        let current = read_entries(&path)?.len();
+        let file = open_append(&path)?;
+        let bytes = file.metadata()?.len();
+        Ok(Self {
+            path: Arc::new(path),
+            rotated: Arc::new(rotated),
+            max_bytes,
+            state: Arc::new(Mutex::new(FileState { file: Some(file), bytes, current, previous })),
+        })
+    }
+
+    // Write one line, rotating first if it would not fit
+    fn append_blocking(&self, entry: &HistoryEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.bytes > 0 && state.bytes + line.len() as u64 > self.max_bytes {
+            state.file = None;
+            fs::rename(self.path.as_ref(), self.rotated.as_ref())?;
+            state.previous = state.current;
+            state.current = 0;
+            state.bytes = 0;
+        }
+        let file = match &mut state.file {
+            Some(file) => file,
+            None => state.file.insert(open_append(&self.path)?),
+        };
+        let written = file.write_all(&line).and_then(|()| file.flush());
+        match written {
+            Ok(()) => {
+                state.bytes += line.len() as u64;
+                state.current += 1;
+            }
+            Err(_) => state.file = None,
+        }
+        written
+    }
+
+    // Newest entries of the rotated file and then the current one
+    fn recent_blocking(&self, limit: usize) -> io::Result<Vec<HistoryEntry>> {
+        // Held so a rotation cannot move entries between the two reads
+        let _state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<HistoryEntry> = read_entries(&self.path)?.into_iter().rev().take(limit).collect();
+        if entries.len() < limit {
+            let older = read_entries(&self.rotated)?;
+            entries.extend(older.into_iter().rev().take(limit - entries.len()));
+        }
+        Ok(entries)
+    }
+
+    // Run `f` on the blocking pool
+    async fn blocking<T: Send + 'static>(&self, f: impl FnOnce(&Self) -> io::Result<T> + Send + 'static) -> io::Result<T> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || f(&store))
+            .await
+            .unwrap_or_else(|e| Err(io::Error::other(e)))
+    }
+}
+
+#[tonic::async_trait]
+impl HistoryStore for FileHistory {
+    async fn append(&self, entry: HistoryEntry) -> io::Result<()> {
+        self.blocking(move |store| store.append_blocking(&entry)).await
+    }
+
+    async fn recent(&self, limit: usize) -> io::Result<Vec<HistoryEntry>> {
+        self.blocking(move |store| store.recent_blocking(limit)).await
+    }
+
+    async fn len(&self) -> io::Result<usize> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(state.previous + state.current)
+    }
+}
+
+// Entries of a history file, oldest first; a missing file has none
+fn read_entries(path: &Path) -> io::Result<Vec<HistoryEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!("Skipping line {} of {}: {}", number + 1, path.display(), e),
+        }
+    }
+    Ok(entries)
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+// The store a CalculatorServer writes to, MemoryHistory unless replaced
+#[derive(Clone)]
+pub(crate) struct History(Arc<dyn HistoryStore>);
+
+impl Default for History {
+    fn default() -> Self {
+        Self(Arc::new(MemoryHistory::default()))
+    }
+}
+
+impl fmt::Debug for History {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("History")
+    }
+}
+
+impl History {
+    pub(crate) fn new(store: Arc<dyn HistoryStore>) -> Self {
+        Self(store)
+    }
+
+    // Append `entry`, logging and dropping it if the store fails
+    pub(crate) async fn record(&self, entry: HistoryEntry) {
+        if let Err(e) = self.0.append(entry).await {
+            error!("Dropped calculator history entry: {}", e);
+        }
+    }
+
+    // Up to `limit` recent entries and the number kept
+    pub(crate) async fn read(&self, limit: usize) -> io::Result<(Vec<HistoryEntry>, usize)> {
+        Ok((self.0.recent(limit).await?, self.0.len().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(first_number: f64) -> HistoryEntry {
+        HistoryEntry::new(first_number, 1.0, Operation::Add, first_number + 1.0)
+    }
+
+    #[tokio::test]
+    async fn test_memory_history_keeps_newest() {
+        let history = MemoryHistory::new(3);
+        for i in 0..5 {
+            history.append(entry(f64::from(i))).await.unwrap();
+        }
+        let firsts: Vec<f64> = history.recent(10).await.unwrap().iter().map(|e| e.first_number).collect();
+        assert_eq!(firsts, [4.0, 3.0, 2.0]);
+        assert_eq!(history.len().await.unwrap(), 3);
+        assert_eq!(history.recent(1).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_entries_stored_by_operation_name() {
+        let line = serde_json::to_string(&HistoryEntry { timestamp_ms: 7, ..entry(2.0) }).unwrap();
+        assert!(line.contains("\"operation\":\"ADD\""), "{}", line);
+        assert_eq!(serde_json::from_str::<HistoryEntry>(&line).unwrap().timestamp_ms, 7);
+        assert!(serde_json::from_str::<HistoryEntry>(&line.replace("ADD", "MODULO")).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Idle Connection Timeout
+//! Closes client connections that had no RPC in flight for longer than
+//! the configured timeout:
+//! 1. IdleTracker: per-connection count of in-flight RPCs and the time
+//!    the last one finished, keyed by remote address
+//! 2. IdleLayer: marks each RPC active until its response body is done,
+//!    so a streaming RPC keeps its connection open however long it runs
+//! 3. IdleWatch: held by the task serving a connection; it resolves once
+//!    the tracker reports the connection idle
+//!
+//! tonic has no idle timeout of its own, and HTTP/2 keepalive pings only
+//! detect dead peers. The serving task then shuts the connection down
+//! through hyper (serving.rs): a GOAWAY tells the client to stop opening
+//! streams on it and reconnect transparently, with or without TLS.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::time::{Duration, Instant};
+use tonic::body::BoxBody;
+use tonic::codegen::http::HeaderMap;
+use tonic::codegen::Body;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::debug;
+
+// What the tracker knows about one connection
+#[derive(Debug)]
+struct Activity {
+    active: usize,  // RPCs in flight
+    last_active: Instant,  // When the connection opened or the last RPC finished
+}
+
+// In-flight RPCs of every open connection, shared by the layer and the sockets
+#[derive(Debug)]
+pub(crate) struct IdleTracker {
+    timeout: Duration,
+    connections: Mutex<HashMap<SocketAddr, Activity>>,
+}
+
+impl IdleTracker {
+    // Close connections idle for longer than `timeout`
+    pub(crate) fn new(timeout: Duration) -> Arc<Self> {
+        Arc::new(Self { timeout, connections: Mutex::default() })
+    }
+
+    // Layer marking RPCs active on this tracker
+    pub(crate) fn layer(self: &Arc<Self>) -> IdleLayer {
+        IdleLayer { tracker: Arc::clone(self) }
+    }
+
+    // Track the connection of `peer` until the watch is dropped
+    // It counts as active from now
+    pub(crate) fn watch(self: &Arc<Self>, peer: SocketAddr) -> IdleWatch {
+        let activity = Activity { active: 0, last_active: Instant::now() };
+        self.connections.lock().unwrap().insert(peer, activity);
+        IdleWatch { tracker: Arc::clone(self), peer }
+    }
+
+    fn closed(&self, peer: SocketAddr) {
+        self.connections.lock().unwrap().remove(&peer);
+    }
+
+    // Mark one RPC of `peer` in flight until the guard is dropped
+    fn begin(self: &Arc<Self>, peer: SocketAddr) -> ActiveGuard {
+        if let Some(activity) = self.connections.lock().unwrap().get_mut(&peer) {
+            activity.active += 1;
+        }
+        ActiveGuard { tracker: Arc::clone(self), peer }
+    }
+
+    // When the connection became idle, or None while RPCs are in flight
+    fn idle_since(&self, peer: SocketAddr) -> Option<Instant> {
+        self.connections.lock().unwrap()
+            .get(&peer)
+            .filter(|activity| activity.active == 0)
+            .map(|activity| activity.last_active)
+    }
+}
+
+// Keeps one RPC counted as in flight; dropping it records the activity
+struct ActiveGuard {
+    tracker: Arc<IdleTracker>,
+    peer: SocketAddr,
+}
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        if let Some(activity) = self.tracker.connections.lock().unwrap().get_mut(&self.peer) {
+            activity.active -= 1;
+            activity.last_active = Instant::now();
+        }
+    }
+}
+
+/// Layer counting RPCs as in flight until their response is fully sent
+#[derive(Clone)]
+pub(crate) struct IdleLayer {
+    tracker: Arc<IdleTracker>,
+}
+
+impl<S> Layer<S> for IdleLayer {
+    type Service = Idle<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Idle { inner, tracker: self.tracker.clone() }
+    }
+}
+
+/// Service produced by [`IdleLayer`]
+#[derive(Clone)]
+pub(crate) struct Idle<S> {
+    inner: S,
+    tracker: Arc<IdleTracker>,
+}
+
+// Remote address of the connection a request arrived on, with or without TLS
+pub(crate) fn remote_addr<B>(req: &http::Request<B>) -> Option<SocketAddr> {
+    let extensions = req.extensions();
+    extensions.get::<TcpConnectInfo>()
+        .and_then(|info| info.remote_addr())
+        .or_else(|| extensions.get::<TlsConnectInfo<TcpConnectInfo>>().and_then(|info| info.get_ref().remote_addr()))
+}
+
+impl<S, B> Service<http::Request<B>> for Idle<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let guard = remote_addr(&req).map(|peer| self.tracker.begin(peer));
+
+        // Swap in a fresh clone so the ready service is the one we call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let response = inner.call(req).await?;
+            // Streaming responses stay active until the body is done
+            Ok(match guard {
+                Some(guard) => response.map(|body| ActiveBody { inner: body, _guard: guard }.boxed_unsync()),
+                None => response,
+            })
+        })
+    }
+}
+
+// Response body holding its RPC's guard until hyper drops it
+struct ActiveBody {
+    inner: BoxBody,
+    _guard: ActiveGuard,
+}
+
+impl Body for ActiveBody {
+    type Data = <BoxBody as Body>::Data;
+    type Error = Status;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+// One tracked connection, watched by the task serving it
+pub(crate) struct IdleWatch {
+    tracker: Arc<IdleTracker>,
+    peer: SocketAddr,
+}
+
+impl IdleWatch {
+    // Resolves once the connection has had no RPC in flight for the timeout
+    pub(crate) async fn idle(&self) {
+        let timeout = self.tracker.timeout;
+        loop {
+            let deadline = match self.tracker.idle_since(self.peer) {
+                Some(since) => since + timeout,
+                // RPCs in flight: look again one timeout from now
+                None => Instant::now() + timeout,
+            };
+            if Instant::now() >= deadline {
+                debug!("Connection {} idle for {:?}, shutting it down", self.peer, timeout);
+                return;
+            }
+            tokio::time::sleep_until(deadline).await;
+        }
+    }
+}
+
+impl Drop for IdleWatch {
+    fn drop(&mut self) {
+        self.tracker.closed(self.peer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_waits_for_rpcs_to_finish() {
+        let tracker = IdleTracker::new(Duration::from_millis(50));
+        let peer: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        let watch = tracker.watch(peer);
+
+        let rpc = tracker.begin(peer);
+        let busy = tokio::time::timeout(Duration::from_millis(150), watch.idle()).await;
+        assert!(busy.is_err(), "idle while an RPC was in flight");
+
+        drop(rpc);
+        let started = Instant::now();
+        tokio::time::timeout(Duration::from_secs(1), watch.idle()).await.expect("never idle");
+        assert!(started.elapsed() >= Duration::from_millis(50), "idle before the timeout passed");
+
+        drop(watch);
+        assert!(tracker.connections.lock().unwrap().is_empty());
+    }
+}
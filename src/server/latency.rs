@@ -0,0 +1,103 @@
+//! Latency Tracker
+//! Keeps the latencies of the most recent requests in a fixed-size ring
+//! buffer and answers percentile queries over them:
+//! 1. Recording is O(1) under a short lock, so it is cheap per request
+//! 2. Memory is bounded by the window size regardless of traffic
+//! 3. Percentiles are computed on demand (sorting a copy of the window)
+//!
+//! A sliding window rather than an all-time histogram means the numbers
+//! describe current behavior, which is what an operator asking "how slow
+//! are we right now" wants.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Number of most recent requests the percentiles are computed over
+const WINDOW: usize = 4096;
+
+/// Percentiles of the recorded latencies
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct LatencyPercentiles {
+    pub(crate) p50: Duration,
+    pub(crate) p90: Duration,
+    pub(crate) p99: Duration,
+    pub(crate) count: usize,  // Samples the percentiles are based on
+}
+
+/// Thread-safe tracker of recent request latencies
+#[derive(Debug, Default)]
+pub(crate) struct LatencyTracker {
+    window: Mutex<Window>,
+}
+
+// Ring buffer of the last WINDOW samples
+#[derive(Debug, Default)]
+struct Window {
+    samples: Vec<Duration>,
+    next: usize,  // Slot overwritten next once the buffer is full
+}
+
+impl LatencyTracker {
+    /// Record the latency of one finished request
+    pub(crate) fn record(&self, latency: Duration) {
+        let mut window = self.window.lock().unwrap();
+        if window.samples.len() < WINDOW {
+            window.samples.push(latency);
+        } else {
+            let slot = window.next;
+            window.samples[slot] = latency;
+        }
+        window.next = (window.next + 1) % WINDOW;
+    }
+
+    /// Percentiles over the current window (all zero when empty)
+    pub(crate) fn percentiles(&self) -> LatencyPercentiles {
+        // Copy out so the lock is not held while sorting
+        let mut samples = self.window.lock().unwrap().samples.clone();
+        if samples.is_empty() {
+            return LatencyPercentiles::default();
+        }
+        samples.sort_unstable();
+        LatencyPercentiles {
+            p50: nearest_rank(&samples, 50),
+            p90: nearest_rank(&samples, 90),
+            p99: nearest_rank(&samples, 99),
+            count: samples.len(),
+        }
+    }
+}
+
+// Nearest-rank percentile of a sorted, non-empty slice
+// Always returns an actual sample, so p99 of few samples is the maximum
+fn nearest_rank(sorted: &[Duration], percentile: usize) -> Duration {
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_over_window() {
+        let tracker = LatencyTracker::default();
+        assert_eq!(tracker.percentiles(), LatencyPercentiles::default());
+
+        for ms in (1..=100).rev() {
+            tracker.record(Duration::from_millis(ms));
+        }
+        let stats = tracker.percentiles();
+        assert_eq!(stats.count, 100);
+        assert_eq!(stats.p50, Duration::from_millis(50));
+        assert_eq!(stats.p90, Duration::from_millis(90));
+        assert_eq!(stats.p99, Duration::from_millis(99));
+
+        // Old samples fall out of the window
+        for _ in 0..WINDOW {
+            tracker.record(Duration::from_millis(7));
+        }
+        let stats = tracker.percentiles();
+        assert_eq!(stats.count, WINDOW);
+        assert_eq!(stats.p99, Duration::from_millis(7));
+    }
+}
@@ -0,0 +1,86 @@
+//! Per-Connection Request Counting
+//! This layer numbers the RPCs of each connection, which helps debugging
+//! sticky-session load balancers: a client can see how many requests the
+//! connection it is using has served.
+//!
+//! The count is stored in the request extensions as ConnectionRequestCount;
+//! Echo and Calculate return it as `x-connection-request-count` metadata.
+//! Connections are told apart by remote address, and their entry is removed
+//! when they close.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tonic::metadata::MetadataMap;
+use tower::{Layer, Service};
+use crate::proto::CONNECTION_REQUEST_COUNT_METADATA;
+use crate::server::idle::remote_addr;
+
+// RPCs served so far per connection, keyed by remote address
+pub(crate) type ConnectionCounts = Arc<Mutex<HashMap<SocketAddr, u64>>>;
+
+// Position of an RPC on its connection, starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ConnectionRequestCount(pub(crate) u64);
+
+impl ConnectionRequestCount {
+    // Attach the count to response metadata
+    pub(crate) fn insert_into(self, metadata: &mut MetadataMap) {
+        metadata.insert(CONNECTION_REQUEST_COUNT_METADATA, self.0.into());
+    }
+}
+
+/// Layer counting each connection's requests
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionCountLayer {
+    counts: ConnectionCounts,
+}
+
+impl ConnectionCountLayer {
+    // Shared handle to the counts, for cleanup on disconnect
+    pub(crate) fn counts(&self) -> ConnectionCounts {
+        self.counts.clone()
+    }
+}
+
+impl<S> Layer<S> for ConnectionCountLayer {
+    type Service = ConnectionCount<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectionCount {
+            inner,
+            counts: self.counts.clone(),
+        }
+    }
+}
+
+/// Service produced by [`ConnectionCountLayer`]
+#[derive(Clone)]
+pub(crate) struct ConnectionCount<S> {
+    inner: S,
+    counts: ConnectionCounts,
+}
+
+impl<S, B> Service<http::Request<B>> for ConnectionCount<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        if let Some(peer) = remote_addr(&req) {
+            let mut counts = self.counts.lock().unwrap();
+            let count = counts.entry(peer).or_insert(0);
+            *count += 1;
+            req.extensions_mut().insert(ConnectionRequestCount(*count));
+        }
+        self.inner.call(req)
+    }
+}
@@ -0,0 +1,206 @@
+//! Fault Injection
+//! This layer makes application RPCs fail with Unavailable, or respond
+//! late, with configured probabilities, so client retry and backoff logic
+//! can be exercised against a real server.
+//!
+//! Faults are drawn from a seeded SplitMix64 generator shared by every
+//! connection: with the same seed, the same sequence of requests sees the
+//! same faults. Admin RPCs are never faulted, so operators can still reach
+//! the server. Latency is added without blocking, which is why this is a
+//! layer rather than an interceptor.
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::{debug, info};
+
+// Admin RPCs stay reliable
+const ADMIN_PREFIX: &str = "/admin.";
+
+/// Faults injected by `GrpcServerBuilder::fault_injection`
+///
+/// Each request independently gets `latency` added with
+/// `latency_probability` and fails with Unavailable with
+/// `error_probability`; probabilities range from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultConfig {
+    /// Chance that a request fails with Unavailable
+    pub error_probability: f64,
+    /// Chance that a request is delayed by `latency`
+    pub latency_probability: f64,
+    /// Delay added to the requests picked by `latency_probability`
+    pub latency: Duration,
+    /// Seed of the fault sequence; a random one is picked (and logged) if None
+    pub seed: Option<u64>,
+}
+
+impl FaultConfig {
+    // Probabilities outside 0.0..=1.0 (or NaN), described for build()
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        for (name, value) in [("error_probability", self.error_probability), ("latency_probability", self.latency_probability)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("fault injection {} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+// SplitMix64: tiny, fast and good enough to pick faults
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // True with probability `p`; 0.0 never and 1.0 always
+    fn chance(&mut self, p: f64) -> bool {
+        // 53 random bits give a uniform value in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+}
+
+// Faults picked for one request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Faults {
+    delay: bool,
+    fail: bool,
+}
+
+/// Layer injecting the faults of a FaultConfig
+#[derive(Clone)]
+pub(crate) struct FaultInjectionLayer {
+    config: FaultConfig,
+    rng: Arc<Mutex<SplitMix64>>,
+}
+
+impl FaultInjectionLayer {
+    /// Create a layer injecting the faults of `config`
+    ///
+    /// # Arguments
+    /// * `config` - Validated probabilities, latency and optional seed.
+    ///
+    /// # Returns
+    /// * `Self` - A new layer with its own fault sequence.
+    pub(crate) fn new(config: FaultConfig) -> Self {
+        let seed = config.seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        info!(
+            "Fault injection enabled: {:.0}% Unavailable, {:.0}% delayed by {:?} (seed {})",
+            config.error_probability * 100.0,
+            config.latency_probability * 100.0,
+            config.latency,
+            seed
+        );
+        Self { config, rng: Arc::new(Mutex::new(SplitMix64(seed))) }
+    }
+
+    // Draw both faults, always in the same order so the sequence is reproducible
+    fn draw(&self) -> Faults {
+        let mut rng = self.rng.lock().unwrap();
+        let delay = rng.chance(self.config.latency_probability);
+        let fail = rng.chance(self.config.error_probability);
+        Faults { delay, fail }
+    }
+}
+
+impl<S> Layer<S> for FaultInjectionLayer {
+    type Service = FaultInjection<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FaultInjection { inner, layer: self.clone() }
+    }
+}
+
+/// Service produced by [`FaultInjectionLayer`]
+#[derive(Clone)]
+pub(crate) struct FaultInjection<S> {
+    inner: S,
+    layer: FaultInjectionLayer,
+}
+
+impl<S, B> Service<http::Request<B>> for FaultInjection<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let faults = if req.uri().path().starts_with(ADMIN_PREFIX) {
+            Faults { delay: false, fail: false }
+        } else {
+            self.layer.draw()
+        };
+        let latency = self.layer.config.latency;
+
+        // Swap in a fresh clone so the ready service is the one we call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if faults.delay {
+                debug!("Injecting {:?} of latency into {}", latency, req.uri().path());
+                tokio::time::sleep(latency).await;
+            }
+            if faults.fail {
+                debug!("Injecting Unavailable into {}", req.uri().path());
+                return Ok(Status::unavailable("injected fault").to_http());
+            }
+            inner.call(req).await
+        })
+    }
+}
+
+// Unit tests for the fault sequence
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(probability: f64, seed: u64) -> FaultInjectionLayer {
+        let config = FaultConfig { error_probability: probability, seed: Some(seed), ..Default::default() };
+        FaultInjectionLayer::new(config)
+    }
+
+    #[test]
+    fn test_fault_sequence_is_seeded() {
+        let draws = |layer: &FaultInjectionLayer| (0..200).map(|_| layer.draw().fail).collect::<Vec<_>>();
+
+        // Same seed, same faults; another seed differs
+        let first = draws(&layer(0.5, 7));
+        assert_eq!(first, draws(&layer(0.5, 7)));
+        assert_ne!(first, draws(&layer(0.5, 8)));
+        // Roughly half of the requests fail
+        let failed = first.iter().filter(|fail| **fail).count();
+        assert!((60..140).contains(&failed), "{} of 200 failed", failed);
+
+        // The bounds are exact
+        assert!(draws(&layer(0.0, 7)).iter().all(|fail| !fail));
+        assert!(draws(&layer(1.0, 7)).iter().all(|fail| *fail));
+    }
+
+    #[test]
+    fn test_validate_probabilities() {
+        assert!(FaultConfig { error_probability: 1.0, ..Default::default() }.validate().is_ok());
+        assert!(FaultConfig { error_probability: 1.5, ..Default::default() }.validate().is_err());
+        assert!(FaultConfig { latency_probability: f64::NAN, ..Default::default() }.validate().is_err());
+    }
+}
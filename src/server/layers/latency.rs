@@ -0,0 +1,282 @@
+//! Request Latency Recording
+//! This layer times every application RPC and feeds the shared
+//! LatencyTracker queried by the admin service.
+//!
+//! Time is measured until the service returns the response head. For
+//! unary calls that is the full handling time; for streaming calls it is
+//! the time until the stream was opened.
+//!
+//! With a slow request threshold the layer also times every RPC, admin
+//! ones included, until its response body is finished, so a streaming
+//! RPC counts from its start to its last message. RPCs over the threshold
+//! are logged as a warning with method, peer, status, latency and request
+//! ID, and counted in the server metrics; streaming ones are logged and
+//! counted apart, since a long subscription is usually expected.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tonic::body::BoxBody;
+use tonic::codegen::http::HeaderMap;
+use tonic::codegen::Body;
+use tonic::{Code, Status};
+use tower::{Layer, Service};
+use tracing::{warn, Span};
+use crate::server::idle::remote_addr;
+use crate::server::latency::LatencyTracker;
+use crate::server::metrics::ServerMetrics;
+
+// Admin RPCs are not recorded, so querying the stats does not skew them
+const ADMIN_PREFIX: &str = "/admin.";
+
+// Request metadata naming the request in slow request warnings
+const REQUEST_ID: &str = "x-request-id";
+
+// Status of a finished RPC, sent in the trailers (or the headers when there is no body)
+const GRPC_STATUS: &str = "grpc-status";
+
+// Every RPC with a stream on either side; the test below keeps it in line with the .proto files
+const STREAMING_METHODS: [&str; 6] = [
+    "/echo.EchoService/EchoChunked",
+    "/echo.EchoService/EchoStream",
+    "/calculator.CalculatorService/AggregateStream",
+    "/calculator.CalculatorService/RunningTotal",
+    "/admin.AdminService/WatchOperations",
+    "/broadcast.BroadcastService/Subscribe",
+];
+
+// Threshold and counters of the slow request warnings
+#[derive(Clone)]
+struct SlowRequests {
+    threshold: Duration,
+    metrics: ServerMetrics,
+}
+
+/// Layer recording the latency of each request in a LatencyTracker
+#[derive(Clone)]
+pub(crate) struct LatencyLayer {
+    tracker: Arc<LatencyTracker>,
+    slow: Option<SlowRequests>,
+}
+
+impl LatencyLayer {
+    /// Create a layer feeding `tracker`
+    ///
+    /// # Arguments
+    /// * `tracker` - The tracker shared with the admin service.
+    ///
+    /// # Returns
+    /// * `Self` - A new layer.
+    pub(crate) fn new(tracker: Arc<LatencyTracker>) -> Self {
+        Self { tracker, slow: None }
+    }
+
+    /// Also warn about RPCs slower than `threshold`, counting them in `metrics`
+    ///
+    /// # Arguments
+    /// * `threshold` - The slow request threshold; None leaves the warnings off.
+    /// * `metrics` - The server metrics counting the slow RPCs.
+    ///
+    /// # Returns
+    /// * `Self` - The layer with the warnings configured.
+    pub(crate) fn slow_requests(mut self, threshold: Option<Duration>, metrics: ServerMetrics) -> Self {
+        self.slow = threshold.map(|threshold| SlowRequests { threshold, metrics });
+        self
+    }
+}
+
+impl<S> Layer<S> for LatencyLayer {
+    type Service = Latency<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Latency {
+            inner,
+            tracker: self.tracker.clone(),
+            slow: self.slow.clone(),
+        }
+    }
+}
+
+/// Service produced by [`LatencyLayer`]
+#[derive(Clone)]
+pub(crate) struct Latency<S> {
+    inner: S,
+    tracker: Arc<LatencyTracker>,
+    slow: Option<SlowRequests>,
+}
+
+impl<S, B> Service<http::Request<B>> for Latency<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let tracker = (!req.uri().path().starts_with(ADMIN_PREFIX)).then(|| self.tracker.clone());
+        let started = Instant::now();
+        let watch = self.slow.clone().map(|slow| SlowWatch::new(slow, &req, started));
+
+        // Swap in a fresh clone so the ready service is the one we call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let response = inner.call(req).await;
+            if let Some(tracker) = tracker {
+                tracker.record(started.elapsed());
+            }
+            // The RPC is only over once its body is
+            let Some(mut watch) = watch else { return response };
+            let response = response?;
+            watch.span = Span::current();
+            watch.status = grpc_status(response.headers());
+            Ok(response.map(|body| TimedBody { inner: body, watch: Some(watch) }.boxed_unsync()))
+        })
+    }
+}
+
+// One RPC being timed against the slow request threshold
+struct SlowWatch {
+    slow: SlowRequests,
+    started: Instant,
+    method: String,
+    peer: Option<SocketAddr>,
+    request_id: Option<String>,
+    streaming: bool,
+    // From the response headers; an error without a body has it there
+    status: Option<Code>,
+    // The RPC's span, entered for the warning; the body outlives the call
+    span: Span,
+}
+
+impl SlowWatch {
+    fn new<B>(slow: SlowRequests, req: &http::Request<B>, started: Instant) -> Self {
+        let method = req.uri().path().to_string();
+        Self {
+            slow,
+            started,
+            streaming: STREAMING_METHODS.contains(&method.as_str()),
+            method,
+            peer: remote_addr(req),
+            request_id: req.headers().get(REQUEST_ID).and_then(|id| id.to_str().ok()).map(str::to_string),
+            status: None,
+            span: Span::none(),
+        }
+    }
+
+    // Warn if the RPC took longer than the threshold
+    // `status` is None when the body ended without one, e.g. a cancelled call
+    fn finish(self, status: Option<Code>) {
+        let elapsed = self.started.elapsed();
+        if elapsed <= self.slow.threshold {
+            return;
+        }
+        let _span = self.span.enter();
+        self.slow.metrics.slow_request(self.streaming);
+        let kind = if self.streaming { "Slow streaming RPC" } else { "Slow RPC" };
+        let peer = self.peer.map_or_else(|| "unknown peer".to_string(), |peer| peer.to_string());
+        let status = status.map_or_else(|| "none".to_string(), |code| format!("{:?}", code));
+        warn!(
+            "{} {} from {}: status {}, took {:?} (threshold {:?}), request id {}",
+            kind, self.method, peer, status, elapsed, self.slow.threshold,
+            self.request_id.as_deref().unwrap_or("-"),
+        );
+    }
+}
+
+// grpc-status carried by `headers`, if any
+fn grpc_status(headers: &HeaderMap) -> Option<Code> {
+    headers.get(GRPC_STATUS).map(|value| Code::from_bytes(value.as_bytes()))
+}
+
+// Response body that reports its RPC to the watch once it is finished
+struct TimedBody {
+    inner: BoxBody,
+    watch: Option<SlowWatch>,
+}
+
+impl Body for TimedBody {
+    type Data = <BoxBody as Body>::Data;
+    type Error = Status;
+
+    fn poll_data(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let trailers = Pin::new(&mut self.inner).poll_trailers(cx);
+        if let Poll::Ready(Ok(Some(trailers))) = &trailers {
+            if let Some(watch) = self.watch.take() {
+                let status = grpc_status(trailers).or(watch.status);
+                watch.finish(status);
+            }
+        }
+        trailers
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+// A body dropped before its trailers was cut short or had no trailers to send
+impl Drop for TimedBody {
+    fn drop(&mut self) {
+        if let Some(watch) = self.watch.take() {
+            let status = watch.status;
+            watch.finish(status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Methods of the rpcs declared with a stream on either side
+    fn declared_streaming_methods() -> Vec<String> {
+        let protos = [
+            include_str!("../../proto/echo.proto"),
+            include_str!("../../proto/calculator.proto"),
+            include_str!("../../proto/admin.proto"),
+            include_str!("../../proto/broadcast.proto"),
+        ];
+        let mut methods = Vec::new();
+        for proto in protos {
+            let (mut package, mut service) = ("", "");
+            for line in proto.lines().map(str::trim) {
+                if let Some(name) = line.strip_prefix("package ") {
+                    package = name.trim_end_matches(';');
+                } else if let Some(name) = line.strip_prefix("service ") {
+                    service = name.trim_end_matches('{').trim();
+                } else if let Some(rpc) = line.strip_prefix("rpc ") {
+                    if rpc.contains("(stream ") {
+                        let name = rpc.split_whitespace().next().unwrap();
+                        methods.push(format!("/{}.{}/{}", package, service, name));
+                    }
+                }
+            }
+        }
+        methods
+    }
+
+    // Every streaming rpc is known as one, so its warning is told apart
+    #[test]
+    fn test_streaming_methods_match_protos() {
+        let mut declared = declared_streaming_methods();
+        declared.sort();
+        let mut listed = STREAMING_METHODS.map(str::to_string).to_vec();
+        listed.sort();
+        assert_eq!(declared, listed);
+    }
+}
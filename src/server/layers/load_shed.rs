@@ -0,0 +1,294 @@
+//! Queue Depth Limit and Load Shedding
+//! This file bounds how many requests the server holds at once and
+//! counts them in the server metrics:
+//! 1. InFlightLayer keeps the in-flight request gauges (server-wide and
+//!    per service) up to date and raises the OverloadAlarm
+//! 2. QueueDepthLayer stacks tower's concurrency limit under a load shedder,
+//!    so a request that finds every slot taken fails immediately instead
+//!    of waiting for one
+//! 3. ShedLayer turns the shedder's `Overloaded` error into a
+//!    ResourceExhausted response the client can retry on
+//!
+//! 4. OverloadAlarm runs the overload callback when the in-flight gauge
+//!    rises to its high-water mark, at most once per OVERLOAD_DEBOUNCE
+//!
+//! A slot is held from the moment a request is accepted until its response
+//! head is sent, so requests waiting for a worker thread count as well as
+//! those being handled. The slots are one semaphore shared by every
+//! listener, so the depth is server-wide.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::layer::util::{Identity, Stack};
+use tokio::sync::Semaphore;
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower::load_shed::error::Overloaded;
+use tower::load_shed::LoadShedLayer;
+use tower::{BoxError, Layer, Service, ServiceExt};
+use tracing::{debug, warn};
+use crate::server::metrics::ServerMetrics;
+
+/// Shedding stack applied when `max_queue_depth` is set
+pub(crate) type QueueDepthLayer = Stack<GlobalConcurrencyLimitLayer, Stack<LoadShedLayer, Stack<ShedLayer, Identity>>>;
+
+/// Build the stack admitting at most `depth` requests at once
+///
+/// # Arguments
+/// * `depth` - The maximum number of requests queued or in flight.
+/// * `slots` - Semaphore of `depth` permits, shared by the stacks of every listener.
+/// * `metrics` - Metrics handle counting shed requests.
+///
+/// # Returns
+/// * `QueueDepthLayer` - Outermost ShedLayer, then LoadShed, then the concurrency limit.
+pub(crate) fn queue_depth_layer(depth: usize, slots: Arc<Semaphore>, metrics: ServerMetrics) -> QueueDepthLayer {
+    tower::ServiceBuilder::new()
+        .layer(ShedLayer { depth, metrics })
+        .load_shed()
+        .layer(GlobalConcurrencyLimitLayer::with_semaphore(slots))
+        .into_inner()
+}
+
+/// Layer answering shed requests with ResourceExhausted
+#[derive(Clone)]
+pub(crate) struct ShedLayer {
+    depth: usize,  // Only used in the status message
+    metrics: ServerMetrics,
+}
+
+impl<S> Layer<S> for ShedLayer {
+    type Service = Shed<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Shed {
+            inner,
+            depth: self.depth,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Service produced by [`ShedLayer`]
+#[derive(Clone)]
+pub(crate) struct Shed<S> {
+    inner: S,
+    depth: usize,
+    metrics: ServerMetrics,
+}
+
+impl<S, B> Service<http::Request<B>> for Shed<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    // Always ready: the limit is only consulted once a request arrives
+    // A concurrency limit takes its slot in poll_ready, and hyper polls each
+    // connection's service ahead of time, so idle connections would hold slots
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let depth = self.depth;
+        let metrics = self.metrics.clone();
+
+        // A fresh clone per request holds no slot until it is made ready
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            // The shedder below is ready at once, marking the request overloaded
+            // when no slot is free
+            let result = match inner.ready().await {
+                Ok(inner) => inner.call(req).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Err(e) if e.is::<Overloaded>() => {
+                    warn!("Shedding request: queue depth of {} reached", depth);
+                    metrics.request_shed();
+                    let status = Status::resource_exhausted(format!(
+                        "server is saturated (queue depth {}); retry later",
+                        depth
+                    ));
+                    Ok(status.to_http())
+                }
+                other => other,
+            }
+        })
+    }
+}
+
+// Shortest time between two overload callbacks
+const OVERLOAD_DEBOUNCE: Duration = Duration::from_secs(1);
+
+// Callback run with the in-flight count when it reaches the high-water mark
+type OverloadCallback = Arc<dyn Fn(u64) + Send + Sync + 'static>;
+
+/// Runs the overload callback when the in-flight gauge reaches a mark
+///
+/// Fires on the way up only, so a burst that stays above the mark is
+/// reported once; a burst that dips and returns within OVERLOAD_DEBOUNCE
+/// of the last callback is not reported again. The callback runs on the
+/// blocking pool and never delays the request that tripped it.
+#[derive(Clone)]
+pub(crate) struct OverloadAlarm {
+    high_water: u64,
+    callback: OverloadCallback,
+    last_fired: Arc<Mutex<Option<Instant>>>,
+}
+
+impl OverloadAlarm {
+    pub(crate) fn new(high_water: usize, callback: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        Self { high_water: high_water as u64, callback: Arc::new(callback), last_fired: Arc::default() }
+    }
+
+    // The mark, for the configuration snapshot
+    pub(crate) fn high_water(&self) -> usize {
+        self.high_water as usize
+    }
+
+    // The gauge rose by one to `in_flight`
+    fn observe(&self, in_flight: u64) {
+        if in_flight != self.high_water {
+            return;
+        }
+        {
+            let mut last_fired = self.last_fired.lock().unwrap();
+            if last_fired.is_some_and(|at| at.elapsed() < OVERLOAD_DEBOUNCE) {
+                debug!("{} requests in flight again; overload callback debounced", in_flight);
+                return;
+            }
+            *last_fired = Some(Instant::now());
+        }
+        warn!("{} requests in flight, reaching the overload mark", in_flight);
+        let callback = self.callback.clone();
+        tokio::task::spawn_blocking(move || callback(in_flight));
+    }
+}
+
+/// Layer counting requests in flight in the server metrics
+#[derive(Clone)]
+pub(crate) struct InFlightLayer {
+    metrics: ServerMetrics,
+    services: Arc<[&'static str]>,
+    alarm: Option<OverloadAlarm>,
+}
+
+impl InFlightLayer {
+    /// Create a layer updating the gauges of `metrics`
+    ///
+    /// Requests are counted per service for the names in `services`;
+    /// `alarm`, if any, is told every time the gauge rises.
+    pub(crate) fn new(metrics: ServerMetrics, services: &[&'static str], alarm: Option<OverloadAlarm>) -> Self {
+        metrics.services_registered(services);
+        Self { metrics, services: services.into(), alarm }
+    }
+}
+
+impl<S> Layer<S> for InFlightLayer {
+    type Service = InFlight<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InFlight {
+            inner,
+            metrics: self.metrics.clone(),
+            services: self.services.clone(),
+            alarm: self.alarm.clone(),
+        }
+    }
+}
+
+/// Service produced by [`InFlightLayer`]
+#[derive(Clone)]
+pub(crate) struct InFlight<S> {
+    inner: S,
+    metrics: ServerMetrics,
+    services: Arc<[&'static str]>,
+    alarm: Option<OverloadAlarm>,
+}
+
+// Guard that lowers the gauges again when dropped
+// Dropping also happens when the request is cancelled
+struct InFlightGuard(ServerMetrics, Option<&'static str>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.request_finished(self.1);
+    }
+}
+
+// The registered service a request path ("/echo.EchoService/Echo") names
+fn service_of(services: &[&'static str], path: &str) -> Option<&'static str> {
+    let (name, _method) = path.strip_prefix('/')?.split_once('/')?;
+    services.iter().find(|&&service| service == name).copied()
+}
+
+impl<S, B> Service<http::Request<B>> for InFlight<S>
+where
+    S: Service<http::Request<B>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let service = service_of(&self.services, req.uri().path());
+        let in_flight = self.metrics.request_started(service);
+        let guard = InFlightGuard(self.metrics.clone(), service);
+        if let Some(alarm) = &self.alarm {
+            alarm.observe(in_flight);
+        }
+
+        // Swap in a fresh clone so the ready service is the one we call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let _guard = guard;
+            inner.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn test_service_of_matches_registered_names_only() {
+        let services = ["echo.EchoService", "calculator.CalculatorService"];
+        assert_eq!(service_of(&services, "/echo.EchoService/Echo"), Some("echo.EchoService"));
+        assert_eq!(service_of(&services, "/echo.EchoService"), None);
+        assert_eq!(service_of(&services, "/other.Service/Call"), None);
+    }
+
+    // Only the rise to the mark fires, and not again within the debounce
+    #[tokio::test]
+    async fn test_alarm_fires_on_crossing_and_debounces() {
+        let fired = Arc::new(AtomicU64::new(0));
+        let counter = fired.clone();
+        let alarm = OverloadAlarm::new(3, move |in_flight| {
+            assert_eq!(in_flight, 3);
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        for in_flight in [1, 2, 3, 4, 5, 2, 3, 4] {
+            alarm.observe(in_flight);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,120 @@
+//! Metadata Validation
+//! This layer runs first and gives every later layer, interceptor and
+//! handler only metadata that is well-formed:
+//! 1. Keys outside the gRPC metadata alphabet (`0-9 a-z _ - .`), and
+//!    values of text keys that are not printable ASCII, fail with
+//!    InvalidArgument
+//!
+//! HTTP allows more in a header than gRPC does in metadata (e.g. `!` in a
+//! name, non-ASCII bytes in a value). Without this check such input reached
+//! the authenticator, which could only report it as a missing credential.
+//! Rejections are regular gRPC statuses, never transport errors.
+//!
+//! The size of the metadata (max_metadata_size) is not checked here: HTTP/2
+//! refuses oversized header blocks while decoding them, before they are
+//! buffered (see serving.rs).
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::warn;
+
+/// Metadata a request may carry when max_metadata_size is not set
+pub(crate) const DEFAULT_MAX_METADATA_SIZE: usize = 8 * 1024;
+
+// Suffix of keys whose values are binary (base64 on the wire)
+const BINARY_SUFFIX: &str = "-bin";
+
+// Characters gRPC allows in a metadata key
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && key.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'z' | b'_' | b'-' | b'.'))
+}
+
+// Check keys and text values of the request's metadata
+fn validate<B>(req: &http::Request<B>) -> Result<(), Status> {
+    for (name, value) in req.headers() {
+        let key = name.as_str();
+        if !valid_key(key) {
+            return Err(Status::invalid_argument(format!(
+                "metadata key {:?} contains characters not allowed in gRPC metadata",
+                key
+            )));
+        }
+        if !key.ends_with(BINARY_SUFFIX) && !value.as_bytes().iter().all(|b| (0x20..=0x7e).contains(b)) {
+            return Err(Status::invalid_argument(format!("metadata value of {:?} is not printable ASCII", key)));
+        }
+    }
+    Ok(())
+}
+
+/// Layer rejecting requests with malformed metadata
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct MetadataLayer;
+
+impl<S> Layer<S> for MetadataLayer {
+    type Service = MetadataCheck<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetadataCheck { inner }
+    }
+}
+
+/// Service produced by [`MetadataLayer`]
+#[derive(Clone)]
+pub(crate) struct MetadataCheck<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for MetadataCheck<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        if let Err(status) = validate(&req) {
+            warn!("Rejecting {}: {}", req.uri().path(), status.message());
+            let response = status.to_http();
+            return Box::pin(async move { Ok(response) });
+        }
+        Box::pin(self.inner.call(req))
+    }
+}
+
+// Unit tests for the validation rules
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    fn request(headers: &[(&str, &[u8])]) -> http::Request<()> {
+        let mut builder = http::Request::builder().uri("http://localhost/echo.EchoService/Echo");
+        for (name, value) in headers {
+            builder = builder.header(*name, http::HeaderValue::from_bytes(value).unwrap());
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let ok = request(&[("content-type", b"application/grpc"), ("x-request_id.v2", b"abc 123")]);
+        assert!(validate(&ok).is_ok());
+
+        let err = validate(&request(&[("bad!key", b"v")])).unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let err = validate(&request(&[("authorization", b"Bearer \xff")])).unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(validate(&request(&[("trace-bin", b"\xff\x80")])).is_ok(), "binary values are exempt");
+    }
+}
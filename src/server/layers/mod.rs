@@ -0,0 +1,39 @@
+//! Tower layers wrapped around every gRPC service of the server.
+//! Layers see raw HTTP requests before they reach a service, which makes them
+//! the right place for cross-cutting policies such as connection limits.
+//!
+//! Every request passes the built-in checks in this fixed order, so each
+//! one only sees requests the previous ones accepted:
+//! 1. Idle tracking (counts the request as connection activity)
+//! 2. Metadata validation: well-formed keys and values
+//! 3. Queue depth, then in-flight accounting, then the per-peer limit
+//! 4. Latency recording, per-connection request numbering, the client
+//!    certificate identity (mutual TLS only), fault injection
+//! 5. The service interceptor: serving state, request logging, trace
+//!    context, then authentication (the Authenticator)
+//! 6. The handler
+//!
+//! The metadata size (max_header_list_size, max_metadata_size) is not a
+//! layer: HTTP/2 refuses oversized header blocks while decoding them (see
+//! serving.rs).
+
+// Declare submodules containing our layer implementations
+mod peer_limit;
+mod latency;
+mod load_shed;
+mod connection_count;
+mod fault_injection;
+mod metadata;
+mod peer_identity;
+
+// Re-export the layers so the server builder can stack them
+// The pub(crate) means these are only visible within our crate
+pub(crate) use peer_limit::PeerLimitLayer;
+pub(crate) use latency::LatencyLayer;
+pub(crate) use load_shed::{queue_depth_layer, InFlightLayer, OverloadAlarm};
+pub(crate) use connection_count::{ConnectionCountLayer, ConnectionCounts, ConnectionRequestCount};
+pub(crate) use fault_injection::FaultInjectionLayer;
+pub(crate) use metadata::{MetadataLayer, DEFAULT_MAX_METADATA_SIZE};
+pub(crate) use peer_identity::PeerIdentityLayer;
+// FaultConfig is part of the public builder API
+pub use fault_injection::FaultConfig;
@@ -0,0 +1,61 @@
+//! Peer Certificate Identity
+//! This layer reads the certificate a client presented under mutual TLS
+//! once per request, so handlers and the logs see the same caller:
+//! 1. The PeerIdentity is stored in the request extensions, where
+//!    `server::peer_identity` finds it
+//! 2. It is recorded on the per-RPC span (`peer` field), so every log line
+//!    of the call names the client
+//!
+//! Requests on plaintext connections, or without a client certificate,
+//! pass through untouched.
+
+use std::task::{Context, Poll};
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
+use tower::{Layer, Service};
+use tracing::Span;
+use crate::server::peer_identity::PeerIdentity;
+
+/// Layer resolving the client certificate of each request
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PeerIdentityLayer;
+
+impl<S> Layer<S> for PeerIdentityLayer {
+    type Service = PeerIdentityService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PeerIdentityService { inner }
+    }
+}
+
+/// Service produced by [`PeerIdentityLayer`]
+#[derive(Debug, Clone)]
+pub(crate) struct PeerIdentityService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for PeerIdentityService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        // The leaf certificate comes first
+        let identity = req.extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()
+            .and_then(|info| info.peer_certs())
+            .and_then(|certificates| certificates.first().map(|leaf| PeerIdentity::from_der(leaf.get_ref())));
+        if let Some(identity) = identity {
+            // Called within the span of rpc_span
+            Span::current().record("peer", tracing::field::display(&identity));
+            req.extensions_mut().insert(identity);
+        }
+        self.inner.call(req)
+    }
+}
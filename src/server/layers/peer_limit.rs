@@ -0,0 +1,180 @@
+//! Per-Peer Concurrency Limit
+//! This layer caps the number of in-flight RPCs a single remote IP may have.
+//! It demonstrates:
+//! 1. Writing a tower Layer/Service pair by hand
+//! 2. RAII guards for bookkeeping that must survive cancellation
+//! 3. Rejecting requests with a gRPC status before they reach a service
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tonic::body::BoxBody;
+use tonic::transport::server::TcpConnectInfo;
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::warn;
+
+// Shared map of in-flight request counts keyed by remote IP
+// Entries are removed when their count drops back to zero
+type PeerCounts = Arc<Mutex<HashMap<IpAddr, usize>>>;
+
+/// Layer that limits concurrent requests per remote IP address
+#[derive(Clone)]
+pub(crate) struct PeerLimitLayer {
+    max_per_peer: usize,
+    counts: PeerCounts,
+}
+
+impl PeerLimitLayer {
+    /// Create a layer allowing at most `max_per_peer` in-flight requests per IP
+    /// 
+    /// # Arguments
+    /// * `max_per_peer` - The maximum number of concurrent requests from one IP.
+    /// 
+    /// # Returns
+    /// * `Self` - A new layer with an empty peer map.
+    pub(crate) fn new(max_per_peer: usize) -> Self {
+        Self {
+            max_per_peer,
+            counts: Arc::default(),
+        }
+    }
+}
+
+impl<S> Layer<S> for PeerLimitLayer {
+    type Service = PeerLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        PeerLimit {
+            inner,
+            max_per_peer: self.max_per_peer,
+            counts: self.counts.clone(),
+        }
+    }
+}
+
+/// Service produced by [`PeerLimitLayer`]
+#[derive(Clone)]
+pub(crate) struct PeerLimit<S> {
+    inner: S,
+    max_per_peer: usize,
+    counts: PeerCounts,
+}
+
+// Guard that releases one in-flight slot when dropped
+// Dropping happens when the response future completes *or* is cancelled,
+// so a client hanging up mid-request can never leak a slot
+struct PeerGuard {
+    ip: IpAddr,
+    counts: PeerCounts,
+}
+
+impl PeerGuard {
+    // Try to take a slot for `ip`, returning None when the peer is at its limit
+    fn acquire(counts: &PeerCounts, ip: IpAddr, max_per_peer: usize) -> Option<Self> {
+        let mut map = counts.lock().unwrap();
+        // Check before inserting so a refused peer leaves no entry behind
+        if map.get(&ip).copied().unwrap_or(0) >= max_per_peer {
+            return None;
+        }
+        *map.entry(ip).or_insert(0) += 1;
+        Some(Self { ip, counts: counts.clone() })
+    }
+}
+
+impl Drop for PeerGuard {
+    fn drop(&mut self) {
+        let mut map = self.counts.lock().unwrap();
+        if let Some(count) = map.get_mut(&self.ip) {
+            *count -= 1;
+            // Clean up so the map only holds peers with requests in flight
+            if *count == 0 {
+                map.remove(&self.ip);
+            }
+        }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for PeerLimit<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        // Requests without connection info (e.g. in-memory transports) are not limited
+        let ip = req
+            .extensions()
+            .get::<TcpConnectInfo>()
+            .and_then(|info| info.remote_addr())
+            .map(|addr| addr.ip());
+
+        let guard = match ip {
+            Some(ip) => match PeerGuard::acquire(&self.counts, ip, self.max_per_peer) {
+                Some(guard) => Some(guard),
+                None => {
+                    warn!("Rejecting request from {}: per-peer limit of {} reached", ip, self.max_per_peer);
+                    let status = Status::resource_exhausted(format!(
+                        "too many concurrent requests from {} (limit {})",
+                        ip, self.max_per_peer
+                    ));
+                    return Box::pin(async move { Ok(status.to_http()) });
+                }
+            },
+            None => None,
+        };
+
+        // Swap in a fresh clone so the ready service is the one we call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            // Keep the guard alive until the response future finishes
+            let _guard = guard;
+            inner.call(req).await
+        })
+    }
+}
+
+// Unit tests for the slot bookkeeping
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_guard_limits_and_cleans_up() {
+        let counts = PeerCounts::default();
+        let ip: IpAddr = [127, 0, 0, 1].into();
+
+        // Two slots available, the third is refused
+        let first = PeerGuard::acquire(&counts, ip, 2).unwrap();
+        let second = PeerGuard::acquire(&counts, ip, 2).unwrap();
+        assert!(PeerGuard::acquire(&counts, ip, 2).is_none());
+
+        // Other peers have their own budget
+        let other = PeerGuard::acquire(&counts, [127, 0, 0, 2].into(), 2);
+        assert!(other.is_some());
+        drop(other);
+
+        // Releasing a slot makes room again; releasing all removes the entry
+        drop(first);
+        let third = PeerGuard::acquire(&counts, ip, 2).unwrap();
+        drop(second);
+        drop(third);
+        assert!(counts.lock().unwrap().is_empty());
+
+        // A refused peer without requests in flight is not remembered
+        assert!(PeerGuard::acquire(&counts, ip, 0).is_none());
+        assert!(counts.lock().unwrap().is_empty());
+    }
+}
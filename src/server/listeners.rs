@@ -0,0 +1,76 @@
+//! Listening Sockets
+//! Binds the sockets the server accepts connections on:
+//! 1. One listener by default, with the TCP settings of tonic's
+//!    Server::builder() (TCP_NODELAY on, no TCP keepalive)
+//! 2. Several listeners on the same address with SO_REUSEPORT (Unix only),
+//!    so the kernel spreads incoming connections over one accept loop each
+//!
+//! All listeners share one logical address; with port 0 the first socket
+//! picks the port and the others join it.
+
+use std::net::SocketAddr;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Code, Status};
+use tracing::error;
+
+// Small RPC frames are sent right away, as tonic's server does
+const TCP_NODELAY: bool = true;
+
+// Whether this platform can share a port between listeners
+pub(crate) const REUSE_PORT_SUPPORTED: bool = cfg!(unix);
+
+// Bind `count` listeners on `addr`; more than one requires REUSE_PORT_SUPPORTED
+pub(crate) fn bind(addr: SocketAddr, count: usize) -> Result<Vec<TcpIncoming>, Status> {
+    if count <= 1 {
+        let listener = TcpIncoming::new(addr, TCP_NODELAY, None).map_err(|e| bind_error(addr, e))?;
+        return Ok(vec![listener]);
+    }
+    bind_reuse_port(addr, count)
+}
+
+#[cfg(unix)]
+fn bind_reuse_port(addr: SocketAddr, count: usize) -> Result<Vec<TcpIncoming>, Status> {
+    let first = reuse_port_socket(addr).map_err(|e| bind_error(addr, e))?;
+    // Resolve port 0 once so every listener shares the same port
+    let shared = first.local_addr().map_err(|e| bind_error(addr, e))?;
+
+    let mut listeners = vec![first];
+    for _ in 1..count {
+        listeners.push(reuse_port_socket(shared).map_err(|e| bind_error(shared, e))?);
+    }
+    listeners
+        .into_iter()
+        .map(|listener| {
+            let listener = tokio::net::TcpListener::from_std(listener).map_err(|e| bind_error(shared, e))?;
+            TcpIncoming::from_listener(listener, TCP_NODELAY, None).map_err(|e| bind_error(shared, e))
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn bind_reuse_port(_addr: SocketAddr, count: usize) -> Result<Vec<TcpIncoming>, Status> {
+    // Rejected by the builder already; kept as a safety net
+    Err(Status::new(Code::InvalidArgument, format!(
+        "{} listeners requested, but SO_REUSEPORT is not supported on this platform", count
+    )))
+}
+
+// Non-blocking listener that other sockets may bind to the same address
+#[cfg(unix)]
+fn reuse_port_socket(addr: SocketAddr) -> std::io::Result<std::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    // Same backlog as tokio's TcpListener::bind
+    socket.listen(1024)?;
+    Ok(socket.into())
+}
+
+fn bind_error(addr: SocketAddr, e: impl std::fmt::Display) -> Status {
+    error!("Failed to bind {}: {}", addr, e);
+    Status::new(Code::Internal, format!("server error: failed to bind {}: {}", addr, e))
+}
@@ -0,0 +1,29 @@
+//! Live Configuration
+//! Settings that may change while the server is running:
+//! 1. Initial values come from the server builder
+//! 2. The admin SetConfig RPC replaces them without a restart
+//! 3. Handlers load the current snapshot once per request, so a request
+//!    never sees a mix of old and new values
+//!
+//! The snapshot sits behind an `ArcSwap`: reads are lock-free and an
+//! update swaps in a whole new snapshot atomically.
+
+use std::sync::Arc;
+use arc_swap::ArcSwap;
+
+/// Snapshot of the runtime-adjustable settings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LiveConfig {
+    pub(crate) allow_empty_echo: bool,  // Echo empty messages instead of rejecting them
+    pub(crate) echo_max_chars: Option<usize>,  // Longest echo message, in characters
+}
+
+// Settings shared by the services that read them and the admin service
+pub(crate) type SharedConfig = Arc<ArcSwap<LiveConfig>>;
+
+impl LiveConfig {
+    // Wrap the initial settings for sharing
+    pub(crate) fn shared(self) -> SharedConfig {
+        Arc::new(ArcSwap::from_pointee(self))
+    }
+}
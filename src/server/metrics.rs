@@ -0,0 +1,262 @@
+//! Server Metrics
+//! Counters the server maintains about itself, readable at any time
+//! through a cheap, cloneable handle:
+//! 1. ServerMetrics: shared handle updated by the server
+//! 2. MetricsSnapshot: plain copy of the values at one point in time
+//! 3. Prometheus text rendering of a snapshot (MetricsSnapshot::to_prometheus)
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Handle to the live metrics of one server
+///
+/// Obtained from `GrpcServer::metrics()` before serving; clones share
+/// the same counters, so the handle keeps working while the server runs.
+#[derive(Debug, Clone, Default)]
+pub struct ServerMetrics {
+    counters: Arc<Counters>,
+}
+
+// Backing storage shared by all handles
+#[derive(Debug, Default)]
+struct Counters {
+    active_connections: AtomicU64,
+    total_connections: AtomicU64,
+    refused_connections: AtomicU64,
+    in_flight_requests: AtomicU64,
+    service_in_flight: Mutex<BTreeMap<&'static str, u64>>,
+    shed_requests: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    echo_cache_hits: AtomicU64,
+    echo_cache_misses: AtomicU64,
+    slow_requests: AtomicU64,
+    slow_streams: AtomicU64,
+    listener_connections: Mutex<Vec<u64>>,
+}
+
+/// Point-in-time copy of the server metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Client connections currently open
+    pub active_connections: u64,
+    /// Client connections accepted since the server started
+    pub total_connections: u64,
+    /// Connections closed right away because `max_connections` was reached
+    pub refused_connections: u64,
+    /// Requests accepted and not yet answered (queued or being handled)
+    pub in_flight_requests: u64,
+    /// The in-flight requests of each service, by fully qualified name
+    /// Every service the server registers is listed; empty before serving
+    pub in_flight_by_service: BTreeMap<String, u64>,
+    /// Requests rejected because the queue depth limit was reached
+    pub shed_requests: u64,
+    /// Calculations answered from the calculator cache (see `calculator_cache`)
+    pub cache_hits: u64,
+    /// Cacheable calculations that had to be computed
+    pub cache_misses: u64,
+    /// Unary echoes repeating a recent message (see `echo_cache`)
+    pub echo_cache_hits: u64,
+    /// Unary echoes of a message not seen recently
+    pub echo_cache_misses: u64,
+    /// Non-streaming RPCs slower than `slow_request_threshold`
+    pub slow_requests: u64,
+    /// Streaming RPCs that ran longer than `slow_request_threshold`
+    pub slow_streams: u64,
+    /// Connections accepted by each listener, in bind order
+    /// One entry unless `reuse_port_listeners` is set; empty before serving
+    pub listener_connections: Vec<u64>,
+}
+
+impl ServerMetrics {
+    /// Read the current values
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            active_connections: self.counters.active_connections.load(Ordering::Relaxed),
+            total_connections: self.counters.total_connections.load(Ordering::Relaxed),
+            refused_connections: self.counters.refused_connections.load(Ordering::Relaxed),
+            in_flight_requests: self.counters.in_flight_requests.load(Ordering::Relaxed),
+            in_flight_by_service: self.counters.service_in_flight
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(&service, &count)| (service.to_string(), count))
+                .collect(),
+            shed_requests: self.counters.shed_requests.load(Ordering::Relaxed),
+            cache_hits: self.counters.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.counters.cache_misses.load(Ordering::Relaxed),
+            echo_cache_hits: self.counters.echo_cache_hits.load(Ordering::Relaxed),
+            echo_cache_misses: self.counters.echo_cache_misses.load(Ordering::Relaxed),
+            slow_requests: self.counters.slow_requests.load(Ordering::Relaxed),
+            slow_streams: self.counters.slow_streams.load(Ordering::Relaxed),
+            listener_connections: self.counters.listener_connections.lock().unwrap().clone(),
+        }
+    }
+
+    // A connection was accepted
+    pub(crate) fn connection_opened(&self) {
+        self.counters.active_connections.fetch_add(1, Ordering::Relaxed);
+        self.counters.total_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // A connection was closed on accept by the connection limit
+    pub(crate) fn connection_refused(&self) {
+        self.counters.refused_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // The server started accepting on `count` listeners
+    pub(crate) fn listeners_bound(&self, count: usize) {
+        *self.counters.listener_connections.lock().unwrap() = vec![0; count];
+    }
+
+    // Listener `index` accepted a connection
+    pub(crate) fn connection_accepted_on(&self, index: usize) {
+        if let Some(count) = self.counters.listener_connections.lock().unwrap().get_mut(index) {
+            *count += 1;
+        }
+    }
+
+    // A connection previously reported by connection_opened was closed
+    pub(crate) fn connection_closed(&self) {
+        self.counters.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // The server started serving `services`, each with no request in flight
+    pub(crate) fn services_registered(&self, services: &[&'static str]) {
+        *self.counters.service_in_flight.lock().unwrap() = services.iter().map(|&service| (service, 0)).collect();
+    }
+
+    // A request to `service` (None if it names no registered service) was accepted
+    // Returns the requests in flight across all services, this one included
+    pub(crate) fn request_started(&self, service: Option<&'static str>) -> u64 {
+        if let Some(service) = service {
+            if let Some(count) = self.counters.service_in_flight.lock().unwrap().get_mut(service) {
+                *count += 1;
+            }
+        }
+        self.counters.in_flight_requests.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    // A request previously reported by request_started was answered or cancelled
+    pub(crate) fn request_finished(&self, service: Option<&'static str>) {
+        if let Some(service) = service {
+            if let Some(count) = self.counters.service_in_flight.lock().unwrap().get_mut(service) {
+                *count -= 1;
+            }
+        }
+        self.counters.in_flight_requests.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // A request was rejected by load shedding
+    pub(crate) fn request_shed(&self) {
+        self.counters.shed_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // A calculation was answered from the calculator cache
+    pub(crate) fn cache_hit(&self) {
+        self.counters.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // A cacheable calculation was not in the calculator cache
+    pub(crate) fn cache_miss(&self) {
+        self.counters.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // An echo repeated a recent message
+    pub(crate) fn echo_cache_hit(&self) {
+        self.counters.echo_cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // An echo carried a message not seen recently
+    pub(crate) fn echo_cache_miss(&self) {
+        self.counters.echo_cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // An RPC, streaming or not, exceeded the slow request threshold
+    pub(crate) fn slow_request(&self, streaming: bool) {
+        let counter = if streaming { &self.counters.slow_streams } else { &self.counters.slow_requests };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl MetricsSnapshot {
+    /// Render the values in the Prometheus text exposition format
+    ///
+    /// Metric names start with `grpc_server_`; the per-service gauge is
+    /// labelled with `service`, the caches with `cache`, slow RPCs with
+    /// `kind` (unary or streaming), the listeners with `listener`. Serve the text from any HTTP endpoint to scrape it.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, u64)]| {
+            let _ = writeln!(out, "# HELP grpc_server_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE grpc_server_{} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "grpc_server_{}{} {}", name, labels, value);
+            }
+        };
+        let plain = |value: u64| [(String::new(), value)];
+        let labelled = |label: &str, name: &str, value: u64| (format!("{{{}=\"{}\"}}", label, name), value);
+
+        metric("active_connections", "gauge", "Client connections currently open.", &plain(self.active_connections));
+        metric("connections_total", "counter", "Client connections accepted.", &plain(self.total_connections));
+        metric(
+            "refused_connections_total", "counter", "Connections closed on accept by max_connections.",
+            &plain(self.refused_connections),
+        );
+        metric("in_flight_requests", "gauge", "Requests accepted and not yet answered.", &plain(self.in_flight_requests));
+        let by_service: Vec<_> = self.in_flight_by_service
+            .iter()
+            .map(|(service, &count)| labelled("service", service, count))
+            .collect();
+        metric("service_in_flight_requests", "gauge", "Requests in flight per service.", &by_service);
+        metric("shed_requests_total", "counter", "Requests shed at the queue depth limit.", &plain(self.shed_requests));
+        metric("cache_hits_total", "counter", "Responses answered from a cache.", &[
+            labelled("cache", "calculator", self.cache_hits),
+            labelled("cache", "echo", self.echo_cache_hits),
+        ]);
+        metric("cache_misses_total", "counter", "Cacheable responses that had to be computed.", &[
+            labelled("cache", "calculator", self.cache_misses),
+            labelled("cache", "echo", self.echo_cache_misses),
+        ]);
+        metric("slow_requests_total", "counter", "RPCs slower than slow_request_threshold.", &[
+            labelled("kind", "unary", self.slow_requests),
+            labelled("kind", "streaming", self.slow_streams),
+        ]);
+        let listeners: Vec<_> = self.listener_connections
+            .iter()
+            .enumerate()
+            .map(|(index, &count)| labelled("listener", &index.to_string(), count))
+            .collect();
+        metric("listener_connections_total", "counter", "Connections accepted per listener.", &listeners);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Per-service gauges start at zero for every registered service
+    // and appear as labelled Prometheus samples
+    #[test]
+    fn test_service_gauges_in_prometheus_output() {
+        let metrics = ServerMetrics::default();
+        metrics.services_registered(&["echo.EchoService", "calculator.CalculatorService"]);
+        assert_eq!(metrics.request_started(Some("echo.EchoService")), 1);
+        assert_eq!(metrics.request_started(None), 2);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.in_flight_by_service["echo.EchoService"], 1);
+        assert_eq!(snapshot.in_flight_by_service["calculator.CalculatorService"], 0);
+        let text = snapshot.to_prometheus();
+        assert!(text.contains("grpc_server_in_flight_requests 2\n"), "{}", text);
+        assert!(text.contains("grpc_server_service_in_flight_requests{service=\"echo.EchoService\"} 1\n"), "{}", text);
+        assert!(text.contains("# TYPE grpc_server_shed_requests_total counter\n"), "{}", text);
+
+        metrics.request_finished(Some("echo.EchoService"));
+        metrics.request_finished(None);
+        assert!(metrics.snapshot().in_flight_by_service.values().all(|&count| count == 0));
+    }
+}
@@ -4,16 +4,76 @@
 //! 
 //! Key components:
 //! - server: Contains the main GrpcServer implementation with Builder pattern
-//! - services: Contains individual service implementations (Calculator, Echo)
+//! - services: Contains individual service implementations (Calculator, Echo, Admin, Broadcast)
+//! - layers: Contains tower layers applied to every service (limits, policies)
+//! - shutdown: Shutdown handle that can be shared by several servers, and
+//!   why serve() stopped (ShutdownReason)
+//! - connections: Connection tracking (lifecycle hooks, connection gauge)
+//! - metrics: Counters the server keeps about itself
+//! - events: Optional lifecycle event stream (ServerEvent)
+//! - latency: Recent request latencies, reported by the admin service
+//! - auth: Pluggable request authentication (Authenticator, Identity)
+//! - cache: Optional LRU caches of calculator results and echoed messages
+//! - history: Stores of the calculator history (HistoryStore, MemoryHistory, FileHistory)
+//! - text: Echo message validation (control characters, NFC normalization)
+//! - live_config: Settings the admin service can change at runtime
+//! - idle: Optional idle connection timeout
+//! - serving: Serves each accepted connection with hyper (TLS, graceful shutdown)
+//! - tls: The rustls configuration of the server, down to the minimum version (TlsVersion)
+//! - peer_identity: The client certificate of a request under mutual TLS (PeerIdentity)
+//! - listeners: Binds one listener, or several sharing a port (SO_REUSEPORT)
+//! - pipe: Accepts connections on a named pipe instead (Windows)
+//! - service_state: Per-service serving flags the admin service can flip
+//! - snapshot: Serializable summary of the configuration (ServerConfigSnapshot)
+//! - streams: Bounded outbound channels of the streaming RPCs (backpressure)
+//! - deadline: The client's deadline of a call (grpc-timeout), public for custom services
+//! - audit: Append-only record of the mutating admin RPCs
 //!
 //! The pub use statement provides a clean public API by re-exporting
 //! the GrpcServer type at the module level, following the facade pattern.
 
 // Internal modules that make up our server implementation
+#[allow(clippy::module_inception)]
 mod server;
 mod services;
+mod layers;
+mod banner;
+mod shutdown;
+mod connections;
+mod metrics;
+mod events;
+mod latency;
+mod auth;
+mod cache;
+mod history;
+mod text;
+mod live_config;
+mod idle;
+mod serving;
+mod tls;
+mod peer_identity;
+mod listeners;
+#[cfg(windows)]
+mod pipe;
+mod service_state;
+mod snapshot;
+mod streams;
+mod audit;
+pub mod deadline;
 
 // Re-export the main server type for cleaner external usage
 // This allows users to just use `use crate::server::GrpcServer`
 // instead of `use crate::server::server::GrpcServer`
-pub use server::GrpcServer;
\ No newline at end of file
+pub use server::{GrpcServer, GrpcServerBuilder};
+pub use shutdown::{Shutdown, ShutdownReason};
+pub use metrics::{ServerMetrics, MetricsSnapshot};
+pub use events::ServerEvent;
+pub use snapshot::ServerConfigSnapshot;
+pub use layers::FaultConfig;
+pub use streams::StreamOverflowPolicy;
+pub use tls::TlsVersion;
+pub use peer_identity::{peer_identity, PeerIdentity};
+pub use auth::{Authenticator, Identity, NoAuth, StaticTokenAuth};
+pub use history::{FileHistory, HistoryEntry, HistoryStore, MemoryHistory};
+// Service handlers, callable without a transport (e.g. by fuzz tests)
+pub use services::{CalculatorServer, EchoServer, SubnormalPolicy};
\ No newline at end of file
@@ -0,0 +1,255 @@
+//! Peer Certificate Identity
+//! Who is calling, according to the certificate a client presented under
+//! mutual TLS (GrpcServerBuilder::tls_client_ca):
+//! 1. PeerIdentity: the certificate's common name, subject alternative
+//!    names and SHA-256 fingerprint
+//! 2. `peer_identity`: the identity of a request's client, for handlers;
+//!    None on plaintext connections or when no certificate was presented
+//!
+//! The server resolves it once per request (layers/peer_identity.rs) and
+//! stores it in the request extensions; the helper also works on requests
+//! of other tonic servers by reading the peer certificates directly.
+//! Only the fields needed to tell clients apart are read from the
+//! certificate, with a minimal DER reader; the TLS library has already
+//! verified it by the time a request arrives.
+
+use std::fmt;
+use std::net::IpAddr;
+use tonic::Request;
+
+// Universal DER tags
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const OID: u8 = 0x06;
+const OCTET_STRING: u8 = 0x04;
+const BMP_STRING: u8 = 0x1E;
+
+// Context-specific tags of the TBSCertificate and GeneralName (RFC 5280)
+const VERSION: u8 = 0xA0;
+const EXTENSIONS: u8 = 0xA3;
+const RFC822_NAME: u8 = 0x81;
+const DNS_NAME: u8 = 0x82;
+const URI: u8 = 0x86;
+const IP_ADDRESS: u8 = 0x87;
+
+// Encoded object identifiers of the common name (2.5.4.3) and the subject
+// alternative name extension (2.5.29.17)
+const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1D, 0x11];
+
+/// Identity of a client, taken from the certificate it presented
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PeerIdentity {
+    common_name: Option<String>,
+    subject_alt_names: Vec<String>,
+    fingerprint: String,
+}
+
+impl PeerIdentity {
+    // Identity of a DER-encoded certificate
+    // The names are left empty if the certificate cannot be read; the
+    // fingerprint is always there
+    pub(crate) fn from_der(der: &[u8]) -> Self {
+        let (common_name, subject_alt_names) = names(der).unwrap_or_default();
+        let digest = ring::digest::digest(&ring::digest::SHA256, der);
+        let fingerprint = digest.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+        Self { common_name, subject_alt_names, fingerprint }
+    }
+
+    /// Common name (CN) of the certificate's subject, if it has one
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// DNS names, email addresses, URIs and IP addresses of the subject
+    /// alternative name extension, in certificate order
+    pub fn subject_alt_names(&self) -> &[String] {
+        &self.subject_alt_names
+    }
+
+    /// SHA-256 of the DER-encoded certificate, as 64 lowercase hex digits
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+}
+
+impl fmt::Display for PeerIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CN={} sha256={}", self.common_name.as_deref().unwrap_or("-"), self.fingerprint)
+    }
+}
+
+/// Identity of the client certificate of `request`
+///
+/// # Arguments
+/// * `request` - The incoming request.
+///
+/// # Returns
+/// * `Option<PeerIdentity>` - The identity, or `None` on a plaintext connection
+///   or when the client presented no certificate.
+pub fn peer_identity<T>(request: &Request<T>) -> Option<PeerIdentity> {
+    if let Some(identity) = request.extensions().get::<PeerIdentity>() {
+        return Some(identity.clone());
+    }
+    // Not resolved by our layer, e.g. in a server of its own
+    let certificates = request.peer_certs()?;
+    certificates.first().map(|leaf| PeerIdentity::from_der(leaf.get_ref()))
+}
+
+// Reader over consecutive DER elements
+struct Der<'a>(&'a [u8]);
+
+impl<'a> Der<'a> {
+    // Next element as (tag, contents); None at the end or on malformed input
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.0.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (length, rest) = if first < 0x80 {
+            (usize::from(first), rest)
+        } else {
+            // Long form: the low bits count the length bytes that follow
+            let count = usize::from(first & 0x7F);
+            if count == 0 || count > std::mem::size_of::<usize>() || rest.len() < count {
+                return None;
+            }
+            let (bytes, rest) = rest.split_at(count);
+            (bytes.iter().fold(0usize, |length, &byte| (length << 8) | usize::from(byte)), rest)
+        };
+        if rest.len() < length {
+            return None;
+        }
+        let (contents, rest) = rest.split_at(length);
+        self.0 = rest;
+        Some((tag, contents))
+    }
+
+    // Contents of the next element, which must have `tag`
+    fn expect(&mut self, tag: u8) -> Option<&'a [u8]> {
+        self.next().filter(|&(found, _)| found == tag).map(|(_, contents)| contents)
+    }
+}
+
+// Common name and subject alternative names of a certificate
+fn names(der: &[u8]) -> Option<(Option<String>, Vec<String>)> {
+    let certificate = Der(der).expect(SEQUENCE)?;
+    let mut tbs = Der(Der(certificate).expect(SEQUENCE)?);
+    // version is optional, serialNumber is not
+    let (tag, _) = tbs.next()?;
+    if tag == VERSION {
+        tbs.next()?;
+    }
+    // signature, issuer, validity
+    for _ in 0..3 {
+        tbs.expect(SEQUENCE)?;
+    }
+    let common_name = common_name(tbs.expect(SEQUENCE)?);
+    // subjectPublicKeyInfo, then the optional unique IDs before the extensions
+    tbs.expect(SEQUENCE)?;
+    let mut subject_alt_names = Vec::new();
+    while let Some((tag, contents)) = tbs.next() {
+        if tag == EXTENSIONS {
+            subject_alt_names = alt_names(Der(contents).expect(SEQUENCE)?).unwrap_or_default();
+        }
+    }
+    Some((common_name, subject_alt_names))
+}
+
+// The first common name of a subject Name
+fn common_name(name: &[u8]) -> Option<String> {
+    let mut rdns = Der(name);
+    while let Some(rdn) = rdns.expect(SET) {
+        let mut attributes = Der(rdn);
+        while let Some(attribute) = attributes.expect(SEQUENCE) {
+            let mut attribute = Der(attribute);
+            if attribute.expect(OID)? == COMMON_NAME {
+                let (tag, value) = attribute.next()?;
+                return Some(directory_string(tag, value));
+            }
+        }
+    }
+    None
+}
+
+// Text of a DirectoryString; BMPString is UTF-16, the others are read as UTF-8
+fn directory_string(tag: u8, value: &[u8]) -> String {
+    if tag == BMP_STRING {
+        let units: Vec<u16> = value.chunks_exact(2).map(|unit| u16::from_be_bytes([unit[0], unit[1]])).collect();
+        return String::from_utf16_lossy(&units);
+    }
+    String::from_utf8_lossy(value).into_owned()
+}
+
+// Names of the subject alternative name extension, if present
+fn alt_names(extensions: &[u8]) -> Option<Vec<String>> {
+    let mut extensions = Der(extensions);
+    while let Some(extension) = extensions.expect(SEQUENCE) {
+        let mut extension = Der(extension);
+        if extension.expect(OID)? != SUBJECT_ALT_NAME {
+            continue;
+        }
+        // Skip the optional critical flag
+        let value = loop {
+            let (tag, contents) = extension.next()?;
+            if tag == OCTET_STRING {
+                break contents;
+            }
+        };
+        let mut general_names = Der(Der(value).expect(SEQUENCE)?);
+        let mut names = Vec::new();
+        while let Some((tag, name)) = general_names.next() {
+            match tag {
+                RFC822_NAME | DNS_NAME | URI => names.push(String::from_utf8_lossy(name).into_owned()),
+                IP_ADDRESS => names.extend(ip_address(name).map(|ip| ip.to_string())),
+                // Other name forms (directory names, ...) do not identify a client here
+                _ => {}
+            }
+        }
+        return Some(names);
+    }
+    None
+}
+
+// An iPAddress name, 4 or 16 bytes
+fn ip_address(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertificateParams, DnType, SanType};
+
+    #[test]
+    fn test_identity_of_certificate() {
+        let mut params = CertificateParams::new(vec!["tenant-a.example".to_string()]);
+        params.distinguished_name.push(DnType::OrganizationName, "Tenants");
+        params.distinguished_name.push(DnType::CommonName, "tenant-a");
+        params.subject_alt_names.push(SanType::IpAddress("10.0.0.7".parse().unwrap()));
+        params.subject_alt_names.push(SanType::Rfc822Name("ops@tenant-a.example".to_string()));
+        let der = rcgen::Certificate::from_params(params).unwrap().serialize_der().unwrap();
+
+        let identity = PeerIdentity::from_der(&der);
+        assert_eq!(identity.common_name(), Some("tenant-a"));
+        assert_eq!(identity.subject_alt_names(), ["tenant-a.example", "10.0.0.7", "ops@tenant-a.example"]);
+        assert_eq!(identity.fingerprint().len(), 64);
+        assert_eq!(identity.to_string(), format!("CN=tenant-a sha256={}", identity.fingerprint()));
+    }
+
+    #[test]
+    fn test_unreadable_certificate_keeps_fingerprint() {
+        let identity = PeerIdentity::from_der(b"abc");
+        assert_eq!(identity.common_name(), None);
+        assert!(identity.subject_alt_names().is_empty());
+        // SHA-256 of the bytes, whatever they are (FIPS 180-2 test vector)
+        assert_eq!(identity.fingerprint(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    fn test_plaintext_request_has_no_identity() {
+        assert_eq!(peer_identity(&Request::new(())), None);
+    }
+}
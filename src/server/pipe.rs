@@ -0,0 +1,92 @@
+//! Named Pipe Listener (Windows)
+//! Accepts connections on the named pipe behind GrpcServerBuilder::named_pipe:
+//! 1. One pipe instance is always listening; when a client connects, the
+//!    next instance is created before the connection is handed over, so
+//!    concurrent clients each get their own instance
+//! 2. The first instance is created when serving starts, so a name already
+//!    taken by another server fails serve() right away
+//! 3. Connected instances are wrapped in PipeIo, which the server can serve
+//!
+//! Pipes have no peer address: connection hooks, per-peer limits and the
+//! idle timeout, which all key on the peer, do not apply to them.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::server::Connected;
+use tonic::{Code, Status};
+use tracing::{error, warn, Instrument};
+
+// Incoming connections on the named pipe `path`
+pub(crate) fn bind(path: &str) -> Result<ReceiverStream<io::Result<PipeIo>>, Status> {
+    let mut listening = ServerOptions::new().first_pipe_instance(true).create(path).map_err(|e| {
+        error!("Failed to create named pipe {}: {}", path, e);
+        Status::new(Code::Internal, format!("server error: failed to create named pipe {}: {}", path, e))
+    })?;
+    let (tx, rx) = mpsc::channel(1);
+    let path = path.to_string();
+    tokio::spawn(async move {
+        loop {
+            // Stop accepting once the server is gone
+            let connected = tokio::select! {
+                _ = tx.closed() => return,
+                connected = listening.connect() => connected,
+            };
+            // Create the next instance before handing this one over, so a
+            // client arriving meanwhile finds an instance to connect to
+            let next = match ServerOptions::new().create(&path) {
+                Ok(next) => next,
+                Err(e) => {
+                    error!("Failed to create named pipe instance {}: {}", path, e);
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            let client = std::mem::replace(&mut listening, next);
+            match connected {
+                Ok(()) => {
+                    if tx.send(Ok(PipeIo(client))).await.is_err() {
+                        return;
+                    }
+                }
+                // The client went away while connecting; its instance is dropped
+                Err(e) => warn!("Failed to accept on named pipe {}: {}", path, e),
+            }
+        }
+    }.in_current_span());
+    Ok(ReceiverStream::new(rx))
+}
+
+// One client's instance of the named pipe
+pub(crate) struct PipeIo(NamedPipeServer);
+
+// No peer address, so the connection info is empty
+impl Connected for PipeIo {
+    type ConnectInfo = ();
+
+    fn connect_info(&self) -> Self::ConnectInfo {}
+}
+
+impl AsyncRead for PipeIo {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for PipeIo {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
@@ -1,32 +1,173 @@
 //! Main gRPC Server Implementation
 //! This file demonstrates several advanced Rust patterns:
 //! 1. Builder Pattern: For flexible server configuration
-//! 2. Shutdown handling using tokio channels
+//! 2. Shutdown handling using a shared tokio watch channel
 //! 3. Error handling with Status
 //! 4. Service registration and lifecycle management
 
 // Import required dependencies
 // tonic: The gRPC framework we're using
 // tokio: For async runtime and utilities
-use tonic::{transport::Server, Status, Code, Request};
-use tokio::sync::oneshot;  // Channel for shutdown signal
-use tracing::{info, error};  // Import tracing for logging
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tonic::{server::NamedService, transport::Server, Status, Code, Request};
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_stream::StreamExt;
+use tracing::{info, warn, error, debug, info_span, Instrument, Span};  // Import tracing for logging
 // Import our service implementations
 use crate::proto::echo::echo_service_server::EchoServiceServer;
 use crate::proto::calculator::calculator_service_server::CalculatorServiceServer;
-use super::services::{EchoServer, CalculatorServer};
+use crate::proto::admin::admin_service_server::AdminServiceServer;
+use crate::proto::broadcast::broadcast_service_server::BroadcastServiceServer;
+use super::services::{EchoServer, CalculatorServer, AdminServer, AdminToken, BroadcastServer, SubnormalPolicy};
+use super::layers::{queue_depth_layer, ConnectionCountLayer, InFlightLayer, OverloadAlarm, PeerLimitLayer, LatencyLayer, FaultInjectionLayer, FaultConfig, MetadataLayer, PeerIdentityLayer, DEFAULT_MAX_METADATA_SIZE};
+use super::banner::{StartupBanner, DEFAULT_MAX_MESSAGE_SIZE};
+use super::shutdown::{Shutdown, ShutdownCause, ShutdownReason, ShutdownSignal};
+use super::connections::{ConnectionHooks, ConnectionLimit};
+use super::metrics::ServerMetrics;
+use super::latency::LatencyTracker;
+use super::cache::{CalculationCache, EchoCache};
+use super::live_config::LiveConfig;
+use super::idle::IdleTracker;
+use super::serving::Serving;
+use super::tls::{self, TlsIdentity, TlsVersion};
+use super::listeners::{self, REUSE_PORT_SUPPORTED};
+use super::service_state::ServiceStates;
+use super::events::{EventTap, ServerEvent};
+use super::auth::{Authenticator, NoAuth};
+use super::history::{History, HistoryStore};
+use super::snapshot::ServerConfigSnapshot;
+use super::audit::{AuditLogger, DEFAULT_AUDIT_LOG};
+use super::streams::{StreamOverflowPolicy, StreamSettings};
+use super::peer_identity::peer_identity;
+use crate::config::{ServerConfig, RuntimeConfig};
+use crate::pipe::{self, NAMED_PIPES_SUPPORTED};
+use crate::trace::{is_valid_correlation_id, TraceContext, CORRELATION_ID, TRACEPARENT};
+
+// Optional server settings shared by the builder and the built server
+// Grouping them avoids copying every field from one struct to the other
+// No Debug: it would print the TLS key; use ServerConfigSnapshot instead
+#[derive(Clone)]
+pub(crate) struct ServerOptions {
+    pub(crate) max_concurrent_per_peer: Option<usize>,  // Per-IP in-flight request cap
+    pub(crate) max_queue_depth: Option<usize>,  // Requests held at once before shedding
+    pub(crate) overload_alarm: Option<OverloadAlarm>,  // Callback run when in-flight requests reach a mark
+    pub(crate) startup_banner: bool,  // Log the effective configuration on start
+    pub(crate) skip_logging_init: bool,  // Leave the tracing subscriber to the application
+    pub(crate) instance_name: Option<String>,  // Recorded as `instance` on every log event of the server
+    pub(crate) max_message_size: usize,  // Largest message decoded or encoded
+    pub(crate) request_timeout: Option<Duration>,  // Per-RPC deadline enforced by the server
+    pub(crate) compression: Option<CompressionEncoding>,  // Compress responses, accept compressed requests
+    pub(crate) tls: Option<TlsIdentity>,  // Certificate and key; plaintext when unset
+    pub(crate) min_tls_version: TlsVersion,  // Oldest TLS version clients may negotiate
+    pub(crate) tls_client_ca: Option<Vec<u8>>,  // PEM of the CA client certificates must chain to (mutual TLS)
+    pub(crate) runtime: RuntimeConfig,  // Runtime settings reported by ServerInfo
+    pub(crate) admin_token: Option<AdminToken>,  // Unlocks mutating admin RPCs
+    pub(crate) allow_empty_echo: bool,  // Echo empty messages instead of rejecting them
+    pub(crate) echo_max_chars: Option<usize>,  // Longest echo message, in characters
+    pub(crate) calculator_cache: Option<usize>,  // Capacity of the calculate result cache
+    pub(crate) echo_cache: Option<usize>,  // Echoed messages remembered to count repeats
+    pub(crate) idle_connection_timeout: Option<Duration>,  // Close connections without RPCs for this long
+    pub(crate) slow_request_threshold: Option<Duration>,  // Log RPCs that take longer than this
+    pub(crate) reuse_port_listeners: usize,  // Listeners sharing the address via SO_REUSEPORT
+    pub(crate) named_pipe: Option<String>,  // Windows pipe served instead of TCP; full path once built
+    pub(crate) max_connections: Option<usize>,  // Connections open at once, across all listeners
+    pub(crate) max_stream_bytes: Option<usize>,  // Bytes one chunked echo may carry
+    pub(crate) max_header_list_size: Option<u32>,  // Bytes of metadata one request may carry
+    pub(crate) max_metadata_size: usize,  // Same, with a default; HTTP/2 enforces the smaller
+    pub(crate) fault_injection: Option<FaultConfig>,  // Failures and delays injected for resilience tests
+    pub(crate) streams: StreamSettings,  // Outbound buffering of streaming responses
+    pub(crate) subnormals: SubnormalPolicy,  // Handling of subnormal Calculate operands
+    pub(crate) calculator_history: Option<History>,  // Store of the calculator history; in memory when unset
+    pub(crate) audit_log: PathBuf,  // File recording the mutating admin RPCs
+    pub(crate) audit_fail_open: bool,  // Let admin RPCs succeed when their audit entry is lost
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrent_per_peer: None,
+            max_queue_depth: None,
+            overload_alarm: None,
+            startup_banner: true,
+            skip_logging_init: false,
+            instance_name: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            request_timeout: None,
+            compression: None,
+            tls: None,
+            min_tls_version: TlsVersion::default(),
+            tls_client_ca: None,
+            runtime: RuntimeConfig::default(),
+            admin_token: None,
+            allow_empty_echo: false,
+            echo_max_chars: None,
+            calculator_cache: None,
+            echo_cache: None,
+            idle_connection_timeout: None,
+            slow_request_threshold: None,
+            reuse_port_listeners: 1,
+            named_pipe: None,
+            max_connections: None,
+            max_stream_bytes: None,
+            max_header_list_size: None,
+            max_metadata_size: DEFAULT_MAX_METADATA_SIZE,
+            fault_injection: None,
+            streams: StreamSettings::default(),
+            subnormals: SubnormalPolicy::default(),
+            calculator_history: None,
+            audit_log: PathBuf::from(DEFAULT_AUDIT_LOG),
+            audit_fail_open: false,
+        }
+    }
+}
+
+// Where the server address came from
+// Text is parsed in build() so bad input is reported before serve()
+enum AddressSource {
+    Text(String),
+    Parsed(SocketAddr),
+}
 
 // Builder pattern implementation
 // This allows flexible configuration of server parameters
 #[derive(Default)]
 pub struct GrpcServerBuilder {
-    addr: Option<String>,  // Server address is optional during building
+    addr: Option<AddressSource>,  // Server address is optional during building
+    options: ServerOptions,  // Optional settings with sensible defaults
+    shutdown: Option<Shutdown>,  // Shared shutdown handle; a new one if unset
+    connections: ConnectionHooks,  // Connection callbacks and gauge
+    authenticator: Option<Arc<dyn Authenticator>>,  // NoAuth when unset
 }
 
 // The actual server struct that will be built
 pub struct GrpcServer {
-    addr: String,  // Server address (required for running)
-    shutdown: oneshot::Receiver<()>,  // Channel for graceful shutdown
+    addr: SocketAddr,  // Server address, validated by the builder
+    shutdown: ShutdownSignal,  // Completes when shutdown is requested
+    options: ServerOptions,  // Settings captured from the builder
+    connections: ConnectionHooks,  // Run for every accepted connection
+    authenticator: Arc<dyn Authenticator>,  // Resolves the caller of every request
+    custom_authenticator: bool,  // Whether the builder replaced NoAuth, for config_snapshot
+}
+
+// Debug output is the configuration snapshot, so secrets stay redacted
+impl fmt::Debug for GrpcServerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GrpcServerBuilder").field(&self.config_snapshot()).finish()
+    }
+}
+
+impl fmt::Debug for GrpcServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GrpcServer").field(&self.config_snapshot()).finish()
+    }
 }
 
 // Builder implementation
@@ -39,35 +180,522 @@ impl GrpcServerBuilder {
     // Set the server address
     // Uses generic Into<String> to accept different string types
     pub fn address(mut self, addr: impl Into<String>) -> Self {
-        self.addr = Some(addr.into());
+        self.addr = Some(AddressSource::Text(addr.into()));
+        self
+    }
+
+    // Set an already parsed server address, skipping string parsing
+    // Overrides any earlier address() call, and vice versa
+    pub fn socket_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = Some(AddressSource::Parsed(addr));
+        self
+    }
+
+    // Limit how many RPCs a single remote IP may have in flight at once
+    // Excess requests are rejected with ResourceExhausted instead of queued,
+    // so one noisy client cannot consume the whole server's capacity
+    // build() rejects 0
+    pub fn max_concurrent_per_peer(mut self, limit: usize) -> Self {
+        self.options.max_concurrent_per_peer = Some(limit);
+        self
+    }
+
+    // Limit how many client connections may be open at once
+    // Connections beyond the limit are closed as soon as they are accepted,
+    // before any HTTP/2 exchange; refusals appear in metrics()
+    pub fn max_connections(mut self, limit: usize) -> Self {
+        self.options.max_connections = Some(limit);
+        self
+    }
+
+    // Limit how many requests the whole server holds at once, queued or in flight
+    // Requests beyond the depth are shed with ResourceExhausted right away
+    // rather than queued indefinitely; shed counts appear in metrics()
+    pub fn max_queue_depth(mut self, depth: usize) -> Self {
+        self.options.max_queue_depth = Some(depth);
+        self
+    }
+
+    // Run `callback` with the in-flight count when it rises to `high_water`
+    // At most once per second, on the blocking pool; a burst that stays at or
+    // above the mark is reported once. Useful for paging or scaling out
+    // before max_queue_depth starts shedding
+    pub fn overload_callback(mut self, high_water: usize, callback: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        self.options.overload_alarm = Some(OverloadAlarm::new(high_water, callback));
+        self
+    }
+
+    // Enable or disable the configuration banner logged by serve()
+    // Enabled by default
+    pub fn startup_banner(mut self, enabled: bool) -> Self {
+        self.options.startup_banner = enabled;
+        self
+    }
+
+    // Leave logging setup to the application: serve() then neither installs
+    // the crate's subscriber nor creates logs/server, and the server's events
+    // go to whatever subscriber the application set up. Off by default
+    pub fn skip_logging_init(mut self, skip: bool) -> Self {
+        self.options.skip_logging_init = skip;
+        self
+    }
+
+    // Name this server in its logs, for processes running several servers
+    // serve() then runs in a "server" span with an `instance` field, the
+    // parent of every RPC's span, so the server's events carry the name
+    // Unnamed servers log without the span
+    pub fn instance_name(mut self, name: impl Into<String>) -> Self {
+        self.options.instance_name = Some(name.into());
+        self
+    }
+
+    // Largest message, in bytes, the services accept or send
+    // Defaults to 4 MiB, tonic's own decoding limit
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.options.max_message_size = bytes;
+        self
+    }
+
+    // Fail any RPC that takes longer than `timeout` with Cancelled
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.options.request_timeout = Some(timeout);
+        self
+    }
+
+    // Compress responses with `encoding` and accept requests compressed with it
+    pub fn compression(mut self, encoding: CompressionEncoding) -> Self {
+        self.options.compression = Some(encoding);
         self
     }
 
+    // Serve over TLS with the given PEM-encoded certificate chain and private key
+    pub fn tls(mut self, cert_pem: impl AsRef<[u8]>, key_pem: impl AsRef<[u8]>) -> Self {
+        self.options.tls = Some(TlsIdentity { cert: cert_pem.as_ref().to_vec(), key: key_pem.as_ref().to_vec() });
+        self
+    }
+
+    // Refuse TLS clients that cannot negotiate at least `version`
+    // Tls12 (the default) accepts every version the TLS library supports;
+    // Tls13 limits the handshake to TLS 1.3, so older clients fail it
+    // Requires tls(); build() rejects it on a plaintext server
+    pub fn min_tls_version(mut self, version: TlsVersion) -> Self {
+        self.options.min_tls_version = version;
+        self
+    }
+
+    // Require every TLS client to present a certificate signed by the
+    // PEM-encoded CA (mutual TLS); handlers read it with server::peer_identity
+    // Requires tls(); build() rejects it on a plaintext server
+    pub fn tls_client_ca(mut self, ca_pem: impl AsRef<[u8]>) -> Self {
+        self.options.tls_client_ca = Some(ca_pem.as_ref().to_vec());
+        self
+    }
+
+    // Require `token` for admin RPCs that change the server, such as SetLogLevel
+    // Clients send it as "authorization: Bearer <token>" metadata; without a
+    // token those RPCs are refused, while read-only admin RPCs stay open
+    pub fn admin_token(mut self, token: impl Into<String>) -> Self {
+        self.options.admin_token = Some(AdminToken::new(token));
+        self
+    }
+
+    // Record every mutating admin RPC (time, peer, method, parameters,
+    // outcome) as one JSON line in `path`; defaults to logs/audit.log
+    // The file is independent of the log level and created on first use
+    pub fn audit_log_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.audit_log = path.into();
+        self
+    }
+
+    // Let mutating admin RPCs go ahead when their audit entry cannot be
+    // written; by default they fail with Internal instead (fail-closed)
+    pub fn audit_fail_open(mut self, fail_open: bool) -> Self {
+        self.options.audit_fail_open = fail_open;
+        self
+    }
+
+    // Echo empty or whitespace-only messages back unchanged instead of
+    // rejecting them with InvalidArgument (the default)
+    // Clients need GrpcClientBuilder::allow_empty_echo(true) to send them
+    pub fn allow_empty_echo(mut self, allowed: bool) -> Self {
+        self.options.allow_empty_echo = allowed;
+        self
+    }
+
+    // Reject echo messages longer than `limit` characters (not bytes)
+    // The InvalidArgument status carries "limit" and "actual" metadata,
+    // which the client wrapper turns into EchoError::TooLong
+    pub fn echo_max_chars(mut self, limit: usize) -> Self {
+        self.options.echo_max_chars = Some(limit);
+        self
+    }
+
+    // End a chunked echo with ResourceExhausted once it has carried more
+    // than `limit` bytes, so one client cannot stream unbounded data through
+    // the server; chunks within the budget are echoed normally
+    pub fn max_stream_bytes(mut self, limit: usize) -> Self {
+        self.options.max_stream_bytes = Some(limit);
+        self
+    }
+
+    // Refuse requests whose headers (metadata) exceed `size` bytes with HTTP
+    // 431, which clients see as Unknown; the limit is HTTP/2's
+    // SETTINGS_MAX_HEADER_LIST_SIZE, announced to clients and enforced while
+    // the headers are decoded. Only max_metadata_size applies when unset
+    pub fn max_header_list_size(mut self, size: u32) -> Self {
+        self.options.max_header_list_size = Some(size);
+        self
+    }
+
+    // Refuse requests carrying more than `bytes` of metadata, like
+    // max_header_list_size and counted the same way (name plus value plus
+    // 32 bytes per entry); defaults to 8 KiB. The smaller of the two limits
+    // is the one HTTP/2 enforces. Malformed keys and values are rejected
+    // with InvalidArgument before every other layer and interceptor; see
+    // layers/mod.rs for the full order
+    // build() rejects 0
+    pub fn max_metadata_size(mut self, bytes: usize) -> Self {
+        self.options.max_metadata_size = bytes;
+        self
+    }
+
+    // Fail or delay application RPCs at random, for testing client retries
+    // Give FaultConfig a seed to make the faults reproducible; admin RPCs
+    // are never faulted. build() rejects probabilities outside 0..=1
+    pub fn fault_injection(mut self, config: FaultConfig) -> Self {
+        self.options.fault_injection = Some(config);
+        self
+    }
+
+    // Hold at most `size` responses per streaming RPC (EchoStream, EchoChunked,
+    // RunningTotal, AggregateStream) waiting for a slow client; defaults to 16
+    // build() rejects 0
+    pub fn stream_buffer_size(mut self, size: usize) -> Self {
+        self.options.streams.buffer = size;
+        self
+    }
+
+    // What a streaming RPC does once that buffer is full: wait for the client
+    // (Block, the default) or end the stream with ResourceExhausted (Error)
+    pub fn stream_overflow_policy(mut self, policy: StreamOverflowPolicy) -> Self {
+        self.options.streams.overflow = policy;
+        self
+    }
+
+    // What Calculate does with subnormal operands: calculate with them
+    // (Allow, the default), treat them as zero (FlushToZero) or fail with
+    // InvalidArgument (Reject)
+    pub fn subnormal_policy(mut self, policy: SubnormalPolicy) -> Self {
+        self.options.subnormals = policy;
+        self
+    }
+
+    // Keep the calculator history (GetHistory) in `store`, e.g. a FileHistory
+    // that survives restarts; defaults to the last 1000 calculations in memory
+    pub fn calculator_history_store(mut self, store: Arc<dyn HistoryStore>) -> Self {
+        self.options.calculator_history = Some(History::new(store));
+        self
+    }
+
+    // Cache up to `capacity` results of Calculate requests, evicting the
+    // least recently used; hits and misses appear in metrics()
+    // Errors and calculations with a NaN operand are never cached
+    pub fn calculator_cache(mut self, capacity: usize) -> Self {
+        self.options.calculator_cache = Some(capacity);
+        self
+    }
+
+    // Count repeats among the last `capacity` messages of unary Echo
+    // requests, forgetting the least recently used; hits and misses appear
+    // in metrics() and CacheStats. Only hashes are kept, never the messages:
+    // an echo moves its message into the response, so there is nothing to
+    // serve from a cache
+    pub fn echo_cache(mut self, capacity: usize) -> Self {
+        self.options.echo_cache = Some(capacity);
+        self
+    }
+
+    // Close connections that had no RPC in flight for longer than `timeout`
+    // Connections get a GOAWAY first, with or without TLS, so clients reconnect
+    // transparently; RPCs in progress, streaming ones included, are never cut
+    pub fn idle_connection_timeout(mut self, timeout: Duration) -> Self {
+        self.options.idle_connection_timeout = Some(timeout);
+        self
+    }
+
+    // Log a warning for every RPC that takes longer than `threshold`, with
+    // its method, peer, status, latency and request ID, and count it in metrics()
+    // Streaming RPCs are timed until the stream completes and logged apart
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.options.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    // Accept on `count` sockets bound to the same address with SO_REUSEPORT,
+    // each with its own accept loop, so accepting scales across cores
+    // Unix only; build() rejects a count above 1 elsewhere. Defaults to 1
+    pub fn reuse_port_listeners(mut self, count: usize) -> Self {
+        self.options.reuse_port_listeners = count;
+        self
+    }
+
+    // Serve on the Windows named pipe `name` instead of a TCP address
+    // `\\.\pipe\` is prepended unless present, and every client gets its own
+    // pipe instance; no address is needed. Connection hooks, max_connections,
+    // per-peer limits and the idle timeout key on TCP peers and do not apply
+    // Windows only; build() fails with Unimplemented elsewhere
+    pub fn named_pipe(mut self, name: impl Into<String>) -> Self {
+        self.options.named_pipe = Some(name.into());
+        self
+    }
+
+    // Authenticate every request with `authenticator` before it reaches a service
+    // The resolved Identity is stored in the request extensions; a rejected
+    // request fails with the authenticator's status. Defaults to NoAuth
+    pub fn authenticator(mut self, authenticator: Arc<dyn Authenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    // Record the tokio runtime settings the server runs on
+    // Only reported through the admin ServerInfo RPC; the runtime itself
+    // is built by the caller, e.g. with RuntimeConfig::build_runtime
+    pub fn runtime_config(mut self, runtime: RuntimeConfig) -> Self {
+        self.options.runtime = runtime;
+        self
+    }
+
+    // Create a builder from a configuration, e.g. one read by ServerConfig::from_env
+    // Reads the TLS files immediately so missing files are reported here
+    pub fn from_config(config: ServerConfig) -> Result<Self, Status> {
+        let mut builder = Self::new()
+            .socket_addr(config.addr)
+            .runtime_config(config.runtime);
+
+        if let Some(bytes) = config.max_message_size {
+            builder = builder.max_message_size(bytes);
+        }
+        if let Some(timeout) = config.request_timeout {
+            builder = builder.request_timeout(timeout);
+        }
+        if let Some(encoding) = config.compression {
+            builder = builder.compression(encoding);
+        }
+        if let Some(limit) = config.max_concurrent_per_peer {
+            builder = builder.max_concurrent_per_peer(limit);
+        }
+        if let Some(enabled) = config.startup_banner {
+            builder = builder.startup_banner(enabled);
+        }
+        if let (Some(cert), Some(key)) = (&config.tls_cert, &config.tls_key) {
+            let read = |path: &std::path::Path| std::fs::read(path).map_err(|e| Status::new(
+                Code::InvalidArgument,
+                format!("cannot read TLS file {}: {}", path.display(), e)
+            ));
+            builder = builder.tls(read(cert)?, read(key)?);
+        }
+
+        Ok(builder)
+    }
+
+    // Run `hook` with the peer address whenever a client connection is established
+    // Hooks run on tokio's blocking pool, so a slow hook never delays accepting
+    // connections; several hooks run in registration order
+    pub fn on_connect(mut self, hook: impl Fn(SocketAddr) + Send + Sync + 'static) -> Self {
+        self.connections.on_connect.push(Arc::new(hook));
+        self
+    }
+
+    // Run `hook` with the peer address whenever a client connection is closed
+    // Runs after the connection's on_connect hooks have finished
+    pub fn on_disconnect(mut self, hook: impl Fn(SocketAddr) + Send + Sync + 'static) -> Self {
+        self.connections.on_disconnect.push(Arc::new(hook));
+        self
+    }
+
+    // Send lifecycle events (start, connections, shutdown) to `tx`
+    // Sending never blocks the server; events are dropped once the receiver is gone
+    pub fn with_event_tap(mut self, tx: UnboundedSender<ServerEvent>) -> Self {
+        self.connections.events = EventTap::new(tx);
+        self
+    }
+
+    // Stop this server together with every other server using the same handle
+    // Pass a clone of the handle returned by another server's build()
+    pub fn shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    // Summary of every option set so far, with secrets redacted
+    // Serializable, e.g. with serde_json, for logging the configuration
+    pub fn config_snapshot(&self) -> ServerConfigSnapshot {
+        let address = self.addr.as_ref().map(|addr| match addr {
+            AddressSource::Text(text) => text.clone(),
+            AddressSource::Parsed(addr) => addr.to_string(),
+        });
+        ServerConfigSnapshot::new(address, &self.options, self.authenticator.is_some())
+    }
+
     // Finalize the server configuration
-    // Returns both the server and a shutdown signal sender
-    pub fn build(self) -> Result<(GrpcServer, oneshot::Sender<()>), Status> {
-        // Ensure address was provided
-        let addr = self.addr.ok_or_else(|| Status::new(
-            Code::InvalidArgument,
-            "Server address must be provided"
-        ))?;
-
-        // Create shutdown channel
-        let (tx, rx) = oneshot::channel();
-        
+    // Returns both the server and its shutdown handle
+    pub fn build(self) -> Result<(GrpcServer, Shutdown), Status> {
+        // Named pipes exist on Windows only; the name is checked either way
+        let named_pipe = self.options.named_pipe.as_deref().map(pipe::pipe_path).transpose()?;
+        if named_pipe.is_some() && !NAMED_PIPES_SUPPORTED {
+            return Err(pipe::unsupported());
+        }
+
+        // Ensure address was provided; a named pipe needs none
+        let addr = match (self.addr, &named_pipe) {
+            (Some(addr), _) => addr,
+            (None, Some(_)) => AddressSource::Parsed(SocketAddr::from(([0, 0, 0, 0], 0))),
+            (None, None) => return Err(Status::new(
+                Code::InvalidArgument,
+                "Server address must be provided"
+            )),
+        };
+
+        // Parse text addresses now so a typo fails here, not inside a spawned serve()
+        let addr = match addr {
+            AddressSource::Parsed(addr) => addr,
+            AddressSource::Text(input) => input.parse().map_err(|e| Status::new(
+                Code::InvalidArgument,
+                format!("invalid server address {:?}: {}", input, e)
+            ))?,
+        };
+
+        // Reject listener counts this platform cannot serve
+        let listeners = self.options.reuse_port_listeners;
+        if listeners == 0 {
+            return Err(Status::new(Code::InvalidArgument, "reuse_port_listeners must be at least 1"));
+        }
+        if listeners > 1 && !REUSE_PORT_SUPPORTED {
+            return Err(Status::new(Code::InvalidArgument, format!(
+                "reuse_port_listeners({}) needs SO_REUSEPORT, which is only supported on Unix",
+                listeners
+            )));
+        }
+
+        if self.options.min_tls_version > TlsVersion::default() && self.options.tls.is_none() {
+            return Err(Status::new(Code::InvalidArgument, "min_tls_version requires tls to be configured"));
+        }
+        if self.options.tls_client_ca.is_some() && self.options.tls.is_none() {
+            return Err(Status::new(Code::InvalidArgument, "tls_client_ca requires tls to be configured"));
+        }
+
+        if self.options.max_concurrent_per_peer == Some(0) {
+            return Err(Status::new(Code::InvalidArgument, "max_concurrent_per_peer must be at least 1"));
+        }
+        if self.options.max_metadata_size == 0 {
+            return Err(Status::new(Code::InvalidArgument, "max_metadata_size must be at least 1"));
+        }
+        if self.options.overload_alarm.as_ref().is_some_and(|alarm| alarm.high_water() == 0) {
+            return Err(Status::new(Code::InvalidArgument, "overload_callback high-water mark must be at least 1"));
+        }
+        if self.options.streams.buffer == 0 {
+            return Err(Status::new(Code::InvalidArgument, "stream_buffer_size must be at least 1"));
+        }
+        if let Some(faults) = &self.options.fault_injection {
+            faults.validate().map_err(|e| Status::new(Code::InvalidArgument, e))?;
+        }
+
+        // Join the shared shutdown handle or start a new one
+        let shutdown = self.shutdown.unwrap_or_default();
+
         Ok((GrpcServer {
             addr,
-            shutdown: rx,
-        }, tx))
+            shutdown: shutdown.signal(),
+            options: ServerOptions { named_pipe, ..self.options },
+            connections: self.connections,
+            custom_authenticator: self.authenticator.is_some(),
+            authenticator: self.authenticator.unwrap_or_else(|| Arc::new(NoAuth)),
+        }, shutdown))
     }
 }
 
 // Define an interceptor function to log incoming connections
+// The client certificate is named when one was presented (mutual TLS)
 fn log_interceptor(req: Request<()>) -> Result<Request<()>, Status> {
-    info!("Incoming connection from: {:?}", req.remote_addr());
+    match peer_identity(&req) {
+        Some(identity) => info!("Incoming connection from: {:?}, client certificate {}", req.remote_addr(), identity),
+        None => info!("Incoming connection from: {:?}", req.remote_addr()),
+    }
+    Ok(req)
+}
+
+// Pick up the caller's W3C trace context, if any
+// Records the trace ID on the per-RPC span (see rpc_span) and stores the
+// context in the request extensions for handlers; malformed headers are
+// ignored rather than rejected
+fn trace_interceptor(mut req: Request<()>) -> Result<Request<()>, Status> {
+    let Some(header) = req.metadata().get(TRACEPARENT) else {
+        return Ok(req);
+    };
+    match header.to_str().ok().and_then(TraceContext::parse) {
+        Some(context) => {
+            Span::current().record("trace_id", tracing::field::display(context.trace_id()));
+            req.extensions_mut().insert(context);
+        }
+        None => debug!("Ignoring malformed traceparent header: {:?}", header),
+    }
+    Ok(req)
+}
+
+// Pick up the caller's correlation ID, if any
+// Records it on the per-RPC span so every server log line for the call
+// carries it; IDs that are not plain visible ASCII are ignored
+fn correlation_interceptor(req: Request<()>) -> Result<Request<()>, Status> {
+    let Some(header) = req.metadata().get(CORRELATION_ID) else {
+        return Ok(req);
+    };
+    match header.to_str().ok().filter(|id| is_valid_correlation_id(id)) {
+        Some(id) => {
+            Span::current().record("correlation_id", tracing::field::display(id));
+        }
+        None => debug!("Ignoring malformed correlation ID: {:?}", header),
+    }
+    Ok(req)
+}
+
+// Resolve the caller and store the Identity for handlers
+fn auth_interceptor(authenticator: &dyn Authenticator, mut req: Request<()>) -> Result<Request<()>, Status> {
+    let identity = authenticator.authenticate(req.metadata())?;
+    debug!("Authenticated request as {}", identity);
+    req.extensions_mut().insert(identity);
     Ok(req)
 }
 
+// Interceptor applied to every service
+// Cloned into each service, sharing the one authenticator; `service` is
+// the name checked against the serving flags
+fn interceptor(
+    authenticator: Arc<dyn Authenticator>,
+    states: ServiceStates,
+    service: &'static str,
+) -> impl FnMut(Request<()>) -> Result<Request<()>, Status> + Clone {
+    move |req| {
+        states.check(service)?;
+        let req = correlation_interceptor(trace_interceptor(log_interceptor(req)?)?)?;
+        auth_interceptor(authenticator.as_ref(), req)
+    }
+}
+
+// Span wrapping each RPC; trace_id is filled in by trace_interceptor,
+// correlation_id by correlation_interceptor, peer by PeerIdentityLayer
+// Connections are served in tasks of their own, so the server's span is
+// passed in rather than taken from the current one
+fn rpc_span(server: &Span, req: &http::Request<()>) -> Span {
+    info_span!(
+        parent: server,
+        "rpc",
+        method = %req.uri().path(),
+        trace_id = tracing::field::Empty,
+        correlation_id = tracing::field::Empty,
+        peer = tracing::field::Empty,
+    )
+}
+
 // Main server implementation
 impl GrpcServer {
     // Create a new builder (entry point for server configuration)
@@ -75,39 +703,327 @@ impl GrpcServer {
         GrpcServerBuilder::new()
     }
 
+    // Configured listen address (port 0 is only resolved when serving)
+    // Unused, and 0.0.0.0:0 unless set, when serving on a named pipe
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // Summary of the configuration the server was built with, secrets redacted
+    pub fn config_snapshot(&self) -> ServerConfigSnapshot {
+        ServerConfigSnapshot::new(Some(self.addr.to_string()), &self.options, self.custom_authenticator)
+    }
+
+    // Live metrics of this server, e.g. the active_connections gauge
+    // Take the handle before serve(); it stays valid while the server runs
+    pub fn metrics(&self) -> ServerMetrics {
+        self.connections.metrics.clone()
+    }
+
     // Start the server and run until shutdown signal
-    pub async fn serve(self) -> Result<(), Status> {
-        // Initialize logging for server
-        crate::logging::init_server()
-            .map_err(|e| Status::internal(format!("Failed to initialize logging: {}", e)))?;
-        
-        // Parse the address string into a socket address
-        let addr = self.addr.parse()
-            .map_err(|e| {
-                error!("Invalid server address: {}", e);
-                Status::new(Code::InvalidArgument, "invalid server address format")
-            })?;
+    // Returns why it stopped; failing to start, or a listener failing while
+    // serving, is the Err instead
+    pub async fn serve(self) -> Result<ShutdownReason, Status> {
+        // Initialize logging for server, unless the application does its own
+        // Done first: a span created without a subscriber stays disabled
+        if !self.options.skip_logging_init {
+            crate::logging::init_server()
+                .map_err(|e| Status::internal(format!("Failed to initialize logging: {}", e)))?;
+        }
 
+        let span = match &self.options.instance_name {
+            Some(name) => info_span!("server", instance = %name),
+            None => Span::none(),
+        };
+        self.run(span.clone()).instrument(span).await
+    }
+
+    // Body of serve(), within `span`
+    async fn run(self, span: Span) -> Result<ShutdownReason, Status> {
+        let addr = self.addr;
         info!("Starting gRPC server on {}", addr);
 
-        // Create intercepted services
-        let echo_service = EchoServiceServer::with_interceptor(EchoServer::default(), log_interceptor);
-        let calculator_service = CalculatorServiceServer::with_interceptor(CalculatorServer::default(), log_interceptor);
+        // One-time summary of the effective configuration
+        if self.options.startup_banner {
+            let banner = StartupBanner {
+                addr,
+                tls: self.options.tls.is_some(),
+                services: vec![
+                    <EchoServiceServer<EchoServer> as NamedService>::NAME,
+                    <CalculatorServiceServer<CalculatorServer> as NamedService>::NAME,
+                    <AdminServiceServer<AdminServer> as NamedService>::NAME,
+                    <BroadcastServiceServer<BroadcastServer> as NamedService>::NAME,
+                ],
+                max_concurrent_per_peer: self.options.max_concurrent_per_peer,
+                max_queue_depth: self.options.max_queue_depth,
+                max_message_size: self.options.max_message_size,
+            };
+            info!("{}", banner);
+        }
+
+        // Apply message limits and compression, then wrap with the logging interceptor
+        let max_message_size = self.options.max_message_size;
+        // Echo settings start from the builder and may be changed by the admin service
+        let live_config = LiveConfig {
+            allow_empty_echo: self.options.allow_empty_echo,
+            echo_max_chars: self.options.echo_max_chars,
+        }.shared();
+        let mut echo_server = EchoServer::default()
+            .config(live_config.clone())
+            .max_stream_bytes(self.options.max_stream_bytes)
+            .streams(self.options.streams);
+        if let Some(capacity) = self.options.echo_cache {
+            echo_server = echo_server.with_cache(EchoCache::new(capacity, self.connections.metrics.clone()));
+        }
+        let sequences = echo_server.connection_sequences();
+        let mut echo = EchoServiceServer::new(echo_server)
+            .max_decoding_message_size(max_message_size)
+            .max_encoding_message_size(max_message_size);
+        let mut calculator_server = CalculatorServer::default()
+            .streams(self.options.streams)
+            .subnormals(self.options.subnormals);
+        if let Some(history) = &self.options.calculator_history {
+            calculator_server = calculator_server.history(history.clone());
+        }
+        if let Some(capacity) = self.options.calculator_cache {
+            let cache = CalculationCache::new(capacity, self.connections.metrics.clone());
+            calculator_server = calculator_server.with_cache(cache);
+        }
+        let mut calculator = CalculatorServiceServer::new(calculator_server)
+            .max_decoding_message_size(max_message_size)
+            .max_encoding_message_size(max_message_size);
+        // Subscriptions end at shutdown so they do not hold up the graceful stop
+        let mut broadcast = BroadcastServiceServer::new(BroadcastServer::new(self.shutdown.clone()))
+            .max_decoding_message_size(max_message_size)
+            .max_encoding_message_size(max_message_size);
+        if let Some(encoding) = self.options.compression {
+            echo = echo.send_compressed(encoding).accept_compressed(encoding);
+            calculator = calculator.send_compressed(encoding).accept_compressed(encoding);
+            broadcast = broadcast.send_compressed(encoding).accept_compressed(encoding);
+        }
+        // Application services can be taken offline through the admin service
+        let echo_name = <EchoServiceServer<EchoServer> as NamedService>::NAME;
+        let calculator_name = <CalculatorServiceServer<CalculatorServer> as NamedService>::NAME;
+        let broadcast_name = <BroadcastServiceServer<BroadcastServer> as NamedService>::NAME;
+        let states = ServiceStates::new(&[echo_name, calculator_name, broadcast_name]);
+        let authenticator = self.authenticator.clone();
+        let echo_service = InterceptedService::new(echo, interceptor(authenticator.clone(), states.clone(), echo_name));
+        let calculator_service = InterceptedService::new(
+            calculator,
+            interceptor(authenticator.clone(), states.clone(), calculator_name),
+        );
+        let broadcast_service = InterceptedService::new(
+            broadcast,
+            interceptor(authenticator.clone(), states.clone(), broadcast_name),
+        );
+
+        // Latencies recorded by the layer below, reported by the admin service
+        let latency = Arc::new(LatencyTracker::default());
+        // Per-connection request counts, also listed by the admin service
+        let connection_count = ConnectionCountLayer::default();
+        let admin = AdminServer::new(latency.clone(), self.options.runtime.clone(), self.options.admin_token.clone())
+            .live_config(live_config)
+            .service_states(states.clone())
+            .audit(AuditLogger::new(self.options.audit_log.clone(), self.options.audit_fail_open))
+            .metrics(self.connections.metrics.clone())
+            .connections(self.connections.open.clone(), connection_count.counts());
+        let admin_name = <AdminServiceServer<AdminServer> as NamedService>::NAME;
+        let admin_service = InterceptedService::new(AdminServiceServer::new(admin), interceptor(authenticator, states, admin_name));
+
+        // TLS is negotiated by the server itself, per connection (serving.rs)
+        let tls = self.options.tls.as_ref()
+            .map(|identity| tls::acceptor(identity, self.options.tls_client_ca.as_deref(), self.options.min_tls_version))
+            .transpose()?;
+
+        // Both metadata limits bound the same header block; HTTP/2 enforces
+        // the smaller one while decoding it
+        let metadata_size = u32::try_from(self.options.max_metadata_size).unwrap_or(u32::MAX);
+        let header_list_size = self.options.max_header_list_size.map_or(metadata_size, |size| size.min(metadata_size));
+        // Optional fault injection, innermost so injected delays count as handling time
+        let faults = tower::util::option_layer(self.options.fault_injection.map(FaultInjectionLayer::new));
+        // Client certificates only exist under mutual TLS
+        let peer_identity = tower::util::option_layer(self.options.tls_client_ca.as_ref().map(|_| PeerIdentityLayer));
+        // Optional per-peer limit; option_layer is a no-op when unset
+        let peer_limit = tower::util::option_layer(self.options.max_concurrent_per_peer.map(PeerLimitLayer::new));
+        // Optional server-wide queue depth, checked before the per-peer limit
+        let metrics = self.connections.metrics.clone();
+        // The slots are shared by every listener, like the connection limit
+        let queue_depth = tower::util::option_layer(self.options.max_queue_depth.map(|depth| {
+            queue_depth_layer(depth, Arc::new(Semaphore::new(depth)), metrics.clone())
+        }));
 
-        // Configure and start the server with logging interceptor
-        Server::builder()
+        // Bind here instead of in tonic so every accepted socket can be tracked
+        // (same TCP settings as tonic's own listener)
+        // A named pipe replaces the TCP listeners; build() only allows it on Windows
+        let listeners = match self.options.named_pipe {
+            Some(_) => Vec::new(),
+            None => listeners::bind(addr, self.options.reuse_port_listeners)?,
+        };
+        #[cfg(windows)]
+        let named_pipe = self.options.named_pipe.as_deref().map(super::pipe::bind).transpose()?;
+        metrics.listeners_bound(listeners.len());
+        let events = self.connections.events.clone();
+        events.emit(ServerEvent::Started(addr));
+        // Per-connection echo sequences end with their connection
+        let mut connections = self.connections;
+        connections.cleanup.push(Arc::new(move |addr| {
+            sequences.lock().unwrap().remove(&addr);
+        }));
+        // So do the per-connection request counts
+        let counts = connection_count.counts();
+        connections.cleanup.push(Arc::new(move |addr| {
+            counts.lock().unwrap().remove(&addr);
+        }));
+        connections.limit = self.options.max_connections.map(ConnectionLimit::new);
+        let connections = Arc::new(connections);
+        // Optional idle timeout, applied by the task serving each connection
+        let idle = self.options.idle_connection_timeout.map(IdleTracker::new);
+        let idle_layer = tower::util::option_layer(idle.as_ref().map(IdleTracker::layer));
+
+        // Layers shared by every listener
+        let service = Server::builder()
+            // Count every RPC as connection activity, even a rejected one
+            .layer(idle_layer)
+            // Apply policies that must run before any service
+            // (the order is documented in layers/mod.rs)
+            .layer(MetadataLayer)
+            .layer(queue_depth)
+            .layer(InFlightLayer::new(
+                metrics.clone(),
+                &[echo_name, calculator_name, broadcast_name, admin_name],
+                self.options.overload_alarm.clone(),
+            ))
+            .layer(peer_limit)
+            // Time requests that passed the policies above
+            .layer(LatencyLayer::new(latency).slow_requests(self.options.slow_request_threshold, metrics.clone()))
+            // Number the RPCs that reach a service, per connection
+            .layer(connection_count)
+            .layer(peer_identity)
+            .layer(faults)
             // Register our services
             .add_service(echo_service)
             .add_service(calculator_service)
+            .add_service(broadcast_service)
+            .add_service(admin_service)
+            .into_service();
+        let serving = Serving::new(service, move |req| rpc_span(&span, req))
+            .tls(tls)
+            .idle(idle)
+            .max_header_list_size(header_list_size)
+            .request_timeout(self.options.request_timeout);
+
+        // Resolves once shutdown is requested; the first listener reports it
+        // The cause is kept for the return value
+        let requested = Arc::new(OnceLock::new());
+        let shutdown_requested = |report: bool| {
+            let (shutdown, events, requested) = (self.shutdown.clone(), events.clone(), requested.clone());
+            async move {
+                let cause = shutdown.recv().await;
+                let _ = requested.set(cause);
+                // Every listener sees the signal; report it once
+                if report {
+                    match cause {
+                        ShutdownCause::Triggered => info!("Received shutdown signal, stopping gRPC server"),
+                        // Usually a handle that went out of scope by mistake
+                        ShutdownCause::HandlesDropped => warn!(
+                            "Shutdown sender dropped: every Shutdown handle is gone without trigger(), stopping gRPC server"
+                        ),
+                    }
+                    events.emit(ServerEvent::ShutdownRequested);
+                }
+            }
+        };
+
+        // One accept loop per listener, sharing the services and the shutdown signal
+        let mut servers = JoinSet::new();
+        for (index, listener) in listeners.into_iter().enumerate() {
+            let (connections, metrics) = (connections.clone(), metrics.clone());
+            let incoming = listener.filter_map(move |io| match io {
+                Ok(io) => {
+                    metrics.connection_accepted_on(index);
+                    connections.admit(io).map(Ok)
+                }
+                Err(e) => Some(Err(e)),
+            });
             // Start serving with shutdown handler
-            .serve_with_shutdown(addr, async { 
-                self.shutdown.await.ok(); 
-                info!("Received shutdown signal, stopping gRPC server");
-            })
-            .await
-            .map_err(|e| {
-                error!("Server error: {}", e);
-                Status::new(Code::Internal, format!("server error: {}", e))
-            })
+            servers.spawn(serving.clone().serve(incoming, shutdown_requested(index == 0)).in_current_span());
+        }
+        // Or the named pipe, as the only listener
+        #[cfg(windows)]
+        if let Some(incoming) = named_pipe {
+            info!("Serving on named pipe {}", self.options.named_pipe.as_deref().unwrap_or_default());
+            servers.spawn(serving.clone().serve(incoming, shutdown_requested(true)).in_current_span());
+        }
+
+        // Wait for every listener; the first failure stops the others
+        let mut result = Ok(());
+        while let Some(joined) = servers.join_next().await {
+            let error = match joined {
+                Ok(Ok(())) => continue,
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => e.to_string(),
+            };
+            if result.is_ok() {
+                error!("Server error: {}", error);
+                result = Err(Status::new(Code::Internal, format!("server error: {}", error)));
+                servers.abort_all();
+            }
+        }
+
+        events.emit(ServerEvent::Stopped);
+        result?;
+        // No shutdown was requested if every listener just ran out of connections
+        let reason = requested.get().map_or(ShutdownReason::AllListenersClosed, |&cause| cause.into());
+        info!("gRPC server stopped: {}", reason);
+        Ok(reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The parsed trace context reaches handlers through the extensions
+    #[test]
+    fn test_trace_interceptor_stores_context() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut req = Request::new(());
+        req.metadata_mut().insert(TRACEPARENT, header.parse().unwrap());
+
+        let req = trace_interceptor(req).unwrap();
+        let context = req.extensions().get::<TraceContext>().expect("context missing");
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+
+        let mut req = Request::new(());
+        req.metadata_mut().insert(TRACEPARENT, "bogus".parse().unwrap());
+        let req = trace_interceptor(req).expect("malformed header must not be rejected");
+        assert!(req.extensions().get::<TraceContext>().is_none());
+    }
+
+    // Correlation IDs are recorded when valid; no header, or a bad one, never fails the call
+    #[test]
+    fn test_correlation_interceptor_never_rejects() {
+        for value in [Some("order-1234"), Some("has space"), None] {
+            let mut req = Request::new(());
+            if let Some(value) = value {
+                req.metadata_mut().insert(CORRELATION_ID, value.parse().unwrap());
+            }
+            assert!(correlation_interceptor(req).is_ok(), "rejected {:?}", value);
+        }
+    }
+
+    // The resolved identity reaches handlers through the extensions
+    #[test]
+    fn test_auth_interceptor_stores_identity() {
+        use crate::server::{Identity, StaticTokenAuth};
+
+        let auth = StaticTokenAuth::new("s3cret", "alice");
+        let mut req = Request::new(());
+        req.metadata_mut().insert("authorization", "Bearer s3cret".parse().unwrap());
+        let req = auth_interceptor(&auth, req).unwrap();
+        assert_eq!(req.extensions().get::<Identity>(), Some(&Identity::new("alice")));
+
+        let req = auth_interceptor(&NoAuth, Request::new(())).unwrap();
+        assert!(req.extensions().get::<Identity>().unwrap().is_anonymous());
     }
 }
@@ -0,0 +1,111 @@
+//! Service Serving State
+//! Lets operators take one application service offline while the others
+//! keep running, e.g. the calculator during maintenance:
+//! 1. Every application service starts out serving
+//! 2. The admin SetServiceState RPC flips a service's flag
+//! 3. The interceptor of that service answers Unavailable while it is off
+//!
+//! The admin service is never listed, so it can always bring services back.
+//! Watchers (the admin WatchOperations RPC) are woken on every flip.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tonic::Status;
+
+// Serving flag of each application service, by fully qualified name
+// The set of services is fixed when the server starts; only the flags change
+#[derive(Debug, Clone)]
+pub(crate) struct ServiceStates {
+    flags: Arc<HashMap<&'static str, AtomicBool>>,
+    // Ticked whenever a flag actually changes
+    changed: Arc<watch::Sender<()>>,
+}
+
+impl Default for ServiceStates {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl ServiceStates {
+    // All `services` start out serving
+    pub(crate) fn new(services: &[&'static str]) -> Self {
+        Self {
+            flags: Arc::new(services.iter().map(|&name| (name, AtomicBool::new(true))).collect()),
+            changed: Arc::new(watch::channel(()).0),
+        }
+    }
+
+    // Whether `service` handles calls; unlisted services always do
+    pub(crate) fn is_serving(&self, service: &str) -> bool {
+        self.flags.get(service).is_none_or(|serving| serving.load(Ordering::Relaxed))
+    }
+
+    // Woken after every change of a flag
+    pub(crate) fn subscribe(&self) -> watch::Receiver<()> {
+        self.changed.subscribe()
+    }
+
+    // Reject a call to `service` if it was taken offline
+    // Services not listed (the admin service) always pass
+    pub(crate) fn check(&self, service: &str) -> Result<(), Status> {
+        if self.is_serving(service) {
+            Ok(())
+        } else {
+            Err(Status::unavailable(format!("service {} is not serving", service)))
+        }
+    }
+
+    // Take `service` offline or bring it back; NotFound for an unlisted name
+    pub(crate) fn set(&self, service: &str, serving: bool) -> Result<(), Status> {
+        let flag = self.flags.get(service).ok_or_else(|| {
+            let mut known: Vec<_> = self.flags.keys().copied().collect();
+            known.sort_unstable();
+            Status::not_found(format!("unknown service {:?}; expected one of {}", service, known.join(", ")))
+        })?;
+        if flag.swap(serving, Ordering::Relaxed) != serving {
+            self.changed.send_replace(());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    #[test]
+    fn test_service_states_flip_one_service() {
+        let states = ServiceStates::new(&["echo.EchoService", "calculator.CalculatorService"]);
+        assert!(states.check("calculator.CalculatorService").is_ok());
+
+        states.set("calculator.CalculatorService", false).unwrap();
+        let err = states.check("calculator.CalculatorService").unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+        assert!(states.check("echo.EchoService").is_ok());
+        assert!(states.check("admin.AdminService").is_ok(), "unlisted services always pass");
+
+        states.set("calculator.CalculatorService", true).unwrap();
+        assert!(states.check("calculator.CalculatorService").is_ok());
+
+        let err = states.set("admin.AdminService", false).unwrap_err();
+        assert_eq!(err.code(), Code::NotFound);
+        assert!(err.message().contains("calculator.CalculatorService, echo.EchoService"), "{}", err.message());
+    }
+
+    #[test]
+    fn test_service_states_notify_only_on_change() {
+        let states = ServiceStates::new(&["calculator.CalculatorService"]);
+        let changes = states.subscribe();
+
+        states.set("calculator.CalculatorService", true).unwrap();
+        assert!(!changes.has_changed().unwrap(), "setting the current state is not a change");
+
+        states.set("calculator.CalculatorService", false).unwrap();
+        assert!(changes.has_changed().unwrap());
+        assert!(!states.is_serving("calculator.CalculatorService"));
+    }
+}
@@ -0,0 +1,495 @@
+//! Implementation of the Admin gRPC service.
+//! Exposes operational data about the running server, such as request
+//! latency percentiles and runtime settings, to operators and tooling.
+//!
+//! Read-only RPCs are open to any caller. RPCs that change the server
+//! (SetLogLevel, SetConfig, SetServiceState) require the admin token configured on the
+//! builder and are refused outright when none is configured. Every call
+//! to them, refused or not, is recorded in the audit log (see audit.rs).
+
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::metadata::MetadataMap;
+use tonic::server::NamedService;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn, Instrument};
+use serde_json::json;
+use tracing_subscriber::filter::LevelFilter;
+// Import the generated protobuf code for our admin service
+use crate::proto::admin::admin_service_server::AdminService;
+use crate::proto::admin::{
+    LatencyStatsRequest, LatencyStatsResponse, ServerInfoRequest, ServerInfoResponse,
+    SetLogLevelRequest, GetLogLevelRequest, LogLevelResponse,
+    GetConfigRequest, SetConfigRequest, ConfigResponse,
+    SetServiceStateRequest, ServiceStateResponse, ServingState,
+    WatchOperationsRequest, OperationsResponse, CacheStatsRequest, CacheStatsResponse,
+    ListConnectionsRequest, ListConnectionsResponse, ConnectionInfo,
+};
+use crate::proto::calculator::calculator_service_server::CalculatorServiceServer;
+use crate::proto::calculator::Operation;
+use crate::logging::{self, LogLevelError};
+use crate::config::RuntimeConfig;
+use crate::redact::Redacted;
+use crate::server::audit::{AuditEntry, AuditLogger};
+use crate::server::connections::OpenConnections;
+use crate::server::layers::ConnectionCounts;
+use crate::server::latency::LatencyTracker;
+use crate::server::live_config::{LiveConfig, SharedConfig};
+use crate::server::metrics::ServerMetrics;
+use crate::server::service_state::ServiceStates;
+use crate::server::services::CalculatorServer;
+
+// Stream of enabled operation sets returned by WatchOperations
+type OperationsStream = Pin<Box<dyn Stream<Item = Result<OperationsResponse, Status>> + Send>>;
+
+// Every operation the calculator implements, in wire order
+const OPERATIONS: [Operation; 5] = [
+    Operation::Add, Operation::Subtract, Operation::Multiply, Operation::Divide, Operation::Percent,
+];
+
+// Metadata key carrying the admin token ("Bearer <token>")
+const AUTHORIZATION: &str = "authorization";
+
+// Secret required by mutating admin RPCs
+// Debug output never shows the value
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct AdminToken(String);
+
+impl AdminToken {
+    pub(crate) fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    // Check the request's authorization metadata against this token
+    fn verify(&self, metadata: &MetadataMap) -> Result<(), Status> {
+        let presented = metadata
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("admin token required"))?;
+        if presented != self.0 {
+            warn!("Rejected admin request with a wrong token");
+            return Err(Status::permission_denied("invalid admin token"));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for AdminToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AdminToken").field(&Redacted).finish()
+    }
+}
+
+// Admin service state
+// Holds handles to the server-wide trackers it reports on
+#[derive(Debug, Default)]
+pub struct AdminServer {
+    // Shared with the latency layer that records every application RPC
+    latency: Arc<LatencyTracker>,
+    // Runtime settings as given to the server builder
+    runtime: RuntimeConfig,
+    // Required by mutating RPCs; they are disabled when None
+    token: Option<AdminToken>,
+    // Settings shared with the services, changed by SetConfig
+    live_config: SharedConfig,
+    // Serving flags checked by the application services, changed by SetServiceState
+    service_states: ServiceStates,
+    // Audit trail of the mutating RPCs
+    audit: AuditLogger,
+    // Server-wide counters, for the cache hit and miss counts
+    metrics: ServerMetrics,
+    // Accept times and request counts of the open connections
+    open_connections: OpenConnections,
+    connection_counts: ConnectionCounts,
+}
+
+impl AdminServer {
+    // Create an admin service reporting on the given tracker and runtime settings
+    pub(crate) fn new(latency: Arc<LatencyTracker>, runtime: RuntimeConfig, token: Option<AdminToken>) -> Self {
+        Self {
+            latency,
+            runtime,
+            token,
+            live_config: SharedConfig::default(),
+            service_states: ServiceStates::default(),
+            audit: AuditLogger::default(),
+            metrics: ServerMetrics::default(),
+            open_connections: OpenConnections::default(),
+            connection_counts: ConnectionCounts::default(),
+        }
+    }
+
+    // Report and change `config`, the settings the services read
+    pub(crate) fn live_config(mut self, config: SharedConfig) -> Self {
+        self.live_config = config;
+        self
+    }
+
+    // Switch the services behind `states` on and off
+    pub(crate) fn service_states(mut self, states: ServiceStates) -> Self {
+        self.service_states = states;
+        self
+    }
+
+    // Record mutating RPCs with `audit`
+    pub(crate) fn audit(mut self, audit: AuditLogger) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    // Report the cache counts kept in `metrics`
+    pub(crate) fn metrics(mut self, metrics: ServerMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    // List the connections in `open` with their requests from `counts`
+    pub(crate) fn connections(mut self, open: OpenConnections, counts: ConnectionCounts) -> Self {
+        self.open_connections = open;
+        self.connection_counts = counts;
+        self
+    }
+
+    // Allow a mutating RPC only with the configured token
+    fn authorize(&self, metadata: &MetadataMap) -> Result<(), Status> {
+        match &self.token {
+            Some(token) => token.verify(metadata),
+            None => Err(Status::permission_denied("mutating admin RPCs are disabled; configure an admin token")),
+        }
+    }
+
+    // SetLogLevel without the audit entry
+    fn change_log_level(&self, request: Request<SetLogLevelRequest>) -> Result<Response<LogLevelResponse>, Status> {
+        self.authorize(request.metadata())?;
+        let req = request.into_inner();
+        let level: LevelFilter = req.level.trim().parse().map_err(|_| Status::invalid_argument(format!(
+            "unknown log level {:?}; expected off, error, warn, info, debug or trace",
+            req.level
+        )))?;
+        let target = Some(req.target.trim()).filter(|target| !target.is_empty());
+
+        info!("Setting log level of {} to {}", target.unwrap_or("the default"), level);
+        logging::set_level(target, level).map_err(log_level_status)?;
+        let filter = logging::current_filter().map_err(log_level_status)?;
+        Ok(Response::new(LogLevelResponse { filter }))
+    }
+
+    // SetConfig without the audit entry
+    fn change_config(&self, request: Request<SetConfigRequest>) -> Result<Response<ConfigResponse>, Status> {
+        self.authorize(request.metadata())?;
+        let req = request.into_inner();
+        let echo_max_chars = req.echo_max_chars
+            .map(|limit| usize::try_from(limit)
+                .map_err(|_| Status::invalid_argument(format!("echo_max_chars {} is too large", limit))))
+            .transpose()?;
+
+        let previous = self.live_config.rcu(|current| {
+            let mut next = LiveConfig::clone(current);
+            if let Some(allowed) = req.allow_empty_echo {
+                next.allow_empty_echo = allowed;
+            }
+            if let Some(limit) = echo_max_chars {
+                next.echo_max_chars = Some(limit).filter(|&limit| limit > 0);
+            }
+            next
+        });
+        let current = self.live_config.load();
+        info!("Changed runtime settings from {:?} to {:?}", previous, current);
+        Ok(Response::new(config_response(&current)))
+    }
+
+    // SetServiceState without the audit entry
+    fn change_service_state(
+        &self,
+        request: Request<SetServiceStateRequest>,
+    ) -> Result<Response<ServiceStateResponse>, Status> {
+        self.authorize(request.metadata())?;
+        let req = request.into_inner();
+        let state = ServingState::try_from(req.state)
+            .map_err(|_| Status::invalid_argument(format!("unknown serving state {}", req.state)))?;
+
+        self.service_states.set(&req.service, state == ServingState::Serving)?;
+        info!("Service {} is now {}", req.service, state.as_str_name());
+        Ok(Response::new(ServiceStateResponse { service: req.service, state: state.into() }))
+    }
+}
+
+// Map logging errors onto gRPC codes
+fn log_level_status(error: LogLevelError) -> Status {
+    match error {
+        LogLevelError::InvalidDirective(_) => Status::invalid_argument(error.to_string()),
+        LogLevelError::NotInitialized => Status::failed_precondition(error.to_string()),
+    }
+}
+
+// Settings as reported on the wire; 0 means no echo limit
+fn config_response(config: &LiveConfig) -> ConfigResponse {
+    ConfigResponse {
+        allow_empty_echo: config.allow_empty_echo,
+        echo_max_chars: config.echo_max_chars.map_or(0, |limit| limit as u64),
+    }
+}
+
+// Operations the calculator accepts right now; none while it is offline
+fn enabled_operations(states: &ServiceStates) -> OperationsResponse {
+    let calculator = <CalculatorServiceServer<CalculatorServer> as NamedService>::NAME;
+    let operations = if states.is_serving(calculator) { &OPERATIONS[..] } else { &[] };
+    OperationsResponse { operations: operations.iter().map(|&operation| operation.into()).collect() }
+}
+
+// Fractional milliseconds, as reported on the wire
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// Whole milliseconds since the Unix epoch; 0 for a clock set before it
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| u64::try_from(since.as_millis()).unwrap_or(u64::MAX))
+}
+
+#[tonic::async_trait]
+impl AdminService for AdminServer {
+    /// Report latency percentiles of recent application RPCs
+    ///
+    /// # Arguments
+    /// * `_request` - An empty LatencyStatsRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<LatencyStatsResponse>, Status>` - The p50/p90/p99 latencies in milliseconds.
+    async fn latency_stats(
+        &self,
+        _request: Request<LatencyStatsRequest>,
+    ) -> Result<Response<LatencyStatsResponse>, Status> {
+        let stats = self.latency.percentiles();
+        info!("Reporting latency stats over {} requests", stats.count);
+        Ok(Response::new(LatencyStatsResponse {
+            p50_ms: millis(stats.p50),
+            p90_ms: millis(stats.p90),
+            p99_ms: millis(stats.p99),
+            count: stats.count as u64,
+        }))
+    }
+
+    /// Report the server version and the runtime it is running on
+    ///
+    /// The worker thread count is read from the live runtime; the blocking
+    /// limit and thread name prefix are the configured values.
+    ///
+    /// # Arguments
+    /// * `_request` - An empty ServerInfoRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<ServerInfoResponse>, Status>` - The server information.
+    async fn server_info(
+        &self,
+        _request: Request<ServerInfoRequest>,
+    ) -> Result<Response<ServerInfoResponse>, Status> {
+        let workers = tokio::runtime::Handle::current().metrics().num_workers();
+        info!("Reporting server info");
+        Ok(Response::new(ServerInfoResponse {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            worker_threads: u32::try_from(workers).unwrap_or(u32::MAX),
+            max_blocking_threads: u32::try_from(self.runtime.effective_max_blocking_threads()).unwrap_or(u32::MAX),
+            thread_name_prefix: self.runtime.effective_thread_name_prefix().to_string(),
+        }))
+    }
+
+    /// Change the server's log level
+    ///
+    /// # Arguments
+    /// * `request` - A SetLogLevelRequest carrying the admin token in its metadata.
+    ///
+    /// # Returns
+    /// * `Result<Response<LogLevelResponse>, Status>` - The new filter, `Unauthenticated` or
+    ///   `PermissionDenied` for a missing or wrong token, `InvalidArgument` for a bad target or level,
+    ///   `Internal` if the call cannot be audited.
+    async fn set_log_level(
+        &self,
+        request: Request<SetLogLevelRequest>,
+    ) -> Result<Response<LogLevelResponse>, Status> {
+        let parameters = json!({ "target": request.get_ref().target, "level": request.get_ref().level });
+        let entry = AuditEntry::new("SetLogLevel", request.remote_addr(), parameters);
+        let result = self.change_log_level(request);
+        self.audit.record(entry, &result).await?;
+        result
+    }
+
+    /// Report the server's current log filter
+    ///
+    /// # Arguments
+    /// * `_request` - An empty GetLogLevelRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<LogLevelResponse>, Status>` - The filter in RUST_LOG syntax.
+    async fn get_log_level(
+        &self,
+        _request: Request<GetLogLevelRequest>,
+    ) -> Result<Response<LogLevelResponse>, Status> {
+        let filter = logging::current_filter().map_err(log_level_status)?;
+        Ok(Response::new(LogLevelResponse { filter }))
+    }
+
+    /// Report the runtime-adjustable settings currently in effect
+    ///
+    /// # Arguments
+    /// * `_request` - An empty GetConfigRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<ConfigResponse>, Status>` - The settings.
+    async fn get_config(
+        &self,
+        _request: Request<GetConfigRequest>,
+    ) -> Result<Response<ConfigResponse>, Status> {
+        Ok(Response::new(config_response(&self.live_config.load())))
+    }
+
+    /// Change runtime-adjustable settings; unset fields keep their value
+    ///
+    /// The new settings are swapped in as a whole, so a request being
+    /// handled meanwhile sees either the old or the new settings.
+    ///
+    /// # Arguments
+    /// * `request` - A SetConfigRequest carrying the admin token in its metadata.
+    ///
+    /// # Returns
+    /// * `Result<Response<ConfigResponse>, Status>` - The settings now in effect, `Unauthenticated`
+    ///   or `PermissionDenied` for a missing or wrong token, `InvalidArgument` for a limit too large,
+    ///   `Internal` if the call cannot be audited.
+    async fn set_config(
+        &self,
+        request: Request<SetConfigRequest>,
+    ) -> Result<Response<ConfigResponse>, Status> {
+        let parameters = json!({
+            "allow_empty_echo": request.get_ref().allow_empty_echo,
+            "echo_max_chars": request.get_ref().echo_max_chars,
+        });
+        let entry = AuditEntry::new("SetConfig", request.remote_addr(), parameters);
+        let result = self.change_config(request);
+        self.audit.record(entry, &result).await?;
+        result
+    }
+
+    /// Take one application service offline or bring it back
+    ///
+    /// Calls already running finish normally; later calls to a service that
+    /// is not serving fail with `Unavailable`. The admin service itself
+    /// cannot be switched off.
+    ///
+    /// # Arguments
+    /// * `request` - A SetServiceStateRequest carrying the admin token in its metadata.
+    ///
+    /// # Returns
+    /// * `Result<Response<ServiceStateResponse>, Status>` - The state now in effect, `Unauthenticated`
+    ///   or `PermissionDenied` for a missing or wrong token, `NotFound` for an unknown service,
+    ///   `InvalidArgument` for an unknown state, `Internal` if the call cannot be audited.
+    async fn set_service_state(
+        &self,
+        request: Request<SetServiceStateRequest>,
+    ) -> Result<Response<ServiceStateResponse>, Status> {
+        let parameters = json!({ "service": request.get_ref().service, "state": request.get_ref().state });
+        let entry = AuditEntry::new("SetServiceState", request.remote_addr(), parameters);
+        let result = self.change_service_state(request);
+        self.audit.record(entry, &result).await?;
+        result
+    }
+
+    type WatchOperationsStream = OperationsStream;
+
+    /// Stream the calculator operations the server accepts
+    ///
+    /// The current set is sent immediately, then again each time it
+    /// changes, until the client goes away or the server shuts down.
+    ///
+    /// # Arguments
+    /// * `_request` - An empty WatchOperationsRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<Self::WatchOperationsStream>, Status>` - The enabled operations after each change.
+    async fn watch_operations(
+        &self,
+        _request: Request<WatchOperationsRequest>,
+    ) -> Result<Response<Self::WatchOperationsStream>, Status> {
+        let states = self.service_states.clone();
+        let mut changes = states.subscribe();
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            let mut last = None;
+            loop {
+                // Other services flipping wake us too; only send real changes
+                let current = enabled_operations(&states);
+                if last.as_ref() != Some(&current) {
+                    if tx.send(Ok(current.clone())).await.is_err() {
+                        break;
+                    }
+                    last = Some(current);
+                }
+                tokio::select! {
+                    changed = changes.changed() => if changed.is_err() { break },
+                    _ = tx.closed() => break,
+                }
+            }
+        }.in_current_span());
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    /// Report hit and miss counts of the response caches
+    ///
+    /// # Arguments
+    /// * `_request` - An empty CacheStatsRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<CacheStatsResponse>, Status>` - The counts since the server started.
+    async fn cache_stats(
+        &self,
+        _request: Request<CacheStatsRequest>,
+    ) -> Result<Response<CacheStatsResponse>, Status> {
+        let snapshot = self.metrics.snapshot();
+        Ok(Response::new(CacheStatsResponse {
+            calculator_hits: snapshot.cache_hits,
+            calculator_misses: snapshot.cache_misses,
+            echo_hits: snapshot.echo_cache_hits,
+            echo_misses: snapshot.echo_cache_misses,
+        }))
+    }
+
+    /// List the open client connections
+    ///
+    /// A connection that has not sent a request yet is listed with a count
+    /// of zero; the ListConnections call itself counts towards its own connection.
+    ///
+    /// # Arguments
+    /// * `_request` - An empty ListConnectionsRequest.
+    ///
+    /// # Returns
+    /// * `Result<Response<ListConnectionsResponse>, Status>` - Peer, accept time and
+    ///   request count of each connection, oldest first.
+    async fn list_connections(
+        &self,
+        _request: Request<ListConnectionsRequest>,
+    ) -> Result<Response<ListConnectionsResponse>, Status> {
+        let mut open: Vec<_> = self.open_connections
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&peer, &since)| (since, peer))
+            .collect();
+        open.sort();
+        let counts = self.connection_counts.lock().unwrap();
+        let connections = open
+            .into_iter()
+            .map(|(since, peer)| ConnectionInfo {
+                peer: peer.to_string(),
+                connected_at_ms: unix_millis(since),
+                request_count: counts.get(&peer).copied().unwrap_or(0),
+            })
+            .collect();
+        Ok(Response::new(ListConnectionsResponse { connections }))
+    }
+}
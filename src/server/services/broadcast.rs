@@ -0,0 +1,192 @@
+//! Broadcast Echo Service
+//! Publish/subscribe for chat room style demos:
+//! 1. Subscribe: a server stream of every message published to a topic
+//!    after the subscription started
+//! 2. Publish: sends one message to every current subscriber of its topic
+//!
+//! Each topic is a tokio broadcast channel, created by its first
+//! subscriber and removed when its last one leaves. A subscriber that
+//! falls more than TOPIC_CAPACITY messages behind skips the ones it
+//! missed, with a warning, instead of holding up the others.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::{Request, Response, Status};
+use tracing::{debug, error, info, warn, Instrument};
+use crate::proto::broadcast::broadcast_service_server::BroadcastService;
+use crate::proto::broadcast::{BroadcastMessage, PublishRequest, PublishResponse, SubscribeRequest};
+use crate::server::shutdown::ShutdownSignal;
+
+// Messages a topic holds for its slowest subscriber before that one skips
+const TOPIC_CAPACITY: usize = 64;
+
+// Stream of published messages returned by Subscribe
+type MessageStream = Pin<Box<dyn Stream<Item = Result<BroadcastMessage, Status>> + Send>>;
+
+// Channel of every topic that has subscribers
+type Topics = Arc<Mutex<HashMap<String, broadcast::Sender<BroadcastMessage>>>>;
+
+#[derive(Debug)]
+pub(crate) struct BroadcastServer {
+    topics: Topics,
+    // Messages each topic buffers; TOPIC_CAPACITY outside tests
+    capacity: usize,
+    // Ends the subscriptions, which would otherwise hold up a graceful shutdown
+    shutdown: ShutdownSignal,
+}
+
+impl BroadcastServer {
+    pub(crate) fn new(shutdown: ShutdownSignal) -> Self {
+        Self { topics: Topics::default(), capacity: TOPIC_CAPACITY, shutdown }
+    }
+
+    // Topics are named by their clients; an empty name is a mistake
+    fn check_topic(topic: &str) -> Result<(), Status> {
+        if topic.is_empty() {
+            error!("Received broadcast request without a topic");
+            return Err(Status::invalid_argument("topic must not be empty"));
+        }
+        Ok(())
+    }
+}
+
+// Remove `topic` if its last subscriber has gone
+fn forget_if_unused(topics: &Topics, topic: &str) {
+    let mut topics = topics.lock().unwrap();
+    if topics.get(topic).is_some_and(|sender| sender.receiver_count() == 0) {
+        topics.remove(topic);
+        debug!("Removed broadcast topic {} without subscribers", topic);
+    }
+}
+
+#[tonic::async_trait]
+impl BroadcastService for BroadcastServer {
+    type SubscribeStream = MessageStream;
+
+    /// Stream the messages published to a topic from now on
+    ///
+    /// The subscription is registered before the response is returned, so
+    /// a publish made after `Subscribe` returned always reaches it.
+    ///
+    /// # Arguments
+    /// * `request` - A SubscribeRequest naming the topic.
+    ///
+    /// # Returns
+    /// * `Result<Response<Self::SubscribeStream>, Status>` - One message per publish,
+    ///   or `InvalidArgument` for an empty topic.
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let topic = request.into_inner().topic;
+        Self::check_topic(&topic)?;
+        let mut messages = self.topics
+            .lock()
+            .unwrap()
+            .entry(topic.clone())
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .subscribe();
+        info!("Client subscribed to broadcast topic {}", topic);
+
+        let (topics, shutdown) = (self.topics.clone(), self.shutdown.clone());
+        let (tx, rx) = mpsc::channel(1);
+        // Forwarded within the RPC's span, so the subscription's log lines keep its fields
+        tokio::spawn(async move {
+            let shutdown = shutdown.recv();
+            tokio::pin!(shutdown);
+            loop {
+                tokio::select! {
+                    received = messages.recv() => match received {
+                        Ok(message) => if tx.send(Ok(message)).await.is_err() { break },
+                        Err(RecvError::Lagged(skipped)) => {
+                            warn!("Subscriber of broadcast topic {} fell behind, skipped {} messages", topic, skipped);
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = tx.closed() => break,
+                    _ = &mut shutdown => break,
+                }
+            }
+            drop(messages);
+            forget_if_unused(&topics, &topic);
+            info!("Client left broadcast topic {}", topic);
+        }.in_current_span());
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    /// Send a message to every current subscriber of its topic
+    ///
+    /// # Arguments
+    /// * `request` - A PublishRequest with the topic and the message.
+    ///
+    /// # Returns
+    /// * `Result<Response<PublishResponse>, Status>` - The number of subscribers it was sent to
+    ///   (zero for a topic nobody follows), or `InvalidArgument` for an empty topic.
+    async fn publish(
+        &self,
+        request: Request<PublishRequest>,
+    ) -> Result<Response<PublishResponse>, Status> {
+        let PublishRequest { topic, message } = request.into_inner();
+        Self::check_topic(&topic)?;
+        let sender = self.topics.lock().unwrap().get(&topic).cloned();
+        // send fails only when every subscriber has just left
+        let subscribers = sender
+            .and_then(|sender| sender.send(BroadcastMessage { topic: topic.clone(), message }).ok())
+            .unwrap_or(0);
+        debug!("Published to broadcast topic {} with {} subscribers", topic, subscribers);
+        Ok(Response::new(PublishResponse { subscribers: subscribers as u64 }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+    use crate::server::Shutdown;
+
+    fn publish(topic: &str, message: &str) -> Request<PublishRequest> {
+        Request::new(PublishRequest { topic: topic.into(), message: message.into() })
+    }
+
+    // A subscriber that falls behind skips to the messages still buffered
+    #[tokio::test]
+    async fn test_lagged_subscriber_skips_missed_messages() {
+        let shutdown = Shutdown::new();
+        let server = BroadcastServer { capacity: 2, ..BroadcastServer::new(shutdown.signal()) };
+        let request = Request::new(SubscribeRequest { topic: "room".into() });
+        let mut stream = server.subscribe(request).await.unwrap().into_inner();
+
+        // The forwarding task only runs once the test yields, so all six
+        // are published before it reads any
+        for i in 0..6 {
+            let response = server.publish(publish("room", &i.to_string())).await.unwrap().into_inner();
+            assert_eq!(response.subscribers, 1);
+        }
+        let received: Vec<String> = vec![
+            stream.next().await.unwrap().unwrap().message,
+            stream.next().await.unwrap().unwrap().message,
+        ];
+        assert_eq!(received, ["4", "5"]);
+    }
+
+    // Topics go away with their last subscriber, and so do subscriptions at shutdown
+    #[tokio::test]
+    async fn test_topic_removed_after_last_subscriber() {
+        let shutdown = Shutdown::new();
+        let server = BroadcastServer::new(shutdown.signal());
+        let request = Request::new(SubscribeRequest { topic: "room".into() });
+        let mut stream = server.subscribe(request).await.unwrap().into_inner();
+        assert_eq!(server.topics.lock().unwrap().len(), 1);
+
+        shutdown.trigger();
+        assert!(stream.next().await.is_none());
+        assert!(server.topics.lock().unwrap().is_empty());
+        let response = server.publish(publish("room", "anyone?")).await.unwrap().into_inner();
+        assert_eq!(response.subscribers, 0);
+    }
+}
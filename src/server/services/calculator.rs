@@ -5,22 +5,274 @@
 //! 2. Pattern matching in Rust
 //! 3. Input validation
 //! 4. Unit testing async code
+//! 5. Per-stream state in a bidirectional streaming RPC
+//! 6. Exact decimal arithmetic for operands given as strings
+//! 7. Evaluating whole expressions planned by crate::expr
+//! 8. Optional result caching for repeated identical calculations
+//! 9. Left folds of a list with one operation
+//! 10. Rejecting unknown operations and non-finite numbers instead of guessing
+//! 11. Structured error details (CalculatorErrorDetail) on every rejection
+//! 12. Giving up with DeadlineExceeded once the client's deadline has passed
+//! 13. A configurable policy for subnormal operands (allow, flush to zero, reject)
+//! 14. Running statistics over a streamed dataset (Welford's algorithm)
+//! 15. A history of successful calculations in a pluggable store (GetHistory)
 
-use tonic::{Request, Response, Status, Code};
-use tracing::{info, error};
+use std::num::FpCategory;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Code, Streaming};
+use tracing::{info, error, Instrument};
 // Import generated Protocol Buffer code
 // CalculatorService: The trait we need to implement
 // CalculateRequest/Response: The message types for our RPC
 // Operation: Enum defining supported mathematical operations
+use crate::expr;
+use crate::proto::validation::{self, Validate};
+use crate::server::cache::{Cached, CalculationCache};
+use crate::server::deadline::Deadline;
+use crate::server::history::{History, HistoryEntry};
+use crate::server::layers::ConnectionRequestCount;
+use crate::server::streams::{self, SendFailure, StreamSettings};
 use crate::proto::calculator::calculator_service_server::CalculatorService;
-use crate::proto::calculator::{CalculateRequest, CalculateResponse, Operation};
+use crate::proto::calculator::calculator_error_detail::Kind;
+use crate::proto::calculator::{
+    CalculatorErrorDetail,
+    CalculateRequest, CalculateResponse, Operation,
+    CalculateDecimalRequest, CalculateDecimalResponse,
+    EvaluateRequest, EvaluateResponse,
+    ReduceRequest, ReduceResponse,
+    AggregateRequest, AggregateResponse, AggregateValue, AggregateProgress,
+    RunningTotalStep, RunningTotalResponse,
+    GetHistoryRequest, GetHistoryResponse, HistoryEntry as HistoryEntryMessage,
+};
+
+// Long loops check the client's deadline once per this many items
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+// Entries GetHistory returns when the request does not say
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+// Boxed stream type returned by the running total
+type RunningTotalStream = Pin<Box<dyn Stream<Item = Result<RunningTotalResponse, Status>> + Send>>;
+// Boxed stream type returned by the streaming aggregate
+type AggregateProgressStream = Pin<Box<dyn Stream<Item = Result<AggregateProgress, Status>> + Send>>;
+
+// Status carrying a CalculatorErrorDetail of `kind`
+fn failure(code: Code, message: impl Into<String>, kind: Kind, operand: Option<u32>) -> Status {
+    CalculatorErrorDetail::new(kind, operand).to_status(code, message)
+}
+
+// Same failure with more context in the message, keeping its code and kind
+// The operand index is replaced, as it is relative to the failing step
+fn in_context(status: Status, message: String, operand: Option<u32>) -> Status {
+    let mut detail = CalculatorErrorDetail::from_status(&status).unwrap_or_default();
+    detail.operand = operand;
+    detail.to_status(status.code(), message)
+}
+
+// Decode an operation from the wire (see validation::operation)
+fn operation(value: i32) -> Result<Operation, Status> {
+    validation::operation(value).inspect_err(|_| error!("Unknown operation {}", value))
+}
+
+/// What Calculate does with a subnormal (denormal) operand
+///
+/// Subnormals are finite but slow on some hardware and carry less
+/// precision than normal numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubnormalPolicy {
+    /// Calculate with the value as given
+    #[default]
+    Allow,
+    /// Treat the operand as zero, keeping its sign
+    FlushToZero,
+    /// Fail with InvalidArgument
+    Reject,
+}
+
+// Apply `policy` to an operand, naming it in the error
+fn check_subnormal(policy: SubnormalPolicy, name: &str, operand: u32, value: f64) -> Result<f64, Status> {
+    if value.classify() != FpCategory::Subnormal {
+        return Ok(value);
+    }
+    match policy {
+        SubnormalPolicy::Allow => Ok(value),
+        SubnormalPolicy::FlushToZero => Ok(0.0f64.copysign(value)),
+        SubnormalPolicy::Reject => {
+            error!("Subnormal {}: {:e}", name, value);
+            Err(failure(
+                Code::InvalidArgument,
+                format!("{} must not be subnormal, got {:e}", name, value),
+                Kind::InvalidInput,
+                Some(operand),
+            ))
+        }
+    }
+}
+
+// Incremental statistics of a stream of values
+// Welford's update keeps mean and variance accurate without storing the
+// values or summing their squares
+#[derive(Debug, Default)]
+struct RunningStats {
+    count: u64,
+    sum: f64,
+    mean: f64,
+    // Sum of squared differences from the current mean
+    m2: f64,
+}
+
+impl RunningStats {
+    // Add one value and report the statistics so far
+    fn push(&mut self, value: f64) -> AggregateProgress {
+        self.count += 1;
+        self.sum += value;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+        AggregateProgress {
+            count: self.count,
+            sum: self.sum,
+            mean: self.mean,
+            variance: self.m2 / self.count as f64,
+        }
+    }
+}
+
+// Apply one arithmetic operation
+// Shared by the unary calculate and the running total
+fn apply(first: f64, second: f64, operation: Operation) -> Result<f64, Status> {
+    match operation {
+        // Basic arithmetic operations
+        Operation::Add => Ok(first + second),
+        Operation::Subtract => Ok(first - second),
+        Operation::Multiply => Ok(first * second),
+        // Scaling the percentage first keeps 200 PERCENT 50 exact
+        Operation::Percent => Ok(first * (second / 100.0)),
+        Operation::Divide => {
+            // Division needs special handling for division by zero
+            // This is a common source of runtime errors that we validate
+            // (IEEE 754 equality also matches -0.0 here)
+            if second == 0.0 {
+                error!("Division by zero attempted");
+                Err(failure(
+                    Code::InvalidArgument,
+                    "division by zero is not allowed",
+                    Kind::DivisionByZero,
+                    Some(1),
+                ))
+            } else {
+                Ok(first / second)
+            }
+        }
+    }
+}
+
+// Undo `operation` on its `result`, recovering the first operand up to rounding
+// None when there is no inverse (multiplying by zero, 0 percent) or it is not finite
+fn inverse(result: f64, second: f64, operation: Operation) -> Option<f64> {
+    let inverse = match operation {
+        Operation::Add => result - second,
+        Operation::Subtract => result + second,
+        Operation::Multiply if second == 0.0 => return None,
+        Operation::Multiply => result / second,
+        Operation::Divide => result * second,
+        Operation::Percent if second == 0.0 => return None,
+        Operation::Percent => result / (second / 100.0),
+    };
+    inverse.is_finite().then_some(inverse)
+}
+
+// Parse a decimal operand, naming it in the error
+fn parse_decimal(name: &str, operand: u32, value: &str) -> Result<Decimal, Status> {
+    let value = value.trim();
+    Decimal::from_str(value)
+        .or_else(|_| Decimal::from_scientific(value))
+        .map_err(|e| failure(
+            Code::InvalidArgument,
+            format!("{} {:?} is not a valid decimal: {}", name, value, e),
+            Kind::InvalidInput,
+            Some(operand),
+        ))
+}
+
+// Apply one arithmetic operation on exact decimals
+// Unlike f64, overflow is possible and reported as OutOfRange
+fn apply_decimal(first: Decimal, second: Decimal, operation: Operation) -> Result<Decimal, Status> {
+    let result = match operation {
+        Operation::Add => first.checked_add(second),
+        Operation::Subtract => first.checked_sub(second),
+        Operation::Multiply => first.checked_mul(second),
+        Operation::Divide => {
+            if second.is_zero() {
+                error!("Division by zero attempted");
+                return Err(failure(
+                    Code::InvalidArgument,
+                    "division by zero is not allowed",
+                    Kind::DivisionByZero,
+                    Some(1),
+                ));
+            }
+            first.checked_div(second)
+        }
+        Operation::Percent => second.checked_div(Decimal::ONE_HUNDRED).and_then(|share| first.checked_mul(share)),
+    };
+    result.ok_or_else(|| failure(
+        Code::OutOfRange,
+        format!("{} {:?} {} overflows the decimal range", first, operation, second),
+        Kind::Overflow,
+        None,
+    ))
+}
 
 // CalculatorServer is our service implementation
 // #[derive(Debug, Default)] automatically implements:
 // - Debug: for debugging output formatting
 // - Default: allows creating new instances with default values
 #[derive(Debug, Default)]
-pub struct CalculatorServer {}
+pub struct CalculatorServer {
+    // Results of recent calculate requests; None when caching is off
+    cache: Option<CalculationCache>,
+    // Outbound buffer size and overflow policy of the running total
+    streams: StreamSettings,
+    // Most totals ever queued in one outbound stream
+    stream_high_water: Arc<AtomicUsize>,
+    // Handling of subnormal Calculate operands
+    subnormals: SubnormalPolicy,
+    // Successful calculate requests, for GetHistory
+    history: History,
+}
+
+impl CalculatorServer {
+    // Answer repeated calculate requests from `cache`
+    pub(crate) fn with_cache(mut self, cache: CalculationCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // Buffer the running total's responses as `settings` say
+    pub(crate) fn streams(mut self, settings: StreamSettings) -> Self {
+        self.streams = settings;
+        self
+    }
+
+    // Handle subnormal Calculate operands as `policy` says
+    pub(crate) fn subnormals(mut self, policy: SubnormalPolicy) -> Self {
+        self.subnormals = policy;
+        self
+    }
+
+    // Record calculations in `history` instead of the default in-memory ring
+    pub(crate) fn history(mut self, history: History) -> Self {
+        self.history = history;
+        self
+    }
+}
 
 // tonic::async_trait allows us to use async functions in trait implementations
 // This is needed because Rust's native traits don't support async functions yet
@@ -28,46 +280,439 @@ pub struct CalculatorServer {}
 impl CalculatorService for CalculatorServer {
     /// Calculate method that performs basic arithmetic operations
     /// 
+    /// The result is always finite: NaN or infinite operands and unknown
+    /// operations are rejected, and overflow is reported rather than returned.
+    /// Subnormal operands are allowed, flushed to zero or rejected as the
+    /// server's SubnormalPolicy says. With `include_inverse` the response
+    /// also carries the inverse operation applied to the result, when there
+    /// is one.
+    /// 
     /// # Arguments
     /// * `request` - A gRPC request containing a CalculateRequest message.
     /// 
     /// # Returns
-    /// * `Result<Response<CalculateResponse>, Status>` - A result containing the CalculateResponse or an error status.
+    /// * `Result<Response<CalculateResponse>, Status>` - The result, `InvalidArgument` for an unknown
+    ///   operation, a non-finite operand or division by zero, or `OutOfRange` on overflow.
     async fn calculate(
         &self,
         request: Request<CalculateRequest>,
     ) -> Result<Response<CalculateResponse>, Status> {
         // Extract the actual request data from the gRPC request wrapper
+        let connection_count = request.extensions().get::<ConnectionRequestCount>().copied();
         let req = request.into_inner();
 
-        info!("Received calculate request: {} {:?} {}", req.first_number, req.operation(), req.second_number);
-        // Pattern matching in Rust - a powerful way to handle different cases
-        // The '?' operator at the end propagates any Err returned from the match
-        let result = match req.operation() {
-            // Basic arithmetic operations
-            Operation::Add => Ok(req.first_number + req.second_number),
-            Operation::Subtract => Ok(req.first_number - req.second_number),
-            Operation::Multiply => Ok(req.first_number * req.second_number),
-            Operation::Divide => {
-                // Division needs special handling for division by zero
-                // This is a common source of runtime errors that we validate
-                if req.second_number == 0.0 {
-                    error!("Division by zero attempted");
-                    Err(Status::new(
+        info!("Received calculate request: {} {:?} {}", req.first_number, req.operation, req.second_number);
+        // The same rules the client checked; not every client does
+        req.validate().inspect_err(|e| error!("Rejected calculate request: {}", e.message()))?;
+        // The ? operator unwraps Ok values and returns Err values
+        let (first, second, operation) = (req.first_number, req.second_number, operation(req.operation)?);
+        let first = check_subnormal(self.subnormals, "first_number", 0, first)?;
+        let second = check_subnormal(self.subnormals, "second_number", 1, second)?;
+        let cached = match &self.cache {
+            Some(cache) => cache.get_or_compute(first, second, operation, || apply(first, second, operation))?,
+            None => Cached { result: apply(first, second, operation)?, hit: false },
+        };
+        let result = cached.result;
+        // Finite operands can still overflow, e.g. 1e308 * 10
+        if !result.is_finite() {
+            error!("Calculation overflowed: {} {:?} {}", first, operation, second);
+            return Err(failure(
+                Code::OutOfRange,
+                format!("{} {:?} {} overflows", first, operation, second),
+                Kind::Overflow,
+                None,
+            ));
+        }
+
+        // Left unset for operations that cannot be undone
+        let inverse = if req.include_inverse { inverse(result, second, operation) } else { None };
+        if req.include_inverse && inverse.is_none() {
+            info!("No inverse for {} {:?} {}", first, operation, second);
+        }
+
+        info!("Sending calculate response: {}", result);
+        // Construct the successful response
+        let mut response = Response::new(CalculateResponse {
+            result,
+            inverse,
+        });
+        if let Some(count) = connection_count {
+            count.insert_into(response.metadata_mut());
+        }
+        // A cache hit only counts as a request; its history entry was
+        // recorded when it was computed
+        if cached.hit {
+            return Ok(response);
+        }
+
+        // A store failure is logged there and never fails the calculation
+        self.history.record(HistoryEntry::new(first, second, operation, result)).await;
+        Ok(response)
+    }
+
+    /// Aggregate method that computes summary statistics over a dataset
+    /// 
+    /// Empty datasets are rejected since mean/min/max are undefined for them.
+    /// NaN values are rejected rather than ignored: silently dropping them
+    /// would make `count` disagree with what the client sent. Infinities are
+    /// accepted and propagate through the statistics as IEEE 754 defines.
+    /// Huge datasets are abandoned with `DeadlineExceeded` once the client's
+    /// deadline has passed.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request containing an AggregateRequest message.
+    /// 
+    /// # Returns
+    /// * `Result<Response<AggregateResponse>, Status>` - A result containing the statistics or an error status.
+    async fn aggregate(
+        &self,
+        request: Request<AggregateRequest>,
+    ) -> Result<Response<AggregateResponse>, Status> {
+        let deadline = Deadline::of(&request);
+        let values = request.into_inner().values;
+        info!("Received aggregate request with {} values", values.len());
+
+        if values.is_empty() {
+            error!("Aggregate requested over an empty dataset");
+            return Err(failure(
+                Code::InvalidArgument,
+                "cannot aggregate an empty dataset",
+                Kind::EmptyInput,
+                None,
+            ));
+        }
+        if let Some(index) = values.iter().position(|v| v.is_nan()) {
+            error!("Aggregate dataset contains NaN at index {}", index);
+            return Err(failure(
+                Code::InvalidArgument,
+                format!("dataset contains NaN at index {}", index),
+                Kind::NonFiniteOperand,
+                Some(index as u32),
+            ));
+        }
+
+        // Single pass over the data for all statistics
+        let (mut sum, mut min, mut max) = (0.0, f64::INFINITY, f64::NEG_INFINITY);
+        for chunk in values.chunks(DEADLINE_CHECK_INTERVAL) {
+            deadline.check()?;
+            (sum, min, max) = chunk.iter().fold((sum, min, max), |(sum, min, max), &v| (sum + v, min.min(v), max.max(v)));
+        }
+        let count = values.len() as u64;
+        let response = AggregateResponse {
+            sum,
+            mean: sum / count as f64,
+            min,
+            max,
+            count,
+        };
+
+        info!("Sending aggregate response: {:?}", response);
+        Ok(Response::new(response))
+    }
+
+    // Associated stream type for the streaming aggregate
+    type AggregateStreamStream = AggregateProgressStream;
+
+    /// Streaming aggregate method that reports statistics after every value
+    /// 
+    /// Each inbound value is answered with the count, sum, mean and variance
+    /// of all values so far; the last response matches `aggregate` over the
+    /// whole dataset. Non-finite values end the stream with `InvalidArgument`
+    /// naming their index, since the incremental mean is undefined for them.
+    /// An empty inbound stream ends without any response.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request wrapping the inbound stream of AggregateValue messages.
+    /// 
+    /// # Returns
+    /// * `Result<Response<Self::AggregateStreamStream>, Status>` - A result containing the stream of statistics or an error status.
+    async fn aggregate_stream(
+        &self,
+        request: Request<Streaming<AggregateValue>>,
+    ) -> Result<Response<Self::AggregateStreamStream>, Status> {
+        let mut inbound = request.into_inner();
+        let (tx, rx) = streams::channel(self.streams, &self.stream_high_water);
+
+        info!("Started streaming aggregate");
+        tokio::spawn(async move {
+            let mut stats = RunningStats::default();
+            while let Some(value) = inbound.next().await {
+                let value = match value {
+                    Ok(value) => value.value,
+                    Err(status) => {
+                        error!("Streaming aggregate inbound stream failed: {}", status);
+                        tx.fail(status).await;
+                        return;
+                    }
+                };
+                if !value.is_finite() {
+                    let index = stats.count;
+                    error!("Streaming aggregate received {} at index {}", value, index);
+                    tx.fail(failure(
                         Code::InvalidArgument,
-                        "division by zero is not allowed"
-                    ))
-                } else {
-                    Ok(req.first_number / req.second_number)
+                        format!("value at index {} must be finite, got {}", index, value),
+                        Kind::NonFiniteOperand,
+                        u32::try_from(index).ok(),
+                    )).await;
+                    return;
+                }
+                match tx.send(stats.push(value)).await {
+                    Ok(()) => {}
+                    Err(SendFailure::Closed) => {
+                        info!("Client closed streaming aggregate early");
+                        return;
+                    }
+                    Err(SendFailure::Overflow) => return,
                 }
             }
-        }?;  // The ? operator unwraps Ok values and returns Err values
+            info!("Finished streaming aggregate: {} values, mean {}", stats.count, stats.mean);
+        }.in_current_span());
 
-        info!("Sending calculate response: {}", result);
-        // Construct and return the successful response
-        Ok(Response::new(CalculateResponse {
-            result,
-        }))
+        Ok(Response::new(Box::pin(rx) as Self::AggregateStreamStream))
+    }
+
+    // Associated stream type for the running total
+    type RunningTotalStream = RunningTotalStream;
+
+    /// Running total method that applies each inbound step to an accumulator
+    /// 
+    /// The accumulator lives in the task serving this stream, so concurrent
+    /// streams never share state. A step that cannot be applied is answered
+    /// with an error message and the unchanged total; the stream stays open.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request wrapping the inbound stream of RunningTotalStep messages.
+    /// 
+    /// # Returns
+    /// * `Result<Response<Self::RunningTotalStream>, Status>` - A result containing the stream of totals or an error status.
+    async fn running_total(
+        &self,
+        request: Request<Streaming<RunningTotalStep>>,
+    ) -> Result<Response<Self::RunningTotalStream>, Status> {
+        let mut inbound = request.into_inner();
+        let (tx, rx) = streams::channel(self.streams, &self.stream_high_water);
+
+        info!("Started running total stream");
+        tokio::spawn(async move {
+            let mut total = 0.0;
+            let mut steps = 0usize;
+            while let Some(step) = inbound.next().await {
+                let response = match step {
+                    Ok(step) => {
+                        steps += 1;
+                        match operation(step.operation).and_then(|op| apply(total, step.value, op)) {
+                            Ok(next) => {
+                                total = next;
+                                RunningTotalResponse { total, error: String::new() }
+                            }
+                            Err(status) => RunningTotalResponse {
+                                total,
+                                error: status.message().to_string(),
+                            },
+                        }
+                    }
+                    Err(status) => {
+                        error!("Running total inbound stream failed: {}", status);
+                        tx.fail(status).await;
+                        return;
+                    }
+                };
+                match tx.send(response).await {
+                    Ok(()) => {}
+                    Err(SendFailure::Closed) => {
+                        info!("Client closed running total stream early");
+                        return;
+                    }
+                    Err(SendFailure::Overflow) => return,
+                }
+            }
+            info!("Finished running total stream: {} steps, total {}", steps, total);
+        }.in_current_span());
+
+        Ok(Response::new(Box::pin(rx) as Self::RunningTotalStream))
+    }
+
+    /// Calculate method for exact decimal operands given as strings
+    /// 
+    /// Results are normalized, so `0.10 + 0.20` yields `"0.3"`.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request containing a CalculateDecimalRequest message.
+    /// 
+    /// # Returns
+    /// * `Result<Response<CalculateDecimalResponse>, Status>` - The result, `InvalidArgument` for an
+    ///   unparseable operand or division by zero, or `OutOfRange` on overflow.
+    async fn calculate_decimal(
+        &self,
+        request: Request<CalculateDecimalRequest>,
+    ) -> Result<Response<CalculateDecimalResponse>, Status> {
+        let req = request.into_inner();
+
+        info!("Received decimal calculate request: {} {:?} {}", req.first_number, req.operation, req.second_number);
+        let operation = operation(req.operation)?;
+        let first = parse_decimal("first_number", 0, &req.first_number)?;
+        let second = parse_decimal("second_number", 1, &req.second_number)?;
+        let result = apply_decimal(first, second, operation)?.normalize().to_string();
+
+        info!("Sending decimal calculate response: {}", result);
+        Ok(Response::new(CalculateDecimalResponse { result }))
+    }
+
+    /// Reduce method that folds a list left to right with one operation
+    /// 
+    /// Stops at the first step that fails. NaN and infinite values are
+    /// rejected up front, and every step is checked for overflow: an
+    /// infinite result is reported as OutOfRange rather than folded further,
+    /// so the result is always finite. Long lists are abandoned
+    /// with `DeadlineExceeded` once the client's deadline has passed.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request containing a ReduceRequest message.
+    /// 
+    /// # Returns
+    /// * `Result<Response<ReduceResponse>, Status>` - The folded result, `InvalidArgument` for an
+    ///   empty list, a non-finite value or division by zero, or `OutOfRange` on overflow; errors
+    ///   name the value's index.
+    async fn reduce(
+        &self,
+        request: Request<ReduceRequest>,
+    ) -> Result<Response<ReduceResponse>, Status> {
+        let deadline = Deadline::of(&request);
+        let req = request.into_inner();
+        let operation = operation(req.operation)?;
+        info!("Received reduce request: {:?} over {} values", operation, req.values.len());
+
+        let Some((&first, rest)) = req.values.split_first() else {
+            error!("Reduce requested over an empty list");
+            return Err(failure(Code::InvalidArgument, "cannot reduce an empty list", Kind::EmptyInput, None));
+        };
+        if let Some(index) = req.values.iter().position(|v| !v.is_finite()) {
+            let value = req.values[index];
+            error!("Reduce list contains {} at index {}", value, index);
+            return Err(failure(
+                Code::InvalidArgument,
+                format!("value at index {} must be finite, got {}", index, value),
+                Kind::NonFiniteOperand,
+                Some(index as u32),
+            ));
+        }
+        let mut result = first;
+        for (index, &value) in rest.iter().enumerate().map(|(i, v)| (i + 1, v)) {
+            if index % DEADLINE_CHECK_INTERVAL == 0 {
+                deadline.check()?;
+            }
+            let next = apply(result, value, operation).map_err(|status| {
+                let message = format!("{} at index {}", status.message(), index);
+                in_context(status, message, Some(index as u32))
+            })?;
+            // The values are finite, so this also keeps the result finite
+            if !next.is_finite() {
+                error!("Reduce overflowed at index {}", index);
+                return Err(failure(
+                    Code::OutOfRange,
+                    format!("{} {:?} {} overflows at index {}", result, operation, value, index),
+                    Kind::Overflow,
+                    Some(index as u32),
+                ));
+            }
+            result = next;
+        }
+
+        info!("Sending reduce response: {}", result);
+        Ok(Response::new(ReduceResponse { result }))
+    }
+
+    /// GetHistory method listing recent successful calculations, newest first
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request containing a GetHistoryRequest message.
+    /// 
+    /// # Returns
+    /// * `Result<Response<GetHistoryResponse>, Status>` - The entries and how many are kept,
+    ///   or `Internal` if the history store cannot be read.
+    async fn get_history(
+        &self,
+        request: Request<GetHistoryRequest>,
+    ) -> Result<Response<GetHistoryResponse>, Status> {
+        let limit = match request.into_inner().limit {
+            0 => DEFAULT_HISTORY_LIMIT,
+            limit => limit as usize,
+        };
+        info!("Received history request for up to {} entries", limit);
+        let (entries, total) = self.history.read(limit).await.map_err(|e| {
+            error!("Failed to read the calculator history: {}", e);
+            Status::internal("the calculator history cannot be read")
+        })?;
+
+        info!("Sending {} of {} history entries", entries.len(), total);
+        let entries = entries
+            .into_iter()
+            .map(|entry| HistoryEntryMessage {
+                first_number: entry.first_number,
+                second_number: entry.second_number,
+                operation: entry.operation.into(),
+                result: entry.result,
+                timestamp_ms: entry.timestamp_ms,
+            })
+            .collect();
+        Ok(Response::new(GetHistoryResponse { entries, total: total as u64 }))
+    }
+
+    /// Evaluate method for a whole arithmetic expression
+    /// 
+    /// The expression is planned with `crate::expr::plan` and each step is
+    /// applied like a Calculate request, so the result matches a client
+    /// running the same plan step by step. Long expressions are abandoned
+    /// with `DeadlineExceeded` once the client's deadline has passed.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request containing an EvaluateRequest message.
+    /// 
+    /// # Returns
+    /// * `Result<Response<EvaluateResponse>, Status>` - The value, `InvalidArgument` for a syntax
+    ///   error, or the failing step's status naming its sub-expression (e.g. division by zero,
+    ///   or `OutOfRange` on overflow).
+    async fn evaluate(
+        &self,
+        request: Request<EvaluateRequest>,
+    ) -> Result<Response<EvaluateResponse>, Status> {
+        let deadline = Deadline::of(&request);
+        let expression = request.into_inner().expression;
+        info!("Received evaluate request: {}", expression);
+
+        let plan = expr::plan(&expression).map_err(|e| {
+            error!("Rejected expression {:?}: {}", expression, e);
+            failure(Code::InvalidArgument, e.to_string(), Kind::InvalidInput, None)
+        })?;
+        let mut results = Vec::with_capacity(plan.steps.len());
+        for (index, step) in plan.steps.iter().enumerate() {
+            // Also catches a deadline that passed while planning
+            if index % DEADLINE_CHECK_INTERVAL == 0 {
+                deadline.check()?;
+            }
+            let first = step.first.resolve(&results);
+            let second = step.second.resolve(&results);
+            let value = apply(first, second, step.operation).map_err(|status| {
+                let message = format!("{} in `{}`", status.message(), step.expression);
+                in_context(status, message, None)
+            })?;
+            // Literals are finite, so checking every step keeps the result
+            // finite, as Calculate does
+            if !value.is_finite() {
+                error!("Expression overflowed in `{}`", step.expression);
+                return Err(failure(
+                    Code::OutOfRange,
+                    format!("{} {:?} {} overflows in `{}`", first, step.operation, second, step.expression),
+                    Kind::Overflow,
+                    None,
+                ));
+            }
+            results.push(value);
+        }
+        let result = plan.result.resolve(&results);
+
+        info!("Sending evaluate response: {}", result);
+        Ok(Response::new(EvaluateResponse { result }))
     }
 }
 
@@ -91,6 +736,7 @@ mod tests {
             first_number: 5.0,
             second_number: 3.0,
             operation: Operation::Add.into(),
+            include_inverse: false,
         })).await.unwrap();
         assert_eq!(response.into_inner().result, 8.0);
 
@@ -100,7 +746,141 @@ mod tests {
             first_number: 5.0,
             second_number: 0.0,
             operation: Operation::Divide.into(),
+            include_inverse: false,
+        })).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    // Inputs the generated types would let through are rejected
+    // - Unknown operations no longer decode to Add
+    // - -0.0 is a zero divisor, NaN/infinite operands and overflow are errors
+    #[tokio::test]
+    async fn test_calculate_rejects_invalid_inputs() {
+        let service = CalculatorServer::default();
+        let calculate = |first_number: f64, second_number: f64, operation: i32| {
+            service.calculate(Request::new(CalculateRequest { first_number, second_number, operation, ..Default::default() }))
+        };
+
+        let err = calculate(1.0, 2.0, 99).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("unknown operation 99"));
+
+        let err = calculate(1.0, -0.0, Operation::Divide.into()).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        for (first, second) in [(f64::NAN, 1.0), (1.0, f64::INFINITY), (f64::NEG_INFINITY, 0.0)] {
+            let err = calculate(first, second, Operation::Add.into()).await.unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument, "{} + {}", first, second);
+        }
+
+        let err = calculate(1e308, 10.0, Operation::Multiply.into()).await.unwrap_err();
+        assert_eq!(err.code(), Code::OutOfRange);
+    }
+
+    // Each operation is undone by its inverse; multiplying by zero and 0 percent have none
+    #[test]
+    fn test_inverse() {
+        assert_eq!(inverse(5.0, 3.0, Operation::Add), Some(2.0));
+        assert_eq!(inverse(2.0, 3.0, Operation::Subtract), Some(5.0));
+        assert_eq!(inverse(15.0, 3.0, Operation::Multiply), Some(5.0));
+        assert_eq!(inverse(2.5, 2.0, Operation::Divide), Some(5.0));
+        assert_eq!(inverse(0.0, 0.0, Operation::Multiply), None);
+        assert_eq!(inverse(0.0, -0.0, Operation::Multiply), None);
+        assert_eq!(inverse(1e300, 1e-300, Operation::Multiply), None);
+        assert_eq!(inverse(100.0, 50.0, Operation::Percent), Some(200.0));
+        assert_eq!(inverse(0.0, 0.0, Operation::Percent), None);
+    }
+
+    // Decimal operands are exact and validated
+    #[tokio::test]
+    async fn test_calculate_decimal() {
+        let service = CalculatorServer::default();
+        let decimal = |first: &str, second: &str, operation: Operation| {
+            service.calculate_decimal(Request::new(CalculateDecimalRequest {
+                first_number: first.into(),
+                second_number: second.into(),
+                operation: operation.into(),
+            }))
+        };
+
+        let response = decimal("1.50", "2", Operation::Multiply).await.unwrap();
+        assert_eq!(response.into_inner().result, "3");
+        let response = decimal("19.99", "-15", Operation::Percent).await.unwrap();
+        assert_eq!(response.into_inner().result, "-2.9985");
+
+        let err = decimal("abc", "1", Operation::Add).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("first_number"));
+
+        let err = decimal("1", "0.00", Operation::Divide).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let err = decimal("79228162514264337593543950335", "1", Operation::Add).await.unwrap_err();
+        assert_eq!(err.code(), Code::OutOfRange);
+    }
+
+    // Folds run left to right and stop at the first failing step
+    #[tokio::test]
+    async fn test_reduce() {
+        let service = CalculatorServer::default();
+        let reduce = |values: Vec<f64>, operation: Operation| {
+            service.reduce(Request::new(ReduceRequest { values, operation: operation.into() }))
+        };
+
+        let response = reduce(vec![10.0, 3.0, 2.0], Operation::Subtract).await.unwrap();
+        assert_eq!(response.into_inner().result, 5.0);
+        let response = reduce(vec![7.0], Operation::Divide).await.unwrap();
+        assert_eq!(response.into_inner().result, 7.0);
+
+        let err = reduce(vec![], Operation::Add).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let err = reduce(vec![1e200, 1e200, 1.0], Operation::Multiply).await.unwrap_err();
+        assert_eq!(err.code(), Code::OutOfRange);
+        assert!(err.message().contains("index 1"), "{}", err.message());
+
+        // Non-finite values never reach the fold, wherever they are
+        for values in [vec![f64::NAN], vec![1.0, 2.0, f64::NAN], vec![1.0, f64::INFINITY]] {
+            let err = reduce(values, Operation::Add).await.unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+            assert!(err.message().contains("must be finite"), "{}", err.message());
+        }
+    }
+
+    // Expressions follow precedence and name the failing sub-expression
+    #[tokio::test]
+    async fn test_evaluate() {
+        let service = CalculatorServer::default();
+        let evaluate = |expression: &str| {
+            service.evaluate(Request::new(EvaluateRequest { expression: expression.into() }))
+        };
+
+        let response = evaluate("2 + 3 * (4 - 1)").await.unwrap();
+        assert_eq!(response.into_inner().result, 11.0);
+
+        let err = evaluate("1 + 6 / (4 - 4)").await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("`6 / (4 - 4)`"), "{}", err.message());
+
+        let err = evaluate("2 *").await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("position 3"));
+
+        // Overflow is an error, never an infinite result
+        let err = evaluate("1 + 1e308 * 10").await.unwrap_err();
+        assert_eq!(err.code(), Code::OutOfRange);
+        assert!(err.message().contains("`1e308 * 10`"), "{}", err.message());
+    }
+
+    // NaN inputs are rejected with the offending index
+    #[tokio::test]
+    async fn test_aggregate_rejects_nan() {
+        let service = CalculatorServer::default();
+
+        let err = service.aggregate(Request::new(AggregateRequest {
+            values: vec![1.0, f64::NAN, 3.0],
         })).await.unwrap_err();
         assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("index 1"));
     }
 }
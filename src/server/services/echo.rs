@@ -1,17 +1,122 @@
 //! Implementation of a simple Echo gRPC service that returns the same message it receives.
 //! This serves as a good example of basic gRPC service implementation in Rust.
 
-use tonic::{Request, Response, Status, Code};
-use tracing::{info, error};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Code, Streaming};
+use tracing::{info, error, Instrument};
 // Import the generated protobuf code for our echo service
 use crate::proto::echo::echo_service_server::EchoService;
-use crate::proto::echo::{EchoRequest, EchoResponse};
+use crate::proto::echo::{EchoRequest, EchoResponse, EchoChunk, EchoStreamRequest, LIMIT_METADATA, ACTUAL_METADATA};
+use crate::proto::validation::Validate;
+use crate::server::cache::EchoCache;
+use crate::server::deadline::Deadline;
+use crate::server::live_config::SharedConfig;
+use crate::server::layers::ConnectionRequestCount;
+use crate::server::streams::{self, SendFailure, StreamSettings};
+use crate::server::text;
+
+// Upper bound for the artificial delay a client may request
+// Keeps a single request from pinning a handler for an arbitrary time
+const MAX_ECHO_DELAY_MS: u32 = 10_000;
+
+// Boxed stream types returned by the streaming echoes
+// Pin<Box<dyn Stream>> lets us return any stream implementation to tonic
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<EchoChunk, Status>> + Send>>;
+type ResponseStream = Pin<Box<dyn Stream<Item = Result<EchoResponse, Status>> + Send>>;
+
+// Last echo sequence number handed out per connection, keyed by peer address
+// The server removes an entry when its connection closes
+pub(crate) type ConnectionSequences = Arc<Mutex<HashMap<SocketAddr, u64>>>;
 
 // Our server implementation. We use Debug and Default traits to make it easier to create instances
 // Debug: Allows printing the struct for debugging
 // Default: Provides a default empty constructor
 #[derive(Debug, Default)]
-pub struct EchoServer {}
+pub struct EchoServer {
+    // Total responses produced by streaming echoes
+    // Shared with producer tasks; lets tests observe when production stops
+    streamed_responses: Arc<AtomicU64>,
+    // Echo responses produced so far, reported as server_total_echoes
+    total_echoes: Arc<AtomicU64>,
+    // Per-connection sequence counters of the unary echo
+    sequences: ConnectionSequences,
+    // Empty message policy and character limit; may change at runtime
+    config: SharedConfig,
+    // Bytes one chunked echo may carry before it is ended; unlimited when None
+    max_stream_bytes: Option<usize>,
+    // Outbound buffer size and overflow policy of the streaming echoes
+    streams: StreamSettings,
+    // Most responses ever queued in one outbound stream; lets tests check the bound
+    stream_high_water: Arc<AtomicUsize>,
+    // Counts repeated unary echoes; nothing is counted when None
+    cache: Option<EchoCache>,
+}
+
+impl EchoServer {
+    // Read the empty message policy and character limit from `config`
+    pub(crate) fn config(mut self, config: SharedConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    // End chunked echoes that carry more than `limit` bytes
+    pub(crate) fn max_stream_bytes(mut self, limit: Option<usize>) -> Self {
+        self.max_stream_bytes = limit;
+        self
+    }
+
+    // Buffer the streaming echoes' responses as `settings` say
+    pub(crate) fn streams(mut self, settings: StreamSettings) -> Self {
+        self.streams = settings;
+        self
+    }
+
+    // Count repeated unary echoes in `cache`
+    pub(crate) fn with_cache(mut self, cache: EchoCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // Reject messages over the character limit with the numbers attached
+    // Characters, not bytes, so the limit means the same in every script
+    fn check_length(limit: Option<usize>, message: &str) -> Result<(), Status> {
+        let Some(limit) = limit else { return Ok(()) };
+        let actual = message.chars().count();
+        if actual <= limit {
+            return Ok(());
+        }
+        error!("Received echo message of {} characters, limit is {}", actual, limit);
+        let mut metadata = MetadataMap::new();
+        metadata.insert(LIMIT_METADATA, limit.into());
+        metadata.insert(ACTUAL_METADATA, actual.into());
+        Err(Status::with_metadata(
+            Code::InvalidArgument,
+            format!("message too long: {}/{} characters", actual, limit),
+            metadata,
+        ))
+    }
+
+    // Shared handle to the per-connection sequences, for cleanup on disconnect
+    pub(crate) fn connection_sequences(&self) -> ConnectionSequences {
+        self.sequences.clone()
+    }
+
+    // Next sequence number on the connection from `peer` (0 if unknown)
+    fn next_sequence(&self, peer: Option<SocketAddr>) -> u64 {
+        let Some(peer) = peer else { return 0 };
+        let mut sequences = self.sequences.lock().unwrap();
+        let sequence = sequences.entry(peer).or_insert(0);
+        *sequence += 1;
+        *sequence
+    }
+}
 
 // This attribute generates the async implementation of our service
 // The async_trait is needed because Rust doesn't support async functions in traits natively yet
@@ -19,6 +124,10 @@ pub struct EchoServer {}
 impl EchoService for EchoServer {
     /// Echo method that returns the same message it receives
     /// 
+    /// A requested delay longer than the client's deadline is cut short and
+    /// answered with `DeadlineExceeded`. The message is checked and
+    /// normalized as its `validation` asks before it is echoed.
+    /// 
     /// # Arguments
     /// * `request` - A gRPC request containing an EchoRequest message.
     /// 
@@ -28,26 +137,191 @@ impl EchoService for EchoServer {
         &self,
         request: Request<EchoRequest>,
     ) -> Result<Response<EchoResponse>, Status> {
-        // Extract the inner request data
+        // Handler time reported in server_process_ns starts here
+        let started = Instant::now();
+        // Connection identity for the sequence number, then the request data
+        let peer = request.remote_addr();
+        let connection_count = request.extensions().get::<ConnectionRequestCount>().copied();
+        let deadline = Deadline::of(&request);
         let req = request.into_inner();
+        // One snapshot of the settings for the whole request
+        let config = self.config.load();
         
         // Input validation: Ensure the message isn't empty or just whitespace
         // This is a good practice for robust service implementation
-        if req.message.trim().is_empty() {
-            error!("Received empty message");
+        // Servers built with allow_empty_echo(true), or switched by the admin
+        // SetConfig RPC, echo such messages unchanged; that is the only rule
+        if !config.allow_empty_echo {
+            req.validate().inspect_err(|_| error!("Received empty message"))?;
+        }
+
+        Self::check_length(config.echo_max_chars, &req.message)?;
+
+        // Reject unreasonable delays rather than silently clamping them
+        if req.delay_ms > MAX_ECHO_DELAY_MS {
+            error!("Received echo delay above limit: {}ms", req.delay_ms);
             return Err(Status::new(
                 Code::InvalidArgument,
-                "empty message is not allowed"
+                format!("delay_ms must not exceed {}", MAX_ECHO_DELAY_MS)
             ));
         }
 
-        info!("Received echo request with message: {}", req.message);
-        // Return the same message we received
-        let response = EchoResponse {
-            message: req.message,
+        // Checks and normalization the client asked for; NONE keeps the message as is
+        let message = text::validate(req.validation, req.message)?;
+
+        info!("Received echo request with message: {}", message);
+        // Optional artificial delay, used to hold requests in flight
+        // Never sleep past the client's deadline
+        if req.delay_ms > 0 {
+            let delay = Duration::from_millis(u64::from(req.delay_ms));
+            tokio::time::sleep(deadline.remaining().map_or(delay, |left| delay.min(left))).await;
+            deadline.check()?;
+        }
+        // Return the same message we received, stamped for ordering checks
+        if let Some(cache) = &self.cache {
+            cache.record(&message);
+        }
+        let mut response = EchoResponse {
+            message,
+            sequence: self.next_sequence(peer),
+            server_total_echoes: self.total_echoes.fetch_add(1, Ordering::Relaxed) + 1,
+            server_process_ns: None,
         };
         info!("Sending echo response with message: {}", response.message);
-        Ok(Response::new(response))
+        // Saturates after ~584 years, far beyond any handler
+        response.server_process_ns = Some(u64::try_from(started.elapsed().as_nanos()).unwrap_or(u64::MAX));
+        let mut response = Response::new(response);
+        if let Some(count) = connection_count {
+            count.insert_into(response.metadata_mut());
+        }
+        Ok(response)
+    }
+
+    // Associated stream type for the bidirectional chunked echo
+    type EchoChunkedStream = ChunkStream;
+
+    /// Chunked echo method that forwards each received chunk back as it arrives
+    /// 
+    /// With a byte budget configured, the chunk that would exceed it is not
+    /// echoed and the stream ends with `ResourceExhausted` instead.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request wrapping the inbound stream of EchoChunk messages.
+    /// 
+    /// # Returns
+    /// * `Result<Response<Self::EchoChunkedStream>, Status>` - A result containing the outbound chunk stream or an error status.
+    async fn echo_chunked(
+        &self,
+        request: Request<Streaming<EchoChunk>>,
+    ) -> Result<Response<Self::EchoChunkedStream>, Status> {
+        let mut inbound = request.into_inner();
+        let budget = self.max_stream_bytes;
+        // Bounded channel: at most the configured buffer of chunks is held at any time
+        let (tx, rx) = streams::channel(self.streams, &self.stream_high_water);
+
+        info!("Started chunked echo stream");
+        // Forward chunks in a separate task so the response stream can start
+        // before the client has finished sending
+        tokio::spawn(async move {
+            let mut chunks = 0usize;
+            let mut bytes = 0usize;
+            while let Some(chunk) = inbound.next().await {
+                match chunk {
+                    Ok(chunk) => {
+                        chunks += 1;
+                        bytes += chunk.data.len();
+                        if let Some(limit) = budget.filter(|&limit| bytes > limit) {
+                            error!("Chunked echo stream exceeded its budget of {} bytes after {} chunks", limit, chunks);
+                            let status = Status::resource_exhausted(format!("stream exceeded its budget of {} bytes", limit));
+                            tx.fail(status).await;
+                            return;
+                        }
+                        match tx.send(chunk).await {
+                            Ok(()) => {}
+                            Err(SendFailure::Closed) => {
+                                info!("Client closed chunked echo stream early");
+                                return;
+                            }
+                            Err(SendFailure::Overflow) => return,
+                        }
+                    }
+                    Err(status) => {
+                        error!("Chunked echo inbound stream failed: {}", status);
+                        tx.fail(status).await;
+                        return;
+                    }
+                }
+            }
+            info!("Finished chunked echo stream: {} chunks, {} bytes", chunks, bytes);
+        }.in_current_span());
+
+        Ok(Response::new(Box::pin(rx) as Self::EchoChunkedStream))
+    }
+
+    // Associated stream type for the server-streaming echo
+    type EchoStreamStream = ResponseStream;
+
+    /// Streaming echo method that sends the message back `repeat` times
+    /// 
+    /// A `repeat` of zero yields an empty stream that ends with OK; the
+    /// message must still be non-empty.
+    /// The producer stops as soon as the client drops the response stream,
+    /// so a cancelled call never keeps generating responses nobody reads.
+    /// 
+    /// # Arguments
+    /// * `request` - A gRPC request containing an EchoStreamRequest message.
+    /// 
+    /// # Returns
+    /// * `Result<Response<Self::EchoStreamStream>, Status>` - A result containing the response stream or an error status.
+    async fn echo_stream(
+        &self,
+        request: Request<EchoStreamRequest>,
+    ) -> Result<Response<Self::EchoStreamStream>, Status> {
+        let req = request.into_inner();
+
+        // Same validation as the unary echo, applied before streaming starts
+        req.validate().inspect_err(|_| error!("Received empty message for streaming echo"))?;
+        Self::check_length(self.config.load().echo_max_chars, &req.message)?;
+
+        info!("Received streaming echo request: {} repetitions", req.repeat);
+        // Nothing to produce: complete the stream right away, successfully
+        if req.repeat == 0 {
+            return Ok(Response::new(Box::pin(tokio_stream::empty()) as Self::EchoStreamStream));
+        }
+        // The producer waits (or fails) once the buffer is full, so a slow
+        // client never makes the server hold more than the buffer
+        let (tx, rx) = streams::channel(self.streams, &self.stream_high_water);
+        let produced = self.streamed_responses.clone();
+        let total = self.total_echoes.clone();
+
+        tokio::spawn(async move {
+            for sent in 0..req.repeat {
+                // Cheap early exit when the receiver is already gone
+                if tx.is_closed() {
+                    info!("Client cancelled streaming echo after {} responses", sent);
+                    return;
+                }
+                // Streams number their responses independently of the connection
+                let response = EchoResponse {
+                    message: req.message.clone(),
+                    sequence: u64::from(sent) + 1,
+                    server_total_echoes: total.fetch_add(1, Ordering::Relaxed) + 1,
+                    server_process_ns: None,
+                };
+                match tx.send(response).await {
+                    Ok(()) => {}
+                    Err(SendFailure::Closed) => {
+                        info!("Client cancelled streaming echo after {} responses", sent);
+                        return;
+                    }
+                    Err(SendFailure::Overflow) => return,
+                }
+                produced.fetch_add(1, Ordering::Relaxed);
+            }
+            info!("Finished streaming echo: {} responses", req.repeat);
+        }.in_current_span());
+
+        Ok(Response::new(Box::pin(rx) as Self::EchoStreamStream))
     }
 }
 
@@ -64,14 +338,73 @@ mod tests {
         
         // Test the happy path with a valid message
         let response = service.echo(Request::new(EchoRequest {
-            message: "test".into()
+            message: "test".into(),
+            delay_ms: 0,
+            ..Default::default()
         })).await.unwrap();
         assert_eq!(response.into_inner().message, "test");
 
         // Test error handling with an empty message
         let err = service.echo(Request::new(EchoRequest {
-            message: "   ".into()
+            message: "   ".into(),
+            delay_ms: 0,
+            ..Default::default()
         })).await.unwrap_err();
         assert_eq!(err.code(), Code::InvalidArgument);
     }
+
+    // Dropping the response stream must stop the producer task promptly
+    #[tokio::test]
+    async fn test_echo_stream_stops_when_client_drops() {
+        let service = EchoServer::default();
+        let repeat = 100_000;
+
+        let mut stream = service.echo_stream(Request::new(EchoStreamRequest {
+            message: "cancel me".into(),
+            repeat,
+        })).await.unwrap().into_inner();
+
+        // Read a few items, then hang up
+        for _ in 0..5 {
+            stream.next().await.unwrap().unwrap();
+        }
+        drop(stream);
+
+        // Give the producer a chance to notice, then check it stopped
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let produced = service.streamed_responses.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(produced, service.streamed_responses.load(Ordering::Relaxed));
+        // At most what was read plus what fit in the channel
+        assert!(produced <= 5 + streams::DEFAULT_STREAM_BUFFER as u64 + 1, "produced {} responses", produced);
+    }
+
+    // A slow consumer must hold the producer back instead of growing a buffer
+    #[tokio::test]
+    async fn test_echo_stream_backpressure_bounds_buffer() {
+        const BUFFER: usize = 8;
+        let repeat = 100_000;
+        let service = EchoServer::default()
+            .streams(StreamSettings { buffer: BUFFER, ..Default::default() });
+
+        let mut stream = service.echo_stream(Request::new(EchoStreamRequest {
+            message: "slow".into(),
+            repeat,
+        })).await.unwrap().into_inner();
+
+        // Let the producer run ahead, then read with pauses in between
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let mut expected = 1;
+        while let Some(response) = stream.next().await {
+            assert_eq!(response.unwrap().sequence, expected);
+            if expected % 10_000 == 0 {
+                tokio::time::sleep(Duration::from_millis(2)).await;
+            }
+            expected += 1;
+        }
+
+        assert_eq!(expected - 1, u64::from(repeat));
+        let high_water = service.stream_high_water.load(Ordering::Relaxed);
+        assert!((1..=BUFFER).contains(&high_water), "{} responses were queued", high_water);
+    }
 }
\ No newline at end of file
@@ -5,8 +5,13 @@
 // Declare submodules containing our service implementations
 mod calculator;
 mod echo;
+mod admin;
+mod broadcast;
 
 // Re-export the service structs so they can be used by other modules
-// The pub(crate) means these are only visible within our crate
-pub(crate) use calculator::CalculatorServer;
-pub(crate) use echo::EchoServer;
+// The calculator and echo handlers are public so tests can call them
+// directly; the pub(crate) ones are only visible within our crate
+pub use calculator::{CalculatorServer, SubnormalPolicy};
+pub use echo::EchoServer;
+pub(crate) use admin::{AdminServer, AdminToken};
+pub(crate) use broadcast::BroadcastServer;
@@ -0,0 +1,310 @@
+//! Connection Serving
+//! Serves the accepted connections of a listener with hyper, one task per
+//! connection, in place of tonic's own server loop:
+//! 1. TLS, when configured, is negotiated in the connection's task, so a
+//!    slow handshake never holds up accept
+//! 2. Every request gets what tonic's server would give it: the connect
+//!    info in its extensions, the per-RPC span and the request timeout
+//!    (the shorter of the server's and the client's grpc-timeout)
+//! 3. A connection is shut down gracefully, with a GOAWAY and then closed
+//!    once its streams are done, when shutdown is requested or when the
+//!    idle timeout reports it idle
+//!
+//! hyper only offers a graceful shutdown of one connection to whoever
+//! serves it, which is why the server does so itself (see idle.rs).
+//! Connections get the HTTP/2 settings tonic's server gives them (window
+//! sizes, stream limits, keepalive, frame size), set explicitly in new().
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use hyper::server::conn::Http;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::{Stream, StreamExt};
+use tonic::body::BoxBody;
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tonic::Status;
+use tower::{BoxError, Service};
+use tracing::{debug, Instrument, Span};
+use super::deadline::header_timeout;
+use super::idle::{IdleTracker, IdleWatch};
+
+// Keepalive ping timeout of tonic's server; pings are off unless an
+// interval is set
+const HTTP2_KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(20);
+
+// Span of one RPC, made from its request without the body
+type TraceFn = Arc<dyn Fn(&http::Request<()>) -> Span + Send + Sync + 'static>;
+
+// Adds the connect info of a connection to each of its requests
+type ConnectInfoFn = Arc<dyn Fn(&mut http::Extensions) + Send + Sync + 'static>;
+
+// Remote address in the connect info of a connection, if it has one
+pub(crate) trait PeerAddr {
+    fn peer_addr(&self) -> Option<SocketAddr>;
+}
+
+impl PeerAddr for TcpConnectInfo {
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr()
+    }
+}
+
+// Named pipes have no peer address
+impl PeerAddr for () {
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+// Everything needed to serve a connection, cloned for every listener
+#[derive(Clone)]
+pub(crate) struct Serving<S> {
+    service: S,  // The routes, behind the server's layers
+    http: Http,
+    trace: TraceFn,
+    tls: Option<TlsAcceptor>,  // Plaintext when unset
+    idle: Option<Arc<IdleTracker>>,  // Shut idle connections down
+    timeout: Option<Duration>,  // Per-RPC deadline enforced by the server
+}
+
+impl<S> Serving<S>
+where
+    S: Service<http::Request<hyper::Body>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    // Serve HTTP/2 with `service`, making each RPC's span with `trace`
+    pub(crate) fn new(service: S, trace: impl Fn(&http::Request<()>) -> Span + Send + Sync + 'static) -> Self {
+        // Same settings as tonic 0.10's Server::builder(); None keeps hyper's
+        // default, as tonic passes it
+        let mut http = Http::new();
+        http.http2_only(true)
+            .http2_initial_connection_window_size(None)
+            .http2_initial_stream_window_size(None)
+            .http2_max_concurrent_streams(None)
+            .http2_keep_alive_interval(None)
+            .http2_keep_alive_timeout(HTTP2_KEEPALIVE_TIMEOUT)
+            .http2_adaptive_window(false)
+            .http2_max_pending_accept_reset_streams(None)
+            .http2_max_frame_size(None);
+        Self { service, http, trace: Arc::new(trace), tls: None, idle: None, timeout: None }
+    }
+
+    pub(crate) fn tls(mut self, acceptor: Option<TlsAcceptor>) -> Self {
+        self.tls = acceptor;
+        self
+    }
+
+    pub(crate) fn idle(mut self, tracker: Option<Arc<IdleTracker>>) -> Self {
+        self.idle = tracker;
+        self
+    }
+
+    // Refuse header blocks over `max` bytes while HPACK decodes them,
+    // announcing the limit in SETTINGS_MAX_HEADER_LIST_SIZE
+    pub(crate) fn max_header_list_size(mut self, max: u32) -> Self {
+        self.http.http2_max_header_list_size(max);
+        self
+    }
+
+    pub(crate) fn request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Serve the connections of `incoming` until `signal` resolves, then
+    // shut them down gracefully and wait for them
+    // An accept error ends serving right away
+    pub(crate) async fn serve<I, IO>(self, incoming: I, signal: impl Future<Output = ()>) -> io::Result<()>
+    where
+        I: Stream<Item = io::Result<IO>>,
+        IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
+        IO::ConnectInfo: PeerAddr + Clone + Send + Sync + 'static,
+    {
+        let (stop, stopping) = watch::channel(false);
+        let mut connections = JoinSet::new();
+        tokio::pin!(incoming, signal);
+        let mut stopped = false;
+        loop {
+            tokio::select! {
+                () = &mut signal => {
+                    stopped = true;
+                    break;
+                }
+                accepted = incoming.next() => match accepted {
+                    Some(Ok(io)) => {
+                        connections.spawn(self.serve_connection(io, stopping.clone()).in_current_span());
+                    }
+                    Some(Err(e)) => return Err(e),
+                    None => break,
+                },
+                // Forget connections that have ended
+                Some(_) = connections.join_next() => {}
+            }
+        }
+
+        // Wait for the open connections; once shutdown is requested they
+        // finish their streams and close
+        if stopped {
+            let _ = stop.send(true);
+        }
+        loop {
+            tokio::select! {
+                () = &mut signal, if !stopped => {
+                    stopped = true;
+                    let _ = stop.send(true);
+                }
+                joined = connections.join_next() => if joined.is_none() {
+                    return Ok(());
+                },
+            }
+        }
+    }
+
+    // Serve one connection until it ends, negotiating TLS first if configured
+    fn serve_connection<IO>(&self, io: IO, stopping: watch::Receiver<bool>) -> impl Future<Output = ()> + Send + 'static
+    where
+        IO: AsyncRead + AsyncWrite + Connected + Unpin + Send + 'static,
+        IO::ConnectInfo: PeerAddr + Clone + Send + Sync + 'static,
+    {
+        let info = io.connect_info();
+        let peer = info.peer_addr();
+        let idle = self.idle.as_ref().zip(peer).map(|(tracker, peer)| tracker.watch(peer));
+        let (http, tls, mut service) = (self.http.clone(), self.tls.clone(), self.service());
+        async move {
+            let Some(tls) = tls else {
+                service.connect_info = Arc::new(move |extensions| {
+                    extensions.insert(info.clone());
+                });
+                return drive(http, io, service, idle, stopping).await;
+            };
+            let io = tokio::select! {
+                accepted = tls.accept(io) => match accepted {
+                    Ok(io) => io,
+                    Err(e) => {
+                        debug!("TLS handshake with {:?} failed: {}", peer, e);
+                        return;
+                    }
+                },
+                () = stopped(stopping.clone()) => return,
+            };
+            let info = io.connect_info();
+            service.connect_info = Arc::new(move |extensions| {
+                // Like tonic, the TCP info is there too
+                extensions.insert(info.get_ref().clone());
+                extensions.insert(info.clone());
+            });
+            drive(http, io, service, idle, stopping).await
+        }
+    }
+
+    // Service for one connection; its connect info is set once known
+    fn service(&self) -> ConnectionService<S> {
+        ConnectionService {
+            inner: self.service.clone(),
+            connect_info: Arc::new(|_| {}),
+            trace: self.trace.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+// Run hyper on `io` until the connection ends, shutting it down
+// gracefully on shutdown or once it is idle
+async fn drive<IO, S>(http: Http, io: IO, service: ConnectionService<S>, idle: Option<IdleWatch>, stopping: watch::Receiver<bool>)
+where
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Service<http::Request<hyper::Body>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    let connection = http.serve_connection(io, service);
+    tokio::pin!(connection);
+    let idle = async {
+        match &idle {
+            Some(watch) => watch.idle().await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        result = connection.as_mut() => {
+            if let Err(e) = result {
+                debug!("Connection error: {}", e);
+            }
+            return;
+        }
+        () = stopped(stopping) => {}
+        () = idle => {}
+    }
+    // GOAWAY: the client opens no new streams and reconnects for its
+    // next call; the connection closes once the streams in flight end
+    connection.as_mut().graceful_shutdown();
+    if let Err(e) = connection.await {
+        debug!("Connection error: {}", e);
+    }
+}
+
+// Resolves once shutdown is requested (or the server is gone)
+async fn stopped(mut stopping: watch::Receiver<bool>) {
+    let _ = stopping.wait_for(|&stop| stop).await;
+}
+
+// The server's service as seen by one connection
+#[derive(Clone)]
+struct ConnectionService<S> {
+    inner: S,
+    connect_info: ConnectInfoFn,
+    trace: TraceFn,
+    timeout: Option<Duration>,
+}
+
+impl<S> Service<http::Request<hyper::Body>> for ConnectionService<S>
+where
+    S: Service<http::Request<hyper::Body>, Response = http::Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut req: http::Request<hyper::Body>) -> Self::Future {
+        (self.connect_info)(req.extensions_mut());
+        let (parts, body) = req.into_parts();
+        let bodyless = http::Request::from_parts(parts, ());
+        let span = (self.trace)(&bodyless);
+        let (parts, ()) = bodyless.into_parts();
+        let req = http::Request::from_parts(parts, body);
+        // The shorter of the client's deadline and the server's timeout
+        let timeout = header_timeout(req.headers()).into_iter().chain(self.timeout).min();
+
+        // Swap in a fresh clone so the ready service is the one we call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let call = inner.call(req);
+            let result = match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                    Ok(result) => result,
+                    // Same status as tonic's own timeout
+                    Err(_) => return Ok(Status::cancelled("Timeout expired").to_http()),
+                },
+                None => call.await,
+            };
+            // Errors of the layers become the status of the RPC
+            Ok(result.unwrap_or_else(|e| Status::from_error(e.into()).to_http()))
+        }.instrument(span))
+    }
+}
@@ -0,0 +1,144 @@
+//! Shared Shutdown Signal
+//! A oneshot channel can only wake a single server. Shutdown wraps a
+//! tokio watch channel instead, so every server built with the same
+//! handle (gRPC, admin, metrics, ...) observes one trigger:
+//! 1. Cloning the handle shares the signal
+//! 2. trigger() stops every server waiting on it, even ones started later
+//! 3. Dropping every handle also counts as a shutdown request, reported
+//!    apart from a trigger (ShutdownCause) so it can be logged as suspicious
+//! 4. serve() returns why it stopped (ShutdownReason); failures are its
+//!    Err instead
+
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+// Cloneable handle that fires a shutdown for every attached server
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    // Create a fresh, untriggered signal
+    pub fn new() -> Self {
+        let (tx, _rx) = watch::channel(false);
+        Self { tx: Arc::new(tx) }
+    }
+
+    // Ask every attached server to stop
+    // Safe to call more than once or with no server running
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    // Whether trigger() has been called
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    // Receiving side for one server
+    // Holds only a receiver, so waiting servers never keep the signal alive
+    pub(crate) fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.tx.subscribe())
+    }
+}
+
+// Why a server was asked to stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShutdownCause {
+    Triggered,  // trigger() was called on a handle
+    HandlesDropped,  // Every handle was dropped without a trigger
+}
+
+/// Why `GrpcServer::serve` returned without an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// `Shutdown::trigger` was called
+    SignalReceived,
+    /// Every `Shutdown` handle was dropped without a trigger
+    HandlesDropped,
+    /// Every listener stopped accepting connections on its own,
+    /// with no shutdown requested
+    AllListenersClosed,
+}
+
+impl From<ShutdownCause> for ShutdownReason {
+    fn from(cause: ShutdownCause) -> Self {
+        match cause {
+            ShutdownCause::Triggered => ShutdownReason::SignalReceived,
+            ShutdownCause::HandlesDropped => ShutdownReason::HandlesDropped,
+        }
+    }
+}
+
+impl fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ShutdownReason::SignalReceived => "shutdown signal received",
+            ShutdownReason::HandlesDropped => "every shutdown handle was dropped",
+            ShutdownReason::AllListenersClosed => "every listener closed",
+        })
+    }
+}
+
+// What a server waits on; created from a Shutdown handle
+#[derive(Debug, Clone)]
+pub(crate) struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    // Complete once shutdown is requested, saying how
+    pub(crate) async fn recv(mut self) -> ShutdownCause {
+        // A trigger seen before the last handle went is still a trigger
+        match self.0.wait_for(|triggered| *triggered).await {
+            Ok(_) => ShutdownCause::Triggered,
+            Err(_) => ShutdownCause::HandlesDropped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::{timeout, Duration};
+
+    #[tokio::test]
+    async fn test_trigger_before_wait_completes_immediately() {
+        let shutdown = Shutdown::new();
+        shutdown.trigger();
+        assert!(shutdown.is_triggered());
+
+        let cause = timeout(Duration::from_secs(1), shutdown.signal().recv())
+            .await
+            .expect("recv() should see an earlier trigger");
+        assert_eq!(cause, ShutdownCause::Triggered);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_all_handles_completes_wait() {
+        let shutdown = Shutdown::new();
+        let waiter = shutdown.signal().recv();
+        drop(shutdown);
+
+        let cause = timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("recv() should finish once every handle is dropped");
+        assert_eq!(cause, ShutdownCause::HandlesDropped);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_then_drop_is_a_trigger() {
+        let shutdown = Shutdown::new();
+        let waiter = shutdown.signal().recv();
+        shutdown.trigger();
+        drop(shutdown);
+
+        assert_eq!(waiter.await, ShutdownCause::Triggered);
+    }
+}
@@ -0,0 +1,107 @@
+//! Server Configuration Snapshot
+//! A serializable summary of every option set on a `GrpcServerBuilder`,
+//! for logging how a server was configured. Secrets (TLS key, admin token)
+//! only appear as `Redacted`; the builder and server Debug output is
+//! printed from this snapshot, so it redacts the same way.
+
+use std::path::PathBuf;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::config::RuntimeConfig;
+use crate::redact::Redacted;
+use super::server::ServerOptions;
+use super::layers::FaultConfig;
+use super::streams::StreamOverflowPolicy;
+use super::services::SubnormalPolicy;
+use super::tls::TlsVersion;
+
+/// Options of a server builder or built server, secrets redacted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerConfigSnapshot {
+    /// Listen address; None if the builder has none yet
+    pub address: Option<String>,
+    pub max_concurrent_per_peer: Option<usize>,
+    pub max_connections: Option<usize>,
+    pub max_queue_depth: Option<usize>,
+    /// In-flight requests at which the overload callback runs
+    pub overload_high_water: Option<usize>,
+    pub max_message_size: usize,
+    pub max_stream_bytes: Option<usize>,
+    pub max_header_list_size: Option<u32>,
+    pub max_metadata_size: usize,
+    pub echo_max_chars: Option<usize>,
+    pub calculator_cache: Option<usize>,
+    pub echo_cache: Option<usize>,
+    pub request_timeout: Option<Duration>,
+    pub idle_connection_timeout: Option<Duration>,
+    pub slow_request_threshold: Option<Duration>,
+    pub reuse_port_listeners: usize,
+    /// Named pipe served instead of TCP (Windows)
+    pub named_pipe: Option<String>,
+    /// Compression encoding, e.g. `"gzip"`
+    pub compression: Option<String>,
+    pub allow_empty_echo: bool,
+    pub startup_banner: bool,
+    pub skip_logging_init: bool,
+    pub instance_name: Option<String>,
+    /// Set when serving TLS; the certificate and key are never shown
+    pub tls: Option<Redacted>,
+    pub min_tls_version: TlsVersion,
+    /// Whether TLS clients must present a certificate (mutual TLS)
+    pub tls_client_auth: bool,
+    pub admin_token: Option<Redacted>,
+    pub fault_injection: Option<FaultConfig>,
+    pub stream_buffer_size: usize,
+    pub stream_overflow_policy: StreamOverflowPolicy,
+    pub subnormal_policy: SubnormalPolicy,
+    /// Whether the calculator history goes to a store other than the default in-memory one
+    pub custom_history_store: bool,
+    pub audit_log_path: PathBuf,
+    pub audit_fail_open: bool,
+    /// Whether an authenticator other than NoAuth was installed
+    pub custom_authenticator: bool,
+    pub runtime: RuntimeConfig,
+}
+
+impl ServerConfigSnapshot {
+    // Summarize `options`; secrets go through Redacted::if_set
+    pub(crate) fn new(address: Option<String>, options: &ServerOptions, custom_authenticator: bool) -> Self {
+        Self {
+            address,
+            max_concurrent_per_peer: options.max_concurrent_per_peer,
+            max_connections: options.max_connections,
+            max_queue_depth: options.max_queue_depth,
+            overload_high_water: options.overload_alarm.as_ref().map(|alarm| alarm.high_water()),
+            max_message_size: options.max_message_size,
+            max_stream_bytes: options.max_stream_bytes,
+            max_header_list_size: options.max_header_list_size,
+            max_metadata_size: options.max_metadata_size,
+            echo_max_chars: options.echo_max_chars,
+            calculator_cache: options.calculator_cache,
+            echo_cache: options.echo_cache,
+            request_timeout: options.request_timeout,
+            idle_connection_timeout: options.idle_connection_timeout,
+            slow_request_threshold: options.slow_request_threshold,
+            reuse_port_listeners: options.reuse_port_listeners,
+            named_pipe: options.named_pipe.clone(),
+            compression: options.compression.map(|encoding| format!("{:?}", encoding).to_ascii_lowercase()),
+            allow_empty_echo: options.allow_empty_echo,
+            startup_banner: options.startup_banner,
+            skip_logging_init: options.skip_logging_init,
+            instance_name: options.instance_name.clone(),
+            tls: Redacted::if_set(&options.tls),
+            min_tls_version: options.min_tls_version,
+            tls_client_auth: options.tls_client_ca.is_some(),
+            admin_token: Redacted::if_set(&options.admin_token),
+            fault_injection: options.fault_injection,
+            stream_buffer_size: options.streams.buffer,
+            stream_overflow_policy: options.streams.overflow,
+            subnormal_policy: options.subnormals,
+            custom_history_store: options.calculator_history.is_some(),
+            audit_log_path: options.audit_log.clone(),
+            audit_fail_open: options.audit_fail_open,
+            custom_authenticator,
+            runtime: options.runtime.clone(),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Outbound Streams
+//! Every server-streaming response (EchoStream, EchoChunked, RunningTotal,
+//! AggregateStream) is produced by a task writing into a bounded channel:
+//! 1. The channel holds at most `stream_buffer_size` responses, so a slow
+//!    client bounds the memory of its stream
+//! 2. When the channel is full the producer waits (StreamOverflowPolicy::Block)
+//!    or ends the stream with ResourceExhausted (StreamOverflowPolicy::Error)
+//! 3. The deepest the channel ever got is recorded, so tests can check the bound
+//! 4. Responses queued ahead of an error still reach the client (FlushBeforeError)
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::Status;
+use tracing::warn;
+
+// Responses a stream may hold when stream_buffer_size is not set
+pub(crate) const DEFAULT_STREAM_BUFFER: usize = 16;
+
+/// What a streaming RPC does when its client reads slower than it produces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StreamOverflowPolicy {
+    /// Wait for the client to make room (backpressure)
+    #[default]
+    Block,
+    /// End the stream with ResourceExhausted once the buffer is full
+    Error,
+}
+
+// Buffering shared by the streaming services
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StreamSettings {
+    pub(crate) buffer: usize,  // Responses held per stream, at least 1
+    pub(crate) overflow: StreamOverflowPolicy,  // Full buffer: wait or fail
+}
+
+impl Default for StreamSettings {
+    fn default() -> Self {
+        Self { buffer: DEFAULT_STREAM_BUFFER, overflow: StreamOverflowPolicy::default() }
+    }
+}
+
+// Why a response could not be queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SendFailure {
+    // The client dropped the response stream
+    Closed,
+    // The buffer was full under StreamOverflowPolicy::Error; the stream has been ended
+    Overflow,
+}
+
+// Producer half of an outbound stream
+pub(crate) struct StreamSender<T> {
+    tx: mpsc::Sender<Result<T, Status>>,
+    overflow: StreamOverflowPolicy,
+    // Deepest the channel got, shared by the streams of one service
+    high_water: Arc<AtomicUsize>,
+}
+
+impl<T> StreamSender<T> {
+    // Queue `item` according to the overflow policy
+    pub(crate) async fn send(&self, item: T) -> Result<(), SendFailure> {
+        match self.overflow {
+            StreamOverflowPolicy::Block => {
+                self.tx.send(Ok(item)).await.map_err(|_| SendFailure::Closed)?;
+            }
+            StreamOverflowPolicy::Error => match self.tx.try_send(Ok(item)) {
+                Ok(()) => {}
+                Err(TrySendError::Closed(_)) => return Err(SendFailure::Closed),
+                Err(TrySendError::Full(_)) => {
+                    let buffer = self.tx.max_capacity();
+                    warn!("Ending stream: client fell {} responses behind", buffer);
+                    self.fail(Status::resource_exhausted(format!(
+                        "client is reading too slowly: stream buffer of {} responses is full",
+                        buffer
+                    ))).await;
+                    return Err(SendFailure::Overflow);
+                }
+            },
+        }
+        let queued = self.tx.max_capacity() - self.tx.capacity();
+        self.high_water.fetch_max(queued, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // End the stream with `status`, after the responses already queued
+    pub(crate) async fn fail(&self, status: Status) {
+        self.tx.send(Err(status)).await.ok();
+    }
+
+    // Whether the client has dropped the response stream
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+// Response stream handed to tonic
+pub(crate) type StreamReceiver<T> = FlushBeforeError<ReceiverStream<Result<T, Status>>>;
+
+// Open an outbound stream with `settings`, recording its depth in `high_water`
+pub(crate) fn channel<T>(settings: StreamSettings, high_water: &Arc<AtomicUsize>) -> (StreamSender<T>, StreamReceiver<T>) {
+    let (tx, rx) = mpsc::channel(settings.buffer);
+    let sender = StreamSender { tx, overflow: settings.overflow, high_water: high_water.clone() };
+    (sender, FlushBeforeError::new(ReceiverStream::new(rx)))
+}
+
+// Holds each error back for one poll so the responses before it are sent
+// tonic 0.10 encodes all ready responses into one buffer and discards that
+// buffer when the next item is an error, so without the extra Pending the
+// chunks queued ahead of an error would never reach the client
+pub(crate) struct FlushBeforeError<S> {
+    inner: S,
+    error: Option<Status>,
+}
+
+impl<S> FlushBeforeError<S> {
+    fn new(inner: S) -> Self {
+        Self { inner, error: None }
+    }
+}
+
+impl<S, T> Stream for FlushBeforeError<S>
+where
+    S: Stream<Item = Result<T, Status>> + Unpin,
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(status) = self.error.take() {
+            return Poll::Ready(Some(Err(status)));
+        }
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Err(status))) => {
+                self.error = Some(status);
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            other => other,
+        }
+    }
+}
+
+// Unit tests for the overflow policies
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_error_policy_ends_full_stream() {
+        let high_water = Arc::default();
+        let settings = StreamSettings { buffer: 2, overflow: StreamOverflowPolicy::Error };
+        let (tx, mut rx) = channel(settings, &high_water);
+
+        tx.send(1).await.unwrap();
+        tx.send(2).await.unwrap();
+        // Full: the stream is ended once the client makes room for the error
+        let reader = tokio::spawn(async move {
+            let mut items = Vec::new();
+            while let Some(item) = rx.next().await {
+                items.push(item.map_err(|status| status.code()));
+            }
+            items
+        });
+        assert_eq!(tx.send(3).await, Err(SendFailure::Overflow));
+        drop(tx);
+        let items = reader.await.unwrap();
+        assert_eq!(items, vec![Ok(1), Ok(2), Err(tonic::Code::ResourceExhausted)]);
+        assert_eq!(high_water.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,126 @@
+//! Echo Message Validation
+//! Applies the EchoValidation a unary echo request asks for:
+//! 1. REJECT_CONTROL: refuses control characters other than \n, \r and \t,
+//!    and unassigned code points, naming the byte offset of the first one
+//! 2. NORMALIZE_NFC: converts the message to Unicode Normalization Form C
+//!
+//! The check runs on the message as received, before normalization.
+//! Normalization and the Unicode tables come from unicode-normalization,
+//! behind the nfc feature. Without it NORMALIZE_NFC fails with
+//! UNIMPLEMENTED and only noncharacters count as unassigned.
+
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Status};
+use tracing::error;
+use crate::proto::echo::{EchoValidation, OFFSET_METADATA};
+
+// Apply the validation with wire value `validation` to `message`
+pub(crate) fn validate(validation: i32, message: String) -> Result<String, Status> {
+    let Ok(validation) = EchoValidation::try_from(validation) else {
+        error!("Received unknown echo validation {}", validation);
+        return Err(Status::invalid_argument(format!("unknown echo validation {}", validation)));
+    };
+    let (reject_control, normalize) = match validation {
+        EchoValidation::None => (false, false),
+        EchoValidation::RejectControl => (true, false),
+        EchoValidation::NormalizeNfc => (false, true),
+        EchoValidation::RejectControlAndNormalizeNfc => (true, true),
+    };
+    if reject_control {
+        check_characters(&message)?;
+    }
+    if normalize {
+        return normalize_nfc(message);
+    }
+    Ok(message)
+}
+
+// Reject the first character that is neither allowed whitespace nor assigned
+fn check_characters(message: &str) -> Result<(), Status> {
+    let Some((offset, c)) = message.char_indices().find(|&(_, c)| !allowed(c)) else {
+        return Ok(());
+    };
+    let kind = if c.is_control() { "control character" } else { "unassigned code point" };
+    error!("Rejected echo message: {} U+{:04X} at byte offset {}", kind, u32::from(c), offset);
+    let mut metadata = MetadataMap::new();
+    metadata.insert(OFFSET_METADATA, offset.into());
+    Err(Status::with_metadata(
+        Code::InvalidArgument,
+        format!("{} U+{:04X} at byte offset {}", kind, u32::from(c), offset),
+        metadata,
+    ))
+}
+
+// Whether REJECT_CONTROL lets `c` through
+fn allowed(c: char) -> bool {
+    if c.is_control() {
+        return matches!(c, '\n' | '\r' | '\t');
+    }
+    assigned(c)
+}
+
+// Assigned in the Unicode version of unicode-normalization; private use
+// characters are assigned for this purpose
+#[cfg(feature = "nfc")]
+fn assigned(c: char) -> bool {
+    let private_use = matches!(c, '\u{E000}'..='\u{F8FF}' | '\u{F0000}'..='\u{FFFFD}' | '\u{100000}'..='\u{10FFFD}');
+    private_use || unicode_normalization::char::is_public_assigned(c)
+}
+
+// Without the tables, only the 66 noncharacters are known to be unassigned
+#[cfg(not(feature = "nfc"))]
+fn assigned(c: char) -> bool {
+    let noncharacter = ('\u{FDD0}'..='\u{FDEF}').contains(&c) || u32::from(c) & 0xFFFE == 0xFFFE;
+    !noncharacter
+}
+
+// Normalization Form C; messages already in it are returned as they are
+#[cfg(feature = "nfc")]
+fn normalize_nfc(message: String) -> Result<String, Status> {
+    use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+    if is_nfc_quick(message.chars()) == IsNormalized::Yes {
+        return Ok(message);
+    }
+    Ok(message.nfc().collect())
+}
+
+#[cfg(not(feature = "nfc"))]
+fn normalize_nfc(_message: String) -> Result<String, Status> {
+    error!("Received NFC echo validation, but the nfc feature is not enabled");
+    Err(Status::unimplemented("NFC normalization needs a server built with the nfc feature"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Offsets are in bytes, so multi-byte characters before the culprit count fully
+    #[test]
+    fn test_reject_control_reports_byte_offset() {
+        let validation = EchoValidation::RejectControl as i32;
+        assert_eq!(validate(validation, "tab\tand\r\nnewline".into()).unwrap(), "tab\tand\r\nnewline");
+
+        let err = validate(validation, "é\u{7}".into()).unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert_eq!(err.metadata().get(OFFSET_METADATA).unwrap(), "2");
+
+        let err = validate(validation, "a\u{FFFF}".into()).unwrap_err();
+        assert!(err.message().contains("unassigned code point U+FFFF at byte offset 1"), "{}", err.message());
+    }
+
+    #[test]
+    fn test_unknown_validation_is_rejected() {
+        assert_eq!(validate(99, "text".into()).unwrap_err().code(), Code::InvalidArgument);
+        assert_eq!(validate(EchoValidation::None as i32, "a\u{7}".into()).unwrap(), "a\u{7}");
+    }
+
+    #[cfg(feature = "nfc")]
+    #[test]
+    fn test_assigned_uses_unicode_tables() {
+        assert!(allowed('\u{E000}'), "private use is assigned");
+        assert!(!allowed('\u{0378}'), "U+0378 is unassigned");
+        let validation = EchoValidation::RejectControlAndNormalizeNfc as i32;
+        assert_eq!(validate(validation, "e\u{301}".into()).unwrap(), "\u{E9}");
+    }
+}
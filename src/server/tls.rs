@@ -0,0 +1,127 @@
+//! TLS Termination
+//! The server negotiates TLS itself, with rustls, before hyper serves a
+//! connection (see serving.rs):
+//! 1. TlsIdentity: the PEM certificate chain and private key given to
+//!    GrpcServerBuilder::tls
+//! 2. TlsVersion: the oldest protocol version clients may negotiate
+//! 3. acceptor: the rustls configuration built from them and the optional
+//!    client CA (mutual TLS), offering HTTP/2 over ALPN like tonic's own
+//!
+//! rustls only offers the versions allowed, so a client that cannot speak
+//! the minimum fails the handshake with a protocol_version alert.
+//!
+//! The configuration is built when serving starts; a certificate or key
+//! that cannot be read fails serve() with InvalidArgument.
+
+use std::io::Cursor;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::version::{TLS12, TLS13};
+use tokio_rustls::rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig, SupportedProtocolVersion};
+use tokio_rustls::TlsAcceptor;
+use tonic::{Code, Status};
+use tracing::error;
+
+// Protocol offered over ALPN; gRPC needs HTTP/2
+const ALPN_H2: &[u8] = b"h2";
+
+// Versions rustls may negotiate for each minimum, newest first
+static FROM_TLS12: &[&SupportedProtocolVersion] = &[&TLS13, &TLS12];
+static FROM_TLS13: &[&SupportedProtocolVersion] = &[&TLS13];
+
+/// Lowest TLS version a server accepts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TlsVersion {
+    /// TLS 1.2 or newer; the oldest version the TLS library supports
+    #[default]
+    Tls12,
+    /// TLS 1.3 only
+    Tls13,
+}
+
+impl TlsVersion {
+    fn allowed(self) -> &'static [&'static SupportedProtocolVersion] {
+        match self {
+            TlsVersion::Tls12 => FROM_TLS12,
+            TlsVersion::Tls13 => FROM_TLS13,
+        }
+    }
+}
+
+// PEM-encoded certificate chain and private key of the server
+#[derive(Clone)]
+pub(crate) struct TlsIdentity {
+    pub(crate) cert: Vec<u8>,
+    pub(crate) key: Vec<u8>,
+}
+
+// Acceptor presenting `identity` to clients speaking `min_version` or newer;
+// with `client_ca`, clients must present a certificate chaining to it
+pub(crate) fn acceptor(identity: &TlsIdentity, client_ca: Option<&[u8]>, min_version: TlsVersion) -> Result<TlsAcceptor, Status> {
+    let builder = ServerConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(min_version.allowed())
+        .map_err(|e| invalid(e.to_string()))?;
+    let builder = match client_ca {
+        Some(ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in certificates(ca)? {
+                roots.add(&cert).map_err(|e| invalid(format!("unusable client CA certificate: {}", e)))?;
+            }
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut config = builder
+        .with_single_cert(certificates(&identity.cert)?, private_key(&identity.key)?)
+        .map_err(|e| invalid(e.to_string()))?;
+    config.alpn_protocols.push(ALPN_H2.to_vec());
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+// Every certificate of a PEM bundle; at least one is required
+fn certificates(pem: &[u8]) -> Result<Vec<Certificate>, Status> {
+    let certs = rustls_pemfile::certs(&mut Cursor::new(pem)).map_err(|e| invalid(format!("unreadable certificate: {}", e)))?;
+    if certs.is_empty() {
+        return Err(invalid("no certificate found".to_string()));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+// The first RSA, PKCS#8 or EC private key of a PEM file
+fn private_key(pem: &[u8]) -> Result<PrivateKey, Status> {
+    let mut reader = Cursor::new(pem);
+    while let Ok(Some(item)) = rustls_pemfile::read_one(&mut reader) {
+        if let rustls_pemfile::Item::RSAKey(key) | rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::ECKey(key) = item {
+            return Ok(PrivateKey(key));
+        }
+    }
+    Err(invalid("no private key found".to_string()))
+}
+
+fn invalid(reason: String) -> Status {
+    error!("Invalid TLS configuration: {}", reason);
+    Status::new(Code::InvalidArgument, format!("invalid TLS configuration: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acceptor_reads_generated_identity() {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let identity = TlsIdentity {
+            cert: cert.serialize_pem().unwrap().into_bytes(),
+            key: cert.serialize_private_key_pem().into_bytes(),
+        };
+        assert!(acceptor(&identity, None, TlsVersion::Tls12).is_ok());
+        assert!(acceptor(&identity, Some(&identity.cert), TlsVersion::Tls13).is_ok());
+
+        let swapped = TlsIdentity { cert: identity.key.clone(), key: identity.cert.clone() };
+        let err = acceptor(&swapped, None, TlsVersion::Tls12).err().expect("swapped PEM files accepted");
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+}
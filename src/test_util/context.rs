@@ -0,0 +1,383 @@
+//! Test Context
+//! Isolated server-and-client environments for integration tests:
+//! 1. Parallel test execution through dynamic port allocation
+//! 2. Isolated test environments for each test
+//! 3. Automatic resource cleanup
+//! 4. Simplified test setup and teardown
+//! 5. Connection management
+//! 6. Running many concurrent clients against one server
+//! 7. The same environment over TLS, with a certificate generated per test
+//! 8. Mutual TLS, the clients presenting a certificate of their own
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU16, Ordering};
+use base64::Engine;
+use tokio::time::{timeout_at, Duration, Instant};
+use tonic::Status;
+use tracing::error;
+use crate::client::GrpcClientBuilder;
+use crate::server::{GrpcServerBuilder, ServerMetrics, Shutdown};
+use crate::{GrpcClient, GrpcServer};
+
+// Global atomic counter for port allocation
+// - Starts at 20000 to avoid system-reserved ports and to stay below the
+//   ephemeral range (32768+ on Linux, 49152+ on macOS/Windows); otherwise
+//   outgoing client sockets, including TIME_WAIT leftovers from earlier
+//   runs, can occupy the port a test server is about to bind
+// - Atomic operations ensure thread-safe incrementation
+//   (SeqCst ordering ensures sequential consistency across threads)
+// - Each test gets a unique port to avoid conflicts
+static NEXT_PORT: AtomicU16 = AtomicU16::new(20000);
+
+// Readiness budget for a freshly spawned test server: 50 x 20ms = 1s
+const CONNECT_RETRIES: u32 = 50;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+// Name the generated test certificate is issued for; clients verify against it
+const TLS_DOMAIN: &str = "localhost";
+
+/// Common name of the certificate clients present under `setup_mtls_with`
+pub const TEST_CLIENT_NAME: &str = "test-client";
+
+/// Reserve a fresh port for tests that build their own server
+///
+/// Shares the counter with `TestContext`, so ports never collide within
+/// one test binary.
+pub fn next_port() -> u16 {
+    NEXT_PORT.fetch_add(1, Ordering::SeqCst)
+}
+
+/// Outcome of one task started by `TestContext::run_concurrent`
+///
+/// Boxed so tasks can use `?` on `Status`, `Elapsed` and other errors alike.
+pub type TaskResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// A running server with a connected client, isolated per test
+///
+/// The server listens on its own port and is shut down when the context
+/// is dropped, even if the test panics.
+///
+/// ```
+/// use embedded_recruitment_task::testing::TestContext;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), tonic::Status> {
+/// let ctx = TestContext::setup_with(|builder| builder.startup_banner(false)).await?;
+/// let reply = ctx.client.echo().echo("hello").await?;
+/// assert_eq!(reply, "hello");
+/// # Ok(())
+/// # }
+/// ```
+pub struct TestContext {
+    // Optional shutdown handle allows for graceful server shutdown
+    // None after shutdown is triggered (taken)
+    shutdown: Option<Shutdown>,
+    /// Client connected to the server; clones share its connection
+    pub client: GrpcClient,
+    /// Address the server is listening on (without scheme), for opening
+    /// additional, independent connections
+    pub addr: String,
+    /// Live metrics of the server (connections, in-flight and shed requests)
+    pub metrics: ServerMetrics,
+    // CA certificate (PEM) clients must trust; None for plaintext
+    tls_ca: Option<String>,
+    // Certificate clients present; None unless mutual TLS
+    client_certificate: Option<ClientCertificate>,
+}
+
+// A client certificate and key, PEM-encoded, plus the certificate's DER
+// Signing is randomized, so the PEM is derived from the one DER encoding
+#[derive(Clone)]
+struct ClientCertificate {
+    cert_pem: String,
+    key_pem: String,
+    der: Vec<u8>,
+}
+
+// Generate a self-signed certificate for TLS_DOMAIN, as (cert, key) PEM
+// Kept in memory only, so there are no files to clean up
+fn self_signed_certificate() -> Result<(String, String), Status> {
+    let failed = |e: rcgen::Error| Status::internal(format!("failed to generate test certificate: {}", e));
+    let cert = rcgen::generate_simple_self_signed(vec![TLS_DOMAIN.to_string()]).map_err(failed)?;
+    let cert_pem = cert.serialize_pem().map_err(failed)?;
+    Ok((cert_pem, cert.serialize_private_key_pem()))
+}
+
+// Generate a self-signed client certificate for TEST_CLIENT_NAME
+// The server trusts it as its own CA
+fn client_certificate() -> Result<ClientCertificate, Status> {
+    let failed = |e: rcgen::Error| Status::internal(format!("failed to generate test client certificate: {}", e));
+    let mut params = rcgen::CertificateParams::new(vec![format!("{}.{}", TEST_CLIENT_NAME, TLS_DOMAIN)]);
+    params.distinguished_name.push(rcgen::DnType::CommonName, TEST_CLIENT_NAME);
+    let cert = rcgen::Certificate::from_params(params).map_err(failed)?;
+    let der = cert.serialize_der().map_err(failed)?;
+    let body = base64::engine::general_purpose::STANDARD.encode(&der);
+    let lines: Vec<&str> = body.as_bytes().chunks(64).map(|line| std::str::from_utf8(line).unwrap_or_default()).collect();
+    Ok(ClientCertificate {
+        cert_pem: format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", lines.join("\n")),
+        key_pem: cert.serialize_private_key_pem(),
+        der,
+    })
+}
+
+impl TestContext {
+    /// Start a server with default settings on `[::1]` and connect a client
+    ///
+    /// # Returns
+    /// * `Result<TestContext, Status>` - The running environment, or the status of the failed setup.
+    pub async fn setup() -> Result<Self, Status> {
+        Self::setup_with(|builder| builder).await
+    }
+
+    /// Same as `setup`, but lets the test customize the server builder
+    ///
+    /// # Arguments
+    /// * `configure` - Applied to a builder whose address is already set.
+    ///
+    /// # Returns
+    /// * `Result<TestContext, Status>` - The running environment, or the status of the failed setup.
+    pub async fn setup_with(
+        configure: impl FnOnce(GrpcServerBuilder) -> GrpcServerBuilder,
+    ) -> Result<Self, Status> {
+        Self::setup_on("[::1]", configure).await
+    }
+
+    /// Same as `setup_with`, but binds the server to a specific host
+    ///
+    /// Needed by tests that care about the address family (e.g. per-IP limits).
+    ///
+    /// # Arguments
+    /// * `host` - Host part of the listen address, e.g. "127.0.0.1" or "[::1]".
+    /// * `configure` - Applied to a builder whose address is already set.
+    ///
+    /// # Returns
+    /// * `Result<TestContext, Status>` - The running environment, or the status of the failed setup.
+    pub async fn setup_on(
+        host: &str,
+        configure: impl FnOnce(GrpcServerBuilder) -> GrpcServerBuilder,
+    ) -> Result<Self, Status> {
+        Self::start(host, None, None, configure).await
+    }
+
+    /// Same as `setup`, but served over TLS with a freshly generated certificate
+    ///
+    /// The client (and every `new_client`) trusts it, so test bodies need no changes.
+    ///
+    /// # Returns
+    /// * `Result<TestContext, Status>` - The running environment, or the status of the failed setup.
+    pub async fn setup_tls() -> Result<Self, Status> {
+        Self::setup_tls_with(|builder| builder).await
+    }
+
+    /// Same as `setup_with`, but served over TLS like `setup_tls`
+    ///
+    /// # Arguments
+    /// * `configure` - Applied to a builder whose address and certificate are already set.
+    ///
+    /// # Returns
+    /// * `Result<TestContext, Status>` - The running environment, or the status of the failed setup.
+    pub async fn setup_tls_with(
+        configure: impl FnOnce(GrpcServerBuilder) -> GrpcServerBuilder,
+    ) -> Result<Self, Status> {
+        let (cert_pem, key_pem) = self_signed_certificate()?;
+        Self::start("[::1]", Some((cert_pem, key_pem)), None, configure).await
+    }
+
+    /// Same as `setup_tls_with`, but the server requires client certificates
+    /// (mutual TLS) and every client presents one issued for `TEST_CLIENT_NAME`
+    ///
+    /// # Arguments
+    /// * `configure` - Applied to a builder whose address, certificate and client CA are already set.
+    ///
+    /// # Returns
+    /// * `Result<TestContext, Status>` - The running environment, or the status of the failed setup.
+    pub async fn setup_mtls_with(
+        configure: impl FnOnce(GrpcServerBuilder) -> GrpcServerBuilder,
+    ) -> Result<Self, Status> {
+        let (cert_pem, key_pem) = self_signed_certificate()?;
+        let client = client_certificate()?;
+        let ca_pem = client.cert_pem.clone();
+        Self::start("[::1]", Some((cert_pem, key_pem)), Some(client), |builder| configure(builder.tls_client_ca(ca_pem))).await
+    }
+
+    // Start a server, plaintext or with the given (cert, key), and connect to
+    // it, presenting `client_certificate` if set
+    async fn start(
+        host: &str,
+        tls: Option<(String, String)>,
+        client_certificate: Option<ClientCertificate>,
+        configure: impl FnOnce(GrpcServerBuilder) -> GrpcServerBuilder,
+    ) -> Result<Self, Status> {
+        // Atomically get and increment port number
+        let port = next_port();
+        let addr = format!("{}:{}", host, port);
+
+        // Build and configure server instance
+        let mut builder = GrpcServer::builder().address(addr.clone());
+        if let Some((cert_pem, key_pem)) = &tls {
+            builder = builder.tls(cert_pem, key_pem);
+        }
+        let (server, shutdown) = configure(builder).build()?;
+        let metrics = server.metrics();
+
+        // Spawn server in separate task to not block test execution
+        // Server runs until shutdown signal is received
+        tokio::spawn(async move {
+            if let Err(e) = server.serve().await {
+                error!("Test server error: {}", e);
+            }
+        });
+
+        // Connect eagerly, retrying while the server is still binding
+        // Returns as soon as the server accepts instead of sleeping blindly
+        let tls_ca = tls.map(|(cert_pem, _)| cert_pem);
+        let client = client_builder(&addr, tls_ca.as_deref(), client_certificate.as_ref())?
+            .connect_retries(CONNECT_RETRIES, CONNECT_RETRY_DELAY)
+            .connect_eager()
+            .await?;
+
+        Ok(Self {
+            shutdown: Some(shutdown),
+            client,
+            addr,
+            metrics,
+            tls_ca,
+            client_certificate,
+        })
+    }
+}
+
+impl TestContext {
+    /// DER encoding of the certificate clients present under `setup_mtls_with`
+    ///
+    /// # Returns
+    /// * `Option<&[u8]>` - The certificate, or `None` without mutual TLS.
+    pub fn client_certificate_der(&self) -> Option<&[u8]> {
+        self.client_certificate.as_ref().map(|certificate| certificate.der.as_slice())
+    }
+
+    /// Open an additional, independent connection to the test server
+    ///
+    /// For tests that need a separate channel rather than a clone of `client`.
+    ///
+    /// # Returns
+    /// * `Result<GrpcClient, Status>` - The connected client.
+    pub async fn new_client(&self) -> Result<GrpcClient, Status> {
+        client_builder(&self.addr, self.tls_ca.as_deref(), self.client_certificate.as_ref())?
+            .connect_eager()
+            .await
+    }
+
+    /// Run `per_client` as `clients` concurrent tasks and wait for all of them
+    ///
+    /// # Arguments
+    /// * `clients` - Number of tasks.
+    /// * `per_client` - Called with each task's index and a clone of the shared client.
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Err describing every task that failed or panicked.
+    pub async fn run_concurrent<F, Fut>(&self, clients: usize, per_client: F) -> Result<(), String>
+    where
+        F: Fn(usize, GrpcClient) -> Fut,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        self.run_tasks(clients, per_client, None).await
+    }
+
+    /// Same as `run_concurrent`, but fails tasks still running after `deadline`
+    ///
+    /// The deadline covers the whole run, not each task; late tasks are aborted.
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - Err describing every task that failed, panicked or ran late.
+    pub async fn run_concurrent_within<F, Fut>(
+        &self,
+        deadline: Duration,
+        clients: usize,
+        per_client: F,
+    ) -> Result<(), String>
+    where
+        F: Fn(usize, GrpcClient) -> Fut,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        self.run_tasks(clients, per_client, Some(Instant::now() + deadline)).await
+    }
+
+    async fn run_tasks<F, Fut>(&self, clients: usize, per_client: F, deadline: Option<Instant>) -> Result<(), String>
+    where
+        F: Fn(usize, GrpcClient) -> Fut,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        // Spawn everything first so the tasks really run concurrently
+        let handles: Vec<_> = (0..clients)
+            .map(|id| tokio::spawn(per_client(id, self.client.clone())))
+            .collect();
+
+        let mut failures = Vec::new();
+        for (id, mut handle) in handles.into_iter().enumerate() {
+            let joined = match deadline {
+                Some(deadline) => match timeout_at(deadline, &mut handle).await {
+                    Ok(joined) => joined,
+                    Err(_) => {
+                        handle.abort();
+                        failures.push(format!("client {}: still running at the deadline", id));
+                        continue;
+                    }
+                },
+                None => handle.await,
+            };
+            match joined {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => failures.push(format!("client {}: {}", id, e)),
+                Err(e) if e.is_panic() => failures.push(format!("client {}: panicked: {}", id, panic_message(e.into_panic()))),
+                Err(e) => failures.push(format!("client {}: {}", id, e)),
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} of {} clients failed:\n{}", failures.len(), clients, failures.join("\n")))
+        }
+    }
+}
+
+// Client builder for the test server, trusting `tls_ca` when it serves TLS
+// and presenting `certificate` when it requires one
+fn client_builder(
+    addr: &str,
+    tls_ca: Option<&str>,
+    certificate: Option<&ClientCertificate>,
+) -> Result<GrpcClientBuilder, Status> {
+    let builder = match tls_ca {
+        Some(ca_pem) => GrpcClient::builder(format!("https://{}", addr))?.tls(ca_pem, Some(TLS_DOMAIN))?,
+        None => return GrpcClient::builder(format!("http://{}", addr)),
+    };
+    match certificate {
+        Some(certificate) => builder.tls_identity(&certificate.cert_pem, &certificate.key_pem),
+        None => Ok(builder),
+    }
+}
+
+// Text of a panic payload (panic!/expect produce &str or String)
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "non-string panic payload".to_string()),
+    }
+}
+
+// Drop implementation ensures cleanup happens even if test panics
+// This prevents resource leaks and hanging servers
+impl Drop for TestContext {
+    fn drop(&mut self) {
+        // Take ownership of shutdown handle and trigger server shutdown
+        // take() ensures shutdown happens only once
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.trigger();
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Latency Assertions
+//! Helpers for asserting on performance, not just correctness.
+//!
+//! `LatencyRecorder` collects request durations from many concurrent tasks
+//! and asserts on their percentiles:
+//! 1. Recording goes to one of several shards, so tasks on different
+//!    threads rarely contend for the same lock
+//! 2. Percentiles use the nearest-rank method: the result is always one of
+//!    the recorded samples, so p99 of a small run is its maximum
+//! 3. A failed assertion prints the whole distribution, not just the
+//!    number that crossed the limit
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Independent sample buffers; more than typical test worker threads
+const SHARDS: usize = 16;
+
+/// Concurrent recorder of latencies with percentile assertions
+///
+/// Cloning is cheap and every clone feeds the same samples, so each task
+/// can own one.
+#[derive(Debug, Clone)]
+pub struct LatencyRecorder {
+    shards: Arc<[Mutex<Vec<Duration>>]>,
+}
+
+impl Default for LatencyRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyRecorder {
+    /// Create an empty recorder
+    pub fn new() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::new(Vec::new())).collect(),
+        }
+    }
+
+    /// Record one latency
+    pub fn record(&self, latency: Duration) {
+        // Pick the shard from the current thread, so concurrent recorders
+        // on different threads usually take different locks
+        let mut hasher = DefaultHasher::new();
+        std::thread::current().id().hash(&mut hasher);
+        let shard = hasher.finish() as usize % SHARDS;
+        self.shards[shard].lock().unwrap().push(latency);
+    }
+
+    /// Await `future` and record how long it took
+    ///
+    /// # Returns
+    /// * `F::Output` - The output of the future, whatever it is.
+    pub async fn time<F: Future>(&self, future: F) -> F::Output {
+        let started = Instant::now();
+        let output = future.await;
+        self.record(started.elapsed());
+        output
+    }
+
+    /// Number of recorded samples
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+
+    /// Whether nothing has been recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All samples, sorted ascending
+    pub fn samples(&self) -> Vec<Duration> {
+        let mut samples: Vec<Duration> = self
+            .shards
+            .iter()
+            .flat_map(|shard| shard.lock().unwrap().clone())
+            .collect();
+        samples.sort_unstable();
+        samples
+    }
+
+    /// Nearest-rank percentile of the samples
+    ///
+    /// # Arguments
+    /// * `p` - The percentile, from 0 to 100 (fractions like 99.9 are allowed).
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - The smallest sample with at least `p`% of samples
+    ///   at or below it, or `None` if nothing was recorded.
+    ///
+    /// # Panics
+    /// If `p` is outside 0..=100.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        nearest_rank(&self.samples(), p)
+    }
+
+    /// Arithmetic mean of the samples, or `None` if nothing was recorded
+    pub fn mean(&self) -> Option<Duration> {
+        mean(&self.samples())
+    }
+
+    /// Summary of the recorded distribution, for printing
+    pub fn distribution(&self) -> Distribution {
+        Distribution { samples: self.samples() }
+    }
+
+    /// Panic unless the 99th percentile is strictly below `limit`
+    ///
+    /// The panic message contains the full distribution. Also panics if
+    /// nothing was recorded, since that usually means the test is broken.
+    #[track_caller]
+    pub fn assert_p99_under(&self, limit: Duration) {
+        let distribution = self.distribution();
+        let p99 = nearest_rank(&distribution.samples, 99.0)
+            .unwrap_or_else(|| panic!("no latencies recorded; cannot check p99 < {:?}", limit));
+        assert!(p99 < limit, "p99 latency {:?} is not under {:?}\n{}", p99, limit, distribution);
+    }
+}
+
+/// Sorted snapshot of a recorder's samples; `Display` prints the distribution
+#[derive(Debug, Clone)]
+pub struct Distribution {
+    samples: Vec<Duration>,
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Some(mean), Some(max)) = (mean(&self.samples), self.samples.last()) else {
+            return write!(f, "no samples");
+        };
+        writeln!(f, "{} samples, mean {:?}, min {:?}, max {:?}", self.samples.len(), mean, self.samples[0], max)?;
+        for p in [10.0, 25.0, 50.0, 75.0, 90.0, 95.0, 99.0, 99.9] {
+            // Non-empty, so every percentile exists
+            writeln!(f, "  p{:<5} {:?}", p, nearest_rank(&self.samples, p).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+// Nearest-rank percentile of a sorted slice
+fn nearest_rank(sorted: &[Duration], p: f64) -> Option<Duration> {
+    assert!((0.0..=100.0).contains(&p), "percentile {} is outside 0..=100", p);
+    // Multiply first: p / 100.0 would add rounding error (99.9% of 1000 must be rank 999)
+    let rank = (p * sorted.len() as f64 / 100.0).ceil() as usize;
+    sorted.get(rank.max(1) - 1).copied()
+}
+
+// Mean of a slice; computed in nanoseconds to avoid overflowing Duration sums
+fn mean(samples: &[Duration]) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+    let total: u128 = samples.iter().map(Duration::as_nanos).sum();
+    Some(Duration::from_nanos((total / samples.len() as u128) as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder_of(millis: &[u64]) -> LatencyRecorder {
+        let recorder = LatencyRecorder::new();
+        for &ms in millis {
+            recorder.record(Duration::from_millis(ms));
+        }
+        recorder
+    }
+
+    fn ms(ms: u64) -> Option<Duration> {
+        Some(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn test_percentiles_of_known_dataset() {
+        // Classic nearest-rank example, recorded out of order
+        let recorder = recorder_of(&[35, 20, 50, 15, 40]);
+        assert_eq!(recorder.percentile(0.0), ms(15));
+        assert_eq!(recorder.percentile(5.0), ms(15));
+        assert_eq!(recorder.percentile(30.0), ms(20));
+        assert_eq!(recorder.percentile(40.0), ms(20));
+        assert_eq!(recorder.percentile(50.0), ms(35));
+        assert_eq!(recorder.percentile(100.0), ms(50));
+        assert_eq!(recorder.mean(), ms(32));
+
+        let recorder = recorder_of(&(1..=1000).collect::<Vec<_>>());
+        assert_eq!(recorder.percentile(50.0), ms(500));
+        assert_eq!(recorder.percentile(99.0), ms(990));
+        assert_eq!(recorder.percentile(99.9), ms(999));
+    }
+
+    #[test]
+    fn test_percentiles_of_small_samples() {
+        let empty = LatencyRecorder::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.percentile(50.0), None);
+        assert_eq!(empty.mean(), None);
+
+        let single = recorder_of(&[7]);
+        assert_eq!(single.percentile(0.0), ms(7));
+        assert_eq!(single.percentile(99.0), ms(7));
+
+        // With two samples the median is the lower one and p99 the maximum
+        let pair = recorder_of(&[10, 30]);
+        assert_eq!(pair.percentile(50.0), ms(10));
+        assert_eq!(pair.percentile(51.0), ms(30));
+        assert_eq!(pair.percentile(99.0), ms(30));
+        assert_eq!(pair.mean(), ms(20));
+    }
+
+    #[test]
+    fn test_concurrent_recording() {
+        let recorder = LatencyRecorder::new();
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                let recorder = recorder.clone();
+                scope.spawn(move || {
+                    for ms in 1..=100 {
+                        recorder.record(Duration::from_millis(ms));
+                    }
+                });
+            }
+        });
+        assert_eq!(recorder.len(), 800);
+        assert_eq!(recorder.percentile(100.0), ms(100));
+    }
+
+    #[test]
+    fn test_assert_p99_under() {
+        let recorder = recorder_of(&(1..=100).collect::<Vec<_>>());
+        recorder.assert_p99_under(Duration::from_millis(100));
+
+        let failure = std::panic::catch_unwind(|| recorder.assert_p99_under(Duration::from_millis(99)))
+            .expect_err("p99 of 99ms is not under 99ms");
+        let message = failure.downcast_ref::<String>().unwrap();
+        assert!(message.contains("100 samples"), "{}", message);
+        assert!(message.contains("p50"), "{}", message);
+
+        assert!(std::panic::catch_unwind(|| LatencyRecorder::new().assert_p99_under(Duration::MAX)).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+//! Test Utilities
+//! The harness this crate's own integration tests use, for crates that
+//! embed the server and want to test against it the same way:
+//! 1. TestContext: a server on a fresh port with a connected client, shut
+//!    down when the context is dropped (see context.rs)
+//! 2. LatencyRecorder: percentile assertions on request latencies
+//!    (see latency.rs)
+//!
+//! Also reachable as `embedded_recruitment_task::testing`. Only compiled
+//! with the `test-util` feature, which also exposes the mock
+//! service backends (e.g. `EchoService::with_mock`). Setup failures are
+//! returned as `tonic::Status`, never panics, so callers decide how a
+//! broken environment fails their test.
+
+mod context;
+mod latency;
+
+pub use context::{next_port, TaskResult, TestContext, TEST_CLIENT_NAME};
+pub use latency::{Distribution, LatencyRecorder};
@@ -0,0 +1,215 @@
+//! Trace Context Propagation
+//! Lets logs on the client and the server share a trace ID without a
+//! full OpenTelemetry pipeline:
+//! 1. The client derives a TraceContext from its active tracing span
+//!    and sends it as a W3C `traceparent` metadata header
+//! 2. The server parses the header, records the trace ID on the per-RPC
+//!    span and stores the context in the request extensions
+//! 3. Independently of spans, every call carries an `x-correlation-id`
+//!    that the client logs and the server records on the per-RPC span,
+//!    so one request can be found in both logs by a single ID
+//!
+//! Header format: `00-<32 hex trace id>-<16 hex parent id>-<2 hex flags>`
+//! See https://www.w3.org/TR/trace-context/#traceparent-header
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use tracing_subscriber::registry::{LookupSpan, Registry};
+
+/// Metadata key carrying the trace context
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Metadata key carrying the correlation ID of a request
+pub const CORRELATION_ID: &str = "x-correlation-id";
+
+// Longest correlation ID accepted from callers
+const MAX_CORRELATION_ID_LEN: usize = 128;
+
+// Only version defined by the spec; later versions are parsed leniently
+const VERSION: u8 = 0;
+// Flag bit telling downstream services the trace is sampled
+const SAMPLED: u8 = 0x01;
+
+/// W3C trace context of one call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    trace_id: u128,
+    parent_id: u64,
+    flags: u8,
+}
+
+impl TraceContext {
+    /// Start a new trace with random identifiers
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: (u128::from(random_u64()) << 64) | u128::from(random_u64()),
+            parent_id: random_u64(),
+            flags: SAMPLED,
+        }
+    }
+
+    /// Same trace, new parent ID; used for each outgoing call
+    pub fn child(&self) -> Self {
+        Self { parent_id: random_u64(), ..*self }
+    }
+
+    /// Trace context of the current tracing span
+    ///
+    /// The context is stored on the root of the current span tree the
+    /// first time it is requested, so every call made under the same root
+    /// span shares one trace ID. Outside any span, or with a subscriber
+    /// not built on `tracing_subscriber::Registry`, a new trace is started.
+    pub fn current() -> Self {
+        tracing::Span::current()
+            .with_subscriber(|(id, dispatch)| {
+                let registry = dispatch.downcast_ref::<Registry>()?;
+                let root = registry.span(id)?.scope().from_root().next()?;
+                let mut extensions = root.extensions_mut();
+                if let Some(context) = extensions.get_mut::<TraceContext>() {
+                    return Some(*context);
+                }
+                let context = TraceContext::new_root();
+                extensions.insert(context);
+                Some(context)
+            })
+            .flatten()
+            .unwrap_or_else(Self::new_root)
+    }
+
+    /// Parse a `traceparent` header value
+    ///
+    /// Returns None for anything malformed, including the all-zero IDs
+    /// the spec declares invalid.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let version = parse_hex(parts.next()?, 2)? as u8;
+        let trace_id = parse_hex(parts.next()?, 32)?;
+        let parent_id = parse_hex(parts.next()?, 16)? as u64;
+        let flags = parse_hex(parts.next()?, 2)? as u8;
+
+        // Version 00 has exactly four fields; 0xff is forbidden
+        let extra = parts.next().is_some();
+        if version == 0xff || (version == VERSION && extra) {
+            return None;
+        }
+        if trace_id == 0 || parent_id == 0 {
+            return None;
+        }
+        Some(Self { trace_id, parent_id, flags })
+    }
+
+    /// Trace ID as 32 lowercase hex digits
+    pub fn trace_id(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    /// Parent (caller span) ID as 16 lowercase hex digits
+    pub fn parent_id(&self) -> String {
+        format!("{:016x}", self.parent_id)
+    }
+}
+
+// Header representation
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02x}-{:032x}-{:016x}-{:02x}", VERSION, self.trace_id, self.parent_id, self.flags)
+    }
+}
+
+/// New random correlation ID, as 32 lowercase hex digits
+pub fn new_correlation_id() -> String {
+    format!("{:016x}{:016x}", random_u64(), random_u64())
+}
+
+/// Whether `id` can be used as a correlation ID
+///
+/// Accepts 1 to 128 visible ASCII characters, so the ID fits in a header
+/// and cannot break up a log line.
+pub fn is_valid_correlation_id(id: &str) -> bool {
+    (1..=MAX_CORRELATION_ID_LEN).contains(&id.len()) && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+// Fixed-width lowercase hex field
+fn parse_hex(field: &str, width: usize) -> Option<u128> {
+    let valid = field.len() == width
+        && field.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    valid.then(|| u128::from_str_radix(field, 16).ok()).flatten()
+}
+
+// Non-cryptographic random ID; uniqueness is all tracing needs
+// RandomState is seeded randomly per process, the counter and clock
+// make every call distinct
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+    // Zero is reserved as invalid by the spec
+    hasher.finish().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traceparent_round_trip() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::parse(header).unwrap();
+        assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.parent_id(), "00f067aa0ba902b7");
+        assert_eq!(context.to_string(), header);
+
+        let child = context.child();
+        assert_eq!(child.trace_id(), context.trace_id());
+        assert_ne!(child.parent_id(), context.parent_id());
+    }
+
+    #[test]
+    fn test_malformed_traceparent_is_rejected() {
+        for header in [
+            "",
+            "garbage",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ] {
+            assert_eq!(TraceContext::parse(header), None, "accepted {:?}", header);
+        }
+    }
+
+    #[test]
+    fn test_correlation_id_validation() {
+        let generated = new_correlation_id();
+        assert_eq!(generated.len(), 32);
+        assert!(is_valid_correlation_id(&generated));
+        assert_ne!(generated, new_correlation_id());
+        assert!(is_valid_correlation_id("order-1234"));
+        for id in ["", "two words", "line\nbreak", "caf\u{e9}", &"x".repeat(129)] {
+            assert!(!is_valid_correlation_id(id), "accepted {:?}", id);
+        }
+    }
+
+    #[test]
+    fn test_current_is_shared_under_one_root_span() {
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+
+        let root = tracing::info_span!("root");
+        let _root = root.enter();
+        let first = TraceContext::current();
+        let second = tracing::info_span!("child").in_scope(TraceContext::current);
+        assert_eq!(first.trace_id(), second.trace_id());
+
+        drop(_root);
+        let other = tracing::info_span!("other_root").in_scope(TraceContext::current);
+        assert_ne!(other.trace_id(), first.trace_id());
+    }
+}
@@ -0,0 +1,334 @@
+//! Admin Service Tests
+//! This suite verifies the operational endpoints of the admin service:
+//! 1. Latency percentiles reflect the requests the server handled
+//! 2. Admin calls themselves are not counted
+//! 3. Runtime settings changed with SetConfig take effect without a restart
+//! 4. SetServiceState takes one service offline while the others keep serving
+//! 5. WatchOperations reports the enabled operations and every change to them
+//! 6. Mutating admin RPCs are recorded in the audit log, fail-closed by default
+//! 7. CacheStats counts repeated echoes
+//! 8. ListConnections reports each open connection with its request count
+
+use embedded_recruitment_task::GrpcClient;
+use embedded_recruitment_task::client::{Operation, ServingState, SetConfigRequest};
+use tokio_stream::StreamExt;
+use std::net::SocketAddr;
+use tokio::time::{timeout, Duration};
+use tonic::Code;
+use common::{next_port, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(10);
+
+// Latency stats test
+// Verifies:
+// - Every echo is counted, admin queries are not
+// - Percentiles are ordered (p50 <= p90 <= p99)
+// - Delayed requests push the upper percentile above the delay
+#[tokio::test]
+async fn test_latency_stats_reflect_requests() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = ctx.client.clone();
+
+    let stats = timeout(TIMEOUT_DURATION, async move {
+        let mut echo = client.echo();
+        for i in 0..8 {
+            echo.echo(format!("fast {}", i)).await.expect("Echo failed");
+        }
+        for i in 0..2 {
+            echo.echo_delayed(format!("slow {}", i), Duration::from_millis(100)).await.expect("Echo failed");
+        }
+
+        let mut admin = client.admin();
+        let first = admin.latency_stats().await.expect("Latency stats failed");
+        let second = admin.latency_stats().await.expect("Latency stats failed");
+        assert_eq!(first.count, second.count, "admin calls must not be recorded");
+        second
+    })
+    .await
+    .expect("Test timed out");
+
+    // connect_eager in the test harness makes no RPC, so only our calls count
+    assert_eq!(stats.count, 10);
+    assert!(stats.p50_ms <= stats.p90_ms, "{:?}", stats);
+    assert!(stats.p90_ms <= stats.p99_ms, "{:?}", stats);
+    assert!(stats.p99_ms >= 100.0, "p99 should include the delayed echoes: {:?}", stats);
+    assert!(stats.p50_ms < 100.0, "p50 should be a fast echo: {:?}", stats);
+}
+
+// Runtime settings test
+// Verifies:
+// - SetConfig needs the admin token; GetConfig does not
+// - Flipping allow_empty_echo changes how the next empty echo is handled
+// - Unset fields keep their value
+#[tokio::test]
+async fn test_set_config_toggles_allow_empty_echo() {
+    let ctx = TestContext::setup_with(|builder| builder.admin_token("let-me-in").echo_max_chars(100))
+        .await
+        .expect("Failed to setup test context");
+    // Skips the client-side check so the server's policy decides
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .unwrap()
+        .allow_empty_echo(true)
+        .connect()
+        .expect("Failed to connect");
+    let mut echo = client.echo();
+    let mut admin = client.admin().with_token("let-me-in");
+
+    let initial = admin.get_config().await.expect("GetConfig failed");
+    assert!(!initial.allow_empty_echo);
+    assert_eq!(initial.echo_max_chars, 100);
+    let err = echo.echo("").await.expect_err("empty echo accepted by default");
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let err = client.admin()
+        .set_config(SetConfigRequest { allow_empty_echo: Some(true), ..Default::default() })
+        .await
+        .expect_err("SetConfig without a token succeeded");
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let updated = timeout(TIMEOUT_DURATION, admin.set_config(SetConfigRequest { allow_empty_echo: Some(true), ..Default::default() }))
+        .await
+        .expect("Test timed out")
+        .expect("SetConfig failed");
+    assert!(updated.allow_empty_echo);
+    assert_eq!(updated.echo_max_chars, 100, "unset field changed");
+    assert_eq!(echo.echo("").await.expect("empty echo rejected after SetConfig"), "");
+
+    admin.set_config(SetConfigRequest { allow_empty_echo: Some(false), echo_max_chars: Some(0) })
+        .await
+        .expect("SetConfig failed");
+    let err = echo.echo("").await.expect_err("empty echo accepted after switching back");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(echo.echo("x".repeat(200)).await.expect("limit still applied").len(), 200);
+}
+
+// Service state test
+// Verifies:
+// - SetServiceState needs the admin token
+// - A NotServing calculator answers Unavailable while echo keeps working
+// - Serving brings the calculator back; unknown services are NotFound
+#[tokio::test]
+async fn test_set_service_state_disables_one_service() {
+    let ctx = TestContext::setup_with(|builder| builder.admin_token("let-me-in"))
+        .await
+        .expect("Failed to setup test context");
+    let client = ctx.client.clone();
+
+    timeout(TIMEOUT_DURATION, async move {
+        let mut calculator = client.calculator();
+        let mut echo = client.echo();
+        let mut admin = client.admin().with_token("let-me-in");
+
+        let err = client.admin()
+            .set_service_state("calculator.CalculatorService", ServingState::NotServing)
+            .await
+            .expect_err("SetServiceState without a token succeeded");
+        assert_eq!(err.code(), Code::Unauthenticated);
+        assert_eq!(calculator.calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed"), 42.0);
+
+        let state = admin.set_service_state("calculator.CalculatorService", ServingState::NotServing)
+            .await
+            .expect("SetServiceState failed");
+        assert_eq!(state, ServingState::NotServing);
+        let err = calculator.calculate(6.0, 7.0, Operation::Multiply).await.expect_err("disabled calculator answered");
+        assert_eq!(err.code(), Code::Unavailable);
+        assert_eq!(echo.echo("still here").await.expect("echo affected by the calculator"), "still here");
+
+        admin.set_service_state("calculator.CalculatorService", ServingState::Serving)
+            .await
+            .expect("SetServiceState failed");
+        assert_eq!(calculator.calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed"), 42.0);
+
+        let err = admin.set_service_state("admin.AdminService", ServingState::NotServing)
+            .await
+            .expect_err("admin service switched off");
+        assert_eq!(err.code(), Code::NotFound);
+    })
+    .await
+    .expect("Test timed out");
+}
+
+// Watch operations test
+// Verifies:
+// - The current operation set arrives immediately on subscribe
+// - Taking the calculator offline pushes an empty set
+// - Echo flips do not change the set and push nothing
+// - Bringing the calculator back pushes the full set again
+#[tokio::test]
+async fn test_watch_operations_follows_service_state() {
+    let ctx = TestContext::setup_with(|builder| builder.admin_token("let-me-in"))
+        .await
+        .expect("Failed to setup test context");
+    let client = ctx.client.clone();
+
+    timeout(TIMEOUT_DURATION, async move {
+        let all = vec![Operation::Add, Operation::Subtract, Operation::Multiply, Operation::Divide, Operation::Percent];
+        let mut admin = client.admin().with_token("let-me-in");
+        let mut operations = admin.watch_operations().await.expect("WatchOperations failed");
+        let initial = operations.next().await.expect("no initial set").expect("stream failed");
+        assert_eq!(initial, all);
+
+        admin.set_service_state("echo.EchoService", ServingState::NotServing).await.expect("SetServiceState failed");
+        admin.set_service_state("calculator.CalculatorService", ServingState::NotServing)
+            .await
+            .expect("SetServiceState failed");
+        let disabled = operations.next().await.expect("stream ended").expect("stream failed");
+        assert!(disabled.is_empty(), "calculator offline but {:?} reported", disabled);
+
+        admin.set_service_state("calculator.CalculatorService", ServingState::Serving)
+            .await
+            .expect("SetServiceState failed");
+        let enabled = operations.next().await.expect("stream ended").expect("stream failed");
+        assert_eq!(enabled, all);
+    })
+    .await
+    .expect("Test timed out");
+}
+
+// Fresh audit log location for one test
+fn audit_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("grpc-audit-test-{}-{}", std::process::id(), next_port()))
+        .join(format!("{}.log", name))
+}
+
+// Audit log test
+// Verifies:
+// - SetLogLevel and SetServiceState each add one JSON line to the audit log
+// - Entries carry the time, peer, method, parameters and outcome
+// - Read-only admin RPCs are not audited
+#[tokio::test]
+async fn test_audit_log_records_mutating_rpcs() {
+    let path = audit_path("audit");
+    let audit_log = path.clone();
+    let ctx = TestContext::setup_with(move |builder| builder.admin_token("let-me-in").audit_log_path(audit_log))
+        .await
+        .expect("Failed to setup test context");
+    let client = ctx.client.clone();
+
+    timeout(TIMEOUT_DURATION, async move {
+        let mut admin = client.admin().with_token("let-me-in");
+        // Outcome depends on whether this process installed logging; it is audited either way
+        let _ = admin.set_log_level(Some("embedded_recruitment_task::server::audit"), "info").await;
+        admin.set_service_state("echo.EchoService", ServingState::Serving).await.expect("SetServiceState failed");
+        admin.latency_stats().await.expect("Latency stats failed");
+    })
+    .await
+    .expect("Test timed out");
+
+    let contents = std::fs::read_to_string(&path).expect("audit log not written");
+    let entries: Vec<serde_json::Value> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("malformed audit entry"))
+        .collect();
+    assert_eq!(entries.len(), 2, "{}", contents);
+    assert_eq!(entries[0]["method"], "SetLogLevel");
+    assert_eq!(entries[0]["parameters"]["level"], "info");
+    assert_eq!(entries[1]["method"], "SetServiceState");
+    assert_eq!(entries[1]["parameters"]["service"], "echo.EchoService");
+    assert_eq!(entries[1]["outcome"], "Ok");
+    for entry in &entries {
+        assert!(entry["timestamp_ms"].as_u64().is_some_and(|ms| ms > 0), "{}", entry);
+        let peer: std::net::SocketAddr = entry["peer"].as_str().and_then(|peer| peer.parse().ok()).expect("no peer");
+        assert!(peer.ip().is_loopback(), "{}", entry);
+    }
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+// Audit failure test
+// Verifies:
+// - An audit log that cannot be written fails the RPC with Internal by default
+// - audit_fail_open(true) lets the RPC succeed anyway
+#[tokio::test]
+async fn test_audit_failure_fails_closed() {
+    // The log's directory is taken by a file, so it can never be created
+    let blocked = audit_path("blocked");
+    std::fs::create_dir_all(blocked.parent().unwrap()).unwrap();
+    std::fs::write(&blocked, b"").unwrap();
+    let unwritable = blocked.join("audit.log");
+
+    let closed_log = unwritable.clone();
+    let closed = TestContext::setup_with(move |builder| builder.admin_token("let-me-in").audit_log_path(closed_log))
+        .await
+        .expect("Failed to setup test context");
+    let err = closed.client.admin().with_token("let-me-in")
+        .set_service_state("echo.EchoService", ServingState::Serving)
+        .await
+        .expect_err("unaudited call succeeded");
+    assert_eq!(err.code(), Code::Internal);
+
+    let open = TestContext::setup_with(move |builder| {
+        builder.admin_token("let-me-in").audit_log_path(unwritable).audit_fail_open(true)
+    })
+    .await
+    .expect("Failed to setup test context");
+    let state = open.client.admin().with_token("let-me-in")
+        .set_service_state("echo.EchoService", ServingState::Serving)
+        .await
+        .expect("fail-open call failed");
+    assert_eq!(state, ServingState::Serving);
+    let _ = std::fs::remove_dir_all(blocked.parent().unwrap());
+}
+
+// Echo cache test
+// Verifies:
+// - The first echo of a message is a miss, the identical second one a hit
+// - Both return the message unchanged; the calculator counts stay zero
+#[tokio::test]
+async fn test_cache_stats_count_echo_cache_hits() {
+    let ctx = TestContext::setup_with(|builder| builder.echo_cache(8))
+        .await
+        .expect("Failed to setup test context");
+    let client = ctx.client.clone();
+
+    let stats = timeout(TIMEOUT_DURATION, async move {
+        let mut echo = client.echo();
+        assert_eq!(echo.echo("cache me").await.expect("Echo failed"), "cache me");
+        let before = client.admin().cache_stats().await.expect("Cache stats failed");
+        assert_eq!((before.echo_hits, before.echo_misses), (0, 1), "{:?}", before);
+
+        assert_eq!(echo.echo("cache me").await.expect("Echo failed"), "cache me");
+        client.admin().cache_stats().await.expect("Cache stats failed")
+    })
+    .await
+    .expect("Test timed out");
+
+    assert_eq!((stats.echo_hits, stats.echo_misses), (1, 1), "second echo should be a hit: {:?}", stats);
+    assert_eq!((stats.calculator_hits, stats.calculator_misses), (0, 0), "{:?}", stats);
+}
+
+// Connection listing test
+// Verifies:
+// - Two clients on their own connections are both listed
+// - Each entry carries the requests of its own connection, the listing call included
+// - Peers are the clients' loopback addresses, listed oldest first
+#[tokio::test]
+async fn test_list_connections_reports_each_connection() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let first = ctx.new_client().await.expect("Failed to connect first client");
+    let second = ctx.new_client().await.expect("Failed to connect second client");
+
+    let connections = timeout(TIMEOUT_DURATION, async move {
+        for i in 0..3 {
+            first.echo().echo(format!("first {}", i)).await.expect("Echo failed");
+        }
+        for i in 0..5 {
+            second.echo().echo(format!("second {}", i)).await.expect("Echo failed");
+        }
+        first.admin().list_connections().await.expect("List connections failed")
+    })
+    .await
+    .expect("Test timed out");
+
+    let counts: Vec<u64> = connections.iter().map(|c| c.request_count).collect();
+    assert!(counts.contains(&4), "first connection should have 3 echoes and the listing: {:?}", connections);
+    assert!(counts.contains(&5), "second connection should have 5 echoes: {:?}", connections);
+    for connection in &connections {
+        let peer: SocketAddr = connection.peer.parse().expect("peer is not a socket address");
+        assert!(peer.ip().is_loopback(), "{:?}", connection);
+        assert!(connection.connected_at_ms > 0, "{:?}", connection);
+    }
+    assert!(connections.windows(2).all(|pair| pair[0].connected_at_ms <= pair[1].connected_at_ms), "{:?}", connections);
+}
@@ -0,0 +1,61 @@
+//! Authentication Tests
+//! This suite verifies the pluggable authenticator:
+//! 1. StaticTokenAuth rejects requests without a token or with a wrong one
+//! 2. A valid bearer token is accepted
+//! 3. Without an authenticator every request is accepted (NoAuth)
+
+use std::sync::Arc;
+use embedded_recruitment_task::proto::echo::{echo_service_client::EchoServiceClient, EchoRequest};
+use embedded_recruitment_task::server::StaticTokenAuth;
+use tokio::time::{timeout, Duration};
+use tonic::transport::Channel;
+use tonic::{Code, Request, Status};
+use common::TestContext;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Echo through the raw generated client, optionally with a bearer token
+async fn echo_with_token(addr: &str, token: Option<&str>) -> Result<String, Status> {
+    let mut client: EchoServiceClient<Channel> = EchoServiceClient::connect(format!("http://{}", addr))
+        .await
+        .expect("Failed to connect");
+    let mut request = Request::new(EchoRequest { message: "who am i".into(), delay_ms: 0, ..Default::default() });
+    if let Some(token) = token {
+        request.metadata_mut().insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    }
+    let response = timeout(TIMEOUT_DURATION, client.echo(request)).await.expect("Test timed out")?;
+    Ok(response.into_inner().message)
+}
+
+// Static token test
+// Verifies:
+// - A missing or unknown token fails with Unauthenticated
+// - A registered token reaches the service
+#[tokio::test]
+async fn test_static_token_auth() {
+    let auth = Arc::new(StaticTokenAuth::new("s3cret", "alice"));
+    let ctx = TestContext::setup_with(|builder| builder.authenticator(auth))
+        .await
+        .expect("Failed to setup test context");
+
+    let err = echo_with_token(&ctx.addr, None).await.expect_err("missing token was accepted");
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let err = echo_with_token(&ctx.addr, Some("guess")).await.expect_err("wrong token was accepted");
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let message = echo_with_token(&ctx.addr, Some("s3cret")).await.expect("Valid token was rejected");
+    assert_eq!(message, "who am i");
+}
+
+// Default test
+// Verifies requests need no credentials when no authenticator is registered
+#[tokio::test]
+async fn test_no_auth_by_default() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let message = echo_with_token(&ctx.addr, None).await.expect("Anonymous request was rejected");
+    assert_eq!(message, "who am i");
+}
@@ -0,0 +1,60 @@
+//! Broadcast Service Tests
+//! This test suite verifies the publish/subscribe echo:
+//! 1. A message published to a topic reaches every subscriber of it
+//! 2. Subscribers of other topics do not receive it
+//! 3. Empty topics are rejected
+
+use tokio::time::{timeout, Duration};
+use tokio_stream::StreamExt;
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Fan-out test
+// Verifies:
+// - Two subscribers of a topic each receive a message published once
+// - The publisher learns how many subscribers it reached
+// - A subscriber of another topic receives nothing
+#[tokio::test]
+async fn test_publish_reaches_every_subscriber() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let alice = ctx.new_client().await.expect("Failed to connect");
+    let bob = ctx.new_client().await.expect("Failed to connect");
+
+    let mut first = alice.broadcast().subscribe("lobby").await.expect("Subscribe failed");
+    let mut second = bob.broadcast().subscribe("lobby").await.expect("Subscribe failed");
+    let mut elsewhere = bob.broadcast().subscribe("kitchen").await.expect("Subscribe failed");
+
+    let reached = timeout(TIMEOUT_DURATION, ctx.client.broadcast().publish("lobby", "hello, room"))
+        .await
+        .expect("Publish timed out")
+        .expect("Publish failed");
+    assert_eq!(reached, 2);
+
+    for subscriber in [&mut first, &mut second] {
+        let message = timeout(TIMEOUT_DURATION, subscriber.next())
+            .await
+            .expect("Message not delivered")
+            .expect("Stream ended")
+            .expect("Stream failed");
+        assert_eq!((message.topic.as_str(), message.message.as_str()), ("lobby", "hello, room"));
+    }
+    let nothing = timeout(Duration::from_millis(200), elsewhere.next()).await;
+    assert!(nothing.is_err(), "other topic received {:?}", nothing);
+}
+
+// Empty topic test
+// Verifies subscribing and publishing without a topic fail with InvalidArgument
+#[tokio::test]
+async fn test_empty_topic_rejected() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut broadcast = ctx.client.broadcast();
+
+    let err = broadcast.publish("", "hello").await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    let err = broadcast.subscribe("").await.err().expect("Subscribe without a topic succeeded");
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,174 @@
+//! Calculator Cache Tests
+//! This suite verifies the optional calculate result cache:
+//! 1. Repeated identical requests are answered from the cache
+//! 2. Errors such as division by zero are rejected every time, never cached
+//! 3. Cache hits skip the history: a repeated calculation is recorded once
+//! 4. The client-side cache answers repeated calculate and evaluate calls
+//!    without a round trip, for every clone of the client
+//! 5. Client-side entries expire after their TTL or when cleared, and
+//!    errors are never cached there either
+
+use embedded_recruitment_task::proto::calculator::{
+    calculator_service_client::CalculatorServiceClient, CalculateRequest, Operation,
+};
+use embedded_recruitment_task::GrpcClient;
+use tokio::time::{sleep, timeout, Duration};
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(30);
+
+// Cache hit test
+// Verifies:
+// - 1,000 identical requests produce one miss and at least 999 hits
+// - Every response carries the same result
+#[tokio::test]
+async fn test_repeated_calculation_hits_cache() {
+    let ctx = TestContext::setup_with(|builder| builder.calculator_cache(16))
+        .await
+        .expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let results = timeout(TIMEOUT_DURATION, async {
+        let mut results = Vec::with_capacity(1000);
+        for _ in 0..1000 {
+            results.push(calculator.calculate(0.1, 0.2, Operation::Add).await.expect("Calculate failed"));
+        }
+        results
+    })
+    .await
+    .expect("Test timed out");
+
+    assert!(results.iter().all(|result| result.to_bits() == (0.1f64 + 0.2).to_bits()));
+    let snapshot = ctx.metrics.snapshot();
+    assert!(snapshot.cache_hits >= 999, "{:?}", snapshot);
+    assert_eq!(snapshot.cache_hits + snapshot.cache_misses, 1000, "{:?}", snapshot);
+}
+
+// Cache hit history test
+// Verifies:
+// - A calculation sent twice is recorded in the history once
+// - The second request was a hit, counted like any other request
+#[tokio::test]
+async fn test_cache_hit_skips_history() {
+    let ctx = TestContext::setup_with(|builder| builder.calculator_cache(16))
+        .await
+        .expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let history = timeout(TIMEOUT_DURATION, async {
+        for _ in 0..2 {
+            assert_eq!(calculator.calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed"), 42.0);
+        }
+        calculator.history(0).await.expect("GetHistory failed")
+    })
+    .await
+    .expect("Test timed out");
+
+    assert_eq!(history.total, 1, "{:?}", history);
+    assert_eq!(history.entries[0].result, 42.0);
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (1, 1), "{:?}", snapshot);
+    // Both calculations and the GetHistory call
+    assert_eq!(served(&ctx).await, 3);
+}
+
+// Error test
+// Verifies division by zero is rejected by the server each time and never stored
+#[tokio::test]
+async fn test_division_by_zero_is_not_cached() {
+    let ctx = TestContext::setup_with(|builder| builder.calculator_cache(16))
+        .await
+        .expect("Failed to setup test context");
+
+    // Raw generated client bypasses the wrapper's client-side validation
+    let mut client = CalculatorServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    for _ in 0..5 {
+        let request = CalculateRequest { first_number: 1.0, second_number: 0.0, operation: Operation::Divide.into(), ..Default::default() };
+        let err = timeout(TIMEOUT_DURATION, client.calculate(request))
+            .await
+            .expect("Test timed out")
+            .expect_err("division by zero succeeded");
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    // Rejected by validation before the cache is even consulted
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!((snapshot.cache_hits, snapshot.cache_misses), (0, 0), "{:?}", snapshot);
+}
+
+// Client with a calculator cache of `ttl`, connected to the test server
+fn caching_client(ctx: &TestContext, ttl: Duration) -> GrpcClient {
+    GrpcClient::builder(format!("http://{}", ctx.addr))
+        .expect("Invalid address")
+        .calculator_cache(16, ttl)
+        .connect()
+        .expect("Failed to connect")
+}
+
+// Calculator and echo RPCs the server has answered (admin RPCs are not counted)
+async fn served(ctx: &TestContext) -> u64 {
+    timeout(TIMEOUT_DURATION, ctx.client.admin().latency_stats())
+        .await
+        .expect("Latency stats timed out")
+        .expect("Latency stats failed")
+        .count
+}
+
+// Client cache hit test
+// Verifies:
+// - A repeated calculation, also through a clone, reaches the server once
+// - So does a repeated expression, whatever its whitespace
+// - Hits and misses are counted in the client metrics
+#[tokio::test]
+async fn test_client_cache_skips_round_trip() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = caching_client(&ctx, Duration::from_secs(60));
+
+    let first = client.calculator().calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed");
+    assert_eq!(served(&ctx).await, 1);
+    let second = client.clone().calculator().calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed");
+    assert_eq!(first, second);
+    assert_eq!(served(&ctx).await, 1, "repeated calculation reached the server");
+
+    let evaluated = client.calculator().evaluate("2 + 3 * 4").await.expect("Evaluate failed");
+    let repeated = client.calculator().evaluate("2+3*4").await.expect("Evaluate failed");
+    assert_eq!(evaluated, repeated);
+    assert_eq!(served(&ctx).await, 2, "repeated expression reached the server");
+
+    let snapshot = client.metrics().snapshot();
+    assert_eq!((snapshot.calculator_cache_hits, snapshot.calculator_cache_misses), (2, 2), "{:?}", snapshot);
+}
+
+// Client cache expiry test
+// Verifies:
+// - A result is fetched again once its TTL has passed
+// - clear_cache drops results still within their TTL
+// - Errors reach the server on every call
+#[tokio::test]
+async fn test_client_cache_expiry_and_errors() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = caching_client(&ctx, Duration::from_millis(100));
+    let mut calculator = client.calculator();
+
+    calculator.calculate(1.0, 2.0, Operation::Add).await.expect("Calculate failed");
+    calculator.calculate(1.0, 2.0, Operation::Add).await.expect("Calculate failed");
+    assert_eq!(served(&ctx).await, 1);
+    sleep(Duration::from_millis(200)).await;
+    calculator.calculate(1.0, 2.0, Operation::Add).await.expect("Calculate failed");
+    assert_eq!(served(&ctx).await, 2, "expired result was not fetched again");
+
+    client.clear_cache();
+    calculator.calculate(1.0, 2.0, Operation::Add).await.expect("Calculate failed");
+    assert_eq!(served(&ctx).await, 3, "cleared result was not fetched again");
+
+    for _ in 0..2 {
+        let err = calculator.calculate(f64::MAX, 10.0, Operation::Multiply).await.expect_err("overflow succeeded");
+        assert_eq!(err.code(), Code::OutOfRange);
+    }
+    assert_eq!(served(&ctx).await, 5, "error was cached");
+}
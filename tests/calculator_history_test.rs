@@ -0,0 +1,154 @@
+//! Calculator History Tests
+//! This test suite verifies:
+//! 1. GetHistory returns the default in-memory history, newest first
+//! 2. A FileHistory keeps the history across a server restart
+//! 3. A FileHistory rotates to `<path>.1` at its size cap
+//! 4. Store failures: a failed read fails GetHistory with Internal, a
+//!    failed write does not fail the calculation
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use embedded_recruitment_task::proto::calculator::Operation;
+use embedded_recruitment_task::server::{FileHistory, HistoryEntry, HistoryStore};
+use embedded_recruitment_task::GrpcClient;
+use tokio::time::{timeout, Duration};
+use tonic::Code;
+use common::{next_port, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Fresh history file location for one test
+fn history_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("grpc-history-test-{}-{}", std::process::id(), next_port()))
+        .join(format!("{}.jsonl", name))
+}
+
+// Run calculations (first, second, operation) in order
+async fn calculate_all(client: &GrpcClient, calculations: &[(f64, f64, Operation)]) {
+    let mut calculator = client.calculator();
+    for &(first, second, operation) in calculations {
+        timeout(TIMEOUT_DURATION, calculator.calculate(first, second, operation))
+            .await
+            .expect("Calculate timed out")
+            .expect("Calculate failed");
+    }
+}
+
+// Default memory history test
+// Verifies:
+// - Every successful calculation is recorded with its operands and result
+// - Entries come newest first, limited to the requested number
+// - Failed calculations are not recorded
+#[tokio::test]
+async fn test_memory_history_newest_first() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    calculate_all(&ctx.client, &[(1.0, 2.0, Operation::Add), (9.0, 3.0, Operation::Divide)]).await;
+    let divided = ctx.client.calculator().calculate(1.0, 0.0, Operation::Divide).await;
+    assert!(divided.is_err(), "division by zero must fail");
+
+    let history = timeout(TIMEOUT_DURATION, ctx.client.calculator().history(0))
+        .await
+        .expect("GetHistory timed out")
+        .expect("GetHistory failed");
+    assert_eq!(history.total, 2);
+    let entries: Vec<_> = history.entries.iter().map(|e| (e.first_number, e.operation(), e.result)).collect();
+    assert_eq!(entries, [(9.0, Operation::Divide, 3.0), (1.0, Operation::Add, 3.0)]);
+    assert!(history.entries.iter().all(|e| e.timestamp_ms > 0));
+
+    let latest = ctx.client.calculator().history(1).await.expect("GetHistory failed");
+    assert_eq!(latest.entries.len(), 1);
+    assert_eq!(latest.entries[0].result, 3.0);
+}
+
+// File history restart test
+// Verifies the entries written by one server are served by the next one
+// that opens the same file
+#[tokio::test]
+async fn test_file_history_survives_restart() {
+    let path = history_path("restart");
+    let store = FileHistory::open(&path, 1 << 20).expect("Failed to open history");
+    let ctx = TestContext::setup_with(|builder| builder.calculator_history_store(Arc::new(store)))
+        .await
+        .expect("Failed to setup test context");
+    calculate_all(&ctx.client, &[(2.0, 3.0, Operation::Multiply), (8.0, 5.0, Operation::Subtract)]).await;
+    drop(ctx);
+
+    let store = FileHistory::open(&path, 1 << 20).expect("Failed to reopen history");
+    let ctx = TestContext::setup_with(|builder| builder.calculator_history_store(Arc::new(store)))
+        .await
+        .expect("Failed to setup test context");
+    calculate_all(&ctx.client, &[(1.0, 1.0, Operation::Add)]).await;
+
+    let history = ctx.client.calculator().history(10).await.expect("GetHistory failed");
+    assert_eq!(history.total, 3);
+    let results: Vec<f64> = history.entries.iter().map(|e| e.result).collect();
+    assert_eq!(results, [2.0, 3.0, 6.0]);
+}
+
+// Rotation test
+// Verifies:
+// - Neither file grows past the cap
+// - The full file is kept as `<path>.1` and older entries are dropped
+// - Entries of both files are returned, newest first
+#[tokio::test]
+async fn test_file_history_rotates_at_cap() {
+    let path = history_path("rotation");
+    let entry_bytes = serde_json::to_vec(&HistoryEntry::new(1.0, 1.0, Operation::Add, 2.0)).unwrap().len() as u64 + 1;
+    let cap = entry_bytes * 4;
+    let store = FileHistory::open(&path, cap).expect("Failed to open history");
+    for i in 0..10 {
+        store.append(HistoryEntry::new(1.0, 1.0, Operation::Add, f64::from(i))).await.expect("Append failed");
+    }
+
+    let mut rotated = path.clone().into_os_string();
+    rotated.push(".1");
+    for file in [path.clone(), PathBuf::from(rotated)] {
+        let size = std::fs::metadata(&file).expect("History file missing").len();
+        assert!(size <= cap, "{} is {} bytes, over the {} byte cap", file.display(), size, cap);
+    }
+    let len = store.len().await.expect("len failed");
+    assert!(len < 10, "rotation must drop the oldest entries, kept {}", len);
+
+    let results: Vec<f64> = store.recent(len).await.expect("recent failed").iter().map(|e| e.result).collect();
+    let expected: Vec<f64> = (0..10).rev().take(len).map(f64::from).collect();
+    assert_eq!(results, expected);
+}
+
+// A store whose reads and writes fail
+struct BrokenStore;
+
+#[tonic::async_trait]
+impl HistoryStore for BrokenStore {
+    async fn append(&self, _: HistoryEntry) -> io::Result<()> {
+        Err(io::Error::other("disk full"))
+    }
+
+    async fn recent(&self, _: usize) -> io::Result<Vec<HistoryEntry>> {
+        Err(io::Error::other("disk gone"))
+    }
+
+    async fn len(&self) -> io::Result<usize> {
+        Err(io::Error::other("disk gone"))
+    }
+}
+
+// Store failure test
+// Verifies:
+// - Calculations succeed although their entries cannot be written
+// - GetHistory fails with Internal without leaking the store's error
+#[tokio::test]
+async fn test_store_failures() {
+    let ctx = TestContext::setup_with(|builder| builder.calculator_history_store(Arc::new(BrokenStore)))
+        .await
+        .expect("Failed to setup test context");
+    let sum = ctx.client.calculator().calculate(4.0, 5.0, Operation::Add).await.expect("Calculate failed");
+    assert_eq!(sum, 9.0);
+
+    let err = ctx.client.calculator().history(5).await.unwrap_err();
+    assert_eq!(err.code(), Code::Internal);
+    assert!(!err.message().contains("disk"), "{}", err.message());
+}
@@ -5,14 +5,31 @@
 //! 3. Error conditions and validation
 //! 4. Floating-point precision requirements
 //! 5. Timeout handling for operations
+//! 6. Dataset aggregation (sum, mean, min, max, count)
+//! 7. Running totals over a bidirectional stream
+//! 8. Chained reductions over a list with one operation
+//! 9. Structured error details naming the kind and the offending operand
+//! 10. calculate_and_echo composing the calculator and echo services
+//! 11. The configured policy for subnormal operands
+//! 12. Running statistics over a streamed dataset (aggregate_stream)
+//! 13. The inverse result returned by calculate_with_inverse
+//! 14. Percentages (percent), including negative ones and overflow
 
-use embedded_recruitment_task::proto::calculator::Operation;
+use embedded_recruitment_task::client::{CalculatorError, CalculatorErrorKind};
+use embedded_recruitment_task::server::SubnormalPolicy;
+use embedded_recruitment_task::proto::calculator::{
+    calculator_service_client::CalculatorServiceClient, CalculateRequest, Operation,
+};
 use tonic::Code;
 use tokio::time::{timeout, Duration};
+use tokio_stream::StreamExt;
 use common::TestContext;
 
 mod common;
 
+// A single calculator test case: name, operands, operation and expected outcome
+type TestCase = (&'static str, f64, f64, Operation, Result<f64, Code>);
+
 // Comprehensive test of all calculator operations
 // Tests various number combinations:
 // - Regular integers
@@ -32,7 +49,7 @@ async fn test_basic_operations() {
     // - Floating point precision
     // - Edge case handling
     // - Number range support
-    let test_cases: Vec<(&str, f64, f64, Operation, Result<f64, Code>)> = vec![
+    let test_cases: Vec<TestCase> = vec![
         // Basic arithmetic with regular numbers
         ("Addition", 10.0, 5.0, Operation::Add, Ok(15.0)),
         ("Subtraction", 10.0, 5.0, Operation::Subtract, Ok(5.0)),
@@ -57,8 +74,8 @@ async fn test_basic_operations() {
             Duration::from_secs(5),
             calculator.calculate(first, second, op)
         ).await
-            .expect(&format!("{} timed out", name))
-            .expect(&format!("{} failed", name));
+            .unwrap_or_else(|_| panic!("{} timed out", name))
+            .unwrap_or_else(|e| panic!("{} failed: {:?}", name, e));
 
         // Verify results with floating-point tolerance
         match expected {
@@ -91,10 +108,376 @@ async fn test_error_cases() {
             Duration::from_secs(5),
             calculator.calculate(first, second, op)
         ).await
-            .expect(&format!("{} timed out", name))
+            .unwrap_or_else(|_| panic!("{} timed out", name))
             .unwrap_err();
         
         // All division by zero cases should return InvalidArgument
         assert_eq!(err.code(), Code::InvalidArgument);
     }
 }
+
+// Test aggregation over a regular dataset
+// Verifies every statistic in the response
+#[tokio::test]
+async fn test_aggregate_dataset() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let stats = timeout(
+        Duration::from_secs(5),
+        calculator.aggregate(&[4.0, -2.0, 10.0, 0.5, 7.5])
+    ).await
+        .expect("Test timed out")
+        .expect("Aggregate failed");
+
+    assert_eq!(stats.count, 5);
+    assert!((stats.sum - 20.0).abs() < 1e-10);
+    assert!((stats.mean - 4.0).abs() < 1e-10);
+    assert_eq!(stats.min, -2.0);
+    assert_eq!(stats.max, 10.0);
+}
+
+// Test aggregation edge cases
+// - An empty dataset is rejected with InvalidArgument
+// - A single element is its own sum, mean, min and max
+#[tokio::test]
+async fn test_aggregate_edge_cases() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let err = timeout(Duration::from_secs(5), calculator.aggregate(&[]))
+        .await
+        .expect("Test timed out")
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let stats = timeout(Duration::from_secs(5), calculator.aggregate(&[42.0]))
+        .await
+        .expect("Test timed out")
+        .expect("Aggregate failed");
+    assert_eq!(stats.count, 1);
+    assert_eq!(stats.sum, 42.0);
+    assert_eq!(stats.mean, 42.0);
+    assert_eq!(stats.min, 42.0);
+    assert_eq!(stats.max, 42.0);
+}
+
+// Test chained reductions
+// - [1, 2, 3, 4] folds to 10 with Add and 24 with Multiply
+// - A zero in the middle of a Divide fold fails with its index
+#[tokio::test]
+async fn test_reduce() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+    let values = [1.0, 2.0, 3.0, 4.0];
+
+    for (operation, expected) in [(Operation::Add, 10.0), (Operation::Multiply, 24.0), (Operation::Subtract, -8.0)] {
+        let result = timeout(Duration::from_secs(5), calculator.reduce(&values, operation))
+            .await
+            .expect("Test timed out")
+            .unwrap_or_else(|e| panic!("{:?} reduce failed: {:?}", operation, e));
+        assert_eq!(result, expected, "{:?}", operation);
+    }
+
+    let err = timeout(Duration::from_secs(5), calculator.reduce(&[8.0, 2.0, 0.0, 4.0], Operation::Divide))
+        .await
+        .expect("Test timed out")
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("division by zero"), "{}", err.message());
+    assert!(err.message().contains("index 2"), "{}", err.message());
+
+    let err = calculator.reduce(&[], Operation::Add).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+// Test calculations with the operation given as text
+// - Symbols and names (any case) reach the right operation
+// - Unknown operators fail locally with InvalidArgument
+#[tokio::test]
+async fn test_calculate_str() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let cases = [("+", 9.0), ("subtract", 3.0), ("*", 18.0), ("DIVIDE", 2.0)];
+    for (operation, expected) in cases {
+        let result = timeout(Duration::from_secs(5), calculator.calculate_str(6.0, operation, 3.0))
+            .await
+            .expect("Test timed out")
+            .expect("Calculation failed");
+        assert_eq!(result, expected, "6 {} 3", operation);
+    }
+
+    let err = calculator.calculate_str(6.0, "%%", 3.0).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+// Test exact decimal arithmetic
+// - 0.1 + 0.2 is exactly 0.3, unlike with f64
+// - Unparseable operands and division by zero fail with InvalidArgument
+#[tokio::test]
+async fn test_calculate_decimal() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let sum = timeout(Duration::from_secs(5), calculator.calculate_decimal("0.1", "0.2", Operation::Add))
+        .await
+        .expect("Test timed out")
+        .expect("Calculation failed");
+    assert_eq!(sum, "0.3");
+    assert_ne!(0.1 + 0.2, 0.3, "f64 should not be exact here");
+
+    let quotient = calculator.calculate_decimal("10", "4", Operation::Divide).await.expect("Calculation failed");
+    assert_eq!(quotient, "2.5");
+
+    let err = calculator.calculate_decimal("0.1", "two", Operation::Add).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("second_number"));
+
+    let err = calculator.calculate_decimal("1", "0", Operation::Divide).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+// Test a running total over one stream
+// - Each step yields the updated accumulator
+// - Division by zero is reported for that step only; the total is kept
+//   and the stream stays usable
+#[tokio::test]
+async fn test_running_total() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let steps = vec![
+        (10.0, Operation::Add),
+        (5.0, Operation::Subtract),
+        (0.0, Operation::Divide),
+        (3.0, Operation::Multiply),
+    ];
+    let responses: Vec<_> = timeout(Duration::from_secs(5), async {
+        calculator.running_total(tokio_stream::iter(steps)).await
+            .expect("Running total failed to start")
+            .collect::<Vec<_>>()
+            .await
+    }).await
+        .expect("Test timed out")
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .expect("Running total stream failed");
+
+    let totals: Vec<f64> = responses.iter().map(|r| r.total).collect();
+    assert_eq!(totals, vec![10.0, 5.0, 5.0, 15.0]);
+    assert!(responses[0].error.is_empty());
+    assert!(responses[1].error.is_empty());
+    assert!(responses[2].error.contains("division by zero"), "unexpected error: {:?}", responses[2].error);
+    assert!(responses[3].error.is_empty());
+}
+
+// Test running statistics over a streamed dataset
+// - One response per value, counting up from 1
+// - The final sum matches the batch aggregate, the mean up to rounding
+// - The final variance matches a two-pass computation
+#[tokio::test]
+async fn test_aggregate_stream_matches_batch() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+    let values: Vec<f64> = (0..10_000).map(|i| 1e6 + f64::from(i % 97) * 0.25 - f64::from(i % 13)).collect();
+
+    let progress: Vec<_> = timeout(Duration::from_secs(10), async {
+        calculator.aggregate_stream(tokio_stream::iter(values.clone())).await
+            .expect("Streaming aggregate failed to start")
+            .collect::<Vec<_>>()
+            .await
+    }).await
+        .expect("Test timed out")
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .expect("Streaming aggregate failed");
+    let batch = calculator.aggregate(&values).await.expect("Aggregate failed");
+
+    assert_eq!(progress.len(), values.len());
+    assert!(progress.iter().zip(1..).all(|(p, count)| p.count == count));
+    let last = progress.last().unwrap();
+    assert_eq!(last.count, batch.count);
+    assert_eq!(last.sum, batch.sum);
+    assert!((last.mean - batch.mean).abs() <= batch.mean.abs() * 1e-12, "streamed mean {} vs batch {}", last.mean, batch.mean);
+    let variance = values.iter().map(|v| (v - batch.mean).powi(2)).sum::<f64>() / values.len() as f64;
+    assert!((last.variance - variance).abs() <= variance * 1e-9, "streamed variance {} vs two-pass {}", last.variance, variance);
+}
+
+// Test a NaN in a streamed dataset
+// - The values before it are answered
+// - The stream then ends with InvalidArgument naming the NaN's index
+#[tokio::test]
+async fn test_aggregate_stream_rejects_nan() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let responses: Vec<_> = timeout(Duration::from_secs(5), async {
+        calculator.aggregate_stream(tokio_stream::iter(vec![1.0, 2.0, f64::NAN, 4.0])).await
+            .expect("Streaming aggregate failed to start")
+            .collect::<Vec<_>>()
+            .await
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[1].as_ref().unwrap().mean, 1.5);
+    let status = responses[2].as_ref().unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let detail = CalculatorError::from_status(status).expect("status has no detail");
+    assert_eq!((detail.kind(), detail.operand), (CalculatorErrorKind::NonFiniteOperand, Some(2)));
+}
+
+// Test structured error details through a real round trip
+// Every rejection decodes to its kind and, where one operand is to blame,
+// that operand's index
+#[tokio::test]
+async fn test_error_details() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+    // Raw generated client bypasses the wrapper's client-side validation
+    let raw = CalculatorServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    let raw_calculate = |first_number: f64, second_number: f64, operation: i32| {
+        let request = CalculateRequest { first_number, second_number, operation, ..Default::default() };
+        let mut raw = raw.clone();
+        async move { raw.calculate(request).await.expect_err("calculation succeeded") }
+    };
+
+    let cases = vec![
+        ("division by zero", raw_calculate(1.0, -0.0, Operation::Divide.into()).await, CalculatorErrorKind::DivisionByZero, Some(1)),
+        ("overflow", raw_calculate(1e308, 1e308, Operation::Add.into()).await, CalculatorErrorKind::Overflow, None),
+        ("NaN operand", raw_calculate(1.0, f64::NAN, Operation::Add.into()).await, CalculatorErrorKind::NonFiniteOperand, Some(1)),
+        ("infinite operand", raw_calculate(f64::INFINITY, 1.0, Operation::Add.into()).await, CalculatorErrorKind::NonFiniteOperand, Some(0)),
+        ("unknown operation", raw_calculate(1.0, 2.0, 42).await, CalculatorErrorKind::UnsupportedOperation, None),
+        ("reduce division by zero", calculator.reduce(&[8.0, 2.0, 0.0], Operation::Divide).await.unwrap_err(), CalculatorErrorKind::DivisionByZero, Some(2)),
+        ("reduce overflow", calculator.reduce(&[1e200, 1e200, 1e200], Operation::Multiply).await.unwrap_err(), CalculatorErrorKind::Overflow, Some(1)),
+        ("aggregate NaN", calculator.aggregate(&[1.0, 2.0, f64::NAN]).await.unwrap_err(), CalculatorErrorKind::NonFiniteOperand, Some(2)),
+        ("bad decimal", calculator.calculate_decimal("1", "two", Operation::Add).await.unwrap_err(), CalculatorErrorKind::InvalidInput, Some(1)),
+        ("syntax error", calculator.evaluate("2 +").await.unwrap_err(), CalculatorErrorKind::InvalidInput, None),
+    ];
+    for (name, status, kind, operand) in cases {
+        let detail = CalculatorError::from_status(&status).unwrap_or_else(|| panic!("{} has no detail: {}", name, status));
+        assert_eq!((detail.kind(), detail.operand), (kind, operand), "{}: {}", name, status);
+    }
+
+    // Validated locally, with the same detail the server would attach
+    let err = CalculatorError::from(calculator.calculate(1.0, 0.0, Operation::Divide).await.unwrap_err());
+    assert_eq!(err.kind(), CalculatorErrorKind::DivisionByZero);
+    let err = CalculatorError::from(calculator.reduce(&[], Operation::Add).await.unwrap_err());
+    assert_eq!(err.kind(), CalculatorErrorKind::EmptyInput);
+}
+
+// Composed call test
+// Verifies:
+// - calculate_and_echo returns the formatted result echoed by the server
+// - A failure names the leg it came from and keeps its code
+#[tokio::test]
+async fn test_calculate_and_echo() {
+    let ctx = TestContext::setup_with(|builder| builder.echo_max_chars(2))
+        .await
+        .expect("Failed to setup test context");
+
+    let echoed = timeout(Duration::from_secs(5), ctx.client.calculate_and_echo(2.0, 3.0, Operation::Add))
+        .await
+        .expect("Test timed out")
+        .expect("calculate_and_echo failed");
+    assert_eq!(echoed, "5");
+
+    let err = ctx.client.calculate_and_echo(1.0, 0.0, Operation::Divide).await.expect_err("division by zero succeeded");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().starts_with("calculate failed:"), "{}", err.message());
+    assert_eq!(CalculatorError::from(err).kind(), CalculatorErrorKind::DivisionByZero);
+
+    // "100" is over the echo limit of 2 characters
+    let err = ctx.client.calculate_and_echo(10.0, 10.0, Operation::Multiply).await.expect_err("echo over the limit succeeded");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().starts_with("echo failed:"), "{}", err.message());
+}
+
+// Subnormal policy test
+// Verifies:
+// - By default a subnormal operand takes part in the calculation
+// - FlushToZero treats it as zero
+// - Reject fails with InvalidArgument naming the operand
+#[tokio::test]
+async fn test_subnormal_policy() {
+    let subnormal = f64::MIN_POSITIVE / 4.0;
+    assert!(subnormal.is_subnormal());
+
+    let allow = TestContext::setup().await.expect("Failed to setup test context");
+    let result = allow.client.calculator().calculate(subnormal, 4.0, Operation::Multiply).await.expect("Calculate failed");
+    assert_eq!(result, f64::MIN_POSITIVE);
+
+    let flush = TestContext::setup_with(|builder| builder.subnormal_policy(SubnormalPolicy::FlushToZero))
+        .await
+        .expect("Failed to setup test context");
+    let result = flush.client.calculator().calculate(subnormal, 4.0, Operation::Multiply).await.expect("Calculate failed");
+    assert_eq!(result, 0.0);
+    let result = flush.client.calculator().calculate(1.0, 2.0, Operation::Add).await.expect("Calculate failed");
+    assert_eq!(result, 3.0, "normal operands are unaffected");
+
+    let reject = TestContext::setup_with(|builder| builder.subnormal_policy(SubnormalPolicy::Reject))
+        .await
+        .expect("Failed to setup test context");
+    let err = reject.client.calculator().calculate(1.0, -subnormal, Operation::Add).await.expect_err("subnormal accepted");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("second_number"), "{}", err.message());
+    let detail = CalculatorError::from_status(&err).expect("rejection has no detail");
+    assert_eq!((detail.kind(), detail.operand), (CalculatorErrorKind::InvalidInput, Some(1)));
+}
+
+// Inverse result test
+// Verifies:
+// - The inverse of 2 + 3 = 5 recovers 2, and likewise for the other operations
+// - Multiplying by zero yields the result without an inverse
+#[tokio::test]
+async fn test_calculate_with_inverse() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    timeout(Duration::from_secs(5), async {
+        let (result, inverse) = calculator.calculate_with_inverse(2.0, 3.0, Operation::Add).await.expect("Calculate failed");
+        assert_eq!((result, inverse), (5.0, Some(2.0)));
+
+        for (operation, expected) in [(Operation::Subtract, -1.0), (Operation::Multiply, 6.0), (Operation::Divide, 2.0 / 3.0)] {
+            let (result, inverse) = calculator.calculate_with_inverse(2.0, 3.0, operation).await.expect("Calculate failed");
+            assert_eq!(result, expected, "{:?}", operation);
+            let inverse = inverse.unwrap_or_else(|| panic!("{:?} has no inverse", operation));
+            assert!((inverse - 2.0).abs() < 1e-12, "{:?} inverse is {}", operation, inverse);
+        }
+
+        let (result, inverse) = calculator.calculate_with_inverse(2.0, 0.0, Operation::Multiply).await.expect("Calculate failed");
+        assert_eq!((result, inverse), (0.0, None));
+    })
+    .await
+    .expect("Test timed out");
+}
+
+// Percentage test
+// Verifies:
+// - 200 percent-of 50 is 100, through percent() and the "%" operator alike
+// - A negative percentage flips the sign of the result
+// - A result too large for f64 fails with OutOfRange and an Overflow detail
+#[tokio::test]
+async fn test_percent() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    timeout(Duration::from_secs(5), async {
+        assert_eq!(calculator.percent(200.0, 50.0).await.expect("Percent failed"), 100.0);
+        assert_eq!(calculator.calculate_str(200.0, "%", 50.0).await.expect("Percent failed"), 100.0);
+        assert_eq!(calculator.percent(80.0, 12.5).await.expect("Percent failed"), 10.0);
+
+        assert_eq!(calculator.percent(200.0, -20.0).await.expect("Percent failed"), -40.0);
+        assert_eq!(calculator.percent(-200.0, -20.0).await.expect("Percent failed"), 40.0);
+
+        let err = calculator.percent(1e308, 1e5).await.expect_err("overflow accepted");
+        assert_eq!(err.code(), Code::OutOfRange);
+        let detail = CalculatorError::from_status(&err).expect("overflow has no detail");
+        assert_eq!(detail.kind(), CalculatorErrorKind::Overflow);
+    })
+    .await
+    .expect("Test timed out");
+}
@@ -0,0 +1,66 @@
+//! Client Close Tests
+//! This suite verifies `GrpcClient::close`:
+//! 1. Closing applies to clones and existing service handles, which fail
+//!    gracefully with FailedPrecondition instead of reaching the server
+//! 2. Closing is idempotent
+//! 3. Closing the only handle releases the connection promptly
+
+use embedded_recruitment_task::proto::calculator::Operation;
+use tokio::time::{sleep, timeout, Duration, Instant};
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Closed client test
+// Verifies:
+// - A clone made before closing refuses new calls
+// - A service handle made before closing refuses new calls
+// - Closing the clone afterwards is a no-op
+#[tokio::test]
+async fn test_close_applies_to_clones_and_is_idempotent() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = ctx.new_client().await.expect("Failed to connect");
+
+    let reply = timeout(TIMEOUT_DURATION, client.echo().echo("before close"))
+        .await
+        .expect("Timeout")
+        .expect("Echo failed");
+    assert_eq!(reply, "before close");
+
+    let clone = client.clone();
+    let mut calculator = client.calculator();
+    client.close();
+    assert!(clone.is_closed());
+
+    let err = clone.echo().echo("after close").await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+    let err = calculator.calculate(1.0, 2.0, Operation::Add).await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    clone.close();
+
+    // Other clients of the same server are unaffected
+    assert!(!ctx.client.is_closed());
+    ctx.client.echo().echo("still open").await.expect("Echo failed");
+}
+
+// Resource release test
+// Verifies the server sees the connection go away once the only handle is closed
+#[tokio::test]
+async fn test_close_releases_connection() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = ctx.new_client().await.expect("Failed to connect");
+    client.echo().echo("hello").await.expect("Echo failed");
+    assert_eq!(ctx.metrics.snapshot().active_connections, 2);
+
+    client.close();
+
+    let deadline = Instant::now() + TIMEOUT_DURATION;
+    while ctx.metrics.snapshot().active_connections != 1 {
+        assert!(Instant::now() < deadline, "connection was not released after close");
+        sleep(Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,251 @@
+//! Eager Connection Tests
+//! This suite verifies `connect_eager` and `connect_retries`:
+//! 1. Without retries, an unreachable server fails immediately with Unavailable
+//! 2. With retries, connecting succeeds once a late server starts listening
+//! 3. With warmup_rpc, a warm-up call has reached the server when connect_eager returns
+//! 4. With resolve_all, a hostname is resolved and calls are balanced across its
+//!    addresses; an unresolvable host fails with Unavailable naming it
+//! 5. connect_timeout bounds connecting to an address that never answers
+//! 6. total_call_budget bounds a call as a whole, connecting and receiving included
+
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use tokio::time::{sleep, timeout, Duration, Instant};
+use tokio_stream::StreamExt;
+use tonic::Code;
+use common::{next_port, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// No-retry test
+// Verifies the failure names the endpoint and comes back quickly
+#[tokio::test]
+async fn test_connect_eager_fails_fast_without_retries() {
+    let addr = format!("127.0.0.1:{}", next_port());
+    let started = Instant::now();
+
+    let err = match timeout(TIMEOUT_DURATION, GrpcClient::builder(format!("http://{}", addr)).unwrap().connect_eager()).await {
+        Ok(Ok(_)) => panic!("connected to a port nobody listens on"),
+        Ok(Err(err)) => err,
+        Err(_) => panic!("connect_eager timed out"),
+    };
+
+    assert_eq!(err.code(), Code::Unavailable);
+    assert!(err.message().contains(&addr), "error missing endpoint: {}", err.message());
+    assert!(err.message().contains("1 attempt"), "unexpected error: {}", err.message());
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+// Late server test
+// Verifies:
+// - connect_eager keeps retrying while nothing listens
+// - It succeeds once the server starts, and the client is usable
+#[tokio::test]
+async fn test_connect_eager_retries_until_server_starts() {
+    let port = next_port();
+    let addr = format!("127.0.0.1:{}", port);
+
+    // Start connecting before the server exists
+    let connecting = tokio::spawn({
+        let endpoint = format!("http://{}", addr);
+        async move {
+            GrpcClient::builder(endpoint)
+                .unwrap()
+                .connect_retries(40, Duration::from_millis(25))
+                .connect_eager()
+                .await
+        }
+    });
+
+    sleep(Duration::from_millis(300)).await;
+    assert!(!connecting.is_finished(), "connect_eager gave up before the server started");
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr)
+        .startup_banner(false)
+        .build()
+        .expect("Failed to build server");
+    tokio::spawn(server.serve());
+
+    let client = timeout(TIMEOUT_DURATION, connecting)
+        .await
+        .expect("Timeout")
+        .unwrap()
+        .expect("connect_eager did not succeed after the server started");
+    let response = client.echo().echo("late but there").await.expect("Echo failed");
+    assert_eq!(response, "late but there");
+
+    shutdown.trigger();
+}
+
+// Warm-up test
+// Verifies:
+// - connect_eager makes exactly one echo with warmup_rpc(true) and none without
+// - The server has answered it before connect_eager returns
+#[tokio::test]
+async fn test_connect_eager_warmup_rpc() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut admin = ctx.client.admin();
+    let connect = |warmup: bool| {
+        GrpcClient::builder(format!("http://{}", ctx.addr))
+            .unwrap()
+            .warmup_rpc(warmup)
+            .warmup_timeout(TIMEOUT_DURATION)
+            .connect_eager()
+    };
+
+    timeout(TIMEOUT_DURATION, connect(false)).await.expect("Test timed out").expect("Connect failed");
+    assert_eq!(admin.latency_stats().await.expect("Latency stats failed").count, 0);
+
+    timeout(TIMEOUT_DURATION, connect(true)).await.expect("Test timed out").expect("Connect failed");
+    assert_eq!(admin.latency_stats().await.expect("Latency stats failed").count, 1);
+}
+
+// Resolve-all test
+// Verifies both connect paths reach a server through `localhost`, which
+// resolves to loopback, and that echoes go through the balanced channel
+#[tokio::test]
+async fn test_resolve_all_balances_over_resolved_addresses() {
+    let ctx = TestContext::setup_on("127.0.0.1", |builder| builder)
+        .await
+        .expect("Failed to setup test context");
+    let port = ctx.addr.rsplit(':').next().unwrap();
+    let url = format!("http://localhost:{}", port);
+
+    let lazy = GrpcClient::builder(&url).unwrap().resolve_all(true).connect().expect("Lazy connect failed");
+    let eager = timeout(TIMEOUT_DURATION, GrpcClient::builder(&url).unwrap().resolve_all(true).connect_eager())
+        .await
+        .expect("connect_eager timed out")
+        .expect("Eager connect failed");
+
+    for client in [lazy, eager] {
+        let mut echo = client.echo();
+        for i in 0..3 {
+            let message = format!("balanced {}", i);
+            let echoed = timeout(TIMEOUT_DURATION, echo.echo(message.clone()))
+                .await
+                .expect("Echo timed out")
+                .expect("Echo failed");
+            assert_eq!(echoed, message);
+        }
+    }
+}
+
+// Resolution failure test
+// Verifies an unresolvable host is reported as Unavailable naming the host
+#[tokio::test]
+async fn test_resolve_all_reports_unresolvable_host() {
+    let url = "http://no-such-host.invalid:50051";
+
+    let err = GrpcClient::builder(url).unwrap().resolve_all(true).connect().expect_err("resolved an invalid host");
+    assert_eq!(err.code(), Code::Unavailable);
+    assert!(err.message().contains("no-such-host.invalid"), "error missing host: {}", err.message());
+
+    let err = timeout(TIMEOUT_DURATION, GrpcClient::builder(url).unwrap().resolve_all(true).connect_eager())
+        .await
+        .expect("connect_eager timed out")
+        .expect_err("resolved an invalid host");
+    assert_eq!(err.code(), Code::Unavailable);
+    assert!(err.message().contains("no-such-host.invalid"), "error missing host: {}", err.message());
+}
+
+// Listener whose accept queue is full, so further connection attempts hang
+// unanswered like those to a blackholed address; keep it alive while in use
+#[cfg(unix)]
+fn blackhole() -> (socket2::Socket, Vec<std::net::TcpStream>, String) {
+    use socket2::{Domain, Socket, Type};
+
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&"127.0.0.1:0".parse::<std::net::SocketAddr>().unwrap().into()).unwrap();
+    listener.listen(0).unwrap();
+    let addr = listener.local_addr().unwrap().as_socket().unwrap();
+    // Never accepted: each connection stays queued until the queue is full
+    let mut queued = Vec::new();
+    while let Ok(stream) = std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(100)) {
+        queued.push(stream);
+        assert!(queued.len() < 64, "accept queue never filled up");
+    }
+    (listener, queued, format!("http://{}", addr))
+}
+
+// Connect timeout test
+// Verifies a call and an eager connect to an address that never answers
+// fail after about the 300 ms connect timeout, not the system's
+#[cfg(unix)]
+#[tokio::test]
+async fn test_connect_timeout_bounds_unanswered_connect() {
+    let (_listener, _queued, url) = blackhole();
+    let builder = GrpcClient::builder(&url).unwrap().connect_timeout(Duration::from_millis(300));
+
+    let started = Instant::now();
+    let client = builder.clone().connect().expect("Lazy connect failed");
+    let err = timeout(TIMEOUT_DURATION, client.echo().echo("anyone there?"))
+        .await
+        .expect("connect_timeout did not bound the call")
+        .expect_err("echo reached a server that never accepts");
+    assert_eq!(err.code(), Code::Unavailable, "{}", err);
+    assert!(started.elapsed() >= Duration::from_millis(250), "failed too early: {:?}", started.elapsed());
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+
+    let started = Instant::now();
+    let err = timeout(TIMEOUT_DURATION, builder.connect_eager())
+        .await
+        .expect("connect_timeout did not bound connect_eager")
+        .expect_err("connected to a server that never accepts");
+    assert_eq!(err.code(), Code::Unavailable);
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+}
+
+// Call budget while connecting test
+// Verifies the budget includes the connection attempt, which the budget
+// alone (no connect_timeout) cuts short with DeadlineExceeded
+#[cfg(unix)]
+#[tokio::test]
+async fn test_total_call_budget_includes_connecting() {
+    let (_listener, _queued, url) = blackhole();
+    let client = GrpcClient::builder(&url).unwrap()
+        .total_call_budget(Duration::from_millis(300))
+        .connect()
+        .expect("Lazy connect failed");
+
+    let started = Instant::now();
+    let err = timeout(TIMEOUT_DURATION, client.echo().echo("anyone there?"))
+        .await
+        .expect("total_call_budget did not bound the call")
+        .expect_err("echo reached a server that never accepts");
+    assert_eq!(err.code(), Code::DeadlineExceeded, "{}", err);
+    assert!(err.message().contains("waiting for the response"), "phase missing: {}", err.message());
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+}
+
+// Call budget while receiving test
+// Verifies:
+// - Calls finishing within the budget are unaffected
+// - A stream still open when the budget runs out ends with DeadlineExceeded
+//   naming the receiving phase, after the messages that did arrive
+#[tokio::test]
+async fn test_total_call_budget_covers_response_stream() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr)).unwrap()
+        .total_call_budget(Duration::from_millis(300))
+        .connect()
+        .expect("Connect failed");
+    let mut echo = client.echo();
+    assert_eq!(echo.echo("quick").await.expect("Echo failed"), "quick");
+
+    // One chunk, then the request stream stays open and nothing more comes back
+    let chunks = tokio_stream::iter(vec![b"first".to_vec()]).chain(tokio_stream::pending());
+    let mut responses = echo.echo_chunked(chunks).await.expect("Chunked echo failed to start");
+    let started = Instant::now();
+    let first = timeout(TIMEOUT_DURATION, responses.next()).await.expect("Test timed out");
+    assert_eq!(first.expect("stream ended").expect("first chunk failed").as_ref(), b"first");
+
+    let err = timeout(TIMEOUT_DURATION, responses.next())
+        .await
+        .expect("total_call_budget did not end the stream")
+        .expect("stream ended without an error")
+        .expect_err("a chunk arrived that was never sent");
+    assert_eq!(err.code(), Code::DeadlineExceeded, "{}", err);
+    assert!(err.message().contains("receiving the response"), "phase missing: {}", err.message());
+    assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+}
@@ -0,0 +1,251 @@
+//! Client Connector Tests
+//! This suite verifies the pluggable client transport:
+//! 1. A custom connector is used for every connection
+//! 2. http_proxy tunnels calls through an HTTP CONNECT proxy,
+//!    including Basic proxy credentials taken from the URL
+//! 3. Proxy failures surface as Unavailable naming the proxy
+//! 4. bind_local originates connections from the given local address
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, atomic::{AtomicUsize, Ordering}};
+use embedded_recruitment_task::GrpcClient;
+use hyper::{Body, Method, Request, Response, StatusCode};
+use hyper::service::{make_service_fn, service_fn};
+use tokio::io::{copy_bidirectional, duplex};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tonic::{transport::Uri, Code};
+use common::{next_port, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Minimal HTTP CONNECT proxy for the tests
+// Records every tunnel target and optionally requires Basic credentials
+struct TestProxy {
+    addr: SocketAddr,
+    targets: Arc<Mutex<Vec<String>>>,
+}
+
+impl TestProxy {
+    async fn start(required_auth: Option<&'static str>) -> Self {
+        let addr: SocketAddr = ([127, 0, 0, 1], next_port()).into();
+        let targets = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = targets.clone();
+        let make_service = make_service_fn(move |_| {
+            let recorded = recorded.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    tunnel(req, required_auth, recorded.clone())
+                }))
+            }
+        });
+        let server = hyper::Server::bind(&addr).serve(make_service);
+        tokio::spawn(server);
+
+        Self { addr, targets }
+    }
+
+    fn targets(&self) -> Vec<String> {
+        self.targets.lock().unwrap().clone()
+    }
+}
+
+// Handle one proxy request: accept CONNECT and splice the upgraded stream
+async fn tunnel(
+    req: Request<Body>,
+    required_auth: Option<&'static str>,
+    targets: Arc<Mutex<Vec<String>>>,
+) -> Result<Response<Body>, Infallible> {
+    let reply = |status: StatusCode| {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = status;
+        Ok(response)
+    };
+
+    if req.method() != Method::CONNECT {
+        return reply(StatusCode::BAD_REQUEST);
+    }
+    if let Some(expected) = required_auth {
+        let given = req.headers().get("proxy-authorization").and_then(|v| v.to_str().ok());
+        if given != Some(expected) {
+            return reply(StatusCode::PROXY_AUTHENTICATION_REQUIRED);
+        }
+    }
+
+    let target = req.uri().authority().map(|a| a.to_string()).unwrap_or_default();
+    targets.lock().unwrap().push(target.clone());
+    let Ok(mut upstream) = TcpStream::connect(&target).await else {
+        return reply(StatusCode::BAD_GATEWAY);
+    };
+
+    tokio::spawn(async move {
+        if let Ok(mut upgraded) = hyper::upgrade::on(req).await {
+            copy_bidirectional(&mut upgraded, &mut upstream).await.ok();
+        }
+    });
+    reply(StatusCode::OK)
+}
+
+// Custom connector test
+// Verifies:
+// - Calls travel over the stream returned by the connector
+//   (an in-process duplex pipe spliced onto the real server)
+// - The connector receives the endpoint URI
+#[tokio::test]
+async fn test_custom_connector_is_used() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let server_addr = ctx.addr.clone();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let counter = connections.clone();
+    let connector = tower::service_fn(move |uri: Uri| {
+        let server_addr = server_addr.clone();
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            assert_eq!(uri.host(), Some("duplex.invalid"));
+            let (client_side, mut server_side) = duplex(64 * 1024);
+            let mut upstream = TcpStream::connect(&server_addr).await?;
+            tokio::spawn(async move {
+                copy_bidirectional(&mut server_side, &mut upstream).await.ok();
+            });
+            Ok::<_, std::io::Error>(client_side)
+        }
+    });
+
+    // The host does not resolve; only the connector knows the way
+    let client = GrpcClient::builder("http://duplex.invalid:1")
+        .unwrap()
+        .connector(connector)
+        .connect()
+        .unwrap();
+
+    let response = timeout(TIMEOUT_DURATION, client.echo().echo("through the pipe"))
+        .await
+        .expect("Timeout")
+        .expect("Echo over custom connector failed");
+    assert_eq!(response, "through the pipe");
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+// Proxy tunnel test
+// Verifies:
+// - An echo call succeeds through the CONNECT proxy
+// - The proxy saw a tunnel to the gRPC server's address
+// - Credentials from the URL are sent as Basic authorization
+#[tokio::test]
+async fn test_http_proxy_tunnels_echo() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    // base64("alice:s3cret")
+    let proxy = TestProxy::start(Some("Basic YWxpY2U6czNjcmV0")).await;
+
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .unwrap()
+        .http_proxy(format!("http://alice:s3cret@{}", proxy.addr))
+        .unwrap()
+        .connect()
+        .unwrap();
+
+    let response = timeout(TIMEOUT_DURATION, client.echo().echo("via proxy"))
+        .await
+        .expect("Timeout")
+        .expect("Echo through proxy failed");
+    assert_eq!(response, "via proxy");
+    assert_eq!(proxy.targets(), vec![ctx.addr.clone()]);
+}
+
+// Proxy failure test
+// Verifies:
+// - An unreachable proxy yields Unavailable naming the proxy
+// - A proxy refusing the tunnel (bad credentials) does the same
+#[tokio::test]
+async fn test_http_proxy_failures_name_the_proxy() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    // Nothing listens on this port
+    let dead_proxy = format!("127.0.0.1:{}", next_port());
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .unwrap()
+        .http_proxy(format!("http://{}", dead_proxy))
+        .unwrap()
+        .connect()
+        .unwrap();
+    let status = timeout(TIMEOUT_DURATION, client.echo().echo("lost"))
+        .await
+        .expect("Timeout")
+        .expect_err("Echo through a dead proxy should fail");
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(status.message().contains(&dead_proxy), "message missing proxy: {}", status.message());
+
+    // Proxy requires credentials we do not send
+    let proxy = TestProxy::start(Some("Basic YWxpY2U6czNjcmV0")).await;
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .unwrap()
+        .http_proxy(format!("http://{}", proxy.addr))
+        .unwrap()
+        .connect()
+        .unwrap();
+    let status = timeout(TIMEOUT_DURATION, client.echo().echo("denied"))
+        .await
+        .expect("Timeout")
+        .expect_err("Echo without proxy credentials should fail");
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(status.message().contains(&proxy.addr.to_string()), "message missing proxy: {}", status.message());
+    assert!(status.message().contains("407"), "message missing proxy reply: {}", status.message());
+}
+
+// Local bind test
+// Verifies:
+// - An echo succeeds with the connection bound to 127.0.0.1:0
+// - The server sees the connection coming from the pinned local address
+// - A local address of the other family yields Unavailable naming it
+#[tokio::test]
+async fn test_bind_local_pins_source_address() {
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let recorded = peers.clone();
+    let ctx = TestContext::setup_on("127.0.0.1", |builder| {
+        builder.on_connect(move |peer| recorded.lock().unwrap().push(peer))
+    })
+    .await
+    .expect("Failed to setup test context");
+    let url = format!("http://{}", ctx.addr);
+
+    let client = GrpcClient::builder(&url)
+        .unwrap()
+        .bind_local(([127, 0, 0, 1], 0).into())
+        .connect()
+        .unwrap();
+    let response = timeout(TIMEOUT_DURATION, client.echo().echo("from loopback"))
+        .await
+        .expect("Timeout")
+        .expect("Echo from a bound local address failed");
+    assert_eq!(response, "from loopback");
+
+    // With a fixed port the server must see exactly that address; ask the
+    // OS for a free one, as next_port() numbers may still be in TIME_WAIT
+    let local = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let client = GrpcClient::builder(&url).unwrap().bind_local(local).connect().unwrap();
+    timeout(TIMEOUT_DURATION, client.echo().echo("pinned"))
+        .await
+        .expect("Timeout")
+        .expect("Echo from a pinned local port failed");
+    // on_connect hooks run on the blocking pool
+    timeout(TIMEOUT_DURATION, async {
+        while !peers.lock().unwrap().contains(&local) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Server never saw the pinned local address");
+
+    let client = GrpcClient::builder(&url).unwrap().bind_local("[::1]:0".parse().unwrap()).connect().unwrap();
+    let status = timeout(TIMEOUT_DURATION, client.echo().echo("wrong family"))
+        .await
+        .expect("Timeout")
+        .expect_err("An IPv6 local address cannot reach an IPv4 server");
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(status.message().contains("[::1]:0"), "message missing local address: {}", status.message());
+}
@@ -0,0 +1,48 @@
+//! Client Logging Initialization Tests
+//! Library consumers may set up logging themselves. This suite verifies:
+//! 1. `skip_logging_init(true)` connects without creating any client log file
+//! 2. Without it, connecting creates `logs/client` as before
+//!
+//! Kept in its own test binary: it changes the working directory and
+//! installs the one global subscriber, which would affect other tests.
+
+use std::path::Path;
+use embedded_recruitment_task::GrpcClient;
+
+// Names of the client log files below `dir`
+fn client_logs(dir: &Path) -> Vec<String> {
+    std::fs::read_dir(dir.join("logs"))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|name| name.starts_with("client"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Skip logging init test
+// Verifies:
+// - connect() with skip_logging_init(true) writes no client* file
+// - The default connect() still creates logs/client
+#[tokio::test]
+async fn test_skip_logging_init_creates_no_log_file() {
+    let dir = std::env::temp_dir().join(format!("grpc-client-logging-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_current_dir(&dir).unwrap();
+
+    GrpcClient::builder("http://127.0.0.1:1")
+        .expect("Failed to create builder")
+        .skip_logging_init(true)
+        .connect()
+        .expect("Failed to connect");
+    assert!(client_logs(&dir).is_empty(), "skipped init created {:?}", client_logs(&dir));
+
+    GrpcClient::builder("http://127.0.0.1:1")
+        .expect("Failed to create builder")
+        .connect()
+        .expect("Failed to connect");
+    assert_eq!(client_logs(&dir), ["client"]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
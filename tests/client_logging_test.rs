@@ -0,0 +1,68 @@
+//! Client Payload Logging Tests
+//! This suite verifies the client wrappers' logging policy:
+//! 1. Payloads are logged as size + short preview at debug level by default
+//! 2. Nothing payload-related is logged at info level
+//! 3. `log_payloads(true)` restores full payload logging
+
+use embedded_recruitment_task::GrpcClient;
+use tokio::time::{timeout, Duration};
+use common::{LogCapture, TestContext};
+
+mod common;
+
+// Target of the client echo wrapper; used to ignore server-side lines,
+// which are captured too because the test server runs on the same thread
+const CLIENT_ECHO_TARGET: &str = "client::services::echo";
+
+// Default policy test
+// Verifies:
+// - A 1 MB echo produces only short client log lines
+// - Those lines are at debug level and carry the byte length
+#[tokio::test]
+async fn test_large_echo_logs_preview_only() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    let long_msg = "a".repeat(1_000_000);
+    timeout(Duration::from_secs(5), ctx.client.echo().echo(long_msg.clone()))
+        .await
+        .expect("Test timed out")
+        .expect("Echo failed");
+
+    let lines = capture.lines_containing(CLIENT_ECHO_TARGET);
+    assert_eq!(lines.len(), 2, "expected request and response lines: {:?}", lines);
+    for line in &lines {
+        assert!(line.len() < 200, "client log line too long: {} bytes", line.len());
+        assert!(line.contains("DEBUG"), "payload logged above debug: {}", line);
+        assert!(line.contains("1000000 bytes"), "missing payload size: {}", line);
+        assert!(line.contains('…'), "missing truncation marker: {}", line);
+    }
+}
+
+// Escape hatch test
+// Verifies `log_payloads(true)` logs the whole message
+#[tokio::test]
+async fn test_log_payloads_flag_restores_full_logging() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .expect("Invalid address")
+        .log_payloads(true)
+        .connect()
+        .expect("Failed to connect");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    let long_msg = "b".repeat(1_000_000);
+    timeout(Duration::from_secs(5), client.echo().echo(long_msg.clone()))
+        .await
+        .expect("Test timed out")
+        .expect("Echo failed");
+
+    let lines = capture.lines_containing(CLIENT_ECHO_TARGET);
+    assert_eq!(lines.len(), 2, "expected request and response lines");
+    for line in &lines {
+        assert!(line.contains("DEBUG"), "payload logged above debug");
+        assert!(line.contains(&long_msg), "full payload missing from log line");
+    }
+}
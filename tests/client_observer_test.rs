@@ -0,0 +1,151 @@
+//! Client Observer Tests
+//! This suite verifies the ClientObserver callbacks of GrpcClient:
+//! 1. Every call reports one request and one response with its path
+//! 2. Failed calls report the error code
+//! 3. Streaming calls report when the response stream ends
+//! 4. A panicking observer does not affect the call
+
+use std::sync::{Arc, Mutex};
+use embedded_recruitment_task::GrpcClient;
+use embedded_recruitment_task::client::{ClientObserver, Operation};
+use tokio_stream::{self as stream, StreamExt};
+use tokio::time::{timeout, Duration};
+use tonic::metadata::MetadataMap;
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// One observed callback
+#[derive(Debug, Clone, PartialEq)]
+enum Seen {
+    Request(String),
+    Response(String, Code),
+}
+
+// Observer recording every callback in order
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Seen>>>);
+
+impl Recorder {
+    fn take(&self) -> Vec<Seen> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl ClientObserver for Recorder {
+    fn on_request(&self, path: &str, _metadata: &MetadataMap) {
+        self.0.lock().unwrap().push(Seen::Request(path.to_string()));
+    }
+
+    fn on_response(&self, path: &str, code: Code, _latency: Duration) {
+        self.0.lock().unwrap().push(Seen::Response(path.to_string(), code));
+    }
+}
+
+// Observer that panics on every callback
+struct Panicking;
+
+impl ClientObserver for Panicking {
+    fn on_request(&self, _path: &str, _metadata: &MetadataMap) {
+        panic!("observer failure on request");
+    }
+
+    fn on_response(&self, _path: &str, _code: Code, _latency: Duration) {
+        panic!("observer failure on response");
+    }
+}
+
+// Connect a second client with `observer` to the test server
+async fn observed_client(ctx: &TestContext, observer: impl ClientObserver) -> GrpcClient {
+    GrpcClient::builder(format!("http://{}", ctx.addr))
+        .expect("Invalid endpoint")
+        .observer(observer)
+        .connect()
+        .expect("Failed to connect")
+}
+
+// The request/response pair expected for one call
+fn pair(path: &str, code: Code) -> Vec<Seen> {
+    vec![Seen::Request(path.to_string()), Seen::Response(path.to_string(), code)]
+}
+
+// Unary call test
+// Verifies:
+// - Successful calls report Ok with the method path
+// - A server-side rejection reports its error code
+// - Calls rejected client-side are not reported
+#[tokio::test]
+async fn test_unary_calls_are_observed() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let recorder = Recorder::default();
+    let client = observed_client(&ctx, recorder.clone()).await;
+
+    timeout(TIMEOUT_DURATION, async {
+        client.echo().echo("observed").await.expect("Echo failed");
+        assert_eq!(recorder.take(), pair("/echo.EchoService/Echo", Code::Ok));
+
+        client.calculator().calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed");
+        assert_eq!(recorder.take(), pair("/calculator.CalculatorService/Calculate", Code::Ok));
+
+        let err = client.calculator().aggregate(&[1.0, f64::NAN]).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert_eq!(recorder.take(), pair("/calculator.CalculatorService/Aggregate", Code::InvalidArgument));
+
+        // Never leaves the client
+        client.echo().echo("").await.unwrap_err();
+        assert!(recorder.take().is_empty());
+    })
+    .await
+    .expect("Test timed out");
+}
+
+// Streaming call test
+// Verifies the response is reported once, after the stream has ended
+#[tokio::test]
+async fn test_streaming_calls_report_on_completion() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let recorder = Recorder::default();
+    let client = observed_client(&ctx, recorder.clone()).await;
+
+    timeout(TIMEOUT_DURATION, async {
+        let mut responses = client.echo().echo_stream("again", 3).await.expect("Stream failed");
+        responses.next().await.unwrap().expect("First item failed");
+        assert_eq!(recorder.take(), vec![Seen::Request("/echo.EchoService/EchoStream".into())]);
+        while let Some(item) = responses.next().await {
+            item.expect("Stream item failed");
+        }
+        assert_eq!(recorder.take(), vec![Seen::Response("/echo.EchoService/EchoStream".into(), Code::Ok)]);
+
+        let steps = stream::iter(vec![(2.0, Operation::Add), (3.0, Operation::Multiply)]);
+        let totals: Vec<_> = client.calculator().running_total(steps).await
+            .expect("Running total failed")
+            .collect()
+            .await;
+        assert_eq!(totals.len(), 2);
+        assert_eq!(recorder.take(), pair("/calculator.CalculatorService/RunningTotal", Code::Ok));
+
+        // Dropping a stream early counts as a cancelled call
+        let responses = client.echo().echo_stream("dropped", 100).await.expect("Stream failed");
+        drop(responses);
+        assert_eq!(recorder.take(), pair("/echo.EchoService/EchoStream", Code::Cancelled));
+    })
+    .await
+    .expect("Test timed out");
+}
+
+// Panicking observer test
+// Verifies observer panics are contained and the call result is unchanged
+#[tokio::test]
+async fn test_observer_panic_is_contained() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let client = observed_client(&ctx, Panicking).await;
+
+    let reply = timeout(TIMEOUT_DURATION, client.echo().echo("still works"))
+        .await
+        .expect("Test timed out")
+        .expect("Echo failed");
+    assert_eq!(reply, "still works");
+}
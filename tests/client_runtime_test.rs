@@ -0,0 +1,105 @@
+//! Client Runtime Tests
+//! This suite verifies clients used from more than one tokio runtime, as in
+//! libraries that drive a short-lived runtime per request with block_on:
+//! 1. A client keeps working after the runtime it was created on is dropped
+//! 2. reconnect() moves a client to the current runtime and needs one
+//! 3. build_per_runtime() hands out one client per runtime and forgets the
+//!    clients of runtimes that are gone
+
+use embedded_recruitment_task::GrpcClient;
+use tokio::runtime::{Builder, Runtime};
+use tokio::time::{timeout, Duration};
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Server on its own multi-threaded runtime, which outlives every client runtime
+fn start_server() -> (Runtime, TestContext) {
+    let runtime = Runtime::new().expect("Failed to build server runtime");
+    let ctx = runtime.block_on(TestContext::setup()).expect("Failed to setup test context");
+    (runtime, ctx)
+}
+
+// Short-lived runtime of the kind applications create per request
+fn client_runtime() -> Runtime {
+    Builder::new_current_thread().enable_all().build().expect("Failed to build client runtime")
+}
+
+// Echo `message` on `client`, failing the test instead of hanging
+fn echo_on(runtime: &Runtime, client: &GrpcClient, message: &str) -> String {
+    runtime.block_on(async {
+        timeout(TIMEOUT_DURATION, client.echo().echo(message))
+            .await
+            .expect("Echo hung")
+            .expect("Echo failed")
+    })
+}
+
+// Dropped runtime test
+// Verifies:
+// - A client created and used on one runtime is usable from the next one
+// - Clones share the reopened channel
+#[test]
+fn test_client_survives_dropped_runtime() {
+    let (_server, ctx) = start_server();
+    let url = format!("http://{}", ctx.addr);
+
+    let first = client_runtime();
+    let client = first.block_on(async { GrpcClient::builder(&url)?.connect() }).expect("Connect failed");
+    assert_eq!(echo_on(&first, &client, "first runtime"), "first runtime");
+    drop(first);
+
+    for round in 0..3 {
+        let next = client_runtime();
+        let message = format!("runtime {}", round);
+        assert_eq!(echo_on(&next, &client.clone(), &message), message);
+    }
+}
+
+// Explicit reconnect test
+// Verifies reconnect() works within a runtime and is refused outside one
+#[test]
+fn test_reconnect_moves_client_to_current_runtime() {
+    let (_server, ctx) = start_server();
+    let url = format!("http://{}", ctx.addr);
+
+    let first = client_runtime();
+    let client = first.block_on(async { GrpcClient::builder(&url)?.connect() }).expect("Connect failed");
+    let err = client.reconnect().unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    let second = client_runtime();
+    second.block_on(async { client.reconnect() }).expect("Reconnect failed");
+    drop(first);
+    assert_eq!(echo_on(&second, &client, "moved"), "moved");
+}
+
+// Per-runtime factory test
+// Verifies:
+// - Runtimes alive at the same time get separate clients, reused on that runtime
+// - The client of a dropped runtime is discarded on the next request
+// - Requesting a client outside any runtime fails with FailedPrecondition
+#[test]
+fn test_factory_hands_out_one_client_per_runtime() {
+    let (_server, ctx) = start_server();
+    let factory = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .unwrap()
+        .build_per_runtime()
+        .expect("Failed to build factory");
+    assert_eq!(factory.client().unwrap_err().code(), Code::FailedPrecondition);
+
+    let (first, second) = (client_runtime(), client_runtime());
+    for (runtime, message) in [(&first, "one"), (&second, "two"), (&first, "one again")] {
+        let client = runtime.block_on(async { factory.client() }).expect("Factory failed");
+        assert_eq!(echo_on(runtime, &client, message), message);
+    }
+    assert_eq!(factory.runtimes(), 2);
+
+    drop(first);
+    let client = second.block_on(async { factory.client() }).expect("Factory failed");
+    assert_eq!(factory.runtimes(), 1);
+    assert_eq!(echo_on(&second, &client, "still here"), "still here");
+}
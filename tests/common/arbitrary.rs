@@ -0,0 +1,100 @@
+//! Arbitrary Input Generators
+//! Proptest strategies and invariant checks shared by the fuzz tests:
+//! 1. Floats covering the awkward corners of IEEE 754 (±0.0, subnormals,
+//!    NaN, infinities, extremes) alongside ordinary values
+//! 2. Operation integers, mostly valid but including unknown values
+//! 3. Unicode strings, including empty, whitespace-only and characters
+//!    next to the surrogate range
+//! 4. Invariants: results are finite or a proper Status, never a panic
+//!
+//! Handlers are called directly, without a transport, on a runtime built
+//! by `runtime()`.
+
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+use tonic::{Code, Status};
+
+// Runtime for driving async handlers from synchronous proptest bodies
+pub fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .expect("Failed to build runtime")
+}
+
+// Any f64, weighted towards the values that break naive validation
+pub fn any_f64() -> BoxedStrategy<f64> {
+    prop_oneof![
+        3 => Just(0.0),
+        3 => Just(-0.0),
+        2 => Just(f64::NAN),
+        2 => Just(f64::INFINITY),
+        2 => Just(f64::NEG_INFINITY),
+        1 => Just(f64::MAX),
+        1 => Just(f64::MIN),
+        1 => Just(f64::MIN_POSITIVE),
+        1 => Just(f64::from_bits(1)),  // smallest subnormal
+        // Subnormals of either sign
+        5 => proptest::num::f64::SUBNORMAL | proptest::num::f64::POSITIVE | proptest::num::f64::NEGATIVE,
+        10 => proptest::num::f64::ANY,
+        10 => -1e6..1e6f64,
+    ]
+    .boxed()
+}
+
+// Operation values as sent on the wire: mostly the known ones, some unknown
+pub fn any_operation() -> BoxedStrategy<i32> {
+    prop_oneof![
+        8 => 0..5i32,
+        1 => Just(-1),
+        1 => Just(5),
+        1 => any::<i32>(),
+    ]
+    .boxed()
+}
+
+// Characters next to the surrogate gap (U+D800..U+DFFF), plus other edges
+// Rust strings cannot hold surrogates, so these are the closest valid ones
+fn surrogate_adjacent_char() -> BoxedStrategy<char> {
+    prop_oneof![
+        proptest::char::range('\u{D7F0}', '\u{D7FF}'),
+        proptest::char::range('\u{E000}', '\u{E00F}'),
+        Just('\u{FFFD}'),
+        Just('\u{FEFF}'),
+        Just('\u{10FFFF}'),
+        Just('\0'),
+    ]
+    .boxed()
+}
+
+// Any echo message, including the empty string
+pub fn any_message() -> BoxedStrategy<String> {
+    prop_oneof![
+        Just(String::new()),
+        "[ \t\r\n\u{00A0}\u{3000}]{1,8}",
+        proptest::collection::vec(surrogate_adjacent_char(), 1..32).prop_map(|chars| chars.into_iter().collect()),
+        any::<String>(),
+    ]
+    .boxed()
+}
+
+// A Status the handlers may legitimately return for bad input
+pub fn check_proper_status(status: &Status) -> Result<(), TestCaseError> {
+    prop_assert!(
+        matches!(status.code(), Code::InvalidArgument | Code::OutOfRange),
+        "unexpected status code {:?}: {}", status.code(), status.message()
+    );
+    prop_assert!(!status.message().is_empty(), "status {:?} has no message", status.code());
+    Ok(())
+}
+
+// A numeric result is finite, or the failure is a proper Status
+pub fn check_finite_or_status(result: &Result<f64, Status>) -> Result<(), TestCaseError> {
+    match result {
+        Ok(value) => {
+            prop_assert!(value.is_finite(), "non-finite result {}", value);
+            Ok(())
+        }
+        Err(status) => check_proper_status(status),
+    }
+}
@@ -0,0 +1,45 @@
+//! HTTP/2 Handshake Utilities
+//! This module lets tests see what the server announces to its peers:
+//! 1. Opens a raw TCP connection and sends the client preface
+//! 2. Reads the server's first SETTINGS frame and decodes its parameters
+//!
+//! Parameters the server leaves at their protocol default are not sent,
+//! so a missing entry means "default", not "unknown".
+
+use std::collections::HashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+// SETTINGS parameter identifiers (RFC 7540 section 6.5.2)
+pub const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+pub const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+pub const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+pub const SETTINGS_MAX_HEADER_LIST_SIZE: u16 = 0x6;
+
+// Client connection preface, followed by an empty SETTINGS frame
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const EMPTY_SETTINGS: [u8; 9] = [0, 0, 0, 0x4, 0, 0, 0, 0, 0];
+const FRAME_TYPE_SETTINGS: u8 = 0x4;
+
+// Connect to `addr` and return the parameters of the server's SETTINGS frame
+// Panics if the server does not open with a SETTINGS frame
+pub async fn server_settings(addr: &str) -> HashMap<u16, u32> {
+    let mut stream = TcpStream::connect(addr).await.expect("Failed to connect");
+    stream.write_all(PREFACE).await.expect("Failed to send preface");
+    stream.write_all(&EMPTY_SETTINGS).await.expect("Failed to send SETTINGS");
+
+    let mut header = [0u8; 9];
+    stream.read_exact(&mut header).await.expect("Failed to read frame header");
+    assert_eq!(header[3], FRAME_TYPE_SETTINGS, "first frame is not SETTINGS: {:?}", header);
+    let length = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).await.expect("Failed to read SETTINGS");
+
+    payload
+        .chunks_exact(6)
+        .map(|entry| (
+            u16::from_be_bytes([entry[0], entry[1]]),
+            u32::from_be_bytes([entry[2], entry[3], entry[4], entry[5]]),
+        ))
+        .collect()
+}
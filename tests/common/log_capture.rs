@@ -0,0 +1,72 @@
+//! Log Capture Utilities
+//! This module lets tests assert on emitted tracing output:
+//! 1. An in-memory writer shared between a subscriber and the test
+//! 2. A ready-made fmt subscriber that writes into it
+//! 3. Helpers to read and search the captured lines
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+
+// LogCapture: in-memory sink for formatted log lines
+// - Clone shares the same buffer, so the test keeps a handle
+//   while the subscriber owns another
+#[derive(Clone, Default)]
+pub struct LogCapture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl LogCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Build a subscriber that records every event (TRACE and up) into this capture
+    // Attach it with `tracing::subscriber::set_default` or `WithSubscriber`
+    pub fn subscriber(&self) -> impl tracing::Subscriber + Send + Sync {
+        tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(self.clone())
+            .finish()
+    }
+
+    // All captured output as a string
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.lock().unwrap()).into_owned()
+    }
+
+    // Captured output split into lines
+    pub fn lines(&self) -> Vec<String> {
+        self.contents().lines().map(str::to_owned).collect()
+    }
+
+    // Lines containing `needle`
+    pub fn lines_containing(&self, needle: &str) -> Vec<String> {
+        self.lines().into_iter().filter(|line| line.contains(needle)).collect()
+    }
+}
+
+// Writer handed out to the subscriber for each event
+pub struct CaptureWriter {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl io::Write for CaptureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogCapture {
+    type Writer = CaptureWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        CaptureWriter { buffer: self.buffer.clone() }
+    }
+}
@@ -1,8 +1,16 @@
 //! Common Test Utilities Module
 //! This module provides shared testing infrastructure:
-//! - Re-exports test utilities for use in all test files
-//! - Centralizes common testing code
+//! - Re-exports the library's test harness (feature "test-util") for use
+//!   in all test files
+//! - Centralizes common testing code (log capture, raw HTTP/2 handshakes)
 //! - Maintains DRY principle in tests
 
-mod test_utils;
-pub use test_utils::*;
+// Each test binary compiles this module but only uses part of it
+#![allow(dead_code, unused_imports)]
+
+mod log_capture;
+mod http2;
+pub mod arbitrary;
+pub use log_capture::*;
+pub use http2::*;
+pub use embedded_recruitment_task::testing::{next_port, LatencyRecorder, TaskResult, TestContext, TEST_CLIENT_NAME};
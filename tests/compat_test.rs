@@ -0,0 +1,633 @@
+//! Wire Compatibility Tests
+//! This suite guards the protocol against changes that break older clients:
+//! 1. Golden files hold the encoding of every canonical sample as first released
+//! 2. Current code must decode every golden file to the expected values
+//! 3. Current encodings must be accepted by "old" decoders — minimal
+//!    hand-written prost mirrors of the original message definitions
+//!
+//! Changing a field number or type makes these tests fail.
+//! Regenerate golden files only for intentional, compatible additions:
+//!     UPDATE_GOLDEN=1 cargo test --test compat_test
+
+use std::path::PathBuf;
+use embedded_recruitment_task::proto::calculator::CalculateRequest;
+use embedded_recruitment_task::proto::compat;
+use prost::Message;
+
+// Mirrors of the messages as originally published
+// These play the role of an old client's generated code
+mod v1 {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EchoRequest {
+        #[prost(string, tag = "1")]
+        pub message: String,
+        #[prost(uint32, tag = "2")]
+        pub delay_ms: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EchoResponse {
+        #[prost(string, tag = "1")]
+        pub message: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EchoChunk {
+        #[prost(bytes = "vec", tag = "1")]
+        pub data: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EchoStreamRequest {
+        #[prost(string, tag = "1")]
+        pub message: String,
+        #[prost(uint32, tag = "2")]
+        pub repeat: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CalculateRequest {
+        #[prost(double, tag = "1")]
+        pub first_number: f64,
+        #[prost(double, tag = "2")]
+        pub second_number: f64,
+        // Enums travel as int32: ADD = 0, SUBTRACT = 1, MULTIPLY = 2, DIVIDE = 3
+        #[prost(int32, tag = "3")]
+        pub operation: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CalculateResponse {
+        #[prost(double, tag = "1")]
+        pub result: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AggregateRequest {
+        #[prost(double, repeated, tag = "1")]
+        pub values: Vec<f64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AggregateResponse {
+        #[prost(double, tag = "1")]
+        pub sum: f64,
+        #[prost(double, tag = "2")]
+        pub mean: f64,
+        #[prost(double, tag = "3")]
+        pub min: f64,
+        #[prost(double, tag = "4")]
+        pub max: f64,
+        #[prost(uint64, tag = "5")]
+        pub count: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AggregateValue {
+        #[prost(double, tag = "1")]
+        pub value: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AggregateProgress {
+        #[prost(uint64, tag = "1")]
+        pub count: u64,
+        #[prost(double, tag = "2")]
+        pub sum: f64,
+        #[prost(double, tag = "3")]
+        pub mean: f64,
+        #[prost(double, tag = "4")]
+        pub variance: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RunningTotalStep {
+        #[prost(double, tag = "1")]
+        pub value: f64,
+        #[prost(int32, tag = "2")]
+        pub operation: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct RunningTotalResponse {
+        #[prost(double, tag = "1")]
+        pub total: f64,
+        #[prost(string, tag = "2")]
+        pub error: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CalculateDecimalRequest {
+        #[prost(string, tag = "1")]
+        pub first_number: String,
+        #[prost(string, tag = "2")]
+        pub second_number: String,
+        #[prost(int32, tag = "3")]
+        pub operation: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CalculateDecimalResponse {
+        #[prost(string, tag = "1")]
+        pub result: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EvaluateRequest {
+        #[prost(string, tag = "1")]
+        pub expression: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct EvaluateResponse {
+        #[prost(double, tag = "1")]
+        pub result: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ReduceRequest {
+        #[prost(double, repeated, tag = "1")]
+        pub values: Vec<f64>,
+        #[prost(int32, tag = "2")]
+        pub operation: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ReduceResponse {
+        #[prost(double, tag = "1")]
+        pub result: f64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetHistoryRequest {
+        #[prost(uint32, tag = "1")]
+        pub limit: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HistoryEntry {
+        #[prost(double, tag = "1")]
+        pub first_number: f64,
+        #[prost(double, tag = "2")]
+        pub second_number: f64,
+        #[prost(int32, tag = "3")]
+        pub operation: i32,
+        #[prost(double, tag = "4")]
+        pub result: f64,
+        #[prost(uint64, tag = "5")]
+        pub timestamp_ms: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetHistoryResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub entries: Vec<HistoryEntry>,
+        #[prost(uint64, tag = "2")]
+        pub total: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CalculatorErrorDetail {
+        #[prost(int32, tag = "1")]
+        pub kind: i32,
+        #[prost(uint32, optional, tag = "2")]
+        pub operand: Option<u32>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LatencyStatsResponse {
+        #[prost(double, tag = "1")]
+        pub p50_ms: f64,
+        #[prost(double, tag = "2")]
+        pub p90_ms: f64,
+        #[prost(double, tag = "3")]
+        pub p99_ms: f64,
+        #[prost(uint64, tag = "4")]
+        pub count: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ServerInfoResponse {
+        #[prost(string, tag = "1")]
+        pub version: String,
+        #[prost(uint32, tag = "2")]
+        pub worker_threads: u32,
+        #[prost(uint32, tag = "3")]
+        pub max_blocking_threads: u32,
+        #[prost(string, tag = "4")]
+        pub thread_name_prefix: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SetLogLevelRequest {
+        #[prost(string, tag = "1")]
+        pub target: String,
+        #[prost(string, tag = "2")]
+        pub level: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LogLevelResponse {
+        #[prost(string, tag = "1")]
+        pub filter: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SetConfigRequest {
+        #[prost(bool, optional, tag = "1")]
+        pub allow_empty_echo: Option<bool>,
+        #[prost(uint64, optional, tag = "2")]
+        pub echo_max_chars: Option<u64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ConfigResponse {
+        #[prost(bool, tag = "1")]
+        pub allow_empty_echo: bool,
+        #[prost(uint64, tag = "2")]
+        pub echo_max_chars: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SetServiceStateRequest {
+        #[prost(string, tag = "1")]
+        pub service: String,
+        #[prost(int32, tag = "2")]
+        pub state: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ServiceStateResponse {
+        #[prost(string, tag = "1")]
+        pub service: String,
+        #[prost(int32, tag = "2")]
+        pub state: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OperationsResponse {
+        // Repeated enums travel packed as int32
+        #[prost(int32, repeated, tag = "1")]
+        pub operations: Vec<i32>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CacheStatsResponse {
+        #[prost(uint64, tag = "1")]
+        pub calculator_hits: u64,
+        #[prost(uint64, tag = "2")]
+        pub calculator_misses: u64,
+        #[prost(uint64, tag = "3")]
+        pub echo_hits: u64,
+        #[prost(uint64, tag = "4")]
+        pub echo_misses: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ConnectionInfo {
+        #[prost(string, tag = "1")]
+        pub peer: String,
+        #[prost(uint64, tag = "2")]
+        pub connected_at_ms: u64,
+        #[prost(uint64, tag = "3")]
+        pub request_count: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListConnectionsResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub connections: Vec<ConnectionInfo>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct SubscribeRequest {
+        #[prost(string, tag = "1")]
+        pub topic: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PublishRequest {
+        #[prost(string, tag = "1")]
+        pub topic: String,
+        #[prost(string, tag = "2")]
+        pub message: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PublishResponse {
+        #[prost(uint64, tag = "1")]
+        pub subscribers: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BroadcastMessage {
+        #[prost(string, tag = "1")]
+        pub topic: String,
+        #[prost(string, tag = "2")]
+        pub message: String,
+    }
+}
+
+// Location of the checked-in golden files
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.bin", name))
+}
+
+// Read a golden file, failing with a helpful message if it is missing
+fn read_golden(name: &str) -> Vec<u8> {
+    let path = golden_path(name);
+    std::fs::read(&path).unwrap_or_else(|e| {
+        panic!("missing golden file {} ({}); new samples need UPDATE_GOLDEN=1", path.display(), e)
+    })
+}
+
+// Byte-level check
+// Verifies the current encoding of every sample is byte-identical to its golden file
+#[test]
+fn test_current_encoding_matches_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    for sample in compat::samples() {
+        if update {
+            std::fs::write(golden_path(sample.name), &sample.encoded).unwrap();
+        }
+        assert_eq!(
+            sample.encoded,
+            read_golden(sample.name),
+            "encoding of {} changed — this breaks wire compatibility",
+            sample.name
+        );
+    }
+}
+
+// Decode a golden file with the current definition and compare to the sample
+fn assert_golden_decodes<M: Message + Default + PartialEq + std::fmt::Debug>(name: &str, expected: M) {
+    let decoded = M::decode(read_golden(name).as_slice())
+        .unwrap_or_else(|e| panic!("{} no longer decodes: {}", name, e));
+    assert_eq!(decoded, expected, "{} decoded differently", name);
+}
+
+// Forward check
+// Verifies current code decodes every golden file to the canonical values
+#[test]
+fn test_golden_files_decode_to_expected_values() {
+    assert_golden_decodes("echo_request", compat::echo_request());
+    assert_golden_decodes("echo_request_validated", compat::echo_request_validated());
+    assert_golden_decodes("echo_response", compat::echo_response());
+    assert_golden_decodes("echo_response_sequenced", compat::echo_response_sequenced());
+    assert_golden_decodes("echo_response_timed", compat::echo_response_timed());
+    assert_golden_decodes("echo_chunk", compat::echo_chunk());
+    assert_golden_decodes("echo_stream_request", compat::echo_stream_request());
+    assert_golden_decodes("calculate_request_with_inverse", compat::calculate_request_with_inverse());
+    assert_golden_decodes("calculate_response", compat::calculate_response());
+    assert_golden_decodes("calculate_response_with_inverse", compat::calculate_response_with_inverse());
+    assert_golden_decodes("aggregate_request", compat::aggregate_request());
+    assert_golden_decodes("aggregate_response", compat::aggregate_response());
+    assert_golden_decodes("aggregate_value", compat::aggregate_value());
+    assert_golden_decodes("aggregate_progress", compat::aggregate_progress());
+    assert_golden_decodes("running_total_step", compat::running_total_step());
+    assert_golden_decodes("running_total_response", compat::running_total_response());
+    assert_golden_decodes("latency_stats_response", compat::latency_stats_response());
+    assert_golden_decodes("server_info_response", compat::server_info_response());
+    assert_golden_decodes("set_log_level_request", compat::set_log_level_request());
+    assert_golden_decodes("log_level_response", compat::log_level_response());
+    assert_golden_decodes("set_config_request", compat::set_config_request());
+    assert_golden_decodes("config_response", compat::config_response());
+    assert_golden_decodes("set_service_state_request", compat::set_service_state_request());
+    assert_golden_decodes("service_state_response", compat::service_state_response());
+    assert_golden_decodes("operations_response", compat::operations_response());
+    assert_golden_decodes("cache_stats_response", compat::cache_stats_response());
+    assert_golden_decodes("connection_info", compat::connection_info());
+    assert_golden_decodes("list_connections_response", compat::list_connections_response());
+    assert_golden_decodes("subscribe_request", compat::subscribe_request());
+    assert_golden_decodes("publish_request", compat::publish_request());
+    assert_golden_decodes("publish_response", compat::publish_response());
+    assert_golden_decodes("broadcast_message", compat::broadcast_message());
+    assert_golden_decodes("calculate_decimal_request", compat::calculate_decimal_request());
+    assert_golden_decodes("calculate_decimal_response", compat::calculate_decimal_response());
+    assert_golden_decodes("evaluate_request", compat::evaluate_request());
+    assert_golden_decodes("evaluate_response", compat::evaluate_response());
+    assert_golden_decodes("reduce_request", compat::reduce_request());
+    assert_golden_decodes("reduce_response", compat::reduce_response());
+    assert_golden_decodes("get_history_request", compat::get_history_request());
+    assert_golden_decodes("history_entry", compat::history_entry());
+    assert_golden_decodes("get_history_response", compat::get_history_response());
+    assert_golden_decodes("calculator_error_detail", compat::calculator_error_detail());
+
+    for (operation, name) in compat::operations() {
+        assert_golden_decodes(name, compat::calculate_request(operation));
+        let decoded = CalculateRequest::decode(read_golden(name).as_slice()).unwrap();
+        assert_eq!(decoded.operation(), operation);
+    }
+}
+
+// Backward check
+// Verifies bytes produced today are understood by the original definitions
+#[test]
+fn test_current_encoding_accepted_by_old_decoders() {
+    let expected = compat::echo_request();
+    let old = v1::EchoRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.message, expected.message);
+    assert_eq!(old.delay_ms, expected.delay_ms);
+
+    // Old servers skip the validation and echo the message unchanged
+    let expected = compat::echo_request_validated();
+    let old = v1::EchoRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.message, old.delay_ms), (expected.message, expected.delay_ms));
+
+    let expected = compat::echo_response();
+    let old = v1::EchoResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.message, expected.message);
+
+    // Old clients skip the new ordering fields
+    let expected = compat::echo_response_sequenced();
+    let old = v1::EchoResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.message, expected.message);
+
+    // ... and the handler timing
+    let expected = compat::echo_response_timed();
+    let old = v1::EchoResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.message, expected.message);
+
+    let expected = compat::echo_chunk();
+    let old = v1::EchoChunk::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.data, expected.data);
+
+    let expected = compat::echo_stream_request();
+    let old = v1::EchoStreamRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.message, expected.message);
+    assert_eq!(old.repeat, expected.repeat);
+
+    let expected = compat::calculate_response();
+    let old = v1::CalculateResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.result, expected.result);
+
+    // Old servers skip include_inverse, old clients the inverse
+    let expected = compat::calculate_request_with_inverse();
+    let old = v1::CalculateRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.first_number, old.second_number), (expected.first_number, expected.second_number));
+
+    let expected = compat::calculate_response_with_inverse();
+    let old = v1::CalculateResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.result, expected.result);
+
+    let expected = compat::aggregate_request();
+    let old = v1::AggregateRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.values, expected.values);
+
+    let expected = compat::aggregate_response();
+    let old = v1::AggregateResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        (old.sum, old.mean, old.min, old.max, old.count),
+        (expected.sum, expected.mean, expected.min, expected.max, expected.count)
+    );
+
+    let expected = compat::aggregate_value();
+    let old = v1::AggregateValue::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.value, expected.value);
+
+    let expected = compat::aggregate_progress();
+    let old = v1::AggregateProgress::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        (old.count, old.sum, old.mean, old.variance),
+        (expected.count, expected.sum, expected.mean, expected.variance)
+    );
+
+    let expected = compat::running_total_step();
+    let old = v1::RunningTotalStep::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.value, old.operation), (expected.value, expected.operation));
+
+    let expected = compat::running_total_response();
+    let old = v1::RunningTotalResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.total, old.error), (expected.total, expected.error));
+
+    let expected = compat::calculate_decimal_request();
+    let old = v1::CalculateDecimalRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        (old.first_number, old.second_number, old.operation),
+        (expected.first_number, expected.second_number, expected.operation)
+    );
+
+    let expected = compat::calculate_decimal_response();
+    let old = v1::CalculateDecimalResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.result, expected.result);
+
+    let expected = compat::evaluate_request();
+    let old = v1::EvaluateRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.expression, expected.expression);
+
+    let expected = compat::evaluate_response();
+    let old = v1::EvaluateResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.result, expected.result);
+
+    let expected = compat::reduce_request();
+    let old = v1::ReduceRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.values, old.operation), (expected.values, expected.operation));
+
+    let expected = compat::reduce_response();
+    let old = v1::ReduceResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.result, expected.result);
+
+    let expected = compat::get_history_request();
+    let old = v1::GetHistoryRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.limit, expected.limit);
+
+    let expected = compat::get_history_response();
+    let old = v1::GetHistoryResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    let entry = &old.entries[0];
+    assert_eq!(
+        (entry.first_number, entry.second_number, entry.operation, entry.result, entry.timestamp_ms, old.total),
+        (10.5, 2.0, 1, 8.5, 1_700_000_000_000, expected.total),
+    );
+
+    let expected = compat::calculator_error_detail();
+    let old = v1::CalculatorErrorDetail::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.kind, old.operand), (expected.kind, expected.operand));
+
+    let expected = compat::latency_stats_response();
+    let old = v1::LatencyStatsResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        (old.p50_ms, old.p90_ms, old.p99_ms, old.count),
+        (expected.p50_ms, expected.p90_ms, expected.p99_ms, expected.count)
+    );
+
+    let expected = compat::server_info_response();
+    let old = v1::ServerInfoResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        (old.version, old.worker_threads, old.max_blocking_threads, old.thread_name_prefix),
+        (expected.version, expected.worker_threads, expected.max_blocking_threads, expected.thread_name_prefix)
+    );
+
+    let expected = compat::set_log_level_request();
+    let old = v1::SetLogLevelRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.target, old.level), (expected.target, expected.level));
+
+    let expected = compat::log_level_response();
+    let old = v1::LogLevelResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.filter, expected.filter);
+
+    let expected = compat::set_config_request();
+    let old = v1::SetConfigRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.allow_empty_echo, old.echo_max_chars), (expected.allow_empty_echo, expected.echo_max_chars));
+
+    let expected = compat::config_response();
+    let old = v1::ConfigResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.allow_empty_echo, old.echo_max_chars), (expected.allow_empty_echo, expected.echo_max_chars));
+
+    let expected = compat::set_service_state_request();
+    let old = v1::SetServiceStateRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.service, old.state), (expected.service, expected.state));
+
+    let expected = compat::service_state_response();
+    let old = v1::ServiceStateResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.service, old.state), (expected.service, expected.state));
+
+    let expected = compat::operations_response();
+    let old = v1::OperationsResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.operations, expected.operations);
+
+    let expected = compat::cache_stats_response();
+    let old = v1::CacheStatsResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(
+        (old.calculator_hits, old.calculator_misses, old.echo_hits, old.echo_misses),
+        (expected.calculator_hits, expected.calculator_misses, expected.echo_hits, expected.echo_misses)
+    );
+
+    let expected = compat::list_connections_response();
+    let old = v1::ListConnectionsResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    let connections: Vec<_> = old.connections
+        .into_iter()
+        .map(|c| (c.peer, c.connected_at_ms, c.request_count))
+        .collect();
+    assert_eq!(connections, [
+        ("192.0.2.7:51234".to_string(), 1_700_000_000_000, 12),
+        ("[2001:db8::1]:443".to_string(), 1_700_000_000_500, 0),
+    ]);
+
+    let expected = compat::subscribe_request();
+    let old = v1::SubscribeRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.topic, expected.topic);
+
+    let expected = compat::publish_request();
+    let old = v1::PublishRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.topic, old.message), (expected.topic, expected.message));
+
+    let expected = compat::publish_response();
+    let old = v1::PublishResponse::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!(old.subscribers, expected.subscribers);
+
+    let expected = compat::broadcast_message();
+    let old = v1::BroadcastMessage::decode(expected.encode_to_vec().as_slice()).unwrap();
+    assert_eq!((old.topic, old.message), (expected.topic, expected.message));
+
+    // Operation values must keep their original numbers
+    for (operation, number) in compat::operations().into_iter().map(|(op, _)| op).zip(0..) {
+        let expected = compat::calculate_request(operation);
+        let old = v1::CalculateRequest::decode(expected.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(old.first_number, expected.first_number);
+        assert_eq!(old.second_number, expected.second_number);
+        assert_eq!(old.operation, number, "{:?} changed its enum number", operation);
+    }
+}
@@ -0,0 +1,218 @@
+//! Connection Lifecycle Tests
+//! This suite verifies the server's connection hooks and gauge:
+//! 1. on_connect / on_disconnect run once per client connection
+//! 2. The active_connections gauge follows connections opening and closing
+//! 3. A slow hook does not hold up new connections
+//! 4. Idle connections are closed and clients reconnect transparently,
+//!    while a long-lived stream keeps its connection open
+//! 5. The same holds over TLS, where the close is the same graceful one
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use embedded_recruitment_task::server::ServerMetrics;
+use embedded_recruitment_task::proto::calculator::Operation;
+use tokio::time::{sleep, timeout, Duration, Instant};
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+use common::{next_port, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Connect a new client (its own channel) and make one call
+async fn connect_and_echo(addr: &str) -> GrpcClient {
+    let client = GrpcClient::builder(format!("http://{}", addr))
+        .expect("Invalid endpoint")
+        .connect_retries(50, Duration::from_millis(20))
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+    client.echo().echo("hello").await.expect("Echo failed");
+    client
+}
+
+// Poll until `done` holds or the timeout expires
+async fn wait_until(mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + TIMEOUT_DURATION;
+    while Instant::now() < deadline {
+        if done() {
+            return true;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    done()
+}
+
+// Lifecycle test
+// Verifies:
+// - Each client channel triggers exactly one connect and one disconnect
+// - The gauge counts open connections and returns to zero
+#[tokio::test]
+async fn test_connect_and_disconnect_hooks_are_counted() {
+    let connects = Arc::new(AtomicUsize::new(0));
+    let disconnects = Arc::new(AtomicUsize::new(0));
+    let addr = format!("[::1]:{}", next_port());
+
+    let (connected, disconnected) = (connects.clone(), disconnects.clone());
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .on_connect(move |_| { connected.fetch_add(1, Ordering::SeqCst); })
+        .on_disconnect(move |_| { disconnected.fetch_add(1, Ordering::SeqCst); })
+        .build()
+        .expect("Failed to build server");
+    let metrics: ServerMetrics = server.metrics();
+    let handle = tokio::spawn(server.serve());
+
+    let first = connect_and_echo(&addr).await;
+    let second = connect_and_echo(&addr).await;
+    assert!(wait_until(|| connects.load(Ordering::SeqCst) == 2).await, "connect hooks did not run");
+    assert_eq!(metrics.snapshot().active_connections, 2);
+    assert_eq!(disconnects.load(Ordering::SeqCst), 0);
+
+    drop(first);
+    drop(second);
+    assert!(
+        wait_until(|| disconnects.load(Ordering::SeqCst) == 2).await,
+        "disconnect hooks did not run: {}",
+        disconnects.load(Ordering::SeqCst)
+    );
+    assert_eq!(connects.load(Ordering::SeqCst), 2);
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.active_connections, 0);
+    assert_eq!(snapshot.total_connections, 2);
+
+    shutdown.trigger();
+    timeout(TIMEOUT_DURATION, handle)
+        .await
+        .expect("Server did not stop")
+        .unwrap()
+        .expect("Server failed");
+}
+
+// Slow hook test
+// Verifies a blocking on_connect hook does not delay serving other clients
+#[tokio::test]
+async fn test_slow_hook_does_not_block_accept() {
+    let addr = format!("[::1]:{}", next_port());
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .on_connect(|_| std::thread::sleep(Duration::from_secs(1)))
+        .build()
+        .expect("Failed to build server");
+    tokio::spawn(server.serve());
+
+    let started = Instant::now();
+    let _first = connect_and_echo(&addr).await;
+    let _second = connect_and_echo(&addr).await;
+    assert!(started.elapsed() < Duration::from_secs(1), "hook blocked connections: {:?}", started.elapsed());
+
+    shutdown.trigger();
+}
+
+// Idle timeout test
+// Verifies:
+// - A connection without RPCs is closed once the timeout passes
+// - The same client's next call succeeds over a new connection
+#[tokio::test]
+async fn test_idle_connection_is_closed_and_reopened() {
+    let addr = format!("[::1]:{}", next_port());
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .idle_connection_timeout(Duration::from_millis(300))
+        .build()
+        .expect("Failed to build server");
+    let metrics = server.metrics();
+    tokio::spawn(server.serve());
+
+    let client = connect_and_echo(&addr).await;
+    assert_eq!(metrics.snapshot().active_connections, 1);
+
+    sleep(Duration::from_millis(400)).await;
+    assert!(
+        wait_until(|| metrics.snapshot().active_connections == 0).await,
+        "idle connection was not closed"
+    );
+
+    let reply = timeout(TIMEOUT_DURATION, client.echo().echo("again"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo after idle close failed");
+    assert_eq!(reply, "again");
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.total_connections, 2);
+    assert_eq!(snapshot.active_connections, 1);
+
+    shutdown.trigger();
+}
+
+// Idle timeout TLS test
+// Verifies:
+// - A TLS connection without RPCs is closed once the timeout passes
+// - The client's next call succeeds at once over a new connection, because
+//   the server said goodbye (GOAWAY) instead of dropping the socket
+#[tokio::test]
+async fn test_idle_tls_connection_is_closed_gracefully() {
+    let ctx = TestContext::setup_tls_with(|builder| builder.idle_connection_timeout(Duration::from_millis(300)))
+        .await
+        .expect("Failed to setup test context");
+    timeout(TIMEOUT_DURATION, ctx.client.echo().echo("hello"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo failed");
+    assert_eq!(ctx.metrics.snapshot().active_connections, 1);
+
+    sleep(Duration::from_millis(400)).await;
+    assert!(
+        wait_until(|| ctx.metrics.snapshot().active_connections == 0).await,
+        "idle TLS connection was not closed"
+    );
+
+    let reply = timeout(TIMEOUT_DURATION, ctx.client.echo().echo("again"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo after idle close failed");
+    assert_eq!(reply, "again");
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!(snapshot.total_connections, 2);
+    assert_eq!(snapshot.active_connections, 1);
+}
+
+// Idle timeout streaming test
+// Verifies a bidirectional stream pausing longer than the timeout is not
+// cut, because its connection has an RPC in flight
+#[tokio::test]
+async fn test_idle_timeout_spares_active_stream() {
+    let addr = format!("[::1]:{}", next_port());
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .idle_connection_timeout(Duration::from_millis(200))
+        .build()
+        .expect("Failed to build server");
+    let metrics = server.metrics();
+    tokio::spawn(server.serve());
+
+    let client = connect_and_echo(&addr).await;
+    let (steps, rx) = tokio::sync::mpsc::channel(1);
+    let mut totals = client.calculator().running_total(ReceiverStream::new(rx)).await
+        .expect("Running total failed to start");
+
+    steps.send((2.0, Operation::Add)).await.unwrap();
+    let first = timeout(TIMEOUT_DURATION, totals.next()).await.expect("No total").unwrap().unwrap();
+    assert_eq!(first.total, 2.0);
+
+    // Pause well past the timeout with the stream open
+    sleep(Duration::from_millis(700)).await;
+    steps.send((3.0, Operation::Multiply)).await.unwrap();
+    let second = timeout(TIMEOUT_DURATION, totals.next()).await.expect("No total").unwrap().unwrap();
+    assert_eq!(second.total, 6.0);
+    drop(steps);
+    assert!(timeout(TIMEOUT_DURATION, totals.next()).await.expect("Stream did not end").is_none());
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.total_connections, 1, "the stream's connection was replaced");
+    assert_eq!(snapshot.active_connections, 1);
+
+    shutdown.trigger();
+}
@@ -0,0 +1,60 @@
+//! Connection Limit Tests
+//! This suite verifies the `max_connections` server option:
+//! 1. A connection beyond the limit is closed before it can be used
+//! 2. Connections within the limit keep working
+//! 3. Closing a connection frees its slot for a new one
+
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Duration, Instant};
+use common::TestContext;
+
+mod common;
+
+// Test configuration
+const MAX_CONNECTIONS: usize = 2;  // Includes the context's own client
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Poll until `done` holds or the timeout expires
+async fn wait_until(mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + TIMEOUT_DURATION;
+    while Instant::now() < deadline {
+        if done() {
+            return true;
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    done()
+}
+
+#[tokio::test]
+async fn test_max_connections_refuses_excess_connections() {
+    let ctx = TestContext::setup_with(|builder| builder.max_connections(MAX_CONNECTIONS))
+        .await
+        .expect("Failed to setup test context");
+    let second = ctx.new_client().await.expect("Second connection refused");
+
+    // One more than the limit: the server closes the socket right after
+    // accepting, without answering the HTTP/2 preface
+    // (a tonic channel would keep reconnecting, so use a raw socket)
+    let mut refused = TcpStream::connect(&ctx.addr).await.expect("TCP connect failed");
+    let mut buf = [0u8; 64];
+    let read = timeout(TIMEOUT_DURATION, refused.read(&mut buf)).await.expect("Refused connection stayed open");
+    assert!(matches!(read, Ok(0) | Err(_)), "refused connection sent data: {:?}", read);
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!(snapshot.refused_connections, 1);
+    assert_eq!(snapshot.active_connections, MAX_CONNECTIONS as u64);
+
+    // Existing connections are unaffected
+    assert_eq!(ctx.client.echo().echo("first").await.expect("First connection failed"), "first");
+    assert_eq!(second.echo().echo("second").await.expect("Second connection failed"), "second");
+
+    // Closing one connection makes room for a new one
+    drop(second);
+    assert!(
+        wait_until(|| ctx.metrics.snapshot().active_connections < MAX_CONNECTIONS as u64).await,
+        "closed connection was not released"
+    );
+    let third = ctx.new_client().await.expect("Connection refused after a slot was freed");
+    assert_eq!(third.echo().echo("third").await.expect("Third connection failed"), "third");
+}
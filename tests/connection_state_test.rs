@@ -0,0 +1,103 @@
+//! Connection State Tests
+//! This suite verifies `GrpcClientBuilder::on_state_change`:
+//! 1. The first answered call moves the client to Ready
+//! 2. Calls failing with Unavailable after the server stops move it to
+//!    TransientFailure
+//! 3. Once the server is back on the same address, the next call
+//!    reconnects (Connecting) and the client is Ready again
+
+use std::sync::{Arc, Mutex};
+use embedded_recruitment_task::client::ConnectionState;
+use embedded_recruitment_task::server::Shutdown;
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout, Duration};
+use tonic::Code;
+use common::next_port;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+const RETRY_DELAY: Duration = Duration::from_millis(20);
+
+// Serve on `addr` until the returned handle is triggered
+fn start_server(addr: &str) -> (JoinHandle<()>, Shutdown) {
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr)
+        .startup_banner(false)
+        .build()
+        .expect("Failed to build server");
+    let serving = tokio::spawn(async move {
+        server.serve().await.expect("Server failed");
+    });
+    (serving, shutdown)
+}
+
+// How many times `wanted` was reported
+fn count(states: &[ConnectionState], wanted: ConnectionState) -> usize {
+    states.iter().filter(|&&state| state == wanted).count()
+}
+
+// Restart test
+// Verifies:
+// - Ready after the first echo, TransientFailure once the server is gone
+// - Back to Ready through Connecting after the restart, on the same client
+// - Only changes are reported
+#[tokio::test]
+async fn test_state_follows_server_restart() {
+    let addr = format!("127.0.0.1:{}", next_port());
+    let (serving, shutdown) = start_server(&addr);
+
+    let states = Arc::new(Mutex::new(Vec::new()));
+    let client = GrpcClient::builder(format!("http://{}", addr))
+        .unwrap()
+        .on_state_change({
+            let states = states.clone();
+            move |state| states.lock().unwrap().push(state)
+        })
+        .connect_retries(50, RETRY_DELAY)
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+    timeout(TIMEOUT_DURATION, client.echo().echo("up"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo failed");
+    assert_eq!(*states.lock().unwrap(), [ConnectionState::Ready]);
+
+    shutdown.trigger();
+    timeout(TIMEOUT_DURATION, serving).await.expect("Server did not stop").unwrap();
+
+    // The first call may still see the old connection go away; keep calling
+    // until one cannot reach the server at all
+    timeout(TIMEOUT_DURATION, async {
+        loop {
+            match client.echo().echo("down").await {
+                Err(status) if status.code() == Code::Unavailable => break,
+                _ => sleep(RETRY_DELAY).await,
+            }
+        }
+    })
+    .await
+    .expect("Calls never failed with Unavailable");
+    assert_eq!(states.lock().unwrap().last(), Some(&ConnectionState::TransientFailure));
+
+    let (serving, shutdown) = start_server(&addr);
+    timeout(TIMEOUT_DURATION, async {
+        while client.echo().echo("back").await.is_err() {
+            sleep(RETRY_DELAY).await;
+        }
+    })
+    .await
+    .expect("Client never reached the restarted server");
+
+    let states = states.lock().unwrap().clone();
+    assert_eq!(states.last(), Some(&ConnectionState::Ready), "{:?}", states);
+    assert_eq!(states[states.len() - 2], ConnectionState::Connecting, "{:?}", states);
+    assert_eq!(count(&states, ConnectionState::Ready), 2, "{:?}", states);
+    assert!(count(&states, ConnectionState::TransientFailure) >= 1, "{:?}", states);
+    assert!(states.windows(2).all(|pair| pair[0] != pair[1]), "repeated state in {:?}", states);
+
+    shutdown.trigger();
+    timeout(TIMEOUT_DURATION, serving).await.expect("Server did not stop").unwrap();
+}
@@ -12,9 +12,14 @@
 //! 3. Mix different operation types (echo, calculate, large payloads)
 //! 4. Track successful operations using atomic counter
 //! 5. Verify all operations complete successfully
+//! 6. Repeat against two SO_REUSEPORT listeners (Unix) and check that
+//!    separate connections reach both
+//! 7. Repeat with a file-backed calculator history and check that every
+//!    calculation was written as one intact line
 
 // Imports for async operations, atomic counters, and timeouts
 use embedded_recruitment_task::proto::calculator::Operation;
+use embedded_recruitment_task::server::{FileHistory, HistoryEntry, HistoryStore};
 use tokio::time::{timeout, Duration};
 use common::TestContext;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -26,63 +31,108 @@ mod common;
 const CONCURRENT_CLIENTS: usize = 1000;   // Simulates high concurrent load
 const OPERATIONS_PER_CLIENT: usize = 10;  // Multiple operations per client for sustained load
 const TIMEOUT_DURATION: Duration = Duration::from_secs(10);  // Maximum time for any operation
+const HISTORY_MAX_BYTES: u64 = 16 << 20;  // Large enough that the load never rotates
+#[cfg(unix)]
+const SEPARATE_CONNECTIONS: usize = 32;  // Own connections opened against multiple listeners
 
 #[tokio::test]
 async fn test_massive_concurrent_load() {
     // Initialize test environment
     let ctx = TestContext::setup().await.expect("Failed to setup test context");
-    
+    run_mixed_load(&ctx).await;
+}
+
+// Multi-listener load test
+// Verifies:
+// - The same workload succeeds in full against two SO_REUSEPORT listeners
+// - Separate client connections are spread over both listeners
+#[cfg(unix)]
+#[tokio::test]
+async fn test_massive_concurrent_load_reuse_port() {
+    let ctx = TestContext::setup_with(|builder| builder.reuse_port_listeners(2))
+        .await
+        .expect("Failed to setup test context");
+    run_mixed_load(&ctx).await;
+
+    // The kernel picks a listener per connection, so open many of them
+    let mut clients = Vec::new();
+    for i in 0..SEPARATE_CONNECTIONS {
+        let client = ctx.new_client().await.expect("Failed to connect");
+        timeout(TIMEOUT_DURATION, client.echo().echo(format!("listener_{}", i))).await
+            .expect("Echo timed out")
+            .expect("Echo failed");
+        clients.push(client);
+    }
+
+    let accepted = ctx.metrics.snapshot().listener_connections;
+    assert_eq!(accepted.len(), 2);
+    assert_eq!(accepted.iter().sum::<u64>(), SEPARATE_CONNECTIONS as u64 + 1);
+    assert!(accepted.iter().all(|&count| count > 0), "connections not distributed: {:?}", accepted);
+}
+
+// File history load test
+// Verifies:
+// - Concurrent calculations each append exactly one entry
+// - Appends never interleave, so every line parses
+#[tokio::test]
+async fn test_massive_concurrent_load_file_history() {
+    let dir = std::env::temp_dir().join(format!("grpc-history-stress-{}", std::process::id()));
+    let path = dir.join("history.jsonl");
+    let _ = std::fs::remove_dir_all(&dir);
+    let store = Arc::new(FileHistory::open(&path, HISTORY_MAX_BYTES).expect("Failed to open history"));
+    let ctx = TestContext::setup_with(|builder| builder.calculator_history_store(store.clone()))
+        .await
+        .expect("Failed to setup test context");
+    run_mixed_load(&ctx).await;
+
+    // Operations 1, 4 and 7 of every client are calculations
+    let calculations = CONCURRENT_CLIENTS * ((OPERATIONS_PER_CLIENT + 1) / 3);
+    assert_eq!(store.len().await.expect("len failed"), calculations);
+    let contents = std::fs::read_to_string(&path).expect("Failed to read history");
+    assert_eq!(contents.lines().count(), calculations);
+    for line in contents.lines() {
+        serde_json::from_str::<HistoryEntry>(line).unwrap_or_else(|e| panic!("corrupt line {:?}: {}", line, e));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// Run the mixed workload on CONCURRENT_CLIENTS tasks sharing ctx.client
+// and assert every operation succeeded
+async fn run_mixed_load(ctx: &TestContext) {
     // Atomic counter for tracking successful operations
     // Using atomic operations for thread-safe counting
     let success_count = Arc::new(AtomicUsize::new(0));
     let expected_total = CONCURRENT_CLIENTS * OPERATIONS_PER_CLIENT;
     
-    // Create concurrent client tasks
-    let handles: Vec<_> = (0..CONCURRENT_CLIENTS).map(|client_id| {
-        // Clone references for the async task
-        let client = ctx.client.clone();
+    // Each client performs multiple operations, rotating through operation types
+    ctx.run_concurrent(CONCURRENT_CLIENTS, |client_id, client| {
         let counter = success_count.clone();
-        
-        // Spawn individual client task
-        tokio::spawn(async move {
-            // Each client performs multiple operations
+        async move {
             for op_id in 0..OPERATIONS_PER_CLIENT {
-                // Rotate through different operation types
                 match op_id % 3 {
+                    // Simple echo operation
                     0 => {
-                        // Simple echo operation
                         let msg = format!("client_{}_op_{}", client_id, op_id);
-                        timeout(TIMEOUT_DURATION, client.echo().echo(msg))
-                            .await.expect("Timeout").expect("Echo failed");
+                        timeout(TIMEOUT_DURATION, client.echo().echo(msg)).await??;
                     },
+                    // Calculator operation
                     1 => {
-                        // Calculator operation
-                        timeout(
-                            TIMEOUT_DURATION,
-                            client.calculator().calculate(
-                                client_id as f64,
-                                op_id as f64,
-                                Operation::Add
-                            )
-                        ).await.expect("Timeout").expect("Calculate failed");
+                        let mut calculator = client.calculator();
+                        let sum = calculator.calculate(client_id as f64, op_id as f64, Operation::Add);
+                        timeout(TIMEOUT_DURATION, sum).await??;
                     },
+                    // Large message echo operation
                     _ => {
-                        // Large message echo operation
                         let msg = format!("large_{}_{}", client_id, "X".repeat(1000));
-                        timeout(TIMEOUT_DURATION, client.echo().echo(msg))
-                            .await.expect("Timeout").expect("Large message failed");
+                        timeout(TIMEOUT_DURATION, client.echo().echo(msg)).await??;
                     }
                 }
                 // Increment success counter atomically
                 counter.fetch_add(1, Ordering::SeqCst);
             }
-        })
-    }).collect();
-
-    // Wait for all client tasks to complete
-    for handle in handles {
-        handle.await.unwrap();
-    }
+            Ok(())
+        }
+    }).await.expect("Client tasks failed");
 
     // Verify all operations completed successfully
     let final_count = success_count.load(Ordering::SeqCst);
@@ -12,9 +12,33 @@
 //!    - JSON-like content
 //! 4. Large message handling
 //! 5. Performance under various payloads
+//! 6. Chunked streaming of payloads larger than a single message
+//! 7. Server-streaming echo with a repeat count (including zero)
+//! 8. Per-connection sequence numbers stamped by the server
+//! 9. Round-trip latency measurement
+//! 10. Empty messages: rejected by default, echoed with allow_empty_echo
+//! 11. Character limit (echo_max_chars) reported as EchoError::TooLong
+//! 12. Server-side handler timing (server_process_ns)
+//! 13. Basic echo over TLS
+//! 14. Chunked echo ended with ResourceExhausted past max_stream_bytes
+//! 15. Sequential ping probes with statistics (ping_n, behind `grpc_client ping`)
+//! 16. Per-connection request counts in response metadata
+//! 17. Response metadata and latency returned by the _detailed methods (CallResult)
+//! 18. Oversized metadata refused by HTTP/2 (max_header_list_size)
+//! 19. Oversized metadata refused and malformed metadata answered with a status, neither closing the connection
+//! 20. Bytes chunks sharing one 10MB buffer echoed intact
+//! 21. Echo validation: NFC normalization and control character rejection
 
+use embedded_recruitment_task::client::{Bytes, CallResult, EchoError, EchoValidation, PingStats};
+use embedded_recruitment_task::proto::CONNECTION_REQUEST_COUNT_METADATA;
+use embedded_recruitment_task::proto::echo::OFFSET_METADATA;
+use embedded_recruitment_task::GrpcClient;
+use embedded_recruitment_task::proto::calculator::Operation;
+use embedded_recruitment_task::proto::echo::{echo_service_client::EchoServiceClient, EchoRequest, EchoStreamRequest};
+use tonic::Code;
 use tokio::time::{timeout, Duration};
-use common::TestContext;
+use tokio_stream::StreamExt;
+use common::{server_settings, TestContext, SETTINGS_MAX_HEADER_LIST_SIZE};
 
 mod common;
 
@@ -27,6 +51,19 @@ mod common;
 async fn test_echo_simple() {
     // Setup the test context
     let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    check_echo_simple(&ctx).await;
+}
+
+// Basic functionality test over TLS
+// Verifies the same round trip through a TLS handshake
+#[tokio::test]
+async fn test_echo_simple_tls() {
+    let ctx = TestContext::setup_tls().await.expect("Failed to setup TLS test context");
+    check_echo_simple(&ctx).await;
+}
+
+// Body of the basic echo test, shared by the plaintext and TLS variants
+async fn check_echo_simple(ctx: &TestContext) {
     // Define the test message
     let test_msg = "hello";
     // Send the echo request and wait for the response with a timeout
@@ -69,8 +106,8 @@ async fn test_echo_unicode() {
             Duration::from_secs(5),
             ctx.client.echo().echo(msg)
         ).await
-            .expect(&format!("{} timed out", name))
-            .expect(&format!("{} failed", name));
+            .unwrap_or_else(|_| panic!("{} timed out", name))
+            .unwrap_or_else(|e| panic!("{} failed: {:?}", name, e));
         
         // Verify that the response matches the test message
         assert_eq!(response, msg, "{} failed equality check", name);
@@ -105,8 +142,8 @@ async fn test_echo_formatting() {
             Duration::from_secs(5),
             ctx.client.echo().echo(msg)
         ).await
-            .expect(&format!("{} timed out", name))
-            .expect(&format!("{} failed", name));
+            .unwrap_or_else(|_| panic!("{} timed out", name))
+            .unwrap_or_else(|e| panic!("{} failed: {:?}", name, e));
         
         // Verify that the response matches the test message
         assert_eq!(response, msg, "{} failed equality check", name);
@@ -135,3 +172,550 @@ async fn test_echo_long_message() {
     // Verify that the response matches the long test message
     assert_eq!(response, long_msg);
 }
+
+// Chunked streaming test
+// Verifies:
+// - Payloads are split and reassembled without corruption
+// - Chunks come back in the order they were sent
+// - Payloads above the default 4MB message limit work when chunked
+#[tokio::test]
+async fn test_echo_chunked_large_payload() {
+    // Setup the test context
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    // 5MB payload with a repeating byte pattern so misordering is detectable
+    let payload: Vec<u8> = (0..5 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    // Split into 64KB chunks
+    let chunks: Vec<Vec<u8>> = payload.chunks(64 * 1024).map(|c| c.to_vec()).collect();
+
+    // Stream the chunks and reassemble the echoed stream with a timeout
+    let received = timeout(Duration::from_secs(10), async {
+        let mut stream = ctx.client.echo()
+            .echo_chunked(tokio_stream::iter(chunks))
+            .await
+            .expect("Chunked echo failed to start");
+        let mut received = Vec::with_capacity(payload.len());
+        while let Some(chunk) = stream.next().await {
+            received.extend_from_slice(&chunk.expect("Chunk failed"));
+        }
+        received
+    }).await
+        .expect("Test timed out");
+
+    // Verify that the reassembled payload matches the original
+    assert_eq!(received.len(), payload.len());
+    assert_eq!(received, payload);
+}
+
+// Shared chunk integrity test
+// Verifies:
+// - Bytes chunks slicing one 10MB buffer are echoed without corruption
+// - Every echoed chunk has the size it was sent with
+#[tokio::test]
+async fn test_echo_chunked_shared_chunks_intact() {
+    const SIZE: usize = 10 * 1024 * 1024;
+    const CHUNK: usize = 64 * 1024;
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let payload = Bytes::from((0..SIZE).map(|i| (i % 251) as u8).collect::<Vec<u8>>());
+    let chunks: Vec<Bytes> = (0..SIZE).step_by(CHUNK).map(|start| payload.slice(start..start + CHUNK)).collect();
+
+    let received = timeout(Duration::from_secs(20), async {
+        let mut stream = ctx.client.echo()
+            .echo_chunked(tokio_stream::iter(chunks))
+            .await
+            .expect("Chunked echo failed to start");
+        let mut received = Vec::with_capacity(SIZE);
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.expect("Chunk failed");
+            assert_eq!(chunk.len(), CHUNK, "chunk resized in transit");
+            received.extend_from_slice(&chunk);
+        }
+        received
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(received.len(), SIZE);
+    assert!(received == payload, "payload corrupted");
+}
+
+// Stream byte budget test
+// Verifies:
+// - Chunks within max_stream_bytes are echoed
+// - The chunk crossing the budget ends the stream with ResourceExhausted
+#[tokio::test]
+async fn test_echo_chunked_stream_budget() {
+    const BUDGET: usize = 10 * 1024;
+    let ctx = TestContext::setup_with(|builder| builder.max_stream_bytes(BUDGET))
+        .await
+        .expect("Failed to setup test context");
+    // Five times the budget in 1KB chunks
+    let chunks = (0..50).map(|_| vec![7u8; 1024]);
+
+    let (received, error) = timeout(Duration::from_secs(10), async {
+        let mut stream = ctx.client.echo()
+            .echo_chunked(tokio_stream::iter(chunks))
+            .await
+            .expect("Chunked echo failed to start");
+        let mut received = 0;
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(chunk) => received += chunk.len(),
+                Err(status) => return (received, Some(status)),
+            }
+        }
+        (received, None)
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(received, BUDGET, "chunks within the budget must be echoed: {:?}", error);
+    let status = error.expect("stream ended without error past the budget");
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert!(status.message().contains("10240 bytes"), "{}", status.message());
+}
+
+// Streaming echo test
+// Verifies:
+// - The server sends exactly `repeat` responses
+// - Every response carries the original message
+#[tokio::test]
+async fn test_echo_stream_repeats_message() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let responses: Vec<String> = timeout(Duration::from_secs(5), async {
+        let stream = ctx.client.echo()
+            .echo_stream("repeat me", 20)
+            .await
+            .expect("Streaming echo failed to start");
+        stream.map(|item| item.expect("Stream item failed")).collect().await
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(responses.len(), 20);
+    assert!(responses.iter().all(|message| message == "repeat me"));
+}
+
+// Zero repeat test
+// Verifies repeat=0 is not an error: the stream ends at once, empty and OK
+#[tokio::test]
+async fn test_echo_stream_zero_repeat_is_empty() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let items: Vec<_> = timeout(Duration::from_secs(5), async {
+        let stream = ctx.client.echo()
+            .echo_stream("nothing", 0)
+            .await
+            .expect("Streaming echo with repeat=0 failed to start");
+        stream.collect().await
+    }).await
+        .expect("Stream did not complete");
+
+    assert!(items.is_empty(), "expected no items, got {:?}", items);
+}
+
+// Empty message stream test
+// Verifies validation happens before streaming, whatever the repeat count
+#[tokio::test]
+async fn test_echo_stream_empty_message_is_rejected() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    // Raw generated client bypasses the wrapper's client-side validation
+    let mut client = EchoServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    for repeat in [0, 3] {
+        let request = EchoStreamRequest { message: "  ".into(), repeat };
+        let err = timeout(Duration::from_secs(5), client.echo_stream(request))
+            .await
+            .expect("Test timed out")
+            .expect_err("empty message was accepted");
+        assert_eq!(err.code(), Code::InvalidArgument, "repeat={}", repeat);
+    }
+}
+
+// Empty message policy test (default)
+// Verifies both the client wrapper and the server reject empty messages
+#[tokio::test]
+async fn test_echo_empty_message_rejected_by_default() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    // Rejected locally by the wrapper
+    let err = ctx.client.echo().echo("").await.expect_err("empty message was accepted");
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    // A permissive client still gets the server's rejection
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .expect("Invalid address")
+        .allow_empty_echo(true)
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+    for message in ["", "   "] {
+        let err = timeout(Duration::from_secs(5), client.echo().echo(message))
+            .await
+            .expect("Test timed out")
+            .expect_err("empty message was accepted");
+        assert_eq!(err.code(), Code::InvalidArgument, "{:?}", message);
+        assert!(err.message().contains("empty message"));
+    }
+}
+
+// Empty message policy test (permissive)
+// Verifies empty and whitespace-only messages come back unchanged
+#[tokio::test]
+async fn test_echo_empty_message_allowed() {
+    let ctx = TestContext::setup_with(|builder| builder.allow_empty_echo(true))
+        .await
+        .expect("Failed to setup test context");
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .expect("Invalid address")
+        .allow_empty_echo(true)
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+
+    let mut echo = client.echo();
+    for message in ["", "   ", "\t\n"] {
+        let response = timeout(Duration::from_secs(5), echo.echo(message))
+            .await
+            .expect("Test timed out")
+            .expect("Empty echo failed");
+        assert_eq!(response, message);
+    }
+    let response = echo.echo_delayed("", Duration::from_millis(1)).await.expect("Empty delayed echo failed");
+    assert_eq!(response, "");
+}
+
+// Character limit test
+// Verifies:
+// - A message of exactly the limit is accepted
+// - One character over is rejected with the limit and length attached
+// - Multi-byte characters count as one character each
+#[tokio::test]
+async fn test_echo_max_chars() {
+    let ctx = TestContext::setup_with(|builder| builder.echo_max_chars(10))
+        .await
+        .expect("Failed to setup test context");
+    let mut echo = ctx.client.echo();
+
+    let at_limit = "a".repeat(10);
+    let response = timeout(Duration::from_secs(5), echo.echo_checked(at_limit.clone()))
+        .await
+        .expect("Test timed out")
+        .expect("Message at the limit was rejected");
+    assert_eq!(response, at_limit);
+
+    let err = timeout(Duration::from_secs(5), echo.echo_checked("a".repeat(11)))
+        .await
+        .expect("Test timed out")
+        .expect_err("Message over the limit was accepted");
+    assert!(matches!(err, EchoError::TooLong { limit: 10, actual: 11 }), "{:?}", err);
+    assert_eq!(err.to_string(), "message too long: 11/10");
+
+    // 10 characters but 40 bytes
+    let emoji = "🦀".repeat(10);
+    assert_eq!(emoji.len(), 40);
+    let response = echo.echo_checked(emoji.clone()).await.expect("Multi-byte message at the limit was rejected");
+    assert_eq!(response, emoji);
+    let err = echo.echo_checked("é".repeat(12)).await.expect_err("Multi-byte message over the limit was accepted");
+    assert!(matches!(err, EchoError::TooLong { limit: 10, actual: 12 }), "{:?}", err);
+
+    // Other failures stay plain statuses
+    let err = echo.echo_checked("").await.expect_err("empty message was accepted");
+    assert!(matches!(err, EchoError::Status(ref status) if status.code() == Code::InvalidArgument), "{:?}", err);
+}
+
+// Sequence number test
+// Verifies:
+// - Sequential echoes on one channel are numbered 1, 2, 3, ...
+// - The server-wide total grows with every echo
+#[tokio::test]
+async fn test_echo_sequence_increases_per_connection() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let responses = timeout(Duration::from_secs(10), async {
+        let mut echo = ctx.client.echo();
+        let mut responses = Vec::new();
+        for i in 0..100 {
+            responses.push(echo.echo_detailed(format!("ordered {}", i)).await.expect("Echo failed").value);
+        }
+        responses
+    }).await
+        .expect("Test timed out");
+
+    let sequences: Vec<u64> = responses.iter().map(|response| response.sequence).collect();
+    assert_eq!(sequences, (1..=100).collect::<Vec<u64>>());
+    assert!(responses.windows(2).all(|pair| pair[0].server_total_echoes < pair[1].server_total_echoes));
+}
+
+// Independent connection test
+// Verifies two separate channels each get their own sequence starting at 1
+#[tokio::test]
+async fn test_echo_sequence_is_independent_per_channel() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let (first, second) = timeout(Duration::from_secs(5), async {
+        // A second, independent channel to the same server
+        let other = ctx.new_client().await.expect("Failed to connect");
+        let mut first = Vec::new();
+        let mut second = Vec::new();
+        for i in 0..3 {
+            first.push(ctx.client.echo().echo_detailed(format!("a{}", i)).await.expect("Echo failed").value.sequence);
+            second.push(other.echo().echo_detailed(format!("b{}", i)).await.expect("Echo failed").value.sequence);
+        }
+        (first, second)
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(first, vec![1, 2, 3]);
+    assert_eq!(second, vec![1, 2, 3]);
+}
+
+// Connection request count test
+// Verifies:
+// - Sequential echoes on one channel report counts 1..=10
+// - Other RPCs on the connection (Calculate) advance the same count
+// - A second channel starts again at 1
+#[tokio::test]
+async fn test_connection_request_count() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let (first, calculate, second) = timeout(Duration::from_secs(5), async {
+        let mut echo = ctx.client.echo();
+        let mut first = Vec::new();
+        for i in 0..10 {
+            first.push(echo.echo_detailed(format!("count {}", i)).await.expect("Echo failed").connection_request_count());
+        }
+        let calculate = ctx.client.calculator()
+            .calculate_detailed(1.0, 2.0, Operation::Add)
+            .await
+            .expect("Calculate failed");
+
+        let other = ctx.new_client().await.expect("Failed to connect");
+        let second = other.echo().echo_detailed("other").await.expect("Echo failed").connection_request_count();
+        (first, calculate, second)
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(first, (1..=10).map(Some).collect::<Vec<_>>());
+    assert_eq!((calculate.value, calculate.connection_request_count()), (3.0, Some(11)));
+    assert_eq!(second, Some(1));
+}
+
+// Call result test
+// Verifies:
+// - The _detailed methods of both services return latency and metadata
+// - The same CallResult carries echo, decimal (String) and reduce (f64) values
+// - request_id stays None when nothing on the path sets x-request-id
+#[tokio::test]
+async fn test_call_result_metadata_and_latency() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    // Shared checks, independent of the value type
+    fn check<T>(result: &CallResult<T>) {
+        assert!(result.latency > Duration::ZERO);
+        assert!(result.metadata.get(CONNECTION_REQUEST_COUNT_METADATA).is_some());
+        assert!(result.connection_request_count().is_some());
+        assert_eq!(result.request_id, None);
+    }
+
+    timeout(Duration::from_secs(5), async {
+        let echo = ctx.client.echo().echo_detailed("detailed").await.expect("Echo failed");
+        check(&echo);
+        assert_eq!(echo.value.message, "detailed");
+
+        let delayed = ctx.client.echo()
+            .echo_delayed_detailed("later", Duration::from_millis(20))
+            .await
+            .expect("Delayed echo failed");
+        assert!(delayed.latency >= Duration::from_millis(20));
+        assert_eq!(delayed.value, "later");
+
+        let mut calculator = ctx.client.calculator();
+        let calculated = calculator.calculate_detailed(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed");
+        check(&calculated);
+        assert_eq!(calculated.value, 42.0);
+        let decimal = calculator
+            .calculate_decimal_detailed("0.1", "0.2", Operation::Add)
+            .await
+            .expect("Decimal calculate failed");
+        assert_eq!(decimal.value, "0.3");
+        let reduced = calculator
+            .reduce_detailed(&[1.0, 2.0, 3.0, 4.0], Operation::Multiply)
+            .await
+            .expect("Reduce failed");
+        assert_eq!(reduced.value, 24.0);
+        assert!(reduced.latency > Duration::ZERO);
+    }).await
+        .expect("Test timed out");
+}
+
+// Header list size test
+// Verifies:
+// - A request with metadata over max_header_list_size is refused by HTTP/2
+//   with 431 (max_metadata_size is raised so the header list limit is what
+//   rejects it)
+// - The echo handler never ran: the next echo on the connection is sequence 1
+// - Metadata within the limit is accepted
+// - The limit is announced to clients in SETTINGS_MAX_HEADER_LIST_SIZE
+#[tokio::test]
+async fn test_oversized_metadata_rejected() {
+    let ctx = TestContext::setup_with(|builder| builder.max_header_list_size(8 * 1024).max_metadata_size(128 * 1024))
+        .await
+        .expect("Failed to setup test context");
+    // Raw generated client so the metadata can be set directly
+    let mut client = EchoServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    let request = |message: &str, padding: usize| {
+        let mut request = tonic::Request::new(EchoRequest { message: message.into(), delay_ms: 0, ..Default::default() });
+        request.metadata_mut().insert("x-padding", "p".repeat(padding).parse().unwrap());
+        request
+    };
+
+    let (rejected, small) = timeout(Duration::from_secs(5), async {
+        let rejected = client.echo(request("big", 64 * 1024)).await.expect_err("oversized metadata was accepted");
+        let small = client.echo(request("small", 1024)).await.expect("Echo failed").into_inner();
+        (rejected, small)
+    }).await
+        .expect("Test timed out");
+
+    // HTTP/2 answers 431 without a gRPC status, which tonic reports as Unknown
+    assert_eq!(rejected.code(), Code::Unknown, "{:?}", rejected);
+    assert!(rejected.message().contains("431"), "{}", rejected.message());
+    assert_eq!((small.message.as_str(), small.sequence), ("small", 1));
+    let settings = server_settings(&ctx.addr).await;
+    assert_eq!(settings.get(&SETTINGS_MAX_HEADER_LIST_SIZE), Some(&(8 * 1024)), "{:?}", settings);
+}
+
+// Metadata validation test
+// Verifies:
+// - 64 KiB of metadata is refused by HTTP/2 with 431 under the default
+//   8 KiB limit, without closing the connection
+// - A key outside the gRPC metadata alphabet fails with InvalidArgument
+//   (a space cannot be sent at all: HTTP header names reject it client-side,
+//   so `!`, valid in HTTP but not in gRPC, stands in for it)
+// - Both are ordinary statuses: the same connection serves the next call
+#[tokio::test]
+async fn test_malformed_metadata_rejected_with_status() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    // Raw generated client so the metadata can be set directly
+    let mut client = EchoServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    let echo = |message: &str| tonic::Request::new(EchoRequest { message: message.into(), delay_ms: 0, ..Default::default() });
+
+    let mut oversized = echo("big");
+    oversized.metadata_mut().insert("x-padding", "p".repeat(64 * 1024).parse().unwrap());
+    let mut bad_key = echo("bad key");
+    let key = tonic::metadata::AsciiMetadataKey::from_bytes(b"x-bad!key").expect("HTTP rejected the key");
+    bad_key.metadata_mut().insert(key, "value".parse().unwrap());
+
+    let (oversized, bad_key, next) = timeout(Duration::from_secs(5), async {
+        let oversized = client.echo(oversized).await.expect_err("oversized metadata was accepted");
+        let bad_key = client.echo(bad_key).await.expect_err("malformed key was accepted");
+        let next = client.echo(echo("next")).await.expect("connection unusable after a rejection").into_inner();
+        (oversized, bad_key, next)
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(oversized.code(), Code::Unknown, "{:?}", oversized);
+    assert!(oversized.message().contains("431"), "{}", oversized.message());
+    assert_eq!(bad_key.code(), Code::InvalidArgument, "{:?}", bad_key);
+    assert!(bad_key.message().contains("x-bad!key"), "{}", bad_key.message());
+    assert_eq!((next.message.as_str(), next.sequence), ("next", 1), "rejected calls reached the handler");
+}
+
+// Round-trip test
+// Verifies the measured latency is positive and plausible for a local server
+#[tokio::test]
+async fn test_round_trip_latency() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let latency = timeout(Duration::from_secs(5), ctx.client.round_trip())
+        .await
+        .expect("Test timed out")
+        .expect("Round trip failed");
+
+    assert!(latency > Duration::ZERO);
+    assert!(latency < Duration::from_secs(2), "implausible local latency {:?}", latency);
+}
+
+// Ping test
+// Verifies:
+// - ping_n sends exactly `count` probes, reporting each one in order
+// - A healthy local server loses none and the statistics are consistent
+#[tokio::test]
+async fn test_ping_n_without_loss() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut echo = ctx.client.echo();
+    let mut stats = PingStats::default();
+    let mut probes = Vec::new();
+
+    timeout(
+        Duration::from_secs(5),
+        echo.ping_n(5, Duration::from_millis(10), &mut stats, |probe, result| {
+            assert!(result.is_ok(), "probe {} failed: {:?}", probe, result);
+            probes.push(probe);
+        }),
+    )
+    .await
+    .expect("Test timed out");
+
+    assert_eq!(probes, vec![1, 2, 3, 4, 5]);
+    assert_eq!((stats.sent(), stats.received(), stats.lost()), (5, 5, 0));
+    assert_eq!(stats.loss_percent(), 0.0);
+    assert!(Duration::ZERO < stats.min() && stats.min() <= stats.avg(), "{}", stats);
+    assert!(stats.avg() <= stats.max() && stats.p95() <= stats.max(), "{}", stats);
+}
+
+// Handler timing test
+// Verifies a large payload reports a non-zero server processing time that
+// fits within the observed round trip
+#[tokio::test]
+async fn test_echo_with_timing() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut echo = ctx.client.echo();
+    let message = "x".repeat(1024 * 1024);
+
+    let started = std::time::Instant::now();
+    let (echoed, processing) = timeout(Duration::from_secs(10), echo.echo_with_timing(message.clone()))
+        .await
+        .expect("Test timed out")
+        .expect("Echo failed");
+    let round_trip = started.elapsed();
+
+    assert_eq!(echoed, message);
+    assert!(processing > Duration::ZERO);
+    assert!(processing <= round_trip, "handler {:?} exceeds round trip {:?}", processing, round_trip);
+}
+
+// Echo validation test
+// Verifies:
+// - NORMALIZE_NFC turns a decomposed "é" (e + combining acute) into the precomposed form
+// - A plain echo sends NONE and returns the decomposed form unchanged
+// - REJECT_CONTROL rejects U+0007 with its byte offset, in the message and in metadata
+// - Tab, CR and LF pass REJECT_CONTROL
+#[tokio::test]
+async fn test_echo_validation() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut echo = ctx.client.echo();
+    let decomposed = "caf\u{65}\u{301}";
+
+    timeout(Duration::from_secs(5), async {
+        let normalized = echo.echo_validated(decomposed, EchoValidation::NormalizeNfc).await.expect("NFC echo failed");
+        assert_eq!(normalized, "caf\u{e9}");
+        assert_eq!(normalized.len(), decomposed.len() - 1);
+        assert_eq!(echo.echo(decomposed).await.expect("Plain echo failed"), decomposed);
+
+        // "é" is two bytes, so the bell after "é " starts at byte 3
+        let err = echo.echo_validated("\u{e9} \u{7}bell", EchoValidation::RejectControl).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("U+0007 at byte offset 3"), "unexpected message: {}", err.message());
+        assert_eq!(err.metadata().get(OFFSET_METADATA).expect("offset missing"), "3");
+
+        let both = EchoValidation::RejectControlAndNormalizeNfc;
+        let text = "line\ttab\r\ne\u{301}";
+        assert_eq!(echo.echo_validated(text, both).await.expect("Validated echo failed"), "line\ttab\r\n\u{e9}");
+    })
+    .await
+    .expect("Test timed out");
+}
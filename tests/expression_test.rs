@@ -0,0 +1,253 @@
+//! Expression Evaluation Tests
+//! This test suite verifies:
+//! 1. Precedence and parentheses through the server's Evaluate RPC
+//! 2. Step-by-step evaluation as Calculate calls
+//! 3. Automatic fallback when the server has no Evaluate RPC
+//! 4. Division by zero in the middle of an expression names the sub-expression
+//! 5. Server and step-by-step evaluation agree
+//! 6. The server abandons a long evaluation once the client's deadline passes
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use embedded_recruitment_task::proto::calculator::calculator_service_server::{
+    CalculatorService, CalculatorServiceServer,
+};
+use embedded_recruitment_task::proto::calculator::*;
+use embedded_recruitment_task::server::CalculatorServer;
+use embedded_recruitment_task::GrpcClient;
+use tokio::time::{Duration, Instant};
+use tokio_stream::Stream;
+use tonic::{Code, Request, Response, Status, Streaming};
+use common::{next_port, TestContext};
+
+mod common;
+
+// Expressions with their expected values
+const CASES: &[(&str, f64)] = &[
+    ("2 + 3 * 4", 14.0),
+    ("(2 + 3) * 4", 20.0),
+    ("10 - 4 - 3", 3.0),
+    ("64 / 4 / 2", 8.0),
+    ("-(1.5 + 0.5) * (3 - 5)", 4.0),
+    ("0.1 + 0.2 * 3", 0.1 + 0.2 * 3.0),
+    ("42", 42.0),
+];
+
+// A server from before the Evaluate RPC: only Calculate is implemented
+#[derive(Default, Clone)]
+struct LegacyCalculator {
+    calculate_calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl CalculatorService for LegacyCalculator {
+    async fn calculate(&self, request: Request<CalculateRequest>) -> Result<Response<CalculateResponse>, Status> {
+        self.calculate_calls.fetch_add(1, Ordering::SeqCst);
+        let req = request.into_inner();
+        let (first, second) = (req.first_number, req.second_number);
+        let result = match req.operation() {
+            Operation::Add => first + second,
+            Operation::Subtract => first - second,
+            Operation::Multiply => first * second,
+            Operation::Divide => first / second,
+            Operation::Percent => first * (second / 100.0),
+        };
+        Ok(Response::new(CalculateResponse { result, inverse: None }))
+    }
+
+    async fn aggregate(&self, _: Request<AggregateRequest>) -> Result<Response<AggregateResponse>, Status> {
+        Err(Status::unimplemented("aggregate"))
+    }
+
+    type AggregateStreamStream = Pin<Box<dyn Stream<Item = Result<AggregateProgress, Status>> + Send>>;
+
+    async fn aggregate_stream(
+        &self,
+        _: Request<Streaming<AggregateValue>>,
+    ) -> Result<Response<Self::AggregateStreamStream>, Status> {
+        Err(Status::unimplemented("aggregate_stream"))
+    }
+
+    type RunningTotalStream = Pin<Box<dyn Stream<Item = Result<RunningTotalResponse, Status>> + Send>>;
+
+    async fn running_total(
+        &self,
+        _: Request<Streaming<RunningTotalStep>>,
+    ) -> Result<Response<Self::RunningTotalStream>, Status> {
+        Err(Status::unimplemented("running_total"))
+    }
+
+    async fn calculate_decimal(
+        &self,
+        _: Request<CalculateDecimalRequest>,
+    ) -> Result<Response<CalculateDecimalResponse>, Status> {
+        Err(Status::unimplemented("calculate_decimal"))
+    }
+
+    async fn evaluate(&self, _: Request<EvaluateRequest>) -> Result<Response<EvaluateResponse>, Status> {
+        Err(Status::unimplemented("evaluate"))
+    }
+
+    async fn reduce(&self, _: Request<ReduceRequest>) -> Result<Response<ReduceResponse>, Status> {
+        Err(Status::unimplemented("reduce"))
+    }
+
+    async fn get_history(&self, _: Request<GetHistoryRequest>) -> Result<Response<GetHistoryResponse>, Status> {
+        Err(Status::unimplemented("get_history"))
+    }
+}
+
+// Start a legacy server and connect a client to it
+async fn legacy_server(calculator: LegacyCalculator) -> GrpcClient {
+    let addr: SocketAddr = format!("[::1]:{}", next_port()).parse().unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(CalculatorServiceServer::new(calculator))
+            .serve(addr),
+    );
+    GrpcClient::builder(format!("http://{}", addr))
+        .unwrap()
+        .connect_retries(50, Duration::from_millis(20))
+        .connect_eager()
+        .await
+        .expect("Failed to connect to legacy server")
+}
+
+// The server's Evaluate RPC follows precedence and parentheses
+#[tokio::test]
+async fn test_evaluate_on_server() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    for &(expression, expected) in CASES {
+        let evaluation = calculator.evaluate(expression).await.expect(expression);
+        assert!(evaluation.on_server, "{} was not evaluated by the server", expression);
+        assert!(evaluation.steps.is_empty());
+        assert_eq!(evaluation.result, expected, "{}", expression);
+    }
+}
+
+// Step by step evaluation issues one Calculate per operation, in precedence order
+#[tokio::test]
+async fn test_evaluate_locally_steps() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let evaluation = calculator.evaluate_locally("2 + 3 * (4 - 1)").await.unwrap();
+    assert!(!evaluation.on_server);
+    assert_eq!(evaluation.result, 11.0);
+    let steps: Vec<_> = evaluation
+        .steps
+        .iter()
+        .map(|step| (step.first, step.second, step.operation, step.result))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            (4.0, 1.0, Operation::Subtract, 3.0),
+            (3.0, 3.0, Operation::Multiply, 9.0),
+            (2.0, 9.0, Operation::Add, 11.0),
+        ]
+    );
+}
+
+// Without an Evaluate RPC the client falls back to Calculate calls and
+// remembers it, so later expressions do not probe again
+#[tokio::test]
+async fn test_fallback_without_evaluate_rpc() {
+    let legacy = LegacyCalculator::default();
+    let client = legacy_server(legacy.clone()).await;
+    let mut calculator = client.calculator();
+
+    let evaluation = calculator.evaluate("2 + 3 * 4").await.unwrap();
+    assert!(!evaluation.on_server);
+    assert_eq!(evaluation.result, 14.0);
+    assert_eq!(evaluation.steps.len(), 2);
+    assert_eq!(legacy.calculate_calls.load(Ordering::SeqCst), 2);
+
+    let evaluation = calculator.evaluate("(2 + 3) * 4").await.unwrap();
+    assert_eq!(evaluation.result, 20.0);
+    assert_eq!(legacy.calculate_calls.load(Ordering::SeqCst), 4);
+}
+
+// Division by zero mid-expression names the failing sub-expression on both paths
+#[tokio::test]
+async fn test_division_by_zero_names_subexpression() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let legacy = legacy_server(LegacyCalculator::default()).await;
+    let expression = "1 + 6 / (4 - 4) * 2";
+
+    let on_server = ctx.client.calculator().evaluate(expression).await.unwrap_err();
+    let local = ctx.client.calculator().evaluate_locally(expression).await.unwrap_err();
+    let fallback = legacy.calculator().evaluate(expression).await.unwrap_err();
+    for err in [&on_server, &local, &fallback] {
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains("division by zero"), "{}", err.message());
+        assert!(err.message().contains("`6 / (4 - 4)`"), "{}", err.message());
+    }
+}
+
+// Syntax errors are rejected before any call is made
+#[tokio::test]
+async fn test_syntax_error() {
+    let legacy = LegacyCalculator::default();
+    let client = legacy_server(legacy.clone()).await;
+
+    let err = client.calculator().evaluate("2 * (3 + ").await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("position"), "{}", err.message());
+    assert_eq!(legacy.calculate_calls.load(Ordering::SeqCst), 0);
+}
+
+// Server and step by step evaluation perform the same operations in the
+// same order, so their results are bit-for-bit equal
+#[tokio::test]
+async fn test_local_and_server_evaluation_agree() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let mut calculator = ctx.client.calculator();
+
+    let expressions = CASES
+        .iter()
+        .map(|&(expression, _)| expression)
+        .chain(["1 / 3 + 1 / 7 * 0.1", "1e-10 * 3 - 2.5e-11 / 7", "-(-(2 - 0.3)) / 0.7"]);
+    for expression in expressions {
+        let on_server = calculator.evaluate(expression).await.expect(expression);
+        let local = calculator.evaluate_locally(expression).await.expect(expression);
+        assert!(on_server.on_server && !local.on_server);
+        assert_eq!(on_server.result.to_bits(), local.result.to_bits(), "{}", expression);
+    }
+}
+
+// Balanced sum of 2^depth ones, e.g. depth 2 is "((1+1)+(1+1))"
+// Stays within the parser's nesting limit while planning 2^depth - 1 steps
+fn balanced_sum(depth: u32) -> String {
+    match depth {
+        0 => "1".to_string(),
+        _ => {
+            let half = balanced_sum(depth - 1);
+            format!("({}+{})", half, half)
+        }
+    }
+}
+
+// Evaluate deadline test
+// Verifies:
+// - A pathologically long expression under a 50 ms deadline ends in DeadlineExceeded
+// - The server gives up well under a second instead of finishing the work
+// The handler is called directly: over the wire, the tonic client enforces
+// the same deadline itself and reports Cancelled before the server answers
+#[tokio::test]
+async fn test_evaluate_gives_up_at_deadline() {
+    let calculator = CalculatorServer::default();
+    let mut request = Request::new(EvaluateRequest { expression: balanced_sum(16) });
+    request.set_timeout(Duration::from_millis(50));
+
+    let started = Instant::now();
+    let err = calculator.evaluate(request).await.expect_err("evaluation outlived its deadline");
+    let elapsed = started.elapsed();
+
+    assert_eq!(err.code(), Code::DeadlineExceeded, "{}", err.message());
+    assert!(elapsed < Duration::from_secs(1), "server gave up only after {:?}", elapsed);
+}
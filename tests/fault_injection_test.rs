@@ -0,0 +1,191 @@
+//! Fault Injection Tests
+//! This suite verifies the `fault_injection` server option:
+//! 1. With a 100% error probability every application call fails with Unavailable
+//! 2. With 0% every call succeeds
+//! 3. Admin RPCs are never faulted
+//! 4. The same seed produces the same faults for the same sequence of calls
+//! 5. Injected latency delays the response
+//! 6. Probabilities outside 0..=1 are rejected at build()
+//! 7. Client stream_retries re-establish a stream whose first attempts are faulted
+//! 8. A mid-stream error is returned to the caller, not retried away
+
+use embedded_recruitment_task::server::{FaultConfig, StreamOverflowPolicy};
+use embedded_recruitment_task::proto::calculator::Operation;
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use tokio::time::{timeout, Duration, Instant};
+use tokio_stream::StreamExt;
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+// Test configuration
+const CALLS: usize = 20;  // Calls made per scenario
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Start a server injecting Unavailable with `probability`
+async fn setup(probability: f64, seed: u64) -> TestContext {
+    let config = FaultConfig { error_probability: probability, seed: Some(seed), ..Default::default() };
+    TestContext::setup_with(move |builder| builder.fault_injection(config))
+        .await
+        .expect("Failed to setup test context")
+}
+
+// Outcome code of each of CALLS sequential echoes
+async fn echo_codes(ctx: &TestContext) -> Vec<Code> {
+    let mut echo = ctx.client.echo();
+    let mut codes = Vec::with_capacity(CALLS);
+    for i in 0..CALLS {
+        let result = echo.echo(format!("call {}", i)).await;
+        codes.push(result.map_or_else(|status| status.code(), |_| Code::Ok));
+    }
+    codes
+}
+
+// Full fault test
+// Verifies:
+// - Every echo and calculate fails with Unavailable
+// - The admin service still answers
+#[tokio::test]
+async fn test_full_fault_probability_fails_every_call() {
+    let ctx = setup(1.0, 1).await;
+
+    timeout(TIMEOUT_DURATION, async {
+        assert!(echo_codes(&ctx).await.iter().all(|code| *code == Code::Unavailable));
+        let err = ctx.client.calculator()
+            .calculate(1.0, 2.0, Operation::Add)
+            .await
+            .expect_err("calculate was not faulted");
+        assert_eq!(err.code(), Code::Unavailable);
+
+        ctx.client.admin().server_info().await.expect("Admin RPC was faulted");
+    }).await
+        .expect("Test timed out");
+}
+
+// No fault test
+// Verifies every call succeeds with a 0% probability
+#[tokio::test]
+async fn test_zero_fault_probability_passes_every_call() {
+    let ctx = setup(0.0, 1).await;
+
+    let codes = timeout(TIMEOUT_DURATION, echo_codes(&ctx)).await.expect("Test timed out");
+    assert!(codes.iter().all(|code| *code == Code::Ok), "{:?}", codes);
+}
+
+// Reproducibility test
+// Verifies two servers with the same seed fault the same calls, and that
+// a 50% probability fails some calls but not all
+#[tokio::test]
+async fn test_seeded_faults_are_reproducible() {
+    let (first, second) = timeout(TIMEOUT_DURATION, async {
+        let first = echo_codes(&setup(0.5, 42).await).await;
+        let second = echo_codes(&setup(0.5, 42).await).await;
+        (first, second)
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(first, second);
+    assert!(first.contains(&Code::Ok) && first.contains(&Code::Unavailable), "{:?}", first);
+}
+
+// Injected latency test
+// Verifies a 100% latency probability delays each call by the configured latency
+#[tokio::test]
+async fn test_injected_latency() {
+    const LATENCY: Duration = Duration::from_millis(100);
+    let config = FaultConfig { latency_probability: 1.0, latency: LATENCY, seed: Some(1), ..Default::default() };
+    let ctx = TestContext::setup_with(move |builder| builder.fault_injection(config))
+        .await
+        .expect("Failed to setup test context");
+
+    let started = Instant::now();
+    timeout(TIMEOUT_DURATION, ctx.client.echo().echo("slow"))
+        .await
+        .expect("Test timed out")
+        .expect("Delayed echo failed");
+    assert!(started.elapsed() >= LATENCY, "took {:?}", started.elapsed());
+}
+
+// Stream establishment retry test
+// Verifies:
+// - Seed 6 at 50% faults exactly the first two requests
+// - Without stream_retries the faulted establishment fails with Unavailable
+// - With two retries the third attempt yields the complete stream
+#[tokio::test]
+async fn test_stream_establishment_retried() {
+    const REPEAT: u32 = 3;
+    timeout(TIMEOUT_DURATION, async {
+        let ctx = setup(0.5, 6).await;
+        let err = match ctx.client.echo().echo_stream("no retry", REPEAT).await {
+            Ok(_) => panic!("faulted stream was established"),
+            Err(err) => err,
+        };
+        assert_eq!(err.code(), Code::Unavailable);
+
+        let ctx = setup(0.5, 6).await;
+        let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+            .expect("Failed to create builder")
+            .stream_retries(2, Duration::from_millis(10))
+            .connect()
+            .expect("Failed to connect");
+        let stream = client.echo().echo_stream("retried", REPEAT).await.expect("Retries did not establish the stream");
+        let messages: Vec<String> = stream.map(|item| item.expect("Stream failed")).collect().await;
+        assert_eq!(messages, vec!["retried"; REPEAT as usize]);
+    }).await
+        .expect("Test timed out");
+}
+
+// Mid-stream error test
+// Verifies an overflow error raised after messages were delivered reaches
+// the caller even with stream_retries set, and the stream ends there
+#[tokio::test]
+async fn test_mid_stream_error_is_surfaced() {
+    const REPEAT: u32 = 200_000;
+    let ctx = TestContext::setup_with(|builder| {
+        builder.stream_buffer_size(1).stream_overflow_policy(StreamOverflowPolicy::Error)
+    })
+        .await
+        .expect("Failed to setup test context");
+    let client = GrpcClient::builder(format!("http://{}", ctx.addr))
+        .expect("Failed to create builder")
+        .stream_retries(2, Duration::from_millis(10))
+        .connect()
+        .expect("Failed to connect");
+
+    let (received, err) = timeout(TIMEOUT_DURATION, async {
+        let mut stream = client.echo().echo_stream("slow reader", REPEAT).await.expect("Failed to start stream");
+        stream.next().await.expect("Stream ended early").expect("First echo failed");
+        // Stop reading until the server's buffers are full
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let mut received = 1;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(_) => received += 1,
+                Err(err) => {
+                    assert!(stream.next().await.is_none(), "stream continued after the error");
+                    return (received, err);
+                }
+            }
+        }
+        panic!("stream completed without the overflow error");
+    }).await
+        .expect("Test timed out");
+
+    assert_eq!(err.code(), Code::ResourceExhausted);
+    assert!(received < REPEAT, "received all {} echoes", received);
+}
+
+// Validation test
+// Verifies build() rejects probabilities outside 0..=1
+#[test]
+fn test_build_rejects_invalid_probability() {
+    let config = FaultConfig { error_probability: 1.5, ..Default::default() };
+    let err = GrpcServer::builder()
+        .address("127.0.0.1:0")
+        .fault_injection(config)
+        .build()
+        .expect_err("invalid probability was accepted");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("error_probability"), "{}", err.message());
+}
@@ -0,0 +1,76 @@
+//! Service Fuzz Tests
+//! Property-based tests calling the handlers directly with arbitrary input:
+//! 1. Calculate never panics; it returns a finite result or a proper Status
+//! 2. Successful calculations match local f64 arithmetic bit for bit
+//! 3. Reduce never panics; it returns a finite result or a proper Status
+//! 4. Echo never panics; it returns the message unchanged or rejects an
+//!    empty one with InvalidArgument
+
+use embedded_recruitment_task::proto::calculator::{
+    calculator_service_server::CalculatorService, CalculateRequest, Operation, ReduceRequest,
+};
+use embedded_recruitment_task::proto::echo::{echo_service_server::EchoService, EchoRequest};
+use embedded_recruitment_task::server::{CalculatorServer, EchoServer};
+use proptest::prelude::*;
+use tonic::{Code, Request};
+use common::arbitrary::{any_f64, any_message, any_operation, check_finite_or_status, check_proper_status, runtime};
+
+mod common;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    // Calculate invariant
+    // Any operand pair and operation value yields a finite result or a
+    // proper Status; successes agree with plain f64 arithmetic
+    #[test]
+    fn test_calculate_is_finite_or_status(first in any_f64(), second in any_f64(), operation in any_operation()) {
+        let service = CalculatorServer::default();
+        let result = runtime()
+            .block_on(service.calculate(Request::new(CalculateRequest { first_number: first, second_number: second, operation, ..Default::default() })))
+            .map(|response| response.into_inner().result);
+        check_finite_or_status(&result)?;
+
+        if let Ok(value) = result {
+            let expected = match Operation::try_from(operation) {
+                Ok(Operation::Add) => first + second,
+                Ok(Operation::Subtract) => first - second,
+                Ok(Operation::Multiply) => first * second,
+                Ok(Operation::Divide) => first / second,
+                Ok(Operation::Percent) => first * (second / 100.0),
+                Err(_) => return Err(TestCaseError::fail(format!("unknown operation {} succeeded", operation))),
+            };
+            prop_assert_eq!(value.to_bits(), expected.to_bits());
+        }
+    }
+
+    // Reduce invariant
+    // Any list of values and operation value yields a finite result or a
+    // proper Status, even when a NaN or infinity hides behind finite values
+    #[test]
+    fn test_reduce_is_finite_or_status(values in proptest::collection::vec(any_f64(), 1..8), operation in any_operation()) {
+        let service = CalculatorServer::default();
+        let result = runtime()
+            .block_on(service.reduce(Request::new(ReduceRequest { values, operation })))
+            .map(|response| response.into_inner().result);
+        check_finite_or_status(&result)?;
+    }
+
+    // Echo invariant
+    // Any string comes back unchanged, unless it is blank and rejected
+    #[test]
+    fn test_echo_round_trips_or_rejects_blank(message in any_message()) {
+        let service = EchoServer::default();
+        let result = runtime()
+            .block_on(service.echo(Request::new(EchoRequest { message: message.clone(), delay_ms: 0, ..Default::default() })))
+            .map(|response| response.into_inner().message);
+        match result {
+            Ok(echoed) => prop_assert_eq!(echoed, message),
+            Err(status) => {
+                check_proper_status(&status)?;
+                prop_assert_eq!(status.code(), Code::InvalidArgument);
+                prop_assert!(message.trim().is_empty(), "non-blank {:?} rejected: {}", message, status.message());
+            }
+        }
+    }
+}
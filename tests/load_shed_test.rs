@@ -0,0 +1,184 @@
+//! Load Shedding Tests
+//! This suite verifies the `max_queue_depth` server option:
+//! 1. Requests beyond the depth are shed with ResourceExhausted, not queued
+//! 2. Requests within the depth complete normally
+//! 3. Shed and in-flight counts are reported in the server metrics
+//! 4. The server stays responsive once the flood is over
+//! 5. The in-flight gauges, server-wide and per service, follow held requests
+//! 6. The overload callback fires once for a burst above its high-water mark
+//! 7. The depth is server-wide, not per listener (reuse_port_listeners)
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{timeout, Duration};
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+// Test configuration
+const QUEUE_DEPTH: usize = 4;          // Requests the server may hold at once
+const FLOOD_SIZE: usize = 20;          // Requests fired at once, well beyond the depth
+const HOLD_DELAY: Duration = Duration::from_millis(500);  // Keeps requests in flight
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+const HELD_REQUESTS: usize = 6;        // Delayed echoes held in flight at once
+const HIGH_WATER: usize = 4;           // Overload callback mark, below the burst size
+
+#[tokio::test]
+async fn test_flood_beyond_queue_depth_is_shed() {
+    let ctx = TestContext::setup_with(|builder| builder.max_queue_depth(QUEUE_DEPTH))
+        .await
+        .expect("Failed to setup test context");
+
+    // Flood the server with delayed echoes; each either completes or is shed
+    let completed = Arc::new(AtomicUsize::new(0));
+    let shed = Arc::new(AtomicUsize::new(0));
+    ctx.run_concurrent_within(TIMEOUT_DURATION, FLOOD_SIZE, |i, client| {
+        let (completed, shed) = (completed.clone(), shed.clone());
+        async move {
+            match client.echo().echo_delayed(format!("flood_{}", i), HOLD_DELAY).await {
+                Ok(_) => completed.fetch_add(1, Ordering::SeqCst),
+                Err(status) if status.code() == Code::ResourceExhausted => shed.fetch_add(1, Ordering::SeqCst),
+                Err(status) => return Err(status.into()),
+            };
+            Ok(())
+        }
+    }).await.expect("Flood tasks failed");
+
+    // Some requests were served, the excess was shed, and none waited in a queue
+    let (completed, shed) = (completed.load(Ordering::SeqCst), shed.load(Ordering::SeqCst));
+    assert!(completed >= 1, "no request was served");
+    assert!(completed <= QUEUE_DEPTH, "{} requests held at once, depth is {}", completed, QUEUE_DEPTH);
+    assert_eq!(completed + shed, FLOOD_SIZE);
+
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!(snapshot.shed_requests, shed as u64);
+    assert_eq!(snapshot.in_flight_requests, 0);
+
+    // The server still answers once the load is gone
+    let reply = timeout(TIMEOUT_DURATION, ctx.client.echo().echo("after the flood"))
+        .await
+        .expect("Timeout")
+        .expect("Echo after flood failed");
+    assert_eq!(reply, "after the flood");
+}
+
+// Multi-listener depth test
+// Verifies:
+// - With two SO_REUSEPORT listeners, a flood over connections spread across
+//   both is still held to one depth, not one per listener
+#[cfg(unix)]
+#[tokio::test]
+async fn test_queue_depth_shared_by_listeners() {
+    let ctx = TestContext::setup_with(|builder| builder.reuse_port_listeners(2).max_queue_depth(QUEUE_DEPTH))
+        .await
+        .expect("Failed to setup test context");
+
+    // One connection per request; the kernel spreads them over the listeners
+    let mut clients = Vec::new();
+    for _ in 0..FLOOD_SIZE {
+        let client = ctx.new_client().await.expect("Failed to connect");
+        timeout(TIMEOUT_DURATION, client.echo().echo("connect")).await
+            .expect("Echo timed out")
+            .expect("Echo failed");
+        clients.push(client);
+    }
+    let accepted = ctx.metrics.snapshot().listener_connections;
+    assert!(accepted.iter().all(|&count| count > 0), "connections not distributed: {:?}", accepted);
+
+    let flood = clients.into_iter().enumerate().map(|(i, client)| {
+        tokio::spawn(async move { client.echo().echo_delayed(format!("flood_{}", i), HOLD_DELAY).await })
+    });
+    let mut completed = 0;
+    for echo in flood.collect::<Vec<_>>() {
+        match timeout(TIMEOUT_DURATION, echo).await.expect("Flood timed out").expect("Echo task panicked") {
+            Ok(_) => completed += 1,
+            Err(status) => assert_eq!(status.code(), Code::ResourceExhausted, "{:?}", status),
+        }
+    }
+    assert!(completed >= 1, "no request was served");
+    assert!(completed <= QUEUE_DEPTH, "{} requests held at once over two listeners, depth is {}", completed, QUEUE_DEPTH);
+    assert_eq!(ctx.metrics.snapshot().shed_requests, (FLOOD_SIZE - completed) as u64);
+}
+
+#[tokio::test]
+async fn test_requests_within_queue_depth_are_not_shed() {
+    let ctx = TestContext::setup_with(|builder| builder.max_queue_depth(QUEUE_DEPTH))
+        .await
+        .expect("Failed to setup test context");
+
+    // Sequential requests hold one slot at a time and never saturate the server
+    for i in 0..QUEUE_DEPTH * 3 {
+        timeout(TIMEOUT_DURATION, ctx.client.echo().echo(format!("calm_{}", i)))
+            .await
+            .expect("Timeout")
+            .expect("Echo failed");
+    }
+    assert_eq!(ctx.metrics.snapshot().shed_requests, 0);
+}
+
+// In-flight gauge test
+// Verifies:
+// - While delayed echoes are held, both gauges read their number
+// - Other services stay at zero
+// - Both gauges return to zero once the echoes are answered
+// - The per-service gauge is part of the Prometheus output
+#[tokio::test]
+async fn test_in_flight_gauges_follow_held_requests() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+
+    let held: Vec<_> = (0..HELD_REQUESTS)
+        .map(|i| {
+            let client = ctx.client.clone();
+            tokio::spawn(async move { client.echo().echo_delayed(format!("held_{}", i), HOLD_DELAY).await })
+        })
+        .collect();
+
+    // Wait for every echo to arrive; they stay in flight for HOLD_DELAY
+    let snapshot = timeout(TIMEOUT_DURATION, async {
+        loop {
+            let snapshot = ctx.metrics.snapshot();
+            if snapshot.in_flight_requests == HELD_REQUESTS as u64 {
+                break snapshot;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Echoes never all in flight");
+    assert_eq!(snapshot.in_flight_by_service["echo.EchoService"], HELD_REQUESTS as u64);
+    assert_eq!(snapshot.in_flight_by_service["calculator.CalculatorService"], 0);
+    let expected = format!("grpc_server_service_in_flight_requests{{service=\"echo.EchoService\"}} {}\n", HELD_REQUESTS);
+    assert!(snapshot.to_prometheus().contains(&expected), "{}", snapshot.to_prometheus());
+
+    for echo in held {
+        echo.await.expect("Echo task panicked").expect("Echo failed");
+    }
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!(snapshot.in_flight_requests, 0);
+    assert!(snapshot.in_flight_by_service.values().all(|&count| count == 0), "{:?}", snapshot.in_flight_by_service);
+}
+
+// Overload callback test
+// Verifies a sustained burst above the high-water mark runs the callback
+// exactly once, with the in-flight count at the mark
+#[tokio::test]
+async fn test_overload_callback_fires_once_per_burst() {
+    let fired = Arc::new(Mutex::new(Vec::new()));
+    let calls = fired.clone();
+    let ctx = TestContext::setup_with(|builder| {
+        builder.overload_callback(HIGH_WATER, move |in_flight| calls.lock().unwrap().push(in_flight))
+    })
+    .await
+    .expect("Failed to setup test context");
+
+    ctx.run_concurrent_within(TIMEOUT_DURATION, HELD_REQUESTS * 2, |i, client| async move {
+        client.echo().echo_delayed(format!("burst_{}", i), HOLD_DELAY).await?;
+        Ok(())
+    }).await.expect("Burst tasks failed");
+
+    // The callback runs on the blocking pool; give it a moment to finish
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(*fired.lock().unwrap(), [HIGH_WATER as u64]);
+    assert_eq!(ctx.metrics.snapshot().in_flight_requests, 0);
+}
@@ -4,20 +4,43 @@
 //!    - Tests how system handles burst traffic
 //!    - Verifies connection pooling effectiveness
 //!    - Ensures no request failures under load
+//!    - Fails on gross latency regressions (e.g. requests being serialized)
 //!
-//! 2. Large payload handling
+//! 2. Concurrent TLS handshakes
+//!    - Many clients connect to a TLS server at once
+//!    - Every handshake completes and every client can call
+//!
+//! 3. Echo benchmark (run_bench, behind `grpc_client --bench`)
+//!    - Makes exactly the requested number of calls
+//!    - Reports consistent counts and percentiles
+//!    - Runs against the in-process BenchHarness used by `cargo bench`
+//!
+//! 4. Large payload handling
 //!    - Tests memory management
 //!    - Verifies buffer handling
 //!    - Ensures consistent performance with large data
 
+use embedded_recruitment_task::bench_util::BenchHarness;
+use embedded_recruitment_task::client::run_bench;
 use embedded_recruitment_task::proto::calculator::Operation;
 use tokio::time::{timeout, Duration};
-use common::TestContext;
+use common::{LatencyRecorder, TestContext};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 mod common;
 
+// Rapid-fire p99 must stay under this; generous so only gross regressions fail
+// Override with LOAD_TEST_P99_LIMIT_MS on slow CI machines
+const DEFAULT_P99_LIMIT: Duration = Duration::from_millis(500);
+
+fn p99_limit() -> Duration {
+    match std::env::var("LOAD_TEST_P99_LIMIT_MS") {
+        Ok(ms) => Duration::from_millis(ms.parse().expect("LOAD_TEST_P99_LIMIT_MS must be whole milliseconds")),
+        Err(_) => DEFAULT_P99_LIMIT,
+    }
+}
+
 // Test rapid-fire mixed service requests
 // Purpose:
 // - Verify system handles mixed workloads
@@ -28,40 +51,108 @@ async fn test_rapid_fire_requests() {
     let ctx = TestContext::setup().await.expect("Failed to setup test context");
     // Atomic counter for thread-safe success tracking
     let success_count = Arc::new(AtomicUsize::new(0));
+    let latencies = LatencyRecorder::new();
     let total_requests = 100;
     
-    // Create concurrent request tasks
-    let handles: Vec<_> = (0..total_requests).map(|i| {
-        let client = ctx.client.clone();  // Share client connection
+    // Alternate between echo and calculator services on the shared connection
+    // Tests service switching overhead and connection reuse
+    ctx.run_concurrent(total_requests, |i, client| {
         let counter = success_count.clone();
-        
-        tokio::spawn(async move {
-            // Alternate between echo and calculator services
-            // Tests service switching overhead and connection reuse
+        let latencies = latencies.clone();
+        async move {
             if i % 2 == 0 {
-                // Echo service test
                 let msg = format!("rapid {}", i);
-                timeout(Duration::from_secs(2), client.echo().echo(msg))
-                    .await.expect("Timeout").expect("Echo failed");
+                latencies.time(timeout(Duration::from_secs(2), client.echo().echo(msg))).await??;
             } else {
-                // Calculator service test
-                timeout(
-                    Duration::from_secs(2),
-                    client.calculator().calculate(i as f64, 2.0, Operation::Multiply)
-                ).await.expect("Timeout").expect("Calculate failed");
+                let mut calculator = client.calculator();
+                let product = calculator.calculate(i as f64, 2.0, Operation::Multiply);
+                latencies.time(timeout(Duration::from_secs(2), product)).await??;
             }
-            // Track successful completion
             counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }).await.expect("Request tasks failed");
+
+    // Verify all requests succeeded
+    assert_eq!(success_count.load(Ordering::SeqCst), total_requests);
+    latencies.assert_p99_under(p99_limit());
+}
+
+// Test concurrent TLS handshakes
+// Purpose:
+// - Verify handshakes do not fail when many clients connect at once
+// - Validate each new TLS connection serves requests
+#[tokio::test]
+async fn test_parallel_tls_handshakes() {
+    let ctx = Arc::new(TestContext::setup_tls().await.expect("Failed to setup TLS test context"));
+    let total_clients = 50;
+
+    // Each task opens its own connection, so each performs a handshake
+    let handles: Vec<_> = (0..total_clients).map(|i| {
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let client = timeout(Duration::from_secs(5), ctx.new_client()).await
+                .expect("Handshake timed out")
+                .expect("TLS connection failed");
+            let msg = format!("tls {}", i);
+            let reply = timeout(Duration::from_secs(2), client.echo().echo(msg.clone())).await
+                .expect("Echo timed out")
+                .expect("Echo over TLS failed");
+            assert_eq!(reply, msg);
         })
     }).collect();
-
-    // Wait for all requests to complete
     for handle in handles {
-        handle.await.unwrap();
+        handle.await.expect("TLS client task failed");
     }
 
-    // Verify all requests succeeded
-    assert_eq!(success_count.load(Ordering::SeqCst), total_requests);
+    // The shared client stays usable under the same load
+    ctx.run_concurrent(total_clients, |i, client| async move {
+        let mut calculator = client.calculator();
+        let product = calculator.calculate(i as f64, 2.0, Operation::Multiply);
+        timeout(Duration::from_secs(2), product).await??;
+        Ok(())
+    }).await.expect("Request tasks failed");
+}
+
+// Test the client benchmark
+// Purpose:
+// - Verify run_bench makes exactly the requested calls across its tasks
+// - Validate the report's counts and latency percentiles
+#[tokio::test]
+async fn test_run_bench_report_counts() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let before = ctx.metrics.snapshot().total_connections;
+
+    let report = timeout(Duration::from_secs(10), run_bench(&ctx.client, 50, 4))
+        .await
+        .expect("Benchmark timed out");
+    assert_eq!(report.requests, 50);
+    assert_eq!(report.failures, 0);
+    assert!(report.p50 > Duration::ZERO && report.p50 <= report.p99, "bad percentiles: {:?}", report);
+    assert!(report.p99 <= report.elapsed);
+    assert!(report.requests_per_sec() > 0.0);
+    // The calls share the client's connection
+    assert_eq!(ctx.metrics.snapshot().total_connections, before);
+}
+
+// Test the in-process benchmark harness
+// Purpose:
+// - Verify BenchHarness starts a server and hands out working clients
+// - Validate run_bench works against it, as in `grpc_client --bench --in-process`
+#[tokio::test]
+async fn test_bench_harness_serves_clients() {
+    let harness = timeout(Duration::from_secs(10), BenchHarness::start())
+        .await
+        .expect("Harness start timed out")
+        .expect("Failed to start benchmark harness");
+
+    let other = harness.new_client().await.expect("Failed to connect a second client");
+    assert_eq!(other.echo().echo("second").await.expect("Echo failed"), "second");
+
+    let report = timeout(Duration::from_secs(10), run_bench(harness.client(), 20, 2))
+        .await
+        .expect("Benchmark timed out");
+    assert_eq!((report.requests, report.failures), (20, 0));
 }
 
 // Test handling of large messages in parallel
@@ -74,18 +165,13 @@ async fn test_parallel_large_messages() {
     let ctx = TestContext::setup().await.expect("Failed to setup test context");
     let large_msg = "A".repeat(100_000);
     
-    let handles: Vec<_> = (0..5).map(|_| {
-        let client = ctx.client.clone();
+    ctx.run_concurrent(5, |_, client| {
         let msg = large_msg.clone();
-        tokio::spawn(async move {
+        async move {
             for _ in 0..10 {
-                timeout(Duration::from_secs(5), client.echo().echo(msg.clone()))
-                    .await.expect("Timeout").expect("Echo failed");
+                timeout(Duration::from_secs(5), client.echo().echo(msg.clone())).await??;
             }
-        })
-    }).collect();
-
-    for handle in handles {
-        handle.await.unwrap();
-    }
+            Ok(())
+        }
+    }).await.expect("Large message tasks failed");
 }
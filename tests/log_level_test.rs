@@ -0,0 +1,110 @@
+//! Runtime Log Level Tests
+//! This suite verifies that the log level can change while the process runs:
+//! 1. logging::set_level lets previously filtered debug events through
+//! 2. Invalid directives are rejected and leave the filter untouched
+//! 3. The admin SetLogLevel/GetLogLevel RPCs do the same remotely,
+//!    gated by the admin token
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use embedded_recruitment_task::logging::{self, LogLevelError};
+use tokio::sync::Mutex;
+use tokio::time::{timeout, Duration};
+use tonic::Code;
+use tracing_subscriber::filter::LevelFilter;
+use common::TestContext;
+
+mod common;
+
+// Files logging::setup may write to; whichever component initializes
+// logging first in this process decides which one is used
+const LOG_FILES: [&str; 2] = ["logs/server", "logs/client"];
+const ADMIN_TOKEN: &str = "s3cret";
+// Target changed through the admin RPC; kept apart from this crate's
+// default target so the per-target setting cannot mask the default level
+const REMOTE_TARGET: &str = "log_level_test::remote";
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// The filter is process-wide, so tests changing it must not overlap
+static FILTER_LOCK: Mutex<()> = Mutex::const_new(());
+
+// Emit a debug event with a unique marker and report whether it was written
+// Uses REMOTE_TARGET when `remote` is set, this crate's target otherwise
+// Only the bytes appended meanwhile are read; the files grow across runs
+fn debug_event_recorded(label: &str, remote: bool) -> bool {
+    let marker = format!("{}-{}-{:?}", label, std::process::id(), std::time::Instant::now());
+    let offsets = LOG_FILES.map(|path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0));
+    if remote {
+        tracing::debug!(target: REMOTE_TARGET, "log level probe {}", marker);
+    } else {
+        tracing::debug!("log level probe {}", marker);
+    }
+    LOG_FILES.iter().zip(offsets).any(|(path, offset)| {
+        let Ok(mut file) = File::open(path) else { return false };
+        let mut appended = String::new();
+        file.seek(SeekFrom::Start(offset)).is_ok()
+            && file.read_to_string(&mut appended).is_ok()
+            && appended.contains(&marker)
+    })
+}
+
+#[tokio::test]
+async fn test_set_level_enables_debug_events() {
+    let _lock = FILTER_LOCK.lock().await;
+    logging::init_server().expect("Server logging failed to initialize");
+
+    assert!(!debug_event_recorded("before", false), "debug event logged at the default level");
+
+    logging::set_level(None, LevelFilter::DEBUG).expect("Failed to set level");
+    assert!(debug_event_recorded("after", false), "debug event filtered after set_level");
+
+    // A bad target is rejected and the filter stays as it was
+    let before = logging::current_filter().expect("No filter");
+    let err = logging::set_level(Some("not a target"), LevelFilter::TRACE).unwrap_err();
+    assert!(matches!(err, LogLevelError::InvalidDirective(_)), "{:?}", err);
+    assert_eq!(logging::current_filter().expect("No filter"), before);
+
+    logging::set_level(None, LevelFilter::INFO).expect("Failed to restore level");
+    assert!(!debug_event_recorded("restored", false), "debug event logged after restoring the level");
+}
+
+#[tokio::test]
+async fn test_admin_log_level_round_trip() {
+    let _lock = FILTER_LOCK.lock().await;
+    let ctx = TestContext::setup_with(|builder| builder.admin_token(ADMIN_TOKEN))
+        .await
+        .expect("Failed to setup test context");
+    let target = REMOTE_TARGET;
+
+    // Mutating without the right token is refused
+    let err = ctx.client.admin().set_log_level(Some(target), "debug").await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let err = ctx.client.admin().with_token("wrong").set_log_level(Some(target), "debug").await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    assert!(!debug_event_recorded("unauthorized", true));
+
+    // With the token the change takes effect and is reported back
+    let mut admin = ctx.client.admin().with_token(ADMIN_TOKEN);
+    let filter = timeout(TIMEOUT_DURATION, admin.set_log_level(Some(target), "DEBUG"))
+        .await
+        .expect("Timeout")
+        .expect("SetLogLevel failed");
+    assert!(filter.contains(&format!("{}=debug", target)), "{}", filter);
+    assert_eq!(ctx.client.admin().get_log_level().await.expect("GetLogLevel failed"), filter);
+    assert!(debug_event_recorded("remote", true), "debug event filtered after SetLogLevel");
+
+    // Invalid levels are rejected without touching the filter
+    let err = admin.set_log_level(None, "loud").await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(admin.get_log_level().await.expect("GetLogLevel failed"), filter);
+
+    admin.set_log_level(Some(target), "info").await.expect("Failed to restore level");
+    assert!(!debug_event_recorded("restored", true));
+}
+
+#[tokio::test]
+async fn test_admin_set_log_level_disabled_without_token() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let err = ctx.client.admin().with_token(ADMIN_TOKEN).set_log_level(None, "debug").await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
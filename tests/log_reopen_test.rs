@@ -0,0 +1,36 @@
+//! Log Reopen Tests
+//! Log rotation renames the active log file and asks the process to
+//! reopen it. This suite verifies:
+//! 1. Lines logged before reopen_logs stay in the renamed file
+//! 2. Lines logged afterwards go to a new file under the original name
+//!
+//! Kept in its own test binary: it renames the file of the one global
+//! subscriber, which other tests in the same process would write to.
+
+use std::fs;
+use embedded_recruitment_task::logging;
+
+// File written by the test component's subscriber
+const LOG_FILE: &str = "logs/test";
+
+#[test]
+fn test_reopen_logs_follows_rotation() {
+    logging::init_test().expect("Logging failed to initialize");
+    // Unique per run: the log file keeps lines from earlier runs
+    let run = std::process::id();
+    let rotated = format!("{}.rotated-{}", LOG_FILE, run);
+    let (before, after) = (format!("before rotation {}", run), format!("after rotation {}", run));
+
+    tracing::info!("{}", before);
+    fs::rename(LOG_FILE, &rotated).expect("Failed to rotate log file");
+    logging::reopen_logs().expect("Failed to reopen log file");
+    tracing::info!("{}", after);
+
+    let old = fs::read_to_string(&rotated).expect("Rotated log missing");
+    let new = fs::read_to_string(LOG_FILE).expect("New log missing");
+    fs::remove_file(&rotated).ok();
+
+    assert!(old.contains(&before), "first line not in rotated file");
+    assert!(!old.contains(&after), "rotated file written after reopen");
+    assert!(new.contains(&after), "second line not in new file");
+}
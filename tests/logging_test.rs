@@ -0,0 +1,35 @@
+//! Logging Initialization Tests
+//! Server and client initialize logging in the same process during the
+//! in-process tests. This suite verifies:
+//! 1. Initializing server then client logging succeeds without panicking
+//! 2. Concurrent initialization from many threads is safe
+//! 3. Exactly one global subscriber ends up installed
+
+use embedded_recruitment_task::logging;
+
+#[test]
+fn test_server_then_client_logging_share_one_subscriber() {
+    logging::init_server().expect("Server logging failed to initialize");
+    logging::init_client().expect("Client logging failed to initialize");
+
+    // Racing initializations from both components are no-ops
+    let handles: Vec<_> = (0..8).map(|i| {
+        std::thread::spawn(move || {
+            if i % 2 == 0 {
+                logging::init_server()
+            } else {
+                logging::init_client()
+            }
+            .map_err(|e| e.to_string())
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().expect("Logging init panicked").expect("Logging init failed");
+    }
+
+    // The first initialization installed the global subscriber;
+    // installing another one must now be refused
+    assert!(tracing::dispatcher::has_been_set());
+    assert!(tracing_subscriber::fmt().try_init().is_err(), "a second global subscriber was accepted");
+    assert!(std::path::Path::new("logs").is_dir());
+}
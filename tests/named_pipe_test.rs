@@ -0,0 +1,130 @@
+//! Named Pipe Transport Tests
+//! This test suite verifies:
+//! 1. Echo and calculator round trips over a Windows named pipe
+//! 2. Concurrent clients each served on their own pipe instance
+//! 3. A pipe name already served is reported by serve()
+//! 4. Other platforms reject named pipes with Unimplemented, on both sides
+
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use tonic::Code;
+
+mod common;
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+    use embedded_recruitment_task::client::Operation;
+    use embedded_recruitment_task::server::Shutdown;
+    use tokio::time::Duration;
+    use tonic::Status;
+    use crate::common::next_port;
+
+    // Number of clients connected at once in the concurrency test
+    const CLIENTS: usize = 8;
+
+    // Pipe name unique to one test; ports are unique across tests already
+    fn pipe_name() -> String {
+        format!("embedded-recruitment-task-test-{}-{}", std::process::id(), next_port())
+    }
+
+    // Start a server on the pipe `name`
+    fn start(name: &str) -> Shutdown {
+        let (server, shutdown) = GrpcServer::builder()
+            .named_pipe(name)
+            .startup_banner(false)
+            .build()
+            .expect("Failed to build server");
+        tokio::spawn(async move {
+            if let Err(e) = server.serve().await {
+                eprintln!("Test server error: {}", e);
+            }
+        });
+        shutdown
+    }
+
+    // Connect a client to the pipe `name`, retrying while the server starts
+    async fn connect(name: &str) -> Result<GrpcClient, Status> {
+        GrpcClient::builder("http://localhost")?
+            .named_pipe(name)?
+            .connect_retries(50, Duration::from_millis(20))
+            .connect_eager()
+            .await
+    }
+
+    // Echo and calculator calls over the pipe return the usual results
+    #[tokio::test]
+    async fn test_round_trips_over_named_pipe() {
+        let name = pipe_name();
+        let shutdown = start(&name);
+        let client = connect(&name).await.expect("Failed to connect over the pipe");
+
+        let echoed = client.echo().echo("over a pipe").await.expect("Echo failed");
+        assert_eq!(echoed, "over a pipe");
+        let result = client.calculator().calculate(6.0, 7.0, Operation::Multiply).await.expect("Calculate failed");
+        assert_eq!(result, 42.0);
+        let err = client.calculator().calculate(1.0, 0.0, Operation::Divide).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        shutdown.trigger();
+    }
+
+    // Clients connected at the same time are served side by side
+    #[tokio::test]
+    async fn test_concurrent_clients_get_own_instances() {
+        let name = pipe_name();
+        let shutdown = start(&name);
+
+        let tasks: Vec<_> = (0..CLIENTS)
+            .map(|id| {
+                let name = name.clone();
+                tokio::spawn(async move {
+                    let client = connect(&name).await?;
+                    let message = format!("client {}", id);
+                    let echoed = client.echo().echo_delayed(message.clone(), Duration::from_millis(100)).await?;
+                    assert_eq!(echoed, message);
+                    Ok::<_, Status>(())
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.expect("Client task panicked").expect("Client failed");
+        }
+
+        shutdown.trigger();
+    }
+
+    // A second server on the same pipe fails instead of sharing it
+    #[tokio::test]
+    async fn test_pipe_in_use_rejected() {
+        let name = pipe_name();
+        let shutdown = start(&name);
+        connect(&name).await.expect("Failed to connect over the pipe");
+
+        let (second, _) = GrpcServer::builder().named_pipe(&name).build().expect("Failed to build server");
+        let err = second.serve().await.unwrap_err();
+        assert!(err.message().contains("named pipe"), "unexpected error: {}", err);
+
+        shutdown.trigger();
+    }
+}
+
+// Builders reject named pipes where the platform has none
+#[cfg(not(windows))]
+#[test]
+fn test_named_pipe_unsupported() {
+    let err = GrpcServer::builder().named_pipe("calculator").build().unwrap_err();
+    assert_eq!(err.code(), Code::Unimplemented);
+
+    let err = GrpcClient::builder("http://localhost").unwrap().named_pipe("calculator").unwrap_err();
+    assert_eq!(err.code(), Code::Unimplemented);
+}
+
+// Invalid names are reported as such on every platform
+#[test]
+fn test_named_pipe_invalid_name() {
+    let err = GrpcServer::builder().named_pipe("").build().unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let err = GrpcClient::builder("http://localhost").unwrap().named_pipe(r"a\b").unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
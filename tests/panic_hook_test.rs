@@ -0,0 +1,61 @@
+//! Panic Hook Tests
+//! This suite verifies that panics reach the logging system:
+//! 1. The panic message and source location are written to the log file
+//! 2. Installing the hook repeatedly still logs each panic once
+
+use std::fs::File;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use embedded_recruitment_task::logging;
+
+// Fresh log file in the system temp directory, unique per test
+fn temp_log(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("panic_hook_test_{}_{}.log", name, std::process::id()))
+}
+
+// Run `f` with a subscriber writing plain-text lines to `path`
+fn with_file_logging(path: &PathBuf, f: impl FnOnce()) {
+    let file = File::create(path).expect("Failed to create log file");
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer(Mutex::new(file))
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+}
+
+#[test]
+fn test_panic_is_written_to_log_file() {
+    let path = temp_log("message");
+    let mut line = 0;
+    with_file_logging(&path, || {
+        logging::install_panic_hook();
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            line = line!() + 1;
+            panic!("disk quota exceeded in worker 7");
+        }));
+        assert!(result.is_err());
+    });
+
+    let contents = std::fs::read_to_string(&path).expect("Failed to read log file");
+    std::fs::remove_file(&path).ok();
+    assert!(contents.contains("ERROR"), "{}", contents);
+    assert!(contents.contains("disk quota exceeded in worker 7"), "{}", contents);
+    assert!(contents.contains(&format!("tests/panic_hook_test.rs:{}", line)), "{}", contents);
+}
+
+#[test]
+fn test_repeated_install_logs_each_panic_once() {
+    let path = temp_log("idempotent");
+    with_file_logging(&path, || {
+        for _ in 0..3 {
+            logging::install_panic_hook();
+        }
+        let result = std::panic::catch_unwind(|| panic!("{}", String::from("formatted payload")));
+        assert!(result.is_err());
+    });
+
+    let contents = std::fs::read_to_string(&path).expect("Failed to read log file");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(contents.matches("formatted payload").count(), 1, "{}", contents);
+}
@@ -0,0 +1,154 @@
+//! Peer Identity Tests
+//! This suite verifies client certificate identities under mutual TLS:
+//! 1. A handler registered by the test reads the client's common name and
+//!    subject alternative names with server::peer_identity
+//! 2. The access log line of each RPC names the client certificate, with
+//!    its fingerprint
+//! 3. Plaintext connections have no identity and log none
+//! 4. tls_client_ca on a plaintext server is a configuration error
+
+use std::sync::{Arc, Mutex};
+use embedded_recruitment_task::proto::broadcast::broadcast_service_server::{BroadcastService, BroadcastServiceServer};
+use embedded_recruitment_task::proto::broadcast::{BroadcastMessage, PublishRequest, PublishResponse, SubscribeRequest};
+use embedded_recruitment_task::server::{peer_identity, PeerIdentity};
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use rcgen::{CertificateParams, DnType};
+use tokio::sync::oneshot;
+use tokio::time::{timeout, Duration};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+use common::{next_port, LogCapture, TestContext, TEST_CLIENT_NAME};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Start of the access log line of each RPC
+const ACCESS_LOG: &str = "Incoming connection from";
+
+// Broadcast service whose Publish handler records the caller's identity
+#[derive(Default)]
+struct RecordingBroadcast {
+    seen: Arc<Mutex<Vec<Option<PeerIdentity>>>>,
+}
+
+#[tonic::async_trait]
+impl BroadcastService for RecordingBroadcast {
+    type SubscribeStream = tokio_stream::Empty<Result<BroadcastMessage, Status>>;
+
+    async fn subscribe(&self, _request: Request<SubscribeRequest>) -> Result<Response<Self::SubscribeStream>, Status> {
+        Err(Status::unimplemented("not needed by this test"))
+    }
+
+    async fn publish(&self, request: Request<PublishRequest>) -> Result<Response<PublishResponse>, Status> {
+        self.seen.lock().unwrap().push(peer_identity(&request));
+        Ok(Response::new(PublishResponse { subscribers: 0 }))
+    }
+}
+
+// Test-registered handler test
+// Verifies:
+// - A handler of a plain tonic server with a client CA sees the identity
+// - It carries the certificate's common name and subject alternative names
+#[tokio::test]
+async fn test_handler_reads_client_certificate() {
+    let server_cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).expect("Failed to generate certificate");
+    let server_pem = server_cert.serialize_pem().expect("Failed to serialize certificate");
+    let mut params = CertificateParams::new(vec!["tenant-a.example".to_string()]);
+    params.distinguished_name.push(DnType::CommonName, "tenant-a");
+    let client_cert = rcgen::Certificate::from_params(params).expect("Failed to generate client certificate");
+    let client_pem = client_cert.serialize_pem().expect("Failed to serialize client certificate");
+
+    let service = RecordingBroadcast::default();
+    let seen = service.seen.clone();
+    let tls = ServerTlsConfig::new()
+        .identity(Identity::from_pem(&server_pem, server_cert.serialize_private_key_pem()))
+        .client_ca_root(Certificate::from_pem(&client_pem));
+    let addr = format!("[::1]:{}", next_port());
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = Server::builder()
+        .tls_config(tls)
+        .expect("Invalid TLS configuration")
+        .add_service(BroadcastServiceServer::new(service))
+        .serve_with_shutdown(addr.parse().unwrap(), async { stopped.await.ok(); });
+    tokio::spawn(server);
+
+    let client = GrpcClient::builder(format!("https://{}", addr))
+        .unwrap()
+        .tls(&server_pem, Some("localhost"))
+        .unwrap()
+        .tls_identity(&client_pem, client_cert.serialize_private_key_pem())
+        .unwrap()
+        .connect_retries(50, Duration::from_millis(20))
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+    timeout(TIMEOUT_DURATION, client.broadcast().publish("tenants", "hello"))
+        .await
+        .expect("Publish timed out")
+        .expect("Publish failed");
+
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(seen.len(), 1);
+    let identity = seen[0].as_ref().expect("handler saw no client certificate");
+    assert_eq!(identity.common_name(), Some("tenant-a"));
+    assert_eq!(identity.subject_alt_names(), ["tenant-a.example"]);
+    let _ = stop.send(());
+}
+
+// Access log test
+// Verifies:
+// - Under mutual TLS, the access log line names the client's common name
+// - It carries the SHA-256 fingerprint of the certificate presented
+#[tokio::test]
+async fn test_access_log_has_certificate_fingerprint() {
+    let ctx = TestContext::setup_mtls_with(|builder| builder)
+        .await
+        .expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    timeout(TIMEOUT_DURATION, ctx.client.echo().echo("who am i"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo failed");
+
+    let der = ctx.client_certificate_der().expect("no client certificate");
+    let digest = ring::digest::digest(&ring::digest::SHA256, der);
+    let fingerprint: String = digest.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+    let lines = capture.lines_containing(ACCESS_LOG);
+    assert_eq!(lines.len(), 1, "expected one access log line, got {:?}", lines);
+    let expected = format!("client certificate CN={} sha256={}", TEST_CLIENT_NAME, fingerprint);
+    assert!(lines[0].contains(&expected), "missing {:?}: {}", expected, lines[0]);
+}
+
+// Plaintext test
+// Verifies:
+// - Calls over plaintext still succeed, and their access log line names no certificate
+#[tokio::test]
+async fn test_plaintext_has_no_identity() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    let reply = timeout(TIMEOUT_DURATION, ctx.client.echo().echo("anyone"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo failed");
+    assert_eq!(reply, "anyone");
+
+    let lines = capture.lines_containing(ACCESS_LOG);
+    assert_eq!(lines.len(), 1, "expected one access log line, got {:?}", lines);
+    assert!(!lines[0].contains("client certificate"), "plaintext call has an identity: {}", lines[0]);
+}
+
+// A client CA on a plaintext server is a configuration error
+#[test]
+fn test_tls_client_ca_requires_tls() {
+    let err = GrpcServer::builder()
+        .address("127.0.0.1:0")
+        .tls_client_ca("-----BEGIN CERTIFICATE-----\n-----END CERTIFICATE-----\n")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
@@ -0,0 +1,112 @@
+//! Per-Peer Concurrency Limit Tests
+//! This suite verifies the `max_concurrent_per_peer` server option:
+//! 1. Requests beyond the per-IP limit are rejected with ResourceExhausted
+//! 2. Requests within the limit complete normally
+//! 3. Other peers (different source IP) keep their own budget
+//! 4. Slots are released so the same peer can continue afterwards
+//! 5. A limit of 0, which would refuse every request, is rejected at build()
+
+use embedded_recruitment_task::proto::echo::{echo_service_client::EchoServiceClient, EchoRequest};
+use embedded_recruitment_task::GrpcServer;
+use tokio::net::TcpSocket;
+use tokio::time::{timeout, Duration};
+use tonic::transport::{Endpoint, Uri};
+use tonic::Code;
+use common::TestContext;
+
+mod common;
+
+// Test configuration
+const PEER_LIMIT: usize = 3;           // In-flight requests allowed per IP
+const CONCURRENT_CALLS: usize = 5;     // Requests fired at once from one client
+const HOLD_DELAY: Duration = Duration::from_millis(500);  // Keeps requests in flight
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Create an echo client whose connection originates from `local_ip`
+// Binding the socket before connecting gives the server a distinct peer IP
+// (127.0.0.0/8 is all loopback on Linux, so 127.0.0.2 works without setup)
+fn echo_client_from(local_ip: [u8; 4], server_addr: String) -> EchoServiceClient<tonic::transport::Channel> {
+    let channel = Endpoint::from_shared(format!("http://{}", server_addr))
+        .unwrap()
+        .connect_with_connector_lazy(tower::service_fn(move |_: Uri| {
+            let server_addr = server_addr.clone();
+            async move {
+                let socket = TcpSocket::new_v4()?;
+                socket.bind((local_ip, 0).into())?;
+                socket.connect(server_addr.parse().unwrap()).await
+            }
+        }));
+    EchoServiceClient::new(channel)
+}
+
+#[tokio::test]
+async fn test_per_peer_limit_rejects_excess_only_for_that_peer() {
+    // IPv4 server so the second client can use a different loopback address
+    let ctx = TestContext::setup_on("127.0.0.1", |builder| builder.max_concurrent_per_peer(PEER_LIMIT))
+        .await
+        .expect("Failed to setup test context");
+
+    // Fire CONCURRENT_CALLS delayed echoes from the main client (127.0.0.1)
+    let handles: Vec<_> = (0..CONCURRENT_CALLS).map(|i| {
+        let client = ctx.client.clone();
+        tokio::spawn(async move {
+            timeout(
+                TIMEOUT_DURATION,
+                client.echo().echo_delayed(format!("held_{}", i), HOLD_DELAY)
+            ).await.expect("Timeout")
+        })
+    }).collect();
+
+    // While those are in flight, a second peer sends its own delayed echoes
+    // Give the first batch a moment to occupy the server
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let other_peer = echo_client_from([127, 0, 0, 2], ctx.addr.clone());
+    let other_handles: Vec<_> = (0..PEER_LIMIT).map(|i| {
+        let mut client = other_peer.clone();
+        tokio::spawn(async move {
+            timeout(TIMEOUT_DURATION, client.echo(EchoRequest {
+                message: format!("other_{}", i),
+                delay_ms: 100,
+                ..Default::default()
+            })).await.expect("Timeout")
+        })
+    }).collect();
+
+    // Exactly CONCURRENT_CALLS - PEER_LIMIT requests must be rejected
+    let mut rejected = 0;
+    for handle in handles {
+        match handle.await.unwrap() {
+            Ok(_) => {}
+            Err(status) => {
+                assert_eq!(status.code(), Code::ResourceExhausted);
+                rejected += 1;
+            }
+        }
+    }
+    assert_eq!(rejected, CONCURRENT_CALLS - PEER_LIMIT);
+
+    // The second peer was never affected by the first one's budget
+    for handle in other_handles {
+        handle.await.unwrap().expect("Other peer should not be limited");
+    }
+
+    // Slots are released after completion, so the first peer can continue
+    let response = timeout(TIMEOUT_DURATION, ctx.client.echo().echo("after"))
+        .await
+        .expect("Timeout")
+        .expect("Echo after limit should succeed");
+    assert_eq!(response, "after");
+}
+
+// Zero limit test
+// Verifies build() rejects max_concurrent_per_peer(0) with InvalidArgument
+#[test]
+fn test_build_rejects_zero_peer_limit() {
+    let err = GrpcServer::builder()
+        .address("127.0.0.1:0")
+        .max_concurrent_per_peer(0)
+        .build()
+        .expect_err("a per-peer limit of 0 was accepted");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("max_concurrent_per_peer"), "{}", err.message());
+}
@@ -0,0 +1,105 @@
+//! Runtime Configuration Tests
+//! This suite verifies the tokio runtime built from RuntimeConfig:
+//! 1. Thread counts and names follow the configuration
+//! 2. Unset values keep tokio's defaults
+//! 3. Invalid values are rejected together
+//! 4. The chosen settings are reported by the ServerInfo RPC
+//!
+//! Runtimes cannot be created inside another runtime, so these are
+//! plain #[test] functions driving their own runtime.
+
+use embedded_recruitment_task::{GrpcClient, GrpcServer, RuntimeConfig};
+use tokio::time::Duration;
+use common::next_port;
+
+mod common;
+
+// Name of a thread spawned on the runtime's blocking pool
+fn blocking_thread_name(runtime: &tokio::runtime::Runtime) -> String {
+    runtime.block_on(async {
+        tokio::task::spawn_blocking(|| std::thread::current().name().unwrap_or_default().to_string())
+            .await
+            .unwrap()
+    })
+}
+
+// Configured runtime test
+// Verifies worker count (via runtime metrics) and thread names
+#[test]
+fn test_runtime_follows_config() {
+    let config = RuntimeConfig {
+        worker_threads: Some(3),
+        max_blocking_threads: Some(4),
+        thread_name_prefix: Some("grpc-test".to_string()),
+    };
+    let runtime = config.build_runtime().expect("Failed to build runtime");
+
+    assert_eq!(runtime.handle().metrics().num_workers(), 3);
+    let name = blocking_thread_name(&runtime);
+    assert!(name.starts_with("grpc-test-"), "unexpected thread name {:?}", name);
+    assert_eq!(config.effective_max_blocking_threads(), 4);
+}
+
+// Default runtime test
+// Verifies an empty config behaves like #[tokio::main]
+#[test]
+fn test_default_runtime_matches_tokio_defaults() {
+    let config = RuntimeConfig::default();
+    let runtime = config.build_runtime().expect("Failed to build runtime");
+
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    assert_eq!(runtime.handle().metrics().num_workers(), cores);
+    assert_eq!(blocking_thread_name(&runtime), RuntimeConfig::DEFAULT_THREAD_NAME);
+    assert_eq!(config.effective_max_blocking_threads(), RuntimeConfig::DEFAULT_MAX_BLOCKING_THREADS);
+}
+
+// Validation test
+// Verifies zero counts and blank prefixes are all reported at once
+#[test]
+fn test_invalid_runtime_config_is_rejected() {
+    let config = RuntimeConfig {
+        worker_threads: Some(0),
+        max_blocking_threads: Some(0),
+        thread_name_prefix: Some("  ".to_string()),
+    };
+    let err = config.build_runtime().expect_err("invalid config accepted");
+    assert_eq!(err.problems.len(), 3, "{}", err);
+    assert!(err.to_string().contains("worker threads: must be at least 1"), "{}", err);
+}
+
+// Server info test
+// Verifies a server running on a configured runtime reports its settings
+#[test]
+fn test_server_info_reports_runtime() {
+    let config = RuntimeConfig {
+        worker_threads: Some(2),
+        max_blocking_threads: Some(16),
+        thread_name_prefix: Some("info-test".to_string()),
+    };
+    let runtime = config.build_runtime().expect("Failed to build runtime");
+    let addr = format!("[::1]:{}", next_port());
+
+    let info = runtime.block_on(async {
+        let (server, shutdown) = GrpcServer::builder()
+            .address(addr.clone())
+            .runtime_config(config.clone())
+            .build()
+            .expect("Failed to build server");
+        tokio::spawn(server.serve());
+
+        let client = GrpcClient::builder(format!("http://{}", addr))
+            .expect("Invalid endpoint")
+            .connect_retries(50, Duration::from_millis(20))
+            .connect_eager()
+            .await
+            .expect("Failed to connect");
+        let info = client.admin().server_info().await.expect("ServerInfo failed");
+        shutdown.trigger();
+        info
+    });
+
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.worker_threads, 2);
+    assert_eq!(info.max_blocking_threads, 16);
+    assert_eq!(info.thread_name_prefix, "info-test");
+}
@@ -0,0 +1,103 @@
+//! Server Event Tap Tests
+//! This suite verifies the lifecycle events a server emits:
+//! 1. Startup, connection and shutdown events arrive in order
+//! 2. A server without a tap, or with a dropped receiver, runs normally
+
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use embedded_recruitment_task::server::ServerEvent;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+use common::next_port;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Event sequence test
+// Verifies a connect / echo / disconnect / shutdown cycle produces:
+// Started, ConnectionOpened, ConnectionClosed, ShutdownRequested, Stopped
+#[tokio::test]
+async fn test_events_follow_server_lifecycle() {
+    let addr = format!("[::1]:{}", next_port());
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .with_event_tap(tx)
+        .build()
+        .expect("Failed to build server");
+    let handle = tokio::spawn(server.serve());
+
+    let client = GrpcClient::builder(format!("http://{}", addr))
+        .expect("Invalid endpoint")
+        .connect_retries(50, Duration::from_millis(20))
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+    client.echo().echo("events").await.expect("Echo failed");
+    drop(client);
+
+    // Wait for the connection to close before shutting down,
+    // otherwise its close may race with ShutdownRequested
+    let mut events = Vec::new();
+    while !matches!(events.last(), Some(ServerEvent::ConnectionClosed(_))) {
+        let event = timeout(TIMEOUT_DURATION, rx.recv())
+            .await
+            .expect("Timed out waiting for the connection to close")
+            .expect("Event channel closed early");
+        events.push(event);
+    }
+
+    shutdown.trigger();
+    timeout(TIMEOUT_DURATION, handle)
+        .await
+        .expect("Server did not stop")
+        .unwrap()
+        .expect("Server failed");
+
+    // Drain what is left; the channel closes once the server is gone
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+
+    let peer = match events.get(1) {
+        Some(ServerEvent::ConnectionOpened(peer)) => *peer,
+        other => panic!("expected ConnectionOpened second, got {:?} in {:?}", other, events),
+    };
+    assert_eq!(events, vec![
+        ServerEvent::Started(addr.parse().unwrap()),
+        ServerEvent::ConnectionOpened(peer),
+        ServerEvent::ConnectionClosed(peer),
+        ServerEvent::ShutdownRequested,
+        ServerEvent::Stopped,
+    ]);
+}
+
+// Dropped receiver test
+// Verifies the server keeps serving when nobody listens to the tap
+#[tokio::test]
+async fn test_dropped_receiver_is_ignored() {
+    let addr = format!("[::1]:{}", next_port());
+    let (tx, rx) = mpsc::unbounded_channel();
+    drop(rx);
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .with_event_tap(tx)
+        .build()
+        .expect("Failed to build server");
+    let handle = tokio::spawn(server.serve());
+
+    let client = GrpcClient::builder(format!("http://{}", addr))
+        .expect("Invalid endpoint")
+        .connect_retries(50, Duration::from_millis(20))
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+    assert_eq!(client.echo().echo("ignored").await.expect("Echo failed"), "ignored");
+
+    shutdown.trigger();
+    timeout(TIMEOUT_DURATION, handle)
+        .await
+        .expect("Server did not stop")
+        .unwrap()
+        .expect("Server failed");
+}
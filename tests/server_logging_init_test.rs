@@ -0,0 +1,56 @@
+//! Server Logging Initialization Tests
+//! Applications embedding the server may install their own subscriber.
+//! This suite verifies that with `skip_logging_init(true)`:
+//! 1. The application's subscriber receives the server's log lines
+//! 2. No `logs/server` file is created
+//!
+//! Kept in its own test binary: it sets the global subscriber and changes
+//! the working directory, which would affect other tests.
+
+use embedded_recruitment_task::{GrpcClient, GrpcServer};
+use tokio::time::{timeout, Duration};
+use common::{next_port, LogCapture};
+
+mod common;
+
+// Skip logging init test
+// Verifies:
+// - A subscriber installed before serve() captures the server's lines
+// - serve() and connect_eager() with the skip flag create no log files
+#[tokio::test]
+async fn test_skip_logging_init_keeps_custom_subscriber() {
+    let dir = std::env::temp_dir().join(format!("grpc-server-logging-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::env::set_current_dir(&dir).unwrap();
+    let capture = LogCapture::new();
+    tracing::subscriber::set_global_default(capture.subscriber()).expect("Failed to install subscriber");
+
+    let addr = format!("[::1]:{}", next_port());
+    let (server, shutdown) = GrpcServer::builder()
+        .address(addr.clone())
+        .skip_logging_init(true)
+        .build()
+        .expect("Failed to build server");
+    tokio::spawn(server.serve());
+    let client = GrpcClient::builder(format!("http://{}", addr))
+        .expect("Failed to create builder")
+        .skip_logging_init(true)
+        .connect_retries(50, Duration::from_millis(20))
+        .connect_eager()
+        .await
+        .expect("Failed to connect");
+
+    let echoed = timeout(Duration::from_secs(5), client.echo().echo("captured"))
+        .await
+        .expect("Test timed out")
+        .expect("Echo failed");
+    assert_eq!(echoed, "captured");
+    shutdown.trigger();
+
+    let logs = capture.contents();
+    assert!(logs.contains(&format!("Starting gRPC server on {}", addr)), "{}", logs);
+    assert!(logs.contains("Received echo request with message: captured"), "{}", logs);
+    assert!(!dir.join("logs").exists(), "a log directory was created");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,118 @@
+//! Shared Shutdown Tests
+//! This suite verifies that one Shutdown handle can stop several servers:
+//! 1. Servers built with a shared handle all stop when it is triggered
+//! 2. A server stops when every handle is dropped, with a warning that
+//!    tells it apart from a trigger
+//! 3. serve() returns the reason it stopped, and failures to start as errors
+
+use embedded_recruitment_task::GrpcServer;
+use embedded_recruitment_task::server::ShutdownReason;
+use tokio::time::{sleep, timeout, Duration};
+use tonic::Code;
+use common::{next_port, LogCapture};
+
+mod common;
+
+// Shared signal test
+// Verifies:
+// - Two servers built with the same handle both run until it fires
+// - A single trigger() stops both of them, each reporting the signal
+#[tokio::test]
+async fn test_shared_shutdown_stops_all_servers() {
+    let (first, shutdown) = GrpcServer::builder()
+        .address(format!("[::1]:{}", next_port()))
+        .startup_banner(false)
+        .build()
+        .expect("Failed to build first server");
+    let (second, _) = GrpcServer::builder()
+        .address(format!("[::1]:{}", next_port()))
+        .startup_banner(false)
+        .shutdown(shutdown.clone())
+        .build()
+        .expect("Failed to build second server");
+
+    let first = tokio::spawn(first.serve());
+    let second = tokio::spawn(second.serve());
+    sleep(Duration::from_millis(100)).await;
+    assert!(!first.is_finished() && !second.is_finished(), "servers stopped early");
+
+    shutdown.trigger();
+    for handle in [first, second] {
+        let reason = timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("Server did not stop")
+            .unwrap()
+            .expect("Server failed");
+        assert_eq!(reason, ShutdownReason::SignalReceived);
+    }
+}
+
+// Dropped handle test
+// Verifies:
+// - Dropping the only handle still stops the server, matching the
+//   behavior of the old oneshot sender
+// - It is logged as a "sender dropped" warning, not as a received signal,
+//   and returned as HandlesDropped
+#[tokio::test]
+async fn test_dropping_shutdown_handle_stops_server() {
+    // The server runs on this thread, so its logs are captured too
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+    let (server, shutdown) = GrpcServer::builder()
+        .address(format!("[::1]:{}", next_port()))
+        .startup_banner(false)
+        .skip_logging_init(true)
+        .build()
+        .expect("Failed to build server");
+
+    let handle = tokio::spawn(server.serve());
+    sleep(Duration::from_millis(100)).await;
+    drop(shutdown);
+
+    let reason = timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("Server did not stop")
+        .unwrap()
+        .expect("Server failed");
+    assert_eq!(reason, ShutdownReason::HandlesDropped);
+
+    let dropped = capture.lines_containing("Shutdown sender dropped");
+    assert_eq!(dropped.len(), 1, "{}", capture.contents());
+    assert!(dropped[0].contains("WARN"), "not a warning: {}", dropped[0]);
+    assert!(capture.lines_containing("Received shutdown signal").is_empty());
+}
+
+// Startup failure test
+// Verifies:
+// - A port another socket holds fails serve() with an error naming the
+//   address, instead of a shutdown reason
+// - So does a TLS identity that is not valid PEM
+#[tokio::test]
+async fn test_startup_failures_are_errors() {
+    let taken = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind blocker");
+    let addr = taken.local_addr().unwrap();
+    let (server, _shutdown) = GrpcServer::builder()
+        .socket_addr(addr)
+        .startup_banner(false)
+        .build()
+        .expect("Failed to build server");
+    let err = timeout(Duration::from_secs(5), server.serve())
+        .await
+        .expect("serve() did not fail")
+        .expect_err("serve() must fail on a taken port");
+    assert_eq!(err.code(), Code::Internal);
+    assert!(err.message().contains(&addr.to_string()), "{}", err.message());
+
+    let (server, _shutdown) = GrpcServer::builder()
+        .address(format!("[::1]:{}", next_port()))
+        .startup_banner(false)
+        .tls("not a certificate", "not a key")
+        .build()
+        .expect("Failed to build server");
+    let err = timeout(Duration::from_secs(5), server.serve())
+        .await
+        .expect("serve() did not fail")
+        .expect_err("serve() must fail on a broken TLS identity");
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("TLS"), "{}", err.message());
+}
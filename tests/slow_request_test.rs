@@ -0,0 +1,107 @@
+//! Slow Request Logging Tests
+//! This test suite verifies `GrpcServerBuilder::slow_request_threshold`:
+//! 1. An RPC over the threshold is logged once, with method, status,
+//!    latency and request ID, and counted in the metrics
+//! 2. An RPC under the threshold is not logged
+//! 3. Streaming RPCs are timed until the stream ends and flagged apart
+
+use embedded_recruitment_task::proto::echo::echo_service_client::EchoServiceClient;
+use embedded_recruitment_task::proto::echo::EchoRequest;
+use tokio::time::{sleep, timeout, Duration};
+use tokio_stream::StreamExt;
+use tonic::Request;
+use common::{LogCapture, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+const THRESHOLD: Duration = Duration::from_millis(100);
+
+// Start of the slow unary and streaming RPC warnings
+const SLOW_RPC: &str = "Slow RPC";
+const SLOW_STREAM: &str = "Slow streaming RPC";
+
+// Slow unary RPC test
+// Verifies:
+// - An echo delayed past the threshold gives exactly one warning
+// - The warning names the method, status and request ID
+// - A fast echo gives none
+// - Only the slow one is counted, as a unary RPC
+#[tokio::test]
+async fn test_slow_echo_logged_once() {
+    let ctx = TestContext::setup_with(|builder| builder.slow_request_threshold(THRESHOLD))
+        .await
+        .expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    timeout(TIMEOUT_DURATION, ctx.client.echo().echo("quick"))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo failed");
+    assert!(capture.lines_containing(SLOW_RPC).is_empty(), "fast echo logged as slow");
+
+    let mut client = EchoServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    let mut request = Request::new(EchoRequest { message: "sleepy".into(), delay_ms: 300, ..Default::default() });
+    request.metadata_mut().insert("x-request-id", "req-42".parse().unwrap());
+    timeout(TIMEOUT_DURATION, client.echo(request))
+        .await
+        .expect("Echo timed out")
+        .expect("Echo failed");
+
+    let lines = capture.lines_containing(SLOW_RPC);
+    assert_eq!(lines.len(), 1, "expected one slow request line, got {:?}", lines);
+    let line = &lines[0];
+    assert!(line.contains(" WARN "), "slow request not logged as a warning: {}", line);
+    assert!(line.contains("/echo.EchoService/Echo "), "missing method: {}", line);
+    assert!(line.contains("status Ok"), "missing status: {}", line);
+    assert!(line.contains("request id req-42"), "missing request id: {}", line);
+    assert!(line.contains("threshold 100ms"), "missing threshold: {}", line);
+
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!(snapshot.slow_requests, 1);
+    assert_eq!(snapshot.slow_streams, 0);
+}
+
+// Slow stream test
+// Verifies:
+// - A subscription is timed until the client leaves, not until it opened
+// - It is flagged as a streaming RPC and counted apart
+#[tokio::test]
+async fn test_long_stream_flagged_as_streaming() {
+    let ctx = TestContext::setup_with(|builder| builder.slow_request_threshold(THRESHOLD))
+        .await
+        .expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    let mut messages = ctx.client.broadcast().subscribe("lobby").await.expect("Subscribe failed");
+    sleep(THRESHOLD * 2).await;
+    ctx.client.broadcast().publish("lobby", "hi").await.expect("Publish failed");
+    timeout(TIMEOUT_DURATION, messages.next())
+        .await
+        .expect("Subscription timed out")
+        .expect("Subscription ended")
+        .expect("Subscription failed");
+    drop(messages);
+
+    // The server only notices the client left once the cancellation arrives
+    timeout(TIMEOUT_DURATION, async {
+        while capture.lines_containing(SLOW_STREAM).is_empty() {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Slow stream never logged");
+
+    let lines = capture.lines_containing(SLOW_STREAM);
+    assert_eq!(lines.len(), 1, "expected one slow stream line, got {:?}", lines);
+    assert!(lines[0].contains("/broadcast.BroadcastService/Subscribe"), "missing method: {}", lines[0]);
+    assert!(capture.lines_containing(SLOW_RPC).is_empty(), "stream logged as a unary RPC");
+
+    let snapshot = ctx.metrics.snapshot();
+    assert_eq!(snapshot.slow_streams, 1);
+    assert_eq!(snapshot.slow_requests, 0);
+}
@@ -0,0 +1,205 @@
+//! TLS Tests
+//! This suite verifies serving over TLS:
+//! 1. A server configured from PEM files on disk (via ServerConfig)
+//!    accepts calls from a client trusting its certificate
+//! 2. A plaintext client cannot talk to the TLS server
+//! 3. With min_tls_version(Tls13), TLS 1.2-only clients fail the handshake
+//!    while TLS 1.3 clients are served
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use embedded_recruitment_task::{GrpcClient, GrpcServer, ServerConfig};
+use embedded_recruitment_task::server::{GrpcServerBuilder, Shutdown, TlsVersion};
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Duration};
+use tokio_rustls::rustls::{self, ClientConfig, RootCertStore, ServerName, SupportedProtocolVersion};
+use tokio_rustls::TlsConnector;
+use common::next_port;
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Temporary directory holding the generated certificate and key
+// Removed on drop, even when the test fails
+struct TempCerts {
+    dir: PathBuf,
+    cert_pem: String,
+    key_pem: String,
+    cert_der: Vec<u8>,
+}
+
+impl TempCerts {
+    fn generate() -> Self {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])
+            .expect("Failed to generate certificate");
+        let cert_pem = cert.serialize_pem().expect("Failed to serialize certificate");
+        let key_pem = cert.serialize_private_key_pem();
+
+        let dir = std::env::temp_dir().join(format!("grpc-tls-test-{}-{}", std::process::id(), next_port()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cert.pem"), &cert_pem).unwrap();
+        std::fs::write(dir.join("key.pem"), &key_pem).unwrap();
+        let cert_der = cert.serialize_der().expect("Failed to serialize certificate");
+        Self { dir, cert_pem, key_pem, cert_der }
+    }
+}
+
+impl Drop for TempCerts {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+// TLS round trip test
+// Verifies:
+// - from_config loads the certificate and key from disk
+// - A client trusting the certificate completes echo calls
+// - A plaintext client is refused
+#[tokio::test]
+async fn test_tls_server_from_config() {
+    let certs = TempCerts::generate();
+    let port = next_port();
+    let config = ServerConfig {
+        addr: ([127, 0, 0, 1], port).into(),
+        max_message_size: None,
+        request_timeout: None,
+        tls_cert: Some(certs.dir.join("cert.pem")),
+        tls_key: Some(certs.dir.join("key.pem")),
+        compression: None,
+        max_concurrent_per_peer: None,
+        startup_banner: Some(false),
+        runtime: Default::default(),
+    };
+    let (server, shutdown) = GrpcServerBuilder::from_config(config)
+        .expect("Failed to load config")
+        .build()
+        .expect("Failed to build server");
+    tokio::spawn(server.serve());
+    sleep(Duration::from_millis(100)).await;
+
+    let client = GrpcClient::builder(format!("https://127.0.0.1:{}", port))
+        .unwrap()
+        .tls(&certs.cert_pem, Some("localhost"))
+        .unwrap()
+        .connect()
+        .unwrap();
+    let response = timeout(TIMEOUT_DURATION, client.echo().echo("secret"))
+        .await
+        .expect("Timeout")
+        .expect("TLS echo failed");
+    assert_eq!(response, "secret");
+
+    let plaintext = GrpcClient::builder(format!("http://127.0.0.1:{}", port))
+        .unwrap()
+        .connect()
+        .unwrap();
+    let result = timeout(TIMEOUT_DURATION, plaintext.echo().echo("in the clear")).await.expect("Timeout");
+    assert!(result.is_err(), "plaintext call to TLS server succeeded");
+
+    shutdown.trigger();
+}
+
+// Missing TLS files are reported when loading the config, naming the file
+#[tokio::test]
+async fn test_missing_tls_file_is_reported() {
+    let config = ServerConfig {
+        addr: ([127, 0, 0, 1], 0).into(),
+        max_message_size: None,
+        request_timeout: None,
+        tls_cert: Some(PathBuf::from("/nonexistent/cert.pem")),
+        tls_key: Some(PathBuf::from("/nonexistent/key.pem")),
+        compression: None,
+        max_concurrent_per_peer: None,
+        startup_banner: None,
+        runtime: Default::default(),
+    };
+    let err = match GrpcServerBuilder::from_config(config) {
+        Ok(_) => panic!("from_config accepted a missing certificate"),
+        Err(err) => err,
+    };
+    assert!(err.message().contains("/nonexistent/cert.pem"), "unexpected error: {}", err);
+}
+
+// Complete a TLS handshake with `port` offering only `version`
+async fn handshake(certs: &TempCerts, port: u16, version: &'static SupportedProtocolVersion) -> Result<(), String> {
+    let mut roots = RootCertStore::empty();
+    roots.add(&rustls::Certificate(certs.cert_der.clone())).unwrap();
+    let config = ClientConfig::builder()
+        .with_safe_default_cipher_suites()
+        .with_safe_default_kx_groups()
+        .with_protocol_versions(&[version])
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let tcp = TcpStream::connect(("127.0.0.1", port)).await.map_err(|e| e.to_string())?;
+    let server_name = ServerName::try_from("localhost").unwrap();
+    TlsConnector::from(Arc::new(config)).connect(server_name, tcp).await.map(|_| ()).map_err(|e| e.to_string())
+}
+
+// Start a TLS server on a fresh port, requiring `min_version`
+async fn start_tls_server(certs: &TempCerts, min_version: TlsVersion) -> (u16, Shutdown) {
+    let port = next_port();
+    let (server, shutdown) = GrpcServer::builder()
+        .address(format!("127.0.0.1:{}", port))
+        .tls(&certs.cert_pem, &certs.key_pem)
+        .min_tls_version(min_version)
+        .startup_banner(false)
+        .build()
+        .expect("Failed to build server");
+    tokio::spawn(server.serve());
+    sleep(Duration::from_millis(100)).await;
+    (port, shutdown)
+}
+
+// Minimum TLS version test
+// Verifies:
+// - Without a minimum, TLS 1.2 and TLS 1.3 handshakes both succeed
+// - With Tls13 required, a TLS 1.2-only handshake fails and a TLS 1.3 one succeeds
+// - The crate's own client (which offers TLS 1.3) still completes calls
+#[tokio::test]
+async fn test_min_tls_version_refuses_tls12_clients() {
+    let certs = TempCerts::generate();
+
+    let (port, shutdown) = start_tls_server(&certs, TlsVersion::Tls12).await;
+    for version in [&rustls::version::TLS12, &rustls::version::TLS13] {
+        timeout(TIMEOUT_DURATION, handshake(&certs, port, version))
+            .await
+            .expect("Timeout")
+            .expect("handshake without a minimum failed");
+    }
+    shutdown.trigger();
+
+    let (port, shutdown) = start_tls_server(&certs, TlsVersion::Tls13).await;
+    let result = timeout(TIMEOUT_DURATION, handshake(&certs, port, &rustls::version::TLS12)).await.expect("Timeout");
+    assert!(result.is_err(), "TLS 1.2 handshake succeeded although 1.3 is required");
+    timeout(TIMEOUT_DURATION, handshake(&certs, port, &rustls::version::TLS13))
+        .await
+        .expect("Timeout")
+        .expect("TLS 1.3 handshake failed");
+
+    let client = GrpcClient::builder(format!("https://127.0.0.1:{}", port))
+        .unwrap()
+        .tls(&certs.cert_pem, Some("localhost"))
+        .unwrap()
+        .connect()
+        .unwrap();
+    let response = timeout(TIMEOUT_DURATION, client.echo().echo("modern"))
+        .await
+        .expect("Timeout")
+        .expect("TLS 1.3 echo failed");
+    assert_eq!(response, "modern");
+
+    shutdown.trigger();
+}
+
+// A minimum TLS version on a plaintext server is a configuration error
+#[test]
+fn test_min_tls_version_requires_tls() {
+    let err = GrpcServer::builder()
+        .address("127.0.0.1:0")
+        .min_tls_version(TlsVersion::Tls13)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
@@ -0,0 +1,149 @@
+//! Trace Propagation Tests
+//! This suite verifies that client and server logs share a trace ID:
+//! 1. Calls made inside a client span carry a traceparent header
+//! 2. The server records the same trace ID on its per-RPC span
+//! 3. Calls under the same root span share one trace ID
+//! 4. Malformed traceparent headers are ignored, not rejected
+//! 5. A correlation ID set with with_correlation_id appears in the client
+//!    and the server log; without one, every call gets its own
+
+use embedded_recruitment_task::proto::echo::{echo_service_client::EchoServiceClient, EchoRequest};
+use tokio::time::{timeout, Duration};
+use tonic::{Code, Request};
+use tracing::Instrument;
+use common::{LogCapture, TestContext};
+
+mod common;
+
+const TIMEOUT_DURATION: Duration = Duration::from_secs(5);
+
+// Extract every trace ID the client propagated, in order
+fn client_trace_ids(capture: &LogCapture) -> Vec<String> {
+    capture
+        .lines_containing("traceparent=00-")
+        .iter()
+        .filter_map(|line| line.split("traceparent=00-").nth(1))
+        .map(|rest| rest[..32].to_string())
+        .collect()
+}
+
+// Propagation test
+// Verifies:
+// - The client logs the trace context it sends
+// - Server log lines for the call carry trace_id=<same id>
+// - Both calls under one root span use the same trace ID
+#[tokio::test]
+async fn test_trace_id_shared_between_client_and_server() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    let client = ctx.client.clone();
+    timeout(TIMEOUT_DURATION, async move {
+        client.echo().echo("traced").await.expect("Echo failed");
+        client.calculator().calculate_str(1.0, "+", 2.0).await.expect("Calculate failed");
+    }.instrument(tracing::info_span!("checkout")))
+        .await
+        .expect("Test timed out");
+
+    let ids = client_trace_ids(&capture);
+    assert_eq!(ids.len(), 2, "expected one traceparent per call: {:?}", ids);
+    assert_eq!(ids[0], ids[1], "calls under one root span should share a trace ID");
+
+    let server_lines = capture.lines_containing(&format!("trace_id={}", ids[0]));
+    assert!(
+        server_lines.iter().any(|line| line.contains("Received echo request")),
+        "server echo log missing trace ID {}: {:#?}",
+        ids[0],
+        capture.lines()
+    );
+    assert!(server_lines.iter().any(|line| line.contains("Received calculate request")));
+}
+
+// Malformed header test
+// Verifies an invalid traceparent does not fail the call
+// and no trace ID is recorded for it
+#[tokio::test]
+async fn test_malformed_traceparent_is_ignored() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    // Raw generated client: no trace interceptor, header set by hand
+    let mut client = EchoServiceClient::connect(format!("http://{}", ctx.addr))
+        .await
+        .expect("Failed to connect");
+    let mut request = Request::new(EchoRequest { message: "untraced".into(), delay_ms: 0, ..Default::default() });
+    request.metadata_mut().insert("traceparent", "not-a-trace-context".parse().unwrap());
+
+    let response = timeout(TIMEOUT_DURATION, client.echo(request))
+        .await
+        .expect("Test timed out")
+        .expect("Echo with malformed traceparent failed");
+    assert_eq!(response.into_inner().message, "untraced");
+    assert!(capture.lines_containing("trace_id=").is_empty(), "unexpected trace ID recorded");
+    assert_eq!(capture.lines_containing("Ignoring malformed traceparent").len(), 1);
+}
+
+// Correlation ID test
+// Verifies:
+// - The client logs the correlation ID it was given
+// - Server log lines for the call carry correlation_id=<same id>
+// - Invalid IDs are refused before anything is sent
+#[tokio::test]
+async fn test_correlation_id_in_client_and_server_logs() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    let client = ctx.client.with_correlation_id("order-1234").expect("Valid ID refused");
+    timeout(TIMEOUT_DURATION, client.echo().echo("tagged"))
+        .await
+        .expect("Test timed out")
+        .expect("Echo failed");
+
+    let client_lines = capture.lines_containing("Tagging request with correlation_id=order-1234");
+    assert_eq!(client_lines.len(), 1, "client log missing correlation ID: {:#?}", capture.lines());
+    let server_lines = capture.lines_containing("correlation_id=order-1234");
+    assert!(
+        server_lines.iter().any(|line| line.contains("Received echo request")),
+        "server echo log missing correlation ID: {:#?}",
+        capture.lines()
+    );
+
+    let err = ctx.client.with_correlation_id("two words").unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+// Generated correlation ID test
+// Verifies clients without a fixed ID send a new one with every call
+// and the server records each of them
+#[tokio::test]
+async fn test_correlation_id_generated_per_call() {
+    let ctx = TestContext::setup().await.expect("Failed to setup test context");
+    let capture = LogCapture::new();
+    let _guard = tracing::subscriber::set_default(capture.subscriber());
+
+    for message in ["first", "second"] {
+        timeout(TIMEOUT_DURATION, ctx.client.echo().echo(message))
+            .await
+            .expect("Test timed out")
+            .expect("Echo failed");
+    }
+
+    let ids: Vec<String> = capture
+        .lines_containing("Tagging request with correlation_id=")
+        .iter()
+        .filter_map(|line| line.split("correlation_id=").nth(1))
+        .map(|rest| rest[..32].to_string())
+        .collect();
+    assert_eq!(ids.len(), 2, "expected one correlation ID per call: {:?}", ids);
+    assert_ne!(ids[0], ids[1]);
+    for id in &ids {
+        assert!(
+            capture.lines_containing(&format!("correlation_id={}", id)).iter().any(|line| line.contains("Received echo request")),
+            "server echo log missing correlation ID {}",
+            id
+        );
+    }
+}